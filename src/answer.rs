@@ -0,0 +1,63 @@
+use std::fmt::{self, Display};
+
+/// Answer to a part of a puzzle, whatever the type of the output of the day
+///
+/// Most answers are numbers, but some puzzles ask for a word or for the letters drawn by some ASCII art.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i128),
+    UInt(u128),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::UInt(n) => write!(f, "{n}"),
+            Answer::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($variant:ident($target:ty): $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                #[allow(clippy::cast_lossless)]
+                fn from(value: $t) -> Self {
+                    Answer::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(Int(i128): i8, i16, i32, i64, i128, isize);
+impl_from!(UInt(u128): u8, u16, u32, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(-3isize), Answer::Int(-3));
+        assert_eq!(Answer::from(42usize), Answer::UInt(42));
+        assert_eq!(Answer::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(Answer::from("complete"), Answer::Text("complete".into()));
+        assert_eq!(Answer::from(i64::MIN).to_string(), i64::MIN.to_string());
+    }
+}
@@ -0,0 +1,118 @@
+use std::{collections::BTreeMap, fs, time::Duration};
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+
+use crate::color;
+
+const PHASES: [&str; 4] = ["Parse", "Part 1", "Part 2", "Total"];
+
+/// Timings of each phase of each day in nanoseconds, indexed like [`PHASES`]
+type Report = BTreeMap<usize, [u64; 4]>;
+
+/// Read the timings from the output of `--output json`, one document per line. Days that failed are skipped.
+fn parse(text: &str) -> Result<Report> {
+    let mut report = Report::new();
+    for (i, line) in text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+    {
+        let doc: Value = serde_json::from_str(line)
+            .with_context(|| format!("invalid JSON on line {}", i + 1))?;
+        let field = |name: &str| doc.get(name).and_then(Value::as_u64);
+        let (Some(day), Some(parse), Some(part1), Some(part2)) = (
+            field("day"),
+            field("parse_ns"),
+            field("part1_ns"),
+            field("part2_ns"),
+        ) else {
+            continue;
+        };
+        let day = usize::try_from(day)?;
+        report.insert(day, [parse, part1, part2, parse + part1 + part2]);
+    }
+    Ok(report)
+}
+
+fn load(path: &str) -> Result<Report> {
+    let text = fs::read_to_string(path).with_context(|| format!("could not read {path}"))?;
+    parse(&text).with_context(|| format!("could not parse {path}"))
+}
+
+/// Relative change between two timings, in percent
+#[allow(clippy::cast_precision_loss)]
+fn change(before: u64, after: u64) -> f64 {
+    if before == 0 {
+        return 0.;
+    }
+    (after as f64 - before as f64) / before as f64 * 100.
+}
+
+/// Print the change of each phase of each day between two benchmark outputs, highlighting the regressions larger than
+/// `threshold` percent
+pub fn print(before: &str, after: &str, threshold: f64) -> Result<()> {
+    let before = load(before)?;
+    let after = load(after)?;
+    println!(
+        "{:>5} | {:>6} | {:>12} | {:>12} | {:>8}",
+        "Day", "Phase", "Before", "After", "Change"
+    );
+    let mut regressions = 0;
+    for (day, old) in &before {
+        let Some(new) = after.get(day) else {
+            eprintln!("Day {day} is missing from the second report");
+            continue;
+        };
+        for ((phase, &old), &new) in PHASES.iter().zip(old).zip(new) {
+            let change = change(old, new);
+            let line = format!(
+                "{day:>5} | {phase:>6} | {:>12.3?} | {:>12.3?} | {change:>+7.1}%",
+                Duration::from_nanos(old),
+                Duration::from_nanos(new)
+            );
+            if change > threshold {
+                regressions += 1;
+                if color::enabled() {
+                    println!("{}", line.red().bold());
+                } else {
+                    println!("{line} <- regression");
+                }
+            } else {
+                println!("{line}");
+            }
+        }
+    }
+    for day in after.keys().filter(|day| !before.contains_key(day)) {
+        eprintln!("Day {day} is missing from the first report");
+    }
+    println!("{regressions} phase(s) got slower by more than {threshold}%");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let text = r#"{"day":4,"parse_ns":30,"part1":"13","part1_ns":20,"part2":"30","part2_ns":10}
+
+{"day":5,"error":"could not read the input file"}
+"#;
+        let report = parse(text).unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[&4], [30, 20, 10, 60]);
+        assert!(parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_change() {
+        assert_relative_eq!(change(100, 150), 50.);
+        assert_relative_eq!(change(200, 100), -50.);
+        assert_relative_eq!(change(0, 100), 0.);
+    }
+}
@@ -0,0 +1,154 @@
+use std::{fs, io, path::Path, sync::Mutex};
+
+use owo_colors::OwoColorize;
+use serde_json::{Map, Value};
+
+use crate::{color, days::RunResult, inputs, stars};
+
+/// File where the answers of previous runs are stored, keyed by day, part and input hash
+const CACHE_FILE: &str = "answers-cache.json";
+
+/// The parts whose answer doesn't depend on the input, which are left out of the cache: day 25 part 2 reports the
+/// stars verified during the run
+const UNCACHED: [(usize, u8); 1] = [(25, 2)];
+
+/// Serializes the updates of the cache file when days are solved concurrently
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// FNV-1a hash of the input, which unlike `DefaultHasher` is stable across runs and Rust versions
+pub fn hash(input: &[u8]) -> u64 {
+    input.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn key(day: usize, part: u8, hash: u64) -> String {
+    format!("day{day:02}.part{part}.{hash:016x}")
+}
+
+/// Compare the answers with the ones cached for the same input, adding the new ones to the cache
+///
+/// Returns the parts whose answer matches the cached one, and the parts whose answer differs from it along with the
+/// cached answer. Cached answers are never overwritten, so that a regression keeps being reported until the cache file
+/// is deleted.
+fn compare(
+    cache: &mut Map<String, Value>,
+    day: usize,
+    hash: u64,
+    result: &RunResult,
+) -> (Vec<u8>, Vec<(u8, String)>) {
+    let mut matches = Vec::new();
+    let mut mismatches = Vec::new();
+    for (part, answer) in [1, 2].into_iter().zip(&result.answers) {
+        if UNCACHED.contains(&(day, part)) {
+            continue;
+        }
+        let answer = answer.to_string();
+        match cache.get(&key(day, part, hash)) {
+            Some(Value::String(cached)) if *cached == answer => matches.push(part),
+            Some(Value::String(cached)) => mismatches.push((part, cached.clone())),
+            Some(_) => {}
+            None => {
+                cache.insert(key(day, part, hash), Value::String(answer));
+            }
+        }
+    }
+    (matches, mismatches)
+}
+
+type Comparison = (Vec<u8>, Vec<(u8, String)>);
+
+fn update(day: usize, fp: &str, result: &RunResult) -> io::Result<Comparison> {
+    let hash = hash(&fs::read(fp)?);
+    let _lock = LOCK.lock().unwrap();
+    let mut cache = if Path::new(CACHE_FILE).exists() {
+        match serde_json::from_str(&fs::read_to_string(CACHE_FILE)?)? {
+            Value::Object(cache) => cache,
+            _ => Map::new(),
+        }
+    } else {
+        Map::new()
+    };
+    let len = cache.len();
+    let comparison = compare(&mut cache, day, hash, result);
+    if cache.len() != len {
+        fs::write(CACHE_FILE, serde_json::to_string_pretty(&cache)?)?;
+    }
+    Ok(comparison)
+}
+
+/// Warn loudly if the answers of a day differ from the ones computed on a previous run with the same input
+///
+/// The answers which match the cached ones earn their star. Runs on the examples are not cached.
+pub fn check(day: usize, fp: &str, result: &RunResult) {
+    if inputs::use_example() {
+        return;
+    }
+    let (matches, mismatches) = match update(day, fp, result) {
+        Ok(comparison) => comparison,
+        Err(e) => {
+            eprintln!("Error: could not update the answer cache: {e}");
+            return;
+        }
+    };
+    for part in matches {
+        stars::record(day, part);
+    }
+    for (part, cached) in mismatches {
+        let answer = &result.answers[usize::from(part) - 1];
+        let warning = format!(
+            "WARNING: the answer to day {day} part {part} changed from {cached} to {answer} for {fp} \
+             (delete {CACHE_FILE} if the old answer was wrong)"
+        );
+        if color::enabled() {
+            eprintln!("{}", warning.red().bold());
+        } else {
+            eprintln!("{warning}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::days::Timings;
+
+    #[test]
+    fn test_compare() {
+        let solution = |part1: &str, part2: &str| RunResult {
+            answers: [part1.into(), part2.into()],
+            timings: Timings::default(),
+            sub_phases: Vec::new(),
+        };
+        let mut cache = Map::new();
+        assert_eq!(
+            compare(&mut cache, 4, 42, &solution("13", "30")),
+            (vec![], vec![])
+        );
+        assert_eq!(cache.len(), 2);
+        assert_eq!(
+            compare(&mut cache, 4, 42, &solution("13", "30")),
+            (vec![1, 2], vec![])
+        );
+        assert_eq!(
+            compare(&mut cache, 4, 42, &solution("13", "31")),
+            (vec![1], vec![(2, "30".to_string())])
+        );
+        assert_eq!(cache[&key(4, 2, 42)], "30");
+        assert_eq!(
+            compare(&mut cache, 4, 43, &solution("14", "31")),
+            (vec![], vec![])
+        );
+        assert_eq!(cache.len(), 4);
+        // the completion of day 25 changes with the stars, not with the input
+        assert_eq!(
+            compare(&mut cache, 25, 42, &solution("54", "missing: day 3")),
+            (vec![], vec![])
+        );
+        assert_eq!(
+            compare(&mut cache, 25, 42, &solution("54", "complete")),
+            (vec![1], vec![])
+        );
+        assert_eq!(cache.len(), 5);
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    fmt::Display,
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::{Context, Result};
+use toml::{Table, Value};
+
+use crate::{
+    days::{Day, RunResult},
+    parser::MyErr,
+    stars,
+};
+
+/// Number of answers that didn't match the expected ones during this run
+static FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+/// Known-correct answers, read from a TOML file with one table per day:
+///
+/// ```toml
+/// [day01]
+/// part1 = 54697
+/// part2 = "54885"
+/// ```
+#[derive(Debug, Default)]
+pub struct Answers(Table);
+
+impl Answers {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Could not read {path}"))?;
+        Self::parse(&content).with_context(|| format!("Could not parse {path}"))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        Ok(Self(content.parse()?))
+    }
+
+    /// The expected answer for a part of a day, if any
+    pub fn expected(&self, day: usize, part: u8) -> Option<String> {
+        let value = self
+            .0
+            .get(&format!("day{day:02}"))?
+            .get(format!("part{part}"))?;
+        Some(match value {
+            Value::String(s) => s.clone(),
+            v => v.to_string(),
+        })
+    }
+}
+
+/// Number of answers that didn't match so far
+pub fn failures() -> usize {
+    FAILURES.load(Ordering::Relaxed)
+}
+
+/// Print the answer for a part along with the result of the comparison with the expected answer
+///
+/// A part which passes earns its star.
+fn verify(day: usize, part: u8, actual: &impl Display, expected: Option<&str>) {
+    let actual = actual.to_string();
+    match expected {
+        None => println!("Part {part}: {actual} (no expected answer)"),
+        Some(expected) if expected == actual => {
+            stars::record(day, part);
+            println!("Part {part}: {actual} PASS");
+        }
+        Some(expected) => {
+            FAILURES.fetch_add(1, Ordering::Relaxed);
+            println!("Part {part}: {actual} FAIL (expected {expected})");
+        }
+    }
+}
+
+/// Solve both parts and compare the answers with the expected ones
+pub fn check_day<D: Day>(day: usize, fp: &str, answers: &Answers) -> Result<RunResult, MyErr> {
+    let mut result = D::run_day(fp)?;
+    verify(
+        day,
+        1,
+        &result.answers[0],
+        answers.expected(day, 1).as_deref(),
+    );
+    stars::refresh_completion(day, &mut result);
+    verify(
+        day,
+        2,
+        &result.answers[1],
+        answers.expected(day, 2).as_deref(),
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected() {
+        let answers = Answers::parse(
+            "[day01]
+part1 = 142
+part2 = \"281\"
+
+[day25]
+part1 = 54",
+        )
+        .unwrap();
+        assert_eq!(answers.expected(1, 1).as_deref(), Some("142"));
+        assert_eq!(answers.expected(1, 2).as_deref(), Some("281"));
+        assert_eq!(answers.expected(25, 1).as_deref(), Some("54"));
+        assert_eq!(answers.expected(25, 2), None);
+        assert_eq!(answers.expected(2, 1), None);
+    }
+}
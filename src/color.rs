@@ -0,0 +1,55 @@
+use std::{
+    env,
+    io::{stderr, IsTerminal},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use clap::ValueEnum;
+
+/// When to use colors in the drawings printed by some days
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Only if printing to a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whether the rendering code should use colors
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Decide whether to use colors, following <https://no-color.org>
+fn resolve(choice: ColorChoice, no_color: Option<&str>, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => no_color.is_none_or(str::is_empty) && is_terminal,
+    }
+}
+
+/// Set the color policy for the rest of the run. The drawings are printed to stderr, so that's what we check.
+pub fn init(choice: ColorChoice) {
+    let no_color = env::var("NO_COLOR").ok();
+    let enabled = resolve(choice, no_color.as_deref(), stderr().is_terminal());
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert!(resolve(ColorChoice::Auto, None, true));
+        assert!(resolve(ColorChoice::Auto, Some(""), true));
+        assert!(!resolve(ColorChoice::Auto, Some("1"), true));
+        assert!(!resolve(ColorChoice::Auto, None, false));
+        assert!(resolve(ColorChoice::Always, Some("1"), false));
+        assert!(!resolve(ColorChoice::Never, None, true));
+    }
+}
@@ -0,0 +1,215 @@
+use std::{fs, path::Path, sync::OnceLock};
+
+use anyhow::{anyhow, bail, Context, Result};
+use toml::{Table, Value};
+
+use crate::days::Params;
+
+/// Configuration file read from the working directory if it exists
+pub const DEFAULT_FILE: &str = "aoc.toml";
+
+/// The parameters which can be set in the table of each day, see [`Params`]
+///
+/// Day 17 has none: the size of its grid, which was hardcoded for the example and the real input, comes from the input.
+const PARAMS: [(usize, &str); 5] = [
+    (11, "expansion"),
+    (21, "steps_part1"),
+    (21, "steps_part2"),
+    (24, "area_min"),
+    (24, "area_max"),
+];
+
+/// Runtime parameters of the days, read from a TOML file with one table per day, which fill the [`Params`] of each day:
+///
+/// ```toml
+/// [day11]
+/// expansion = 1_000_000
+///
+/// [day24]
+/// area_min = 200_000_000_000_000
+/// area_max = 400_000_000_000_000
+/// ```
+#[derive(Debug, Default)]
+pub struct Config(Table);
+
+/// The configuration for this run
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Could not read {path}"))?;
+        Self::parse(&content).with_context(|| format!("Could not parse {path}"))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let config = Self(content.parse()?);
+        config.check()?;
+        Ok(config)
+    }
+
+    /// Set a parameter from a `dayNN.name=value` override given on the command line, the value being TOML
+    fn set(&mut self, assignment: &str) -> Result<()> {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected dayNN.name=value, got {assignment}"))?;
+        let (day, name) = key
+            .trim()
+            .split_once('.')
+            .ok_or_else(|| anyhow!("expected dayNN.name=value, got {assignment}"))?;
+        let value = format!("value = {value}")
+            .parse::<Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .ok_or_else(|| anyhow!("invalid value in {assignment}"))?;
+        let table = self
+            .0
+            .entry(day)
+            .or_insert_with(|| Value::Table(Table::new()));
+        let Value::Table(table) = table else {
+            return Err(anyhow!("{day} is not a table in the configuration"));
+        };
+        table.insert(name.to_string(), value);
+        self.check()
+    }
+
+    /// Reject the tables which aren't days, the parameters which their day doesn't have and the values which don't fit
+    /// their parameter, which would otherwise be silently ignored
+    fn check(&self) -> Result<()> {
+        for (key, table) in &self.0 {
+            let day = key
+                .strip_prefix("day")
+                .and_then(|n| n.parse().ok())
+                .filter(|&day| (1..=25).contains(&day) && *key == format!("day{day:02}"))
+                .ok_or_else(|| anyhow!("unknown table {key}, expected day01 to day25"))?;
+            let Value::Table(table) = table else {
+                bail!("{key} is not a table in the configuration");
+            };
+            if let Some(name) = table.keys().find(|name| !PARAMS.contains(&(day, name))) {
+                bail!("unknown parameter {key}.{name}");
+            }
+            self.try_params(day, Params::PUZZLE)?;
+        }
+        Ok(())
+    }
+
+    fn get(&self, day: usize, name: &str) -> Option<&Value> {
+        self.0.get(&format!("day{day:02}"))?.get(name)
+    }
+
+    /// The parameters of a day, taking the ones that aren't set from `defaults`
+    pub fn params(&self, day: usize, defaults: Params) -> Params {
+        self.try_params(day, defaults)
+            .expect("the parameters are checked when they are set")
+    }
+
+    fn try_params(&self, day: usize, defaults: Params) -> Result<Params> {
+        Ok(Params {
+            expansion: self.int(day, "expansion")?.unwrap_or(defaults.expansion),
+            steps_part1: self
+                .int(day, "steps_part1")?
+                .unwrap_or(defaults.steps_part1),
+            steps_part2: self
+                .int(day, "steps_part2")?
+                .unwrap_or(defaults.steps_part2),
+            area_min: self.float(day, "area_min")?.unwrap_or(defaults.area_min),
+            area_max: self.float(day, "area_max")?.unwrap_or(defaults.area_max),
+        })
+    }
+
+    /// An integer parameter of a day, `None` if it's not set, or an error if it's not an integer which fits in `T`
+    fn int<T: TryFrom<i64>>(&self, day: usize, name: &str) -> Result<Option<T>> {
+        let Some(value) = self.get(day, name) else {
+            return Ok(None);
+        };
+        let i = value
+            .as_integer()
+            .ok_or_else(|| anyhow!("day{day:02}.{name} must be an integer, got {value}"))?;
+        T::try_from(i)
+            .map(Some)
+            .map_err(|_| anyhow!("day{day:02}.{name} is out of range: {i}"))
+    }
+
+    /// A floating-point parameter of a day, `None` if it's not set, or an error if it's not a finite number. Integers
+    /// are accepted too.
+    #[allow(clippy::cast_precision_loss)]
+    fn float(&self, day: usize, name: &str) -> Result<Option<f64>> {
+        match self.get(day, name) {
+            None => Ok(None),
+            Some(Value::Float(f)) if f.is_finite() => Ok(Some(*f)),
+            Some(Value::Integer(i)) => Ok(Some(*i as f64)),
+            Some(value) => bail!("day{day:02}.{name} must be a finite number, got {value}"),
+        }
+    }
+}
+
+/// Load the configuration for the rest of the run from `path`, or from [`DEFAULT_FILE`] if it exists, then apply the
+/// `dayNN.name=value` overrides from the command line
+pub fn init(path: Option<&str>, overrides: &[String]) -> Result<()> {
+    let mut config = match path {
+        Some(path) => Config::load(path)?,
+        None if Path::new(DEFAULT_FILE).exists() => Config::load(DEFAULT_FILE)?,
+        None => Config::default(),
+    };
+    for assignment in overrides {
+        config.set(assignment)?;
+    }
+    CONFIG.set(config).ok();
+    Ok(())
+}
+
+/// The configuration for this run, empty if none was loaded
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_params() {
+        let config =
+            Config::parse("[day11]\nexpansion = 10\n\n[day24]\narea_min = 7\narea_max = 27.5\n")
+                .unwrap();
+        assert_eq!(config.int(11, "expansion").unwrap(), Some(10));
+        assert_eq!(config.int::<usize>(21, "steps_part1").unwrap(), None);
+        assert_eq!(config.int(11, "expansion").unwrap(), Some(10u8));
+        assert!(config.int::<u8>(24, "area_max").is_err());
+        assert_eq!(config.float(24, "area_min").unwrap(), Some(7.));
+        let params = config.params(24, Params::PUZZLE);
+        assert_eq!(params.expansion, 1_000_000);
+        assert_relative_eq!(params.area_max, 27.5);
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(Config::parse("[day21]\nsteps_part1 = 6\n").is_ok());
+        // unknown days and parameters
+        assert!(Config::parse("[day26]\nsteps_part1 = 6\n").is_err());
+        assert!(Config::parse("[day1]\nsteps_part1 = 6\n").is_err());
+        assert!(Config::parse("[day21]\nsteps = 6\n").is_err());
+        assert!(Config::parse("[day11]\nsteps_part1 = 6\n").is_err());
+        assert!(Config::parse("day21 = 6\n").is_err());
+        // wrong types and values out of range
+        assert!(Config::parse("[day21]\nsteps_part1 = 6.5\n").is_err());
+        assert!(Config::parse("[day21]\nsteps_part1 = -6\n").is_err());
+        assert!(Config::parse("[day24]\narea_min = \"7\"\n").is_err());
+        assert!(Config::parse("[day24]\narea_min = nan\n").is_err());
+    }
+
+    #[test]
+    fn test_set() {
+        let mut config = Config::parse("[day11]\nexpansion = 10\n").unwrap();
+        config.set("day11.expansion=100").unwrap();
+        config.set("day21.steps_part1 = 6").unwrap();
+        assert_eq!(config.params(11, Params::PUZZLE).expansion, 100);
+        assert_eq!(config.params(21, Params::PUZZLE).steps_part1, 6);
+        assert!(config.set("day21.steps_part1").is_err());
+        assert!(config.set("steps_part1=6").is_err());
+        assert!(config.set("day21.steps_part1=six").is_err());
+        assert!(config.set("day21.steps_part1=-1").is_err());
+        assert!(config.set("day21.step_part1=6").is_err());
+    }
+}
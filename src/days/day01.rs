@@ -1,26 +1,52 @@
-use nom::{
-    character::complete::{alphanumeric1, line_ending},
-    combinator::map,
-    multi::separated_list0,
-    IResult,
-};
+use nom::{character::complete::alphanumeric1, IResult};
 
-use crate::days::Day;
+use crate::{
+    days::{Day, Params},
+    parser::{helpers::lines_of, SolveError},
+};
 
 pub struct Day01;
 
+/// Example input from the puzzle description
+const EXAMPLE: &str = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+
+/// Example input for part 2 from the puzzle description
+const EXAMPLE_PART2: &str = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+
 impl Day for Day01 {
-    type Input = Vec<String>;
+    const DAY: u8 = 1;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const EXAMPLE_PART2: &'static str = EXAMPLE_PART2;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("142"), Some("281")];
+
+    fn title() -> &'static str {
+        "Trebuchet?!"
+    }
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(line_ending, map(alphanumeric1, |s: &str| s.to_string()))(input)
+    fn tags() -> &'static [&'static str] {
+        &["string"]
+    }
+
+    type Input<'a> = Vec<&'a str>;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        lines_of(alphanumeric1)(input)
     }
 
     type Output1 = usize;
 
     /// Part 1 took 0.049463ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
+        Ok(input
             .iter()
             .map(|l| {
                 let digits: Vec<usize> = l
@@ -33,14 +59,14 @@ impl Day for Day01 {
 
                 digits.first().unwrap_or(&0) * 10 + digits.last().unwrap_or(&0)
             })
-            .sum()
+            .sum())
     }
 
     type Output2 = usize;
 
     /// Part 2 took 0.164261ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        input
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
+        Ok(input
             .iter()
             .map(|line| {
                 let digits: Vec<_> = line
@@ -67,7 +93,7 @@ impl Day for Day01 {
                     .collect();
                 digits.first().unwrap_or(&0) * 10 + digits.last().unwrap_or(&0)
             })
-            .sum()
+            .sum())
     }
 }
 
@@ -77,26 +103,13 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let input = "1abc2
-pqr3stu8vwx
-a1b2c3d4e5f
-treb7uchet";
-
-        let parsed = Day01::parse(input).unwrap().1;
-        assert_eq!(Day01::part_1(&parsed), 142);
+        let parsed = Day01::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day01::part_1(&parsed, &Day01::EXAMPLE_PARAMS), Ok(142));
     }
 
     #[test]
     fn test_part2() {
-        let input = "two1nine
-eightwothree
-abcone2threexyz
-xtwone3four
-4nineeightseven2
-zoneight234
-7pqrstsixteen";
-
-        let parsed = Day01::parse(input).unwrap().1;
-        assert_eq!(Day01::part_2(&parsed), 281);
+        let parsed = Day01::parse(EXAMPLE_PART2).unwrap().1;
+        assert_eq!(Day01::part_2(&parsed, &Day01::EXAMPLE_PARAMS), Ok(281));
     }
 }
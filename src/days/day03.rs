@@ -1,42 +1,38 @@
-use std::collections::{HashMap, HashSet};
-
 use itertools::Itertools;
-use nom::{
-    character::complete::{line_ending, not_line_ending},
-    combinator::map,
-    multi::separated_list0,
-    IResult,
-};
+use nom::{character::complete::not_line_ending, combinator::map, IResult};
 
-use crate::days::Day;
+use crate::{
+    days::{Day, Params},
+    parser::{self, helpers::lines_of, scan::lines_bytes, SolveError},
+    util::{neighbors::all_neighbors, FastMap, PrefixSum2D, Vec2},
+};
 
 pub struct Day03;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub struct Point {
-    x: usize,
-    y: usize,
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
 
-fn get_symbols(input: &[Vec<char>]) -> HashSet<Point> {
-    input
-        .iter()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.iter()
-                .enumerate()
-                .filter_map(|(x, char)| {
-                    if *char == '.' || char.is_ascii_digit() {
-                        return None;
-                    }
-                    Some(Point { x, y })
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect()
+/// The number of symbols in any rectangle of the schematic
+fn get_symbols(input: &[Vec<u8>]) -> PrefixSum2D<usize> {
+    let bounds = bounds(input);
+    PrefixSum2D::from_fn(bounds.x, bounds.y, |x, y| {
+        input[y]
+            .get(x)
+            .is_some_and(|c| *c != b'.' && !c.is_ascii_digit())
+            .into()
+    })
 }
 
-fn get_numbers(input: &[Vec<char>]) -> HashMap<Point, usize> {
+fn get_numbers(input: &[Vec<u8>]) -> FastMap<Vec2<usize>, usize> {
     input
         .iter()
         .enumerate()
@@ -54,14 +50,15 @@ fn get_numbers(input: &[Vec<char>]) -> HashMap<Point, usize> {
                     .iter()
                     .skip(x)
                     .take_while(|c| c.is_ascii_digit())
+                    .map(|&c| char::from(c))
                     .collect();
                 let numlen = number.len();
                 let num: usize = number.parse::<usize>().unwrap();
-                numbers.push((Point { x, y }, num));
+                numbers.push((Vec2 { x, y }, num));
                 if numlen > 1 {
                     // let's store the last position of the number too, for later checking if it's neighboring a star
                     numbers.push((
-                        Point {
+                        Vec2 {
                             x: x + numlen - 1,
                             y,
                         },
@@ -75,7 +72,7 @@ fn get_numbers(input: &[Vec<char>]) -> HashMap<Point, usize> {
         .collect()
 }
 
-fn get_stars(input: &[Vec<char>]) -> Vec<Point> {
+fn get_stars(input: &[Vec<u8>]) -> Vec<Vec2<usize>> {
     input
         .iter()
         .enumerate()
@@ -83,80 +80,98 @@ fn get_stars(input: &[Vec<char>]) -> Vec<Point> {
             line.iter()
                 .enumerate()
                 .filter_map(|(x, char)| {
-                    if *char != '*' {
+                    if *char != b'*' {
                         return None;
                     }
-                    Some(Point { x, y })
+                    Some(Vec2 { x, y })
                 })
                 .collect::<Vec<_>>()
         })
         .collect()
 }
 
+/// Read the lines of the schematic with nom, to compare with `scan_lines` (see the `nom-parsers` feature)
+fn parse_lines(input: &[u8]) -> IResult<&[u8], Vec<Vec<u8>>> {
+    lines_of(map(not_line_ending, <[u8]>::to_vec))(input)
+}
+
+/// Read the lines of the schematic with `parser::scan`
+fn scan_lines(input: &[u8]) -> (&[u8], Vec<Vec<u8>>) {
+    // only the whitespace at the end is left
+    let lines = lines_bytes(input).map(<[u8]>::to_vec).collect();
+    (&input[input.trim_ascii_end().len()..], lines)
+}
+
+/// The size of the schematic, to check the neighbors of a position
+fn bounds(input: &[Vec<u8>]) -> Vec2<usize> {
+    Vec2::new(input.first().map_or(0, Vec::len), input.len())
+}
+
 fn adjascent_symbol(
-    symbols: &HashSet<Point>,
+    symbols: &PrefixSum2D<usize>,
     number_x: usize,
     number_y: usize,
     number_len: usize,
 ) -> bool {
-    let x_from = number_x.saturating_sub(1);
-    let x_to = number_x + number_len;
-    let y_from = number_y.saturating_sub(1);
-    let y_to = number_y + 1;
-    for y in y_from..=y_to {
-        for x in x_from..=x_to {
-            if y == number_y && x >= number_x && x < number_x + number_len {
-                continue;
-            }
-            if symbols.contains(&Point { x, y }) {
-                return true;
-            }
-        }
-    }
-    false
+    // the digits of the number are not symbols, so we can count the symbols in the box around it
+    let xs = number_x.saturating_sub(1)..=number_x + number_len;
+    let ys = number_y.saturating_sub(1)..=number_y + 1;
+    symbols.sum(xs, ys) > 0
 }
 
-fn adjascent_numbers(numbers: &HashMap<Point, usize>, star_pos: &Point) -> Option<(usize, usize)> {
+fn adjascent_numbers(
+    numbers: &FastMap<Vec2<usize>, usize>,
+    bounds: Vec2<usize>,
+    star_pos: &Vec2<usize>,
+) -> Option<(usize, usize)> {
     let mut res = Vec::new();
-    let x_from = star_pos.x.saturating_sub(1);
-    let x_to = star_pos.x + 1;
-    let y_from = star_pos.y.saturating_sub(1);
-    let y_to = star_pos.y + 1;
-    for y in y_from..=y_to {
-        for x in x_from..=x_to {
-            if y == star_pos.y && x == star_pos.x {
+    for n in all_neighbors(*star_pos, bounds) {
+        if let Some(num) = numbers.get(&n) {
+            // Avoid adding the same number twice in case it's start and end positions are neighboring the star
+            // FIXME: in our case there aren't two separate numbers with the same value that are next to the same
+            // star, but we could miss one if that were the case
+            if res.contains(num) {
                 continue;
             }
-            if let Some(num) = numbers.get(&Point { x, y }) {
-                // Avoid adding the same number twice in case it's start and end positions are neighboring the star
-                // FIXME: in our case there aren't two separate numbers with the same value that are next to the same
-                // star, but we could miss one if that were the case
-                if res.contains(num) {
-                    continue;
-                }
-                res.push(*num);
-            }
+            res.push(*num);
         }
     }
     res.into_iter().collect_tuple() // only returns `Some((a, b))` if there are two elements exactly
 }
 
 impl Day for Day03 {
-    type Input = Vec<Vec<char>>;
+    const DAY: u8 = 3;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("4361"), Some("467835")];
+
+    fn title() -> &'static str {
+        "Gear Ratios"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["grid"]
+    }
+
+    type Input<'a> = Vec<Vec<u8>>;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        parser::from_bytes(input, Self::parse_bytes(input.as_bytes()))
+    }
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
-            line_ending,
-            map(not_line_ending, |s: &str| s.chars().collect::<Vec<_>>()),
-        )(input)
+    fn parse_bytes(input: &[u8]) -> IResult<&[u8], Self::Input<'_>> {
+        if cfg!(feature = "nom-parsers") {
+            parse_lines(input)
+        } else {
+            Ok(scan_lines(input))
+        }
     }
 
     type Output1 = usize;
 
     /// Part 1 took 0.3694ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
         let symbols = get_symbols(input);
-        input
+        Ok(input
             .iter()
             .enumerate()
             .flat_map(|(y, line)| {
@@ -172,6 +187,7 @@ impl Day for Day03 {
                         .iter()
                         .skip(x)
                         .take_while(|c| c.is_ascii_digit())
+                        .map(|&c| char::from(c))
                         .collect();
                     if adjascent_symbol(&symbols, x, y, number.len()) {
                         numbers.push(number.parse::<usize>().unwrap());
@@ -182,20 +198,21 @@ impl Day for Day03 {
                 }
                 numbers
             })
-            .sum()
+            .sum())
     }
 
     type Output2 = usize;
 
     /// Part 2 took 0.3155ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
         let stars = get_stars(input);
         let numbers = get_numbers(input);
-        stars
+        let bounds = bounds(input);
+        Ok(stars
             .iter()
-            .filter_map(|star_pos| adjascent_numbers(&numbers, star_pos))
+            .filter_map(|star_pos| adjascent_numbers(&numbers, bounds, star_pos))
             .map(|(num1, num2)| num1 * num2)
-            .sum()
+            .sum())
     }
 }
 
@@ -203,26 +220,21 @@ impl Day for Day03 {
 mod tests {
     use super::*;
 
-    const INPUT: &str = "467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..";
-
     #[test]
     fn test_part1() {
-        let parsed = Day03::parse(INPUT).unwrap().1;
-        assert_eq!(Day03::part_1(&parsed), 4361);
+        let parsed = Day03::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day03::part_1(&parsed, &Day03::EXAMPLE_PARAMS), Ok(4361));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day03::parse(INPUT).unwrap().1;
-        assert_eq!(Day03::part_2(&parsed), 467_835);
+        let parsed = Day03::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day03::part_2(&parsed, &Day03::EXAMPLE_PARAMS), Ok(467_835));
+    }
+
+    #[test]
+    fn test_parsers() {
+        let input = EXAMPLE.as_bytes();
+        assert_eq!(parse_lines(input).unwrap().1, scan_lines(input).1);
     }
 }
@@ -1,35 +1,60 @@
+//! Scratchcards
+//!
+//! The numbers of each card are kept in a `BitSet`, since the numbers from 128 up wouldn't fit in a `u128`.
+
 use nom::{
     bytes::complete::tag,
-    character::complete::{line_ending, space1, u128, u16},
+    character::complete::{space1, u16, u32},
     combinator::map,
-    multi::{separated_list0, separated_list1},
     sequence::tuple,
     IResult,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::days::Day;
+use crate::{
+    days::{Day, Params},
+    parser::{
+        self,
+        helpers::{lines_of, number_list},
+        scan::{ScanError, Scanner},
+        SolveError,
+    },
+    util::BitSet,
+};
 
 pub struct Day04;
 
-#[derive(Debug)]
+/// Example input from the puzzle description
+const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Card {
     /// Bitmap holding the set of winning numbers
-    pub winning: u128,
+    pub winning: BitSet,
     /// Bitmap holding the set of numbers on the card
-    pub numbers: u128,
+    pub numbers: BitSet,
 }
 
-/// Contruct a bitmap holding the set of all numbers in the input
-fn parse_numbers_bitmap(input: &str) -> IResult<&str, u128> {
-    let (rest, numbers) = separated_list1(space1, u128)(input)?;
-    let mut bitmap = 0u128;
-    for number in numbers {
-        bitmap |= 1 << number;
+impl Card {
+    /// The number of numbers on the card which are winning numbers
+    fn matching(&self) -> usize {
+        self.winning.intersection_len(&self.numbers)
     }
-    Ok((rest, bitmap))
 }
 
-fn parse_card(input: &str) -> IResult<&str, Card> {
+/// Contruct a bitmap holding the set of all numbers in the input
+fn parse_numbers_bitmap(input: &[u8]) -> IResult<&[u8], BitSet> {
+    map(number_list(u32), |numbers| {
+        numbers.into_iter().map(|n| n as usize).collect()
+    })(input)
+}
+
+fn parse_card(input: &[u8]) -> IResult<&[u8], Card> {
     map(
         tuple((
             tag("Card"),
@@ -46,39 +71,80 @@ fn parse_card(input: &str) -> IResult<&str, Card> {
     )(input)
 }
 
+/// Read a card from its line, the numbers are kept in bitmaps
+fn scan_card(mut line: Scanner<'_>) -> Result<Card, ScanError<'_>> {
+    line.tag(b"Card")?;
+    line.next_int::<u16>()?;
+    line.tag(b":")?;
+    let winning = line.int_list::<usize, _>()?;
+    line.tag(b" |")?;
+    let numbers = line.int_list::<usize, _>()?;
+    line.finish()?;
+    Ok(Card { winning, numbers })
+}
+
+/// Parse the cards with nom, to compare with `scan_cards` (see the `nom-parsers` feature)
+fn parse_cards(input: &[u8]) -> IResult<&[u8], Vec<Card>> {
+    lines_of(parse_card)(input)
+}
+
+/// Read the cards with `parser::scan`
+fn scan_cards(input: &[u8]) -> IResult<&[u8], Vec<Card>> {
+    let mut scanner = Scanner::new(input);
+    let cards = scanner.lines().map(scan_card).collect::<Result<_, _>>()?;
+    Ok((scanner.rest(), cards))
+}
+
 impl Day for Day04 {
-    type Input = Vec<Card>;
+    const DAY: u8 = 4;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("13"), Some("30")];
+
+    fn title() -> &'static str {
+        "Scratchcards"
+    }
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(line_ending, parse_card)(input)
+    fn tags() -> &'static [&'static str] {
+        &["bitset"]
+    }
+
+    type Input<'a> = Vec<Card>;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        parser::from_bytes(input, Self::parse_bytes(input.as_bytes()))
+    }
+
+    fn parse_bytes(input: &[u8]) -> IResult<&[u8], Self::Input<'_>> {
+        if cfg!(feature = "nom-parsers") {
+            parse_cards(input)
+        } else {
+            scan_cards(input)
+        }
     }
 
     type Output1 = usize;
 
     /// Part 1 took 0.000976ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
+        Ok(input
             .iter()
             .map(|card| {
                 // number of items in the intersection of the two sets
-                let intersection = (card.winning & card.numbers).count_ones();
+                let intersection = card.matching();
                 match intersection {
                     1.. => 1 << (intersection - 1), // 2^(intersection - 1)
                     0 => 0,
                 }
             })
-            .sum()
+            .sum())
     }
 
     type Output2 = usize;
 
     /// Part 2 took 0.002815ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
         // for each card index, how many numbers are matching
-        let matching: Vec<usize> = input
-            .iter()
-            .map(|card| (card.winning & card.numbers).count_ones() as usize)
-            .collect();
+        let matching: Vec<usize> = input.iter().map(Card::matching).collect();
         let mut cards = vec![1usize; matching.len()]; // we have one of each card initially
         let cards_len = cards.len();
         for i in 0..cards_len {
@@ -91,30 +157,52 @@ impl Day for Day04 {
                 }
             }
         }
-        cards.iter().sum()
+        Ok(cards.iter().sum())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use itertools::Itertools;
 
-    const INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+    use super::*;
 
     #[test]
     fn test_part1() {
-        let parsed = Day04::parse(INPUT).unwrap().1;
-        assert_eq!(Day04::part_1(&parsed), 13);
+        let parsed = Day04::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day04::part_1(&parsed, &Day04::EXAMPLE_PARAMS), Ok(13));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day04::parse(INPUT).unwrap().1;
-        assert_eq!(Day04::part_2(&parsed), 30);
+        let parsed = Day04::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day04::part_2(&parsed, &Day04::EXAMPLE_PARAMS), Ok(30));
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        // numbers which don't fit in a u128 bitmap
+        let parsed = Day04::parse("Card 1: 200 7 130 | 130 200 128 7").unwrap().1;
+        assert_eq!(Day04::part_1(&parsed, &Day04::EXAMPLE_PARAMS), Ok(4));
+    }
+
+    #[test]
+    fn test_parsers() {
+        let numbers = |cards: Vec<Card>| {
+            cards
+                .iter()
+                .map(|card| {
+                    (
+                        card.winning.iter().collect_vec(),
+                        card.numbers.iter().collect_vec(),
+                    )
+                })
+                .collect_vec()
+        };
+        let input = EXAMPLE.as_bytes();
+        assert_eq!(
+            numbers(parse_cards(input).unwrap().1),
+            numbers(scan_cards(input).unwrap().1)
+        );
     }
 }
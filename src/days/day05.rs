@@ -1,34 +1,76 @@
-use std::{collections::VecDeque, ops::Range};
+//! If You Give A Seed A Fertilizer
+//!
+//! Part 2 maps the whole `IntervalSet` of seeds through each table at once instead of every seed.
 
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
-    character::complete::{char, line_ending, not_line_ending, space1, u64},
+    character::complete::{line_ending, not_line_ending, u64},
     combinator::map,
-    multi::{separated_list0, separated_list1},
     sequence::{preceded, separated_pair},
     IResult,
 };
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    days::Params,
+    parser::{
+        helpers::{blank_line, blocks, lines_of, number_list},
+        SolveError,
+    },
+    util::{Interval, IntervalSet},
+};
+
+/// Example input from the puzzle description
+const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
 
-use crate::days::Day;
+temperature-to-humidity map:
+0 69 1
+1 0 69
 
-pub struct Day05;
+humidity-to-location map:
+60 56 37
+56 93 4";
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Almanac {
     pub seeds: Vec<u64>,
     tables: Vec<MappingTable>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MappingTable {
     pub mappings: Vec<Mapping>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mapping {
-    pub source: Range<u64>,
-    pub dest: Range<u64>,
+    pub source: Interval<u64>,
+    pub dest: Interval<u64>,
 }
 
 impl Almanac {
@@ -36,14 +78,14 @@ impl Almanac {
         self.tables.iter().fold(seed, |acc, table| table.dest(acc))
     }
 
-    fn seed_ranges(&self) -> Vec<Range<u64>> {
+    fn seed_ranges(&self) -> Vec<Interval<u64>> {
         self.seeds
             .as_slice()
             .chunks(2)
             .filter_map(|i| {
                 i.iter()
                     .collect_tuple()
-                    .map(|(start, len)| *start..*start + *len)
+                    .map(|(start, len)| Interval::new(*start, *start + *len))
             })
             .collect()
     }
@@ -53,236 +95,205 @@ impl MappingTable {
     fn dest(&self, source: u64) -> u64 {
         self.mappings
             .iter()
-            .find(|&mapping| mapping.source.contains(&source))
+            .find(|&mapping| mapping.source.contains(source))
             .map_or(source, |mapping| {
                 let offset = source - mapping.source.start;
                 mapping.dest.start + offset
             })
     }
-}
-
-impl From<Vec<Mapping>> for MappingTable {
-    fn from(mappings: Vec<Mapping>) -> Self {
-        Self { mappings }
-    }
-}
 
-/// Find the overlap between two ranges
-fn range_overlap(first: &Range<u64>, second: &Range<u64>) -> Option<Range<u64>> {
-    if first.end >= second.start && first.start <= second.end {
-        // some overlap
-        if first.start < second.start {
-            // either first is left or first fully contains second
-            if first.end > second.end {
-                // first fully contains second
-                Some(second.clone())
-            } else {
-                // first is left
-                Some(second.start..first.end)
+    /// Map a whole set of values, with the same rules as [`MappingTable::dest`]: the first mapping which contains a
+    /// value applies, and the values outside of all the mappings are unchanged
+    fn dest_set(&self, sources: &IntervalSet<u64>) -> IntervalSet<u64> {
+        let mut remaining = sources.clone();
+        let mut dests = IntervalSet::new();
+        for mapping in &self.mappings {
+            let mapped = remaining.intersection(&mapping.source.into());
+            for interval in mapped.iter() {
+                let start = mapping.dest.start + (interval.start - mapping.source.start);
+                dests.insert(Interval::new(start, start + interval.len()));
             }
-        } else if second.end > first.end {
-            // second fully contains first
-            Some(first.clone())
-        } else {
-            // second is left
-            Some(first.start..second.end)
+            remaining = remaining.difference(&mapped);
         }
-    } else {
-        // no overlap
-        None
+        dests.union(&remaining)
     }
 }
 
-/// Find all input mappings that overlap with the desired output mapping
-fn compatible_mappings(input_mappings: &[Mapping], output_mapping: &Mapping) -> Vec<Mapping> {
-    input_mappings
-        .iter()
-        .filter_map(|m| {
-            let Some(overlap) = range_overlap(&output_mapping.source, &m.dest) else {
-                return None;
-            };
-            let offset = overlap.start - m.dest.start;
-            let len = overlap.end - overlap.start;
-            let source_start = m.source.start + offset;
-            Some(Mapping {
-                source: source_start..source_start + len,
-                dest: overlap,
-            })
-        })
-        .collect()
+impl From<Vec<Mapping>> for MappingTable {
+    fn from(mappings: Vec<Mapping>) -> Self {
+        Self { mappings }
+    }
 }
 
 fn parse_seeds(input: &str) -> IResult<&str, Vec<u64>> {
-    map(
-        preceded(tag("seeds: "), separated_list0(space1, u64)),
-        |seeds| seeds,
-    )(input)
+    map(preceded(tag("seeds: "), number_list(u64)), |seeds| seeds)(input)
 }
 
 fn parse_mappings(input: &str) -> IResult<&str, Vec<Mapping>> {
     map(
-        separated_pair(
-            not_line_ending,
-            line_ending,
-            separated_list1(line_ending, separated_list1(char(' '), u64)),
-        ),
+        separated_pair(not_line_ending, line_ending, lines_of(number_list(u64))),
         |(_, items)| {
-            let mut mappings: VecDeque<Mapping> = items
+            // the values outside of all the mappings are left unchanged by `dest` and `dest_set`, no need for identity
+            // mappings in the gaps
+            items
                 .iter()
                 .map(|range_info| {
                     let source_start = range_info[1];
                     let dest_start = range_info[0];
                     let len = range_info[2];
                     Mapping {
-                        source: source_start..source_start + len,
-                        dest: dest_start..dest_start + len,
+                        source: Interval::new(source_start, source_start + len),
+                        dest: Interval::new(dest_start, dest_start + len),
                     }
                 })
-                .sorted_by(|a, b| a.dest.start.cmp(&b.dest.start))
-                .collect();
-            // add first identity map range if it doesn't start at 0
-            if let Some(first) = mappings.front() {
-                if first.dest.start > 0 {
-                    mappings.push_front(Mapping {
-                        source: 0..first.dest.start,
-                        dest: 0..first.dest.start,
-                    });
-                }
-            }
-            // add last identity map range up to u64::MAX
-            if let Some(last) = mappings.back() {
-                mappings.push_back(Mapping {
-                    source: last.dest.end..u64::MAX,
-                    dest: last.dest.end..u64::MAX,
-                });
-            }
-            // sort so that the range with the lowest output (dest) values comes first.
-            mappings
-                .into_iter()
-                .sorted_by(|a, b| a.dest.start.cmp(&b.dest.start))
+                .sorted_by_key(|mapping| mapping.source.start)
                 .collect()
         },
     )(input)
 }
 
-impl Day for Day05 {
-    type Input = Almanac;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        map(
-            separated_pair(
-                parse_seeds,
-                tag("\n\n"),
-                separated_list0(tag("\n\n"), parse_mappings),
-            ),
-            |(seeds, mappings)| Almanac {
-                seeds,
-                tables: mappings.iter().map(|m| m.clone().into()).collect(),
-            },
-        )(input)
-    }
-
-    type Output1 = u64;
-
-    /// Part 1 took 0.008237ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
-            .seeds
-            .iter()
-            .map(|s| input.location(*s))
-            .min()
-            .unwrap()
-    }
-
-    type Output2 = u64;
+fn parse(input: &str) -> IResult<&str, Almanac> {
+    map(
+        separated_pair(parse_seeds, blank_line, blocks(parse_mappings)),
+        |(seeds, mappings)| Almanac {
+            seeds,
+            tables: mappings.iter().map(|m| m.clone().into()).collect(),
+        },
+    )(input)
+}
 
-    /// Part 2 took 195.56049ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        // last mappings table
-        let output_table = input.tables.last().unwrap();
+/// Part 1 took 0.008237ms
+fn part_1(input: &Almanac, _params: &Params) -> Result<u64, SolveError> {
+    input
+        .seeds
+        .iter()
+        .map(|s| input.location(*s))
+        .min()
+        .ok_or_else(|| SolveError::invalid("there are no seeds"))
+}
 
-        // let's do a DFS to quickly find a path that connects outputs to inputs
+/// Follow all the seeds of the ranges at once through the tables, as a set of intervals
+fn part_2(input: &Almanac, _params: &Params) -> Result<u64, SolveError> {
+    let seeds: IntervalSet<u64> = input.seed_ranges().into_iter().collect();
+    input
+        .tables
+        .iter()
+        .fold(seeds, |values, table| table.dest_set(&values))
+        .min()
+        .ok_or(SolveError::NoSolution)
+}
 
-        // stack for DFS, initialize with all the mapping ranges in the last table,
-        // sorted by ascending dest.start (lower location comes first)
-        let mut stack: VecDeque<(Mapping, usize)> = output_table
-            .mappings
-            .iter()
-            .map(|m| (m.clone(), input.tables.len() - 1))
+impl_day!(
+    Day05,
+    5,
+    parse -> Almanac,
+    part_1 -> u64,
+    part_2 -> u64,
+    answers = [Some("35"), Some("46")],
+    title = "If You Give A Seed A Fertilizer",
+    tags = ["ranges"],
+    parse_version = 2,
+);
+
+/// Slow solutions which follow the puzzle literally, to cross-check the ones above
+#[cfg(test)]
+mod reference {
+    /// Read the almanac from its text and map every single seed of the ranges through the tables, with the mapping
+    /// lines exactly as written (hopeless for the real input)
+    pub fn lowest_location_of_ranges(input: &str) -> Option<u64> {
+        let numbers = |line: &str| -> Vec<u64> {
+            line.split_whitespace()
+                .filter_map(|n| n.parse().ok())
+                .collect()
+        };
+        let mut blocks = input.split("\n\n");
+        let seeds = numbers(blocks.next()?);
+        let tables: Vec<Vec<Vec<u64>>> = blocks
+            .map(|block| block.lines().skip(1).map(numbers).collect())
             .collect();
-
-        while let Some((mapping, level)) = stack.pop_front() {
-            if level == 0 {
-                // we are at the seed-to-soil level, so let's check if there are compatible seed ranges
-                let Some(seed_range) = input
-                    .seed_ranges()
-                    .iter()
-                    .find_map(|seed_range| range_overlap(seed_range, &mapping.source))
-                else {
-                    // no compatible seed range, let's keep looking
-                    continue;
-                };
-                // we have a matching seed range, so we're done, let's find the lowest location
-                return seed_range.map(|s| input.location(s)).min().unwrap();
-            }
-            // find all compatible mappings in the previous table and add them at the front of the stack
-            let input_table = &input.tables[level - 1];
-            compatible_mappings(&input_table.mappings, &mapping)
-                .into_iter()
-                .for_each(|m| stack.push_front((m, level - 1)));
-        }
-
-        panic!("Couldn't find a suitable seed");
+        seeds
+            .as_chunks()
+            .0
+            .iter()
+            .flat_map(|&[start, len]| start..start + len)
+            .map(|seed| {
+                tables.iter().fold(seed, |value, table| {
+                    table
+                        .iter()
+                        .find(|line| (line[1]..line[1] + line[2]).contains(&value))
+                        .map_or(value, |line| line[0] + value - line[1])
+                })
+            })
+            .min()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-
-    const INPUT: &str = "seeds: 79 14 55 13
+    use std::fmt::Write;
 
-seed-to-soil map:
-50 98 2
-52 50 48
-
-soil-to-fertilizer map:
-0 15 37
-37 52 2
-39 0 15
-
-fertilizer-to-water map:
-49 53 8
-0 11 42
-42 0 7
-57 7 4
-
-water-to-light map:
-88 18 7
-18 25 70
-
-light-to-temperature map:
-45 77 23
-81 45 19
-68 64 13
-
-temperature-to-humidity map:
-0 69 1
-1 0 69
-
-humidity-to-location map:
-60 56 37
-56 93 4";
+    use super::*;
+    use crate::{days::Day, util::gen::Rng};
 
     #[test]
     fn test_part1() {
-        let parsed = Day05::parse(INPUT).unwrap().1;
-        assert_eq!(Day05::part_1(&parsed), 35);
+        let parsed = Day05::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day05::part_1(&parsed, &Day05::EXAMPLE_PARAMS), Ok(35));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day05::parse(INPUT).unwrap().1;
-        assert_eq!(Day05::part_2(&parsed), 46);
+        let parsed = Day05::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day05::part_2(&parsed, &Day05::EXAMPLE_PARAMS), Ok(46));
+    }
+
+    #[test]
+    fn test_mapped_below_dest() {
+        // the values 10 to 14 are mapped even though they are below all the destinations
+        let parsed = Day05::parse("seeds: 10 1\n\nm map:\n50 10 5").unwrap().1;
+        assert_eq!(Day05::part_2(&parsed, &Day05::EXAMPLE_PARAMS), Ok(50));
+    }
+
+    /// A small almanac where each table moves blocks of the values below 200 around, some of them left unmapped
+    fn random_almanac(rng: &mut Rng) -> String {
+        let seeds: Vec<_> = (0..2 + rng.below(3))
+            .map(|_| format!("{} {}", rng.below(150), 1 + rng.below(50)))
+            .collect();
+        let mut input = format!("seeds: {}\n", seeds.join(" "));
+        for table in 0..7 {
+            let mut blocks = Vec::new();
+            let mut start = 0;
+            while start < 200 {
+                let len = (1 + rng.below(40)).min(200 - start);
+                blocks.push((start, len));
+                start += len;
+            }
+            let mut sources = blocks.clone();
+            rng.shuffle(&mut sources);
+            writeln!(input, "\nmap-{table} map:").unwrap();
+            let mut dest = 0;
+            for (source, len) in sources {
+                // the values of the skipped blocks keep their number
+                if !rng.chance(0.2) {
+                    writeln!(input, "{dest} {source} {len}").unwrap();
+                }
+                dest += len;
+            }
+        }
+        input
+    }
+
+    #[test]
+    fn test_reference() {
+        let mut rng = Rng::new(5);
+        for _ in 0..50 {
+            let input = random_almanac(&mut rng);
+            let parsed = Day05::parse(&input).unwrap().1;
+            assert_eq!(
+                Day05::part_2(&parsed, &Day05::EXAMPLE_PARAMS).ok(),
+                reference::lowest_location_of_ranges(&input),
+                "{input}"
+            );
+        }
     }
 }
@@ -1,133 +1,159 @@
+//! Wait For It
+//!
+//! The first winning time is binary searched with `partition_point_by` instead of solving the quadratic with floats.
+
 use std::ops::RangeInclusive;
 
 use nom::{
     bytes::complete::tag,
     character::complete::{line_ending, space1, u64},
     combinator::map,
-    multi::separated_list1,
     sequence::separated_pair,
     IResult,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::days::Day;
+use crate::{days::Params, parser::helpers::number_list, util::bisect::partition_point_by};
 
-pub struct Day06;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "Time:      7  15   30
+Distance:  9  40  200";
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Race {
     pub total_time: u64,
     pub record_distance: u64,
 }
 
-#[allow(
-    clippy::cast_precision_loss,
-    clippy::cast_sign_loss,
-    clippy::cast_possible_truncation
-)]
 fn winning_interval(race: &Race) -> RangeInclusive<u64> {
     // formula for winning the race: (t - x) * x > r, where t is the race total time and r is the previous record
-    // this gives a lower bound of 0.5 * (t - sqrt(t^2 - 4*r)) and a higher bound of 0.5*(t + sqrt(t^2 - 4*r))
-    let t = race.total_time as f64;
-    let r = race.record_distance as f64;
-    let sqrt = (t * t - 4. * r).sqrt();
-    let mut low = 0.5 * (t - sqrt);
-    let mut high = 0.5 * (t + sqrt);
-    // since we have to go strictly farther to win, in case of an integer bound we have to add/subtract one
-    if (low.trunc() - low).abs() <= f64::EPSILON {
-        low += 0.1;
-    }
-    if (high.trunc() - high).abs() <= f64::EPSILON {
-        high -= 0.1;
-    }
-    (low.ceil() as u64)..=(high.floor() as u64)
+    // the distance grows until x = t / 2 and is symmetric around it, so the winning times are x..=(t - x) for the
+    // first winning x before the middle
+    let t = race.total_time;
+    let wins = |x: u64| u128::from(t - x) * u128::from(x) > u128::from(race.record_distance);
+    let low = partition_point_by(0..t / 2 + 1, |x| !wins(x));
+    // when no time wins, low is past the middle and the range is empty (even for a race of zero milliseconds)
+    low..=t.saturating_sub(low)
 }
 
 /// How many integer solutions there are in the range
 fn interval_length(i: RangeInclusive<u64>) -> u64 {
-    i.end() - i.start() + 1
+    (i.end() + 1).saturating_sub(*i.start())
 }
 
 fn parse_times(input: &str) -> IResult<&str, Vec<u64>> {
     map(
-        separated_pair(tag("Time:"), space1, separated_list1(space1, u64)),
+        separated_pair(tag("Time:"), space1, number_list(u64)),
         |(_, numbers)| numbers,
     )(input)
 }
 
 fn parse_distances(input: &str) -> IResult<&str, Vec<u64>> {
     map(
-        separated_pair(tag("Distance:"), space1, separated_list1(space1, u64)),
+        separated_pair(tag("Distance:"), space1, number_list(u64)),
         |(_, numbers)| numbers,
     )(input)
 }
 
-impl Day for Day06 {
-    type Input = Vec<Race>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        map(
-            separated_pair(parse_times, line_ending, parse_distances),
-            |(times, distances)| {
-                times
-                    .iter()
-                    .zip(distances)
-                    .map(|(&time, dist)| Race {
-                        total_time: time,
-                        record_distance: dist,
-                    })
-                    .collect()
-            },
-        )(input)
-    }
+fn parse(input: &str) -> IResult<&str, Vec<Race>> {
+    map(
+        separated_pair(parse_times, line_ending, parse_distances),
+        |(times, distances)| {
+            times
+                .iter()
+                .zip(distances)
+                .map(|(&time, dist)| Race {
+                    total_time: time,
+                    record_distance: dist,
+                })
+                .collect()
+        },
+    )(input)
+}
 
-    type Output1 = u64;
+/// Part 1 took 2.9µs (6.1µs with parsing)
+fn part_1(input: &[Race], _params: &Params) -> u64 {
+    input
+        .iter()
+        .map(|r| interval_length(winning_interval(r)))
+        .product()
+}
 
-    /// Part 1 took 2.9µs (6.1µs with parsing)
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+/// took 1.7µs (4.9µs with parsing)
+fn part_2(input: &[Race], _params: &Params) -> u64 {
+    // collect the indididual numbers into a single number by concatenation
+    let (total_time, record_distance) =
         input
             .iter()
-            .map(|r| interval_length(winning_interval(r)))
-            .product()
-    }
-
-    type Output2 = u64;
+            .fold((String::new(), String::new()), |(acc_t, acc_r), race| {
+                (
+                    format!("{acc_t}{}", race.total_time),
+                    format!("{acc_r}{}", race.record_distance),
+                )
+            });
+    let race = Race {
+        total_time: total_time.parse().unwrap(),
+        record_distance: record_distance.parse().unwrap(),
+    };
+    interval_length(winning_interval(&race))
+}
 
-    /// took 1.7µs (4.9µs with parsing)
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        // collect the indididual numbers into a single number by concatenation
-        let (total_time, record_distance) =
-            input
-                .iter()
-                .fold((String::new(), String::new()), |(acc_t, acc_r), race| {
-                    (
-                        format!("{acc_t}{}", race.total_time),
-                        format!("{acc_r}{}", race.record_distance),
-                    )
-                });
-        let race = Race {
-            total_time: total_time.parse().unwrap(),
-            record_distance: record_distance.parse().unwrap(),
-        };
-        interval_length(winning_interval(&race))
+impl_day!(
+    Day06,
+    6,
+    parse -> Vec<Race>,
+    part_1 -> u64,
+    part_2 -> u64,
+    answers = [Some("288"), Some("71503")],
+    title = "Wait For It",
+    tags = ["math"],
+);
+
+/// Slow solutions which follow the puzzle literally, to cross-check the ones above
+#[cfg(test)]
+mod reference {
+    use super::Race;
+
+    /// Simulate the race for every time the button can be held (very slow for part 2)
+    pub fn ways_to_win(race: &Race) -> u64 {
+        let wins = (0..=race.total_time)
+            .filter(|&hold| hold * (race.total_time - hold) > race.record_distance)
+            .count();
+        u64::try_from(wins).unwrap()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "Time:      7  15   30
-Distance:  9  40  200";
+    use crate::{days::Day, util::gen::Rng};
 
     #[test]
     fn test_part1() {
-        let parsed = Day06::parse(INPUT).unwrap().1;
-        assert_eq!(Day06::part_1(&parsed), 288);
+        let parsed = Day06::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day06::part_1(&parsed, &Day06::EXAMPLE_PARAMS), Ok(288));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day06::parse(INPUT).unwrap().1;
-        assert_eq!(Day06::part_2(&parsed), 71503);
+        let parsed = Day06::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day06::part_2(&parsed, &Day06::EXAMPLE_PARAMS), Ok(71503));
+    }
+
+    #[test]
+    fn test_reference() {
+        let mut rng = Rng::new(6);
+        for _ in 0..500 {
+            let total_time = rng.below(60);
+            let race = Race {
+                total_time: total_time as u64,
+                record_distance: rng.below(total_time.pow(2) / 4 + 2) as u64,
+            };
+            assert_eq!(
+                interval_length(winning_interval(&race)),
+                reference::ways_to_win(&race),
+                "{race:?}"
+            );
+        }
     }
 }
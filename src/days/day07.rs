@@ -1,17 +1,23 @@
 use itertools::Itertools;
 use nom::{
-    character::complete::{anychar, char, line_ending, u64},
-    combinator::map,
-    multi::{count, separated_list0},
+    character::complete::{anychar, char, u64},
+    combinator::{map, map_opt},
+    multi::count,
     sequence::separated_pair,
     IResult,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::days::Day;
+use crate::{days::Params, parser::helpers::lines_of, util::IterExt};
 
-pub struct Day07;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
 
-#[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Debug)]
+#[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Debug, Serialize, Deserialize)]
 pub enum Card {
     Two,
     Three,
@@ -28,7 +34,7 @@ pub enum Card {
     Ace,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Hand {
     pub cards: [Card; 5],
     pub bid: u64,
@@ -59,14 +65,7 @@ impl Card {
 
 impl Hand {
     fn find_pattern(&self) -> Pattern {
-        let counts: Vec<usize> = self
-            .cards
-            .iter()
-            .counts()
-            .iter()
-            .sorted_by(|(_, &a), (_, &b)| b.cmp(&a)) // sort by descending count
-            .map(|(_, &count)| count) // only keep count
-            .collect();
+        let counts = self.cards.iter().counts_sorted_desc();
 
         // qty of most common card
         let first = counts.first().unwrap_or(&0);
@@ -86,14 +85,11 @@ impl Hand {
 
     /// Ugly but it works ¯\_(ツ)_/¯
     fn find_pattern_joker(&self) -> Pattern {
-        let counts_map = self.cards.iter().counts();
-        let jokers_count = *counts_map.get(&Card::Jack).unwrap_or(&0);
+        let (jokers, others): (Vec<_>, Vec<_>) =
+            self.cards.iter().partition(|&card| *card == Card::Jack);
+        let jokers_count = jokers.len();
         // get counts of all cards except jokers, sorted from highest to lowest
-        let counts: Vec<usize> = counts_map
-            .iter()
-            .sorted_by(|(_, &a), (_, &b)| b.cmp(&a)) // sort by descending count
-            .filter_map(|(&card, &count)| (!matches!(card, &Card::Jack)).then_some(count)) // only keep count (non-jack)
-            .collect();
+        let counts = others.into_iter().counts_sorted_desc();
 
         // qty of most common card
         let first = counts.first().unwrap_or(&0);
@@ -136,9 +132,11 @@ impl Hand {
 }
 
 /// Convert from a character to a card
-impl From<char> for Card {
-    fn from(value: char) -> Self {
-        match value {
+impl TryFrom<char> for Card {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
             '2' => Self::Two,
             '3' => Self::Three,
             '4' => Self::Four,
@@ -152,8 +150,8 @@ impl From<char> for Card {
             'Q' => Self::Queen,
             'K' => Self::King,
             'A' => Self::Ace,
-            _ => unimplemented!("Invalid card: {}", value),
-        }
+            _ => return Err(value),
+        })
     }
 }
 
@@ -194,71 +192,69 @@ impl PartialOrd for Hand {
     }
 }
 
-impl Day for Day07 {
-    type Input = Vec<Hand>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
-            line_ending,
-            map(
-                separated_pair(count(anychar, 5), char(' '), u64),
-                |(cards, bid)| {
-                    let cards: [char; 5] = cards[..5].try_into().unwrap();
-                    Hand {
-                        cards: cards.map(Into::into),
-                        bid,
-                    }
-                },
-            ),
-        )(input)
-    }
+fn parse(input: &str) -> IResult<&str, Vec<Hand>> {
+    let card = map_opt(anychar, |c| Card::try_from(c).ok());
+    let cards = map_opt(count(card, 5), |cards| cards.try_into().ok());
+    lines_of(map(
+        separated_pair(cards, char(' '), u64),
+        |(cards, bid)| Hand { cards, bid },
+    ))(input)
+}
 
-    type Output1 = usize;
-
-    /// Part 1 took 2.389249ms
-    #[allow(clippy::cast_possible_truncation)]
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
-            .iter()
-            .sorted()
-            .enumerate()
-            .map(|(i, hand)| (i + 1) * hand.bid as usize)
-            .sum()
-    }
+/// Part 1 took 2.389249ms
+#[allow(clippy::cast_possible_truncation)]
+fn part_1(input: &[Hand], _params: &Params) -> usize {
+    input
+        .iter()
+        .sorted()
+        .enumerate()
+        .map(|(i, hand)| (i + 1) * hand.bid as usize)
+        .sum()
+}
 
-    type Output2 = usize;
-
-    /// Part 2 took 2.926981ms
-    #[allow(clippy::cast_possible_truncation)]
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        input
-            .iter()
-            .sorted_by(|a, b| a.custom_cmp(b))
-            .enumerate()
-            .map(|(i, hand)| (i + 1) * hand.bid as usize)
-            .sum()
-    }
+/// Part 2 took 2.926981ms
+#[allow(clippy::cast_possible_truncation)]
+fn part_2(input: &[Hand], _params: &Params) -> usize {
+    input
+        .iter()
+        .sorted_by(|a, b| a.custom_cmp(b))
+        .enumerate()
+        .map(|(i, hand)| (i + 1) * hand.bid as usize)
+        .sum()
 }
 
+impl_day!(
+    Day07,
+    7,
+    parse -> Vec<Hand>,
+    part_1 -> usize,
+    part_2 -> usize,
+    answers = [Some("6440"), Some("5905")],
+    title = "Camel Cards",
+    tags = ["sorting"],
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483";
+    use crate::days::Day;
 
     #[test]
     fn test_part1() {
-        let parsed = Day07::parse(INPUT).unwrap().1;
-        assert_eq!(Day07::part_1(&parsed), 6440);
+        let parsed = Day07::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day07::part_1(&parsed, &Day07::EXAMPLE_PARAMS), Ok(6440));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day07::parse(INPUT).unwrap().1;
-        assert_eq!(Day07::part_2(&parsed), 5905);
+        let parsed = Day07::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day07::part_2(&parsed, &Day07::EXAMPLE_PARAMS), Ok(5905));
+    }
+
+    #[test]
+    fn test_invalid_card() {
+        let (rest, hands) = Day07::parse("32X3K 765").unwrap();
+        assert_eq!(hands.len(), 0);
+        assert_eq!(rest, "32X3K 765");
     }
 }
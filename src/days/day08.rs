@@ -1,71 +1,143 @@
-use std::collections::HashMap;
+//! Haunted Wasteland
+//!
+//! Part 2 takes the least common multiple of the cycles of the ghosts, which is only right because they have no
+//! offset.
 
 use itertools::{FoldWhile, Itertools};
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{alphanumeric1, char, line_ending},
-    combinator::map,
-    multi::{many1, separated_list0},
+    character::complete::{alphanumeric1, char},
+    combinator::{map, value},
+    multi::many1,
     sequence::{preceded, separated_pair, terminated},
     IResult,
 };
-use num::Integer;
-
-use crate::days::Day;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    days::{Day, Params},
+    parser::{
+        helpers::{blank_line, lines_of},
+        SolveError,
+    },
+    util::{numtheory, Interner},
+};
 
 pub struct Day08;
 
-#[derive(Debug)]
+/// Example input from the puzzle description
+const EXAMPLE: &str = "RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)";
+
+/// Example input for part 2 from the puzzle description
+const EXAMPLE_PART2: &str = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Dir {
     Left,
     Right,
 }
 
-#[derive(Debug)]
-pub struct Node {
-    pub name: String,
-    pub left: String,
-    pub right: String,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Node<'a> {
+    pub name: &'a str,
+    pub left: &'a str,
+    pub right: &'a str,
+}
+
+/// The nodes by id, each with the ids of its left and right neighbors
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Network<'a> {
+    #[serde(borrow)]
+    pub names: Interner<'a>,
+    pub links: Vec<[u32; 2]>,
+}
+
+impl<'a> Network<'a> {
+    fn new(nodes: &[Node<'a>]) -> Self {
+        // the nodes get the first ids so that they index the links
+        let mut names: Interner = nodes.iter().map(|n| n.name).collect();
+        let mut links = vec![[0, 0]; names.len()];
+        for node in nodes {
+            links[names.intern(node.name) as usize] =
+                [names.intern(node.left), names.intern(node.right)];
+        }
+        Self { names, links }
+    }
 }
 
 /// Count how many steps from a start node until the end condition is met
 ///
 /// For part 1, end condition is that the node is named 'ZZZ'. For part 2, any node that ends with 'Z'
+///
+/// Once every pair of instruction and node has been seen, the walk is stuck in a cycle that never meets the end
+/// condition.
 fn count_steps(
     instructions: &[Dir],
-    nodes: &HashMap<String, Node>,
-    start_node: &Node,
+    network: &Network,
+    start: u32,
     part2: bool,
-) -> usize {
-    let (count, _) = instructions
+) -> Result<usize, SolveError> {
+    let is_end = |node: u32| {
+        let name = network.names.name(node);
+        (!part2 && name == "ZZZ") || (part2 && name.ends_with('Z'))
+    };
+    let max_steps = instructions.len() * network.links.len();
+    let (count, node) = instructions
         .iter()
         .cycle()
-        .fold_while((0, start_node), |(i, n), instr| {
+        .take(max_steps)
+        .fold_while((0, start), |(i, n), instr| {
+            let Some(&[left, right]) = network.links.get(n as usize) else {
+                // the node is only mentioned as a neighbor, the walk can't go on
+                return FoldWhile::Done((i, n));
+            };
             let next = match instr {
-                Dir::Left => nodes.get(&n.left).unwrap(),
-                Dir::Right => nodes.get(&n.right).unwrap(),
+                Dir::Left => left,
+                Dir::Right => right,
             };
-            if (!part2 && next.name == "ZZZ") || (part2 && next.name.ends_with('Z')) {
+            if is_end(next) {
                 FoldWhile::Done((i + 1, next))
             } else {
                 FoldWhile::Continue((i + 1, next))
             }
         })
         .into_inner();
-
-    count
+    if is_end(node) {
+        Ok(count)
+    } else {
+        Err(SolveError::invalid(format!(
+            "the end can't be reached from {}",
+            network.names.name(start)
+        )))
+    }
 }
 
 fn parse_instructions(input: &str) -> IResult<&str, Vec<Dir>> {
-    many1(map(alt((char('L'), char('R'))), |c| match c {
-        'L' => Dir::Left,
-        'R' => Dir::Right,
-        _ => unimplemented!(),
-    }))(input)
+    many1(alt((
+        value(Dir::Left, char('L')),
+        value(Dir::Right, char('R')),
+    )))(input)
 }
 
-fn parse_node(input: &str) -> IResult<&str, Node> {
+fn parse_node(input: &str) -> IResult<&str, Node<'_>> {
     map(
         separated_pair(
             alphanumeric1::<&str, _>,
@@ -78,56 +150,61 @@ fn parse_node(input: &str) -> IResult<&str, Node> {
                 ),
             ),
         ),
-        |(name, (left, right))| Node {
-            name: name.to_string(),
-            left: left.to_string(),
-            right: right.to_string(),
-        },
+        |(name, (left, right))| Node { name, left, right },
     )(input)
 }
 
 impl Day for Day08 {
-    type Input = (Vec<Dir>, HashMap<String, Node>);
+    const DAY: u8 = 8;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const EXAMPLE_PART2: &'static str = EXAMPLE_PART2;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("2"), Some("6")];
+
+    fn title() -> &'static str {
+        "Haunted Wasteland"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["graph", "math"]
+    }
+
+    type Input<'a> = (Vec<Dir>, Network<'a>);
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
         map(
-            separated_pair(
-                parse_instructions,
-                tag("\n\n"),
-                separated_list0(line_ending, parse_node),
-            ),
-            |(instructions, nodes)| {
-                (
-                    instructions,
-                    nodes.into_iter().map(|n| (n.name.clone(), n)).collect(),
-                )
-            },
+            separated_pair(parse_instructions, blank_line, lines_of(parse_node)),
+            |(instructions, nodes)| (instructions, Network::new(&nodes)),
         )(input)
     }
 
     type Output1 = usize;
 
     /// Part 1 took 378.1µs
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        // We stored all the nodes in a HashMap with the node name as the key
-        let (instructions, nodes) = input;
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
+        let (instructions, network) = input;
         // Use the "AAA" node as a starting point
-        count_steps(instructions, nodes, nodes.get("AAA").unwrap(), false)
+        let start = network
+            .names
+            .get("AAA")
+            .ok_or_else(|| SolveError::invalid("there is no AAA node"))?;
+        count_steps(instructions, network, start, false)
     }
 
     type Output2 = usize;
 
     /// Part 2 took 2.2817ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let (instructions, nodes) = input;
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
+        let (instructions, network) = input;
         // Find all the starting nodes (ending with 'A') and count how long until we reach and end node for each
-        let counts: Vec<usize> = nodes
+        let counts: Vec<usize> = network
+            .names
             .iter()
-            .filter_map(|(k, v)| k.ends_with('A').then_some(v))
-            .map(|n| count_steps(instructions, nodes, n, true))
-            .collect();
+            .filter_map(|(id, name)| name.ends_with('A').then_some(id))
+            .map(|id| count_steps(instructions, network, id, true))
+            .try_collect()?;
         // Get the lowest common multiplier between all the counts
-        counts.into_iter().reduce(|acc, e| acc.lcm(&e)).unwrap()
+        numtheory::lcm_all(counts)
+            .ok_or_else(|| SolveError::invalid("there is no node ending with A"))
     }
 }
 
@@ -137,34 +214,27 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let input = "RL
-
-AAA = (BBB, CCC)
-BBB = (DDD, EEE)
-CCC = (ZZZ, GGG)
-DDD = (DDD, DDD)
-EEE = (EEE, EEE)
-GGG = (GGG, GGG)
-ZZZ = (ZZZ, ZZZ)";
-
-        let parsed = Day08::parse(input).unwrap().1;
-        assert_eq!(Day08::part_1(&parsed), 2);
+        let parsed = Day08::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day08::part_1(&parsed, &Day08::EXAMPLE_PARAMS), Ok(2));
     }
 
     #[test]
     fn test_part2() {
-        let input = "LR
-
-11A = (11B, XXX)
-11B = (XXX, 11Z)
-11Z = (11B, XXX)
-22A = (22B, XXX)
-22B = (22C, 22C)
-22C = (22Z, 22Z)
-22Z = (22B, 22B)
-XXX = (XXX, XXX)";
+        let parsed = Day08::parse(EXAMPLE_PART2).unwrap().1;
+        assert_eq!(Day08::part_2(&parsed, &Day08::EXAMPLE_PARAMS), Ok(6));
+    }
 
-        let parsed = Day08::parse(input).unwrap().1;
-        assert_eq!(Day08::part_2(&parsed), 6);
+    #[test]
+    fn test_unreachable_end() {
+        for input in [
+            "L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)",
+            "L\n\nAAA = (BBB, ZZZ)",
+        ] {
+            let parsed = Day08::parse(input).unwrap().1;
+            assert!(
+                Day08::part_1(&parsed, &Day08::EXAMPLE_PARAMS).is_err(),
+                "{input}"
+            );
+        }
     }
 }
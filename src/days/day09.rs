@@ -1,27 +1,41 @@
 use itertools::Itertools;
-use nom::{
-    character::complete::{i64, line_ending, space1},
-    multi::{separated_list0, separated_list1},
-    IResult,
-};
+use nom::IResult;
 
-use crate::days::Day;
+use crate::{
+    days::{Day, Params},
+    parser::{
+        self,
+        helpers::{integer, lines_of, number_list},
+        scan::Scanner,
+        SolveError,
+    },
+    util::IterExt,
+};
 
 pub struct Day09;
 
-fn extrapolate(sensor: &[i64], part2: bool) -> i64 {
+/// Example input from the puzzle description
+const EXAMPLE: &str = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+/// Extrapolate the next value of the history (or the previous one for part 2), failing if the differences run out
+/// before they are all equal
+fn extrapolate(sensor: &[i64], part2: bool) -> Result<i64, SolveError> {
     let mut stack: Vec<Vec<i64>> = Vec::with_capacity(100);
     stack.push(sensor.into());
     let mut extrapolated = loop {
-        let diff: Vec<_> = stack
-            .last()
-            .unwrap()
+        let diff: Vec<_> = stack[stack.len() - 1]
             .iter()
-            .tuple_windows()
-            .map(|(a, b)| b - a)
+            .copied()
+            .pairwise_diffs()
             .collect();
         match diff.iter().all_equal_value() {
-            Err(None) => unimplemented!("empty list"),
+            Err(None) => {
+                return Err(SolveError::invalid(format!(
+                    "the differences of the history {sensor:?} are never all equal"
+                )))
+            }
             Err(Some(_)) => {
                 // not all values are equal, continue looking
                 stack.push(diff);
@@ -33,34 +47,74 @@ fn extrapolate(sensor: &[i64], part2: bool) -> i64 {
         }
     };
     // extrapolate the previous list until we reach the original sensor list
+    // the lists of the stack are never empty, since the empty differences were rejected above
     while let Some(list) = stack.pop() {
         if part2 {
-            extrapolated = list.first().unwrap() - extrapolated;
+            extrapolated = list[0] - extrapolated;
         } else {
-            extrapolated += list.last().unwrap();
+            extrapolated += list[list.len() - 1];
         }
     }
-    extrapolated
+    Ok(extrapolated)
+}
+
+/// Parse the history of each sensor with nom, to compare with `scan_sensors` (see the `nom-parsers` feature)
+fn parse_sensors(input: &[u8]) -> IResult<&[u8], Vec<Vec<i64>>> {
+    lines_of(number_list(integer))(input)
+}
+
+/// Read the history of each sensor with `parser::scan`
+fn scan_sensors(input: &[u8]) -> IResult<&[u8], Vec<Vec<i64>>> {
+    let mut scanner = Scanner::new(input);
+    let sensors = scanner
+        .lines()
+        .map(|mut line| {
+            let values = line.int_list()?;
+            line.finish()?;
+            Ok(values)
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((scanner.rest(), sensors))
 }
 
 impl Day for Day09 {
-    type Input = Vec<Vec<i64>>;
+    const DAY: u8 = 9;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("114"), Some("2")];
+
+    fn title() -> &'static str {
+        "Mirage Maintenance"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["math"]
+    }
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(line_ending, separated_list1(space1, i64))(input)
+    type Input<'a> = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        parser::from_bytes(input, Self::parse_bytes(input.as_bytes()))
+    }
+
+    fn parse_bytes(input: &[u8]) -> IResult<&[u8], Self::Input<'_>> {
+        if cfg!(feature = "nom-parsers") {
+            parse_sensors(input)
+        } else {
+            scan_sensors(input)
+        }
     }
 
     type Output1 = i64;
 
     /// Part 1 took 78.5µs
-    fn part_1(input: &Self::Input) -> Self::Output1 {
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
         input.iter().map(|sensor| extrapolate(sensor, false)).sum()
     }
 
     type Output2 = i64;
 
     /// Part 2 took 75.8µs
-    fn part_2(input: &Self::Input) -> Self::Output2 {
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
         input.iter().map(|sensor| extrapolate(sensor, true)).sum()
     }
 }
@@ -69,19 +123,31 @@ impl Day for Day09 {
 mod tests {
     use super::*;
 
-    const INPUT: &str = "0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45";
-
     #[test]
     fn test_part1() {
-        let parsed = Day09::parse(INPUT).unwrap().1;
-        assert_eq!(Day09::part_1(&parsed), 114);
+        let parsed = Day09::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day09::part_1(&parsed, &Day09::EXAMPLE_PARAMS), Ok(114));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day09::parse(INPUT).unwrap().1;
-        assert_eq!(Day09::part_2(&parsed), 2);
+        let parsed = Day09::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day09::part_2(&parsed, &Day09::EXAMPLE_PARAMS), Ok(2));
+    }
+
+    #[test]
+    fn test_parsers() {
+        let input = EXAMPLE.as_bytes();
+        assert_eq!(
+            parse_sensors(input).unwrap().1,
+            scan_sensors(input).unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_single_value() {
+        let parsed = Day09::parse("5").unwrap().1;
+        assert!(Day09::part_1(&parsed, &Day09::EXAMPLE_PARAMS).is_err());
+        assert!(Day09::part_2(&parsed, &Day09::EXAMPLE_PARAMS).is_err());
     }
 }
@@ -1,35 +1,36 @@
-use std::collections::{HashMap, HashSet};
-
-use itertools::Itertools;
-use nom::{
-    character::complete::{line_ending, not_line_ending},
-    combinator::map,
-    multi::separated_list0,
-    IResult,
+use nom::IResult;
+use owo_colors::Style;
+use serde::{Deserialize, Serialize};
+use tracing::{enabled, trace, Level};
+
+use crate::{
+    days::{Day, Params},
+    parser::SolveError,
+    util::{Direction, Grid, GridDisplay, Polygon, Vec2},
 };
-use owo_colors::{OwoColorize, Style};
-
-use crate::days::Day;
-
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
-pub enum Dir {
-    North,
-    East,
-    South,
-    West,
-}
-
-const DIRS: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
 
 pub struct Day10;
 
-#[derive(Debug, Eq, Hash, PartialEq, Clone)]
-pub struct Point {
-    pub x: isize,
-    pub y: isize,
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = "..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...";
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+/// Example input for part 2 from the puzzle description
+const EXAMPLE_PART2: &str = ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub enum Pipe {
     NorthEast,
     NorthSouth,
@@ -39,155 +40,85 @@ pub enum Pipe {
     SouthWest,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Tile {
     Pipe(Pipe),
     Ground,
     Start,
 }
 
-impl Point {
-    fn at_dir(&self, dir: &Dir) -> Self {
-        match dir {
-            Dir::North => Self {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Dir::East => Self {
-                x: self.x + 1,
-                y: self.y,
-            },
-            Dir::South => Self {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Dir::West => Self {
-                x: self.x - 1,
-                y: self.y,
-            },
-        }
-    }
-}
-
 impl Pipe {
-    /// Construct a pipe tile from two sorted directions
-    fn from_dirs(dir1: &Dir, dir2: &Dir) -> Self {
-        match (dir1, dir2) {
-            (Dir::North, Dir::East) => Pipe::NorthEast,
-            (Dir::North, Dir::South) => Pipe::NorthSouth,
-            (Dir::North, Dir::West) => Pipe::NorthWest,
-            (Dir::East, Dir::South) => Pipe::EastSouth,
-            (Dir::East, Dir::West) => Pipe::EastWest,
-            (Dir::South, Dir::West) => Pipe::SouthWest,
-            _ => unimplemented!("sort the dirs"),
-        }
-    }
-
-    fn dirs(&self) -> [Dir; 2] {
+    fn dirs(&self) -> [Direction; 2] {
         match self {
-            Pipe::NorthEast => [Dir::North, Dir::East],
-            Pipe::NorthSouth => [Dir::North, Dir::South],
-            Pipe::NorthWest => [Dir::North, Dir::West],
-            Pipe::EastSouth => [Dir::East, Dir::South],
-            Pipe::EastWest => [Dir::East, Dir::West],
-            Pipe::SouthWest => [Dir::South, Dir::West],
+            Pipe::NorthEast => [Direction::North, Direction::East],
+            Pipe::NorthSouth => [Direction::North, Direction::South],
+            Pipe::NorthWest => [Direction::North, Direction::West],
+            Pipe::EastSouth => [Direction::East, Direction::South],
+            Pipe::EastWest => [Direction::East, Direction::West],
+            Pipe::SouthWest => [Direction::South, Direction::West],
         }
     }
 
-    /// Does the pipe lead to the north
-    fn north(&self) -> bool {
-        matches!(self, Pipe::NorthEast | Pipe::NorthSouth | Pipe::NorthWest)
-    }
-
-    /// Does the pipe lead to the east
-    fn east(&self) -> bool {
-        matches!(self, Pipe::NorthEast | Pipe::EastSouth | Pipe::EastWest)
-    }
-
-    /// Does the pipe lead to the south
-    fn south(&self) -> bool {
-        matches!(self, Pipe::NorthSouth | Pipe::EastSouth | Pipe::SouthWest)
-    }
-
-    /// Does the pipe lead to the west
-    fn west(&self) -> bool {
-        matches!(self, Pipe::NorthWest | Pipe::EastWest | Pipe::SouthWest)
+    /// Can the pipe be entered by moving in `dir`, i.e. does it lead back to where we came from
+    fn accepts(&self, dir: Direction) -> bool {
+        self.dirs().contains(&dir.opposite())
     }
 }
 
-impl std::fmt::Display for Pipe {
-    /// Prettier ASCII representation
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Pipe::NorthEast => write!(f, "└"),
-            Pipe::NorthSouth => write!(f, "|"),
-            Pipe::NorthWest => write!(f, "┘"),
-            Pipe::EastSouth => write!(f, "┌"),
-            Pipe::EastWest => write!(f, "—"),
-            Pipe::SouthWest => write!(f, "┐"),
+impl From<&Pipe> for char {
+    /// Prettier representation
+    fn from(pipe: &Pipe) -> Self {
+        match pipe {
+            Pipe::NorthEast => '└',
+            Pipe::NorthSouth => '|',
+            Pipe::NorthWest => '┘',
+            Pipe::EastSouth => '┌',
+            Pipe::EastWest => '—',
+            Pipe::SouthWest => '┐',
         }
     }
 }
 
-/// Create a map of the tiles in the grid, with their coordinates as key.
-#[allow(clippy::cast_possible_wrap)]
-fn get_grid_hashmap(grid: &[Vec<Tile>]) -> HashMap<Point, &Tile> {
-    grid.iter()
-        .enumerate()
-        .flat_map(move |(y, row)| {
-            row.iter().enumerate().map(move |(x, tile)| {
-                (
-                    Point {
-                        x: x as isize,
-                        y: y as isize,
-                    },
-                    tile,
-                )
-            })
-        })
-        .collect()
+/// Position of the start tile
+fn find_start(grid: &Grid<Tile>) -> Result<Vec2<isize>, SolveError> {
+    grid.positions(|t| matches!(t, Tile::Start))
+        .next()
+        .ok_or_else(|| SolveError::invalid("there is no start tile"))
 }
 
-/// Find all the coordinates that belong to the loop
-fn get_loop_positions(grid: &HashMap<Point, &Tile>) -> HashSet<Point> {
-    let mut pos: Point = grid
-        .iter()
-        .find(|(_, &t)| matches!(t, Tile::Start))
-        .unwrap()
-        .0
-        .clone();
-
-    let mut pipes: HashSet<Point> = HashSet::with_capacity(1000);
-    pipes.insert(pos.clone());
-    'outer: loop {
-        let Some(tile) = grid.get(&pos) else {
-            unreachable!();
-        };
+/// Find the positions of the tiles of the loop, in order starting from the start tile
+///
+/// Fails if the pipes lead to a dead end before getting back to the start.
+fn get_loop(grid: &Grid<Tile>) -> Result<Vec<Vec2<isize>>, SolveError> {
+    let mut pos = find_start(grid)?;
 
+    let mut visited = Grid::new(grid.width(), grid.height(), false);
+    let mut pipes = vec![pos];
+    visited[pos] = true;
+    'outer: loop {
         // only look in directions where the pipe is connected to
-        let dirs: Vec<Dir> = match tile {
-            Tile::Start => DIRS.into(), // look in all 4 directions at the start
-            Tile::Pipe(pipe) => pipe.dirs().into(),
+        let dirs: Vec<Direction> = match grid[pos] {
+            Tile::Start => Direction::ALL.into(), // look in all 4 directions at the start
+            Tile::Pipe(ref pipe) => pipe.dirs().into(),
             Tile::Ground => unreachable!(),
         };
 
+        let mut stepped = false;
         for dir in dirs {
-            let next_pos = pos.at_dir(&dir);
-            let Some(next) = grid.get(&next_pos) else {
+            let next_pos = pos.step(dir);
+            let Some(next) = grid.get(next_pos) else {
                 continue;
             };
             match next {
                 Tile::Pipe(pipe) => {
-                    if pipes.contains(&next_pos) {
+                    if visited[next_pos] {
                         continue;
                     }
-                    if (dir == Dir::North && pipe.south())
-                        || (dir == Dir::East && pipe.west())
-                        || (dir == Dir::South && pipe.north())
-                        || (dir == Dir::West && pipe.east())
-                    {
-                        pipes.insert(next_pos.clone());
+                    if pipe.accepts(dir) {
+                        visited[next_pos] = true;
+                        pipes.push(next_pos);
                         pos = next_pos;
+                        stepped = true;
                         break;
                     }
                 }
@@ -199,199 +130,178 @@ fn get_loop_positions(grid: &HashMap<Point, &Tile>) -> HashSet<Point> {
                     // We went around the loop
                     break 'outer;
                 }
-                Tile::Ground => {
-                    continue;
-                }
+                Tile::Ground => {}
             }
         }
+        if !stepped {
+            return Err(SolveError::invalid(format!(
+                "the loop is open at ({}, {})",
+                pos.x, pos.y
+            )));
+        }
     }
-    pipes
+    Ok(pipes)
 }
 
-/// Get the corresponding pipe tile for the starting position
-fn convert_start_pipe(grid: &HashMap<Point, &Tile>) -> (Point, Tile) {
-    let (start_pos, _) = grid
-        .iter()
-        .find(|(_, &t)| matches!(t, Tile::Start))
-        .unwrap();
-    let (dir1, dir2): (&Dir, &Dir) = DIRS
-        .iter()
-        .filter(|dir| {
-            let pos = start_pos.at_dir(dir);
-            match grid.get(&pos) {
-                Some(Tile::Pipe(pipe)) => match (dir, pipe) {
-                    (Dir::North, p) if p.south() => true,
-                    (Dir::East, p) if p.west() => true,
-                    (Dir::South, p) if p.north() => true,
-                    (Dir::West, p) if p.east() => true,
-                    _ => false,
-                },
-                _ => false,
-            }
-        })
-        .sorted()
-        .collect_tuple()
-        .unwrap();
-    let start_pipe = Pipe::from_dirs(dir1, dir2);
-    (start_pos.clone(), Tile::Pipe(start_pipe))
+/// Count the tiles enclosed by the loop
+///
+/// The centers of the tiles of the loop are the vertices of a polygon, and the tiles inside of the loop are the points
+/// with integer coordinates inside of that polygon, which Pick's theorem gives from its area.
+fn count_inside(grid: &Grid<Tile>, pipes: &[Vec2<isize>]) -> usize {
+    let polygon = Polygon::new(pipes.to_vec());
+    if enabled!(Level::TRACE) {
+        trace!(
+            "tiles inside (i) and outside (o) the loop:\n{}",
+            draw_inside(grid, &polygon)
+        );
+    }
+    polygon.interior_points().unsigned_abs()
+}
+
+/// Draw the loop and whether each other tile is inside (yellow) or outside (blue) of it
+fn draw_inside(grid: &Grid<Tile>, polygon: &Polygon<isize>) -> String {
+    let mut in_loop = Grid::new(grid.width(), grid.height(), false);
+    for &pos in polygon.vertices() {
+        in_loop[pos] = true;
+    }
+    let on_loop = |pos, tile: &Tile| match tile {
+        Tile::Pipe(_) => in_loop[pos],
+        Tile::Start => true,
+        Tile::Ground => false,
+    };
+    GridDisplay::with_glyphs(grid, |pos, tile| match tile {
+        Tile::Pipe(pipe) if in_loop[pos] => pipe.into(),
+        Tile::Start => 'S',
+        _ if polygon.contains(pos) => 'i',
+        _ => 'o',
+    })
+    .styled(|pos, tile| {
+        if on_loop(pos, tile) {
+            Style::new()
+        } else if polygon.contains(pos) {
+            Style::new().yellow()
+        } else {
+            Style::new().blue()
+        }
+    })
+    .to_string()
 }
 
 impl Day for Day10 {
-    type Input = Vec<Vec<Tile>>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
-            line_ending,
-            map(not_line_ending, |s: &str| {
-                s.chars()
-                    .map(|c| match c {
-                        '|' => Tile::Pipe(Pipe::NorthSouth),
-                        '-' => Tile::Pipe(Pipe::EastWest),
-                        'L' => Tile::Pipe(Pipe::NorthEast),
-                        'J' => Tile::Pipe(Pipe::NorthWest),
-                        '7' => Tile::Pipe(Pipe::SouthWest),
-                        'F' => Tile::Pipe(Pipe::EastSouth),
-                        '.' => Tile::Ground,
-                        'S' => Tile::Start,
-                        _ => unimplemented!(),
-                    })
-                    .collect()
-            }),
-        )(input)
+    const DAY: u8 = 10;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const EXAMPLE_PART2: &'static str = EXAMPLE_PART2;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("8"), Some("8")];
+
+    fn title() -> &'static str {
+        "Pipe Maze"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["grid", "geometry"]
+    }
+
+    type Input<'a> = Grid<Tile>;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        let (rest, (grid, [])) = Grid::parse(input, [], |c| {
+            Some(match c {
+                '|' => Tile::Pipe(Pipe::NorthSouth),
+                '-' => Tile::Pipe(Pipe::EastWest),
+                'L' => Tile::Pipe(Pipe::NorthEast),
+                'J' => Tile::Pipe(Pipe::NorthWest),
+                '7' => Tile::Pipe(Pipe::SouthWest),
+                'F' => Tile::Pipe(Pipe::EastSouth),
+                '.' => Tile::Ground,
+                'S' => Tile::Start,
+                _ => return None,
+            })
+        })?;
+        Ok((rest, grid))
     }
 
     type Output1 = usize;
 
     /// Part 1 took 2.935601ms
-    #[allow(clippy::cast_possible_wrap, clippy::too_many_lines)]
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let grid = get_grid_hashmap(input);
-        let loop_pipes = get_loop_positions(&grid);
-        loop_pipes.len() / 2
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
+        Ok(get_loop(input)?.len() / 2)
     }
 
     type Output2 = usize;
 
-    /// Part 2 took 3.7294ms (without printing)
-    #[allow(
-        clippy::too_many_lines,
-        clippy::cast_possible_wrap,
-        clippy::cast_sign_loss
-    )]
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut grid = get_grid_hashmap(input);
-        let loop_pipes = get_loop_positions(&grid);
-
-        // replace starting tile by the corresponding pipe
-        let (start_pos, start_tile) = convert_start_pipe(&grid);
-        grid.insert(start_pos, &start_tile); // replaces the original start tile
-
-        let size_y = input.len() as isize;
-        let size_x = input.first().unwrap().len() as isize;
-
-        // iterate through the grid and keep track of whether we are inside or outside the loop
-        // We have to switch from inside to outside or vice versa when we encounter a vertical pipe, or when a corner
-        // pipe follows another corner pipe with the complementary vertical segment.
-        // E.g. we are outside, we find a └ pipe, we are still outside, but if we then encounter a ┐ pipe later,
-        // then we switch to inside. On the countrary, if we later encounter a ┘ pipe, then we stay outside.
-        let mut inside_count = 0;
-        for y in 0..size_y {
-            let mut inside: bool = false; // we start at each row outside the loop
-            let mut maybe_switch: Option<Dir> = None;
-            for x in 0..size_x {
-                let pos = Point { x, y };
-                let Some(tile) = grid.get(&pos) else {
-                    unreachable!("there are tiles in this range");
-                };
-
-                let style = if inside {
-                    Style::new().yellow()
-                } else {
-                    Style::new().blue()
-                };
-                if loop_pipes.contains(&pos) {
-                    let Tile::Pipe(pipe) = tile else {
-                        unreachable!("pipe loop only contains pipes");
-                    };
-                    if pipe.north() || pipe.south() {
-                        if pipe.north() && pipe.south() {
-                            // fully vertical pipe, we switch
-                            inside = !inside;
-                            maybe_switch = None;
-                        } else if let Some(last_vert) = maybe_switch {
-                            // we had encountered a pipe with a vertical segment earlier
-                            if (last_vert == Dir::North && pipe.south())
-                                || (last_vert == Dir::South && pipe.north())
-                            {
-                                // if the current vertical segment is complementary, we switch
-                                inside = !inside;
-                            }
-                            maybe_switch = None; // in all cases, we reset the pending state
-                        } else {
-                            // it's the first time we encounter a vertical pipe segment, let's register it in the
-                            // pending state
-                            maybe_switch = pipe
-                                .south()
-                                .then_some(Dir::South)
-                                .or(pipe.north().then_some(Dir::North));
-                        }
-                    }
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
+        Ok(count_inside(input, &get_loop(input)?))
+    }
 
-                    if cfg!(not(test)) {
-                        // drawing the grid and state of the right of the current tile (inside = yellow, outside = blue)
-                        let style = if inside {
-                            Style::new().yellow()
-                        } else {
-                            Style::new().blue()
-                        };
-                        print!("{}", pipe.style(style));
-                    }
-                } else if inside {
-                    inside_count += 1;
-                    if cfg!(not(test)) {
-                        print!("{}", "i".style(style));
-                    }
-                } else if cfg!(not(test)) {
-                    print!("{}", "o".style(style));
-                }
-            }
-            if cfg!(not(test)) {
-                println!();
-            }
-        }
-        inside_count
+    const SHARES_WORK: bool = true;
+
+    /// Both parts need the positions of the loop, so we only follow it once
+    fn solve_both(
+        input: &Self::Input<'_>,
+        _params: &Params,
+    ) -> Result<(Self::Output1, Self::Output2), SolveError> {
+        let pipes = get_loop(input)?;
+        Ok((pipes.len() / 2, count_inside(input, &pipes)))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::flood::{flood_fill, Connectivity};
+
+    /// Count the tiles inside of the loop by filling the outside of the loop on a grid with twice the resolution, where
+    /// the fill can go between two pipes which are next to each other but not connected
+    #[allow(clippy::cast_possible_wrap)]
+    fn count_inside_by_flood(grid: &Grid<Tile>, pipes: &[Vec2<isize>]) -> usize {
+        // tile (x, y) is at (2x + 1, 2y + 1), which leaves a border of one cell around the grid
+        let double = |p: Vec2<isize>| p * 2 + Vec2::new(1, 1);
+        let mut walls = Grid::new(grid.width() * 2 + 1, grid.height() * 2 + 1, false);
+        for (i, &pos) in pipes.iter().enumerate() {
+            let next = pipes[(i + 1) % pipes.len()];
+            walls[double(pos)] = true;
+            walls[double(pos) + next - pos] = true;
+        }
+        let outside = flood_fill(&walls, Vec2::new(0, 0), Connectivity::Four, |&wall| !wall);
+        let outside_tiles = outside
+            .iter()
+            .filter(|p| p.x % 2 == 1 && p.y % 2 == 1)
+            .count();
+        grid.cells().len() - pipes.len() - outside_tiles
+    }
 
     #[test]
     fn test_part1() {
-        let input = "..F7.
-.FJ|.
-SJ.L7
-|F--J
-LJ...";
-        let parsed = Day10::parse(input).unwrap().1;
-        assert_eq!(Day10::part_1(&parsed), 8);
+        let parsed = Day10::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day10::part_1(&parsed, &Day10::EXAMPLE_PARAMS), Ok(8));
     }
 
     #[test]
     fn test_part2() {
-        let input = ".F----7F7F7F7F-7....
-.|F--7||||||||FJ....
-.||.FJ||||||||L7....
-FJL7L7LJLJ||LJ.L-7..
-L--J.L7...LJS7F-7L7.
-....F-J..F7FJ|L7L7L7
-....L7.F7||L7|.L7L7|
-.....|FJLJ|FJ|F7|.LJ
-....FJL-7.||.||||...
-....L---J.LJ.LJLJ...";
+        let parsed = Day10::parse(EXAMPLE_PART2).unwrap().1;
+        assert_eq!(Day10::part_2(&parsed, &Day10::EXAMPLE_PARAMS), Ok(8));
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        for example in [EXAMPLE, EXAMPLE_PART2] {
+            let grid = Day10::parse(example).unwrap().1;
+            let pipes = get_loop(&grid).unwrap();
+            assert_eq!(
+                count_inside_by_flood(&grid, &pipes),
+                count_inside(&grid, &pipes)
+            );
+        }
+    }
 
-        let parsed = Day10::parse(input).unwrap().1;
-        assert_eq!(Day10::part_2(&parsed), 8);
+    #[test]
+    fn test_invalid() {
+        for input in ["..\n.-", "S-."] {
+            let grid = Day10::parse(input).unwrap().1;
+            assert!(
+                Day10::part_1(&grid, &Day10::EXAMPLE_PARAMS).is_err(),
+                "{input}"
+            );
+        }
     }
 }
@@ -1,27 +1,32 @@
-use std::collections::HashMap;
-
 use itertools::{repeat_n, Itertools};
 use nom::{
     branch::alt,
-    character::complete::{char, line_ending, u64},
+    character::complete::{char, u64},
     combinator::map,
-    multi::{many0, separated_list0, separated_list1},
+    multi::{many0, separated_list1},
     sequence::separated_pair,
     IResult,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::days::Day;
+use crate::{days::Params, parser::helpers::lines_of, util::memo_fn};
 
-pub struct Day12;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum HotSpring {
     Working,
     Broken,
     Unknown,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct HotSpringRow {
     pub springs: Vec<HotSpring>,
     pub groups: Vec<usize>,
@@ -40,181 +45,211 @@ fn parse_groups(input: &str) -> IResult<&str, Vec<usize>> {
     separated_list1(char(','), map(u64, |n| n as usize))(input)
 }
 
-/// How many solutions are there, considering a subset of the hotsprings and groups where we skip items at the beggining
-///
-/// Solved recursively and caching results (dynamic programming)
-fn count_solutions<'a>(
-    cache: &mut HashMap<(&'a [HotSpring], &'a [usize]), usize>,
-    springs: &'a [HotSpring],
-    groups: &'a [usize],
-    first_is_broken: bool,
-) -> usize {
-    if springs.is_empty() && groups.is_empty() {
-        return 1;
-    } else if springs.is_empty() {
-        // not a valid solution
-        return 0;
-    }
-
-    // check cache and return if possible
-    if let Some(res) = cache.get(&(springs, groups)) {
-        return *res;
-    }
-
-    // check the first item in the subset of springs
-    match (springs.first(), first_is_broken) {
-        (Some(HotSpring::Working), _) => {
-            // if first item is working, then we simply continue looking starting at the next spring
-            let res = count_solutions(cache, &springs[1..], groups, false);
-            cache.insert((springs, groups), res);
-            res
+memo_fn! {
+    /// How many solutions are there, considering a subset of the hotsprings and groups where we skip items at the
+    /// beggining
+    ///
+    /// Solved recursively and caching results (dynamic programming)
+    fn count_solutions<'a>(
+        springs: &'a [HotSpring],
+        groups: &'a [usize],
+        first_is_broken: bool,
+    ) -> usize {
+        if springs.is_empty() && groups.is_empty() {
+            return 1;
+        } else if springs.is_empty() {
+            // not a valid solution
+            return 0;
         }
-        (Some(HotSpring::Broken), _) | (Some(HotSpring::Unknown), true) => {
-            // if the first item is broken (or is unknown but we want to consider it as broken)
-            if groups.is_empty() {
-                // if we have no more groups to "assign", we have no more options to consider
-                cache.insert((springs, groups), 0);
-                return 0;
-            }
-
-            // length of the first group to be assigned
-            let first_group_length = *groups.first().unwrap();
 
-            // number of contiguous positions we have at our disposal to fit this group (broken or unknown)
-            let maybe_broken = springs
-                .iter()
-                .take_while(|s| !matches!(s, HotSpring::Working))
-                .count();
-            if maybe_broken < first_group_length {
-                // first group wouldn't fit first space, dead end
-                cache.insert((springs, groups), 0);
-                return 0;
+        // check the first item in the subset of springs
+        match (springs.first(), first_is_broken) {
+            (Some(HotSpring::Working), _) => {
+                // if first item is working, then we simply continue looking starting at the next spring
+                count_solutions(&springs[1..], groups, false)
             }
-            // group would fit, let's investigate
-
-            // rest after we assign the first group to the start of the space
-            let rest = &springs[first_group_length..];
-            // let's consider the first item of that rest
-            match rest.first() {
-                None => {
-                    // We are at the end of the row, there is nothing left after we assign the group.
-                    // Let's consider the group assigned and count the options for the remaining groups.
-                    // This will either be 0 or 1 depending on if all the groups have been assigned or not.
-                    // If we have assigned the last group, then the call below will return 1.
-                    // If we still ahve leftover groups but no more springs, then this is not a valid solution and the
-                    // call below will return 0.
-                    let res = count_solutions(cache, rest, &groups[1..], false);
-                    cache.insert((springs, groups), res);
-                    res
+            (Some(HotSpring::Broken), _) | (Some(HotSpring::Unknown), true) => {
+                // if the first item is broken (or is unknown but we want to consider it as broken)
+                if groups.is_empty() {
+                    // if we have no more groups to "assign", we have no more options to consider
+                    return 0;
                 }
-                Some(HotSpring::Broken) => {
-                    // Not allowed, there should be a gap after the group
-                    cache.insert((springs, groups), 0);
-                    0
+
+                // length of the first group to be assigned
+                let first_group_length = *groups.first().unwrap();
+
+                // number of contiguous positions we have at our disposal to fit this group (broken or unknown)
+                let maybe_broken = springs
+                    .iter()
+                    .take_while(|s| !matches!(s, HotSpring::Working))
+                    .count();
+                if maybe_broken < first_group_length {
+                    // first group wouldn't fit first space, dead end
+                    return 0;
                 }
-                Some(HotSpring::Working | HotSpring::Unknown) => {
-                    // if unknown, needs to be a working spring anyway (due to us assigning a group of broken springs),
-                    // so we can skip the first item
-                    let res = count_solutions(cache, &rest[1..], &groups[1..], false);
-                    cache.insert((springs, groups), res);
-                    res
+                // group would fit, let's investigate
+
+                // rest after we assign the first group to the start of the space
+                let rest = &springs[first_group_length..];
+                // let's consider the first item of that rest
+                match rest.first() {
+                    None => {
+                        // We are at the end of the row, there is nothing left after we assign the group.
+                        // Let's consider the group assigned and count the options for the remaining groups.
+                        // This will either be 0 or 1 depending on if all the groups have been assigned or not.
+                        // If we have assigned the last group, then the call below will return 1.
+                        // If we still ahve leftover groups but no more springs, then this is not a valid solution and
+                        // the call below will return 0.
+                        count_solutions(rest, &groups[1..], false)
+                    }
+                    Some(HotSpring::Broken) => {
+                        // Not allowed, there should be a gap after the group
+                        0
+                    }
+                    Some(HotSpring::Working | HotSpring::Unknown) => {
+                        // if unknown, needs to be a working spring anyway (due to us assigning a group of broken
+                        // springs), so we can skip the first item
+                        count_solutions(&rest[1..], &groups[1..], false)
+                    }
                 }
             }
+            (Some(HotSpring::Unknown), false) => {
+                // If the first item is unknown, it might be working or broken.
+                // Let's count the solutions when it's working (same as above)
+                let res_if_working = count_solutions(&springs[1..], groups, false);
+                // In case the first item would be broken, we override the first element's type by passing `true` as the
+                // last param, we will consider it just like a broken spring (same as above).
+                let res_if_broken = count_solutions(springs, groups, true); // consider the first one broken
+
+                // The number of valid options is the sum of those cases
+                res_if_working + res_if_broken
+            }
+            (None, _) => unreachable!(),
         }
-        (Some(HotSpring::Unknown), false) => {
-            // If the first item is unknown, it might be working or broken.
-            // Let's count the solutions when it's working (same as above)
-            let res_if_working = count_solutions(cache, &springs[1..], groups, false);
-            // In case the first item would be broken, we override the first element's type by passing `true` as the
-            // last param, we will consider it just like a broken spring (same as above).
-            let res_if_broken = count_solutions(cache, springs, groups, true); // consider the first one broken
-
-            // The number of valid options is the sum of those cases
-            let res = res_if_working + res_if_broken;
-            cache.insert((springs, groups), res);
-            res
-        }
-        (None, _) => unreachable!(),
     }
 }
 
-impl Day for Day12 {
-    type Input = Vec<HotSpringRow>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
-            line_ending,
-            map(
-                separated_pair(parse_springs, char(' '), parse_groups),
-                |(springs, groups)| HotSpringRow { springs, groups },
-            ),
-        )(input)
-    }
+fn parse(input: &str) -> IResult<&str, Vec<HotSpringRow>> {
+    lines_of(map(
+        separated_pair(parse_springs, char(' '), parse_groups),
+        |(springs, groups)| HotSpringRow { springs, groups },
+    ))(input)
+}
 
-    type Output1 = usize;
+/// Part 1 took 5.584ms
+fn part_1(input: &[HotSpringRow], _params: &Params) -> usize {
+    input
+        .iter()
+        .map(|row| count_solutions(&row.springs, &row.groups, false))
+        .sum()
+}
 
-    /// Part 1 took 5.584ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
-            .iter()
-            .map(|row| {
-                let mut cache = HashMap::new();
-                count_solutions(&mut cache, &row.springs, &row.groups, false)
-            })
-            .sum()
-    }
+/// Part 2 took 220.059002ms
+fn part_2(input: &[HotSpringRow], _params: &Params) -> usize {
+    input
+        .iter()
+        .map(|row| {
+            let mut springs = repeat_n(row.springs.iter().copied().collect_vec(), 5)
+                .map(|mut a| {
+                    // add the separator (a "unknown" spring)
+                    a.push(HotSpring::Unknown);
+                    a
+                })
+                .collect_vec()
+                .concat();
+            // drop the last unknown separator
+            springs.pop();
+            let groups = row
+                .groups
+                .iter()
+                .cycle()
+                .take(row.groups.len() * 5)
+                .copied()
+                .collect_vec();
+            count_solutions(&springs, &groups, false)
+        })
+        .sum()
+}
+
+impl_day!(
+    Day12,
+    12,
+    parse -> Vec<HotSpringRow>,
+    part_1 -> usize,
+    part_2 -> usize,
+    answers = [Some("21"), Some("525152")],
+    title = "Hot Springs",
+    tags = ["dynamic-programming"],
+    cost = Medium,
+);
+
+/// Slow solutions which follow the puzzle literally, to cross-check the ones above
+#[cfg(test)]
+mod reference {
+    use itertools::Itertools;
 
-    type Output2 = usize;
+    use super::{HotSpring, HotSpringRow};
 
-    /// Part 2 took 220.059002ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        input
+    /// Try every way to replace the unknown springs, 2^n of them for n unknown springs
+    pub fn arrangements(row: &HotSpringRow) -> usize {
+        let unknown = row
+            .springs
             .iter()
-            .map(|row| {
-                let mut cache = HashMap::new();
-                let mut springs = repeat_n(row.springs.iter().copied().collect_vec(), 5)
-                    .map(|mut a| {
-                        // add the separator (a "unknown" spring)
-                        a.push(HotSpring::Unknown);
-                        a
-                    })
-                    .collect_vec()
-                    .concat();
-                // drop the last unknown separator
-                springs.pop();
-                let groups = row
-                    .groups
+            .positions(|s| *s == HotSpring::Unknown)
+            .collect_vec();
+        (0..1u32 << unknown.len())
+            .filter(|mask| {
+                let mut springs = row.springs.clone();
+                for (bit, &i) in unknown.iter().enumerate() {
+                    springs[i] = if mask & (1 << bit) == 0 {
+                        HotSpring::Working
+                    } else {
+                        HotSpring::Broken
+                    };
+                }
+                let groups = springs
                     .iter()
-                    .cycle()
-                    .take(row.groups.len() * 5)
-                    .copied()
+                    .dedup_with_count()
+                    .filter(|(_, s)| **s == HotSpring::Broken)
+                    .map(|(len, _)| len)
                     .collect_vec();
-                count_solutions(&mut cache, &springs, &groups, false)
+                groups == row.groups
             })
-            .sum()
+            .count()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "???.### 1,1,3
-.??..??...?##. 1,1,3
-?#?#?#?#?#?#?#? 1,3,1,6
-????.#...#... 4,1,1
-????.######..#####. 1,6,5
-?###???????? 3,2,1";
+    use crate::{days::Day, util::gen::Rng};
 
     #[test]
     fn test_part1() {
-        let parsed = Day12::parse(INPUT).unwrap().1;
-        assert_eq!(Day12::part_1(&parsed), 21);
+        let parsed = Day12::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day12::part_1(&parsed, &Day12::EXAMPLE_PARAMS), Ok(21));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day12::parse(INPUT).unwrap().1;
-        assert_eq!(Day12::part_2(&parsed), 525_152);
+        let parsed = Day12::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day12::part_2(&parsed, &Day12::EXAMPLE_PARAMS), Ok(525_152));
+    }
+
+    #[test]
+    fn test_reference() {
+        let mut rng = Rng::new(12);
+        for _ in 0..300 {
+            let springs = (0..=rng.below(14))
+                .map(|_| *rng.choose(&[HotSpring::Working, HotSpring::Broken, HotSpring::Unknown]))
+                .collect_vec();
+            let groups = (0..=rng.below(4)).map(|_| 1 + rng.below(3)).collect();
+            let row = HotSpringRow { springs, groups };
+            assert_eq!(
+                count_solutions(&row.springs, &row.groups, false),
+                reference::arrangements(&row),
+                "{row:?}"
+            );
+        }
     }
 }
@@ -1,19 +1,34 @@
-use std::collections::HashMap;
-
-use itertools::Itertools;
-use nom::{
-    bytes::complete::tag,
-    character::complete::{line_ending, one_of},
-    combinator::map,
-    multi::{many1, separated_list0},
-    IResult,
+//! Point of Incidence
+//!
+//! The horizontal mirrors are found with the same code as the vertical ones, on the transposed pattern.
+
+use nom::IResult;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    days::Params,
+    parser::{helpers::blocks, SolveError},
+    util::{symmetry::find_reflection, Grid},
 };
 
-use crate::days::Day;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
 
-pub struct Day13;
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum Tile {
     Rock,
     Ash,
@@ -28,185 +43,77 @@ impl std::fmt::Display for Tile {
     }
 }
 
-fn parse_pattern(input: &str) -> IResult<&str, Vec<Vec<Tile>>> {
-    separated_list0(
-        line_ending,
-        map(many1(one_of("#.")), |s| {
-            s.iter()
-                .map(|c| match c {
-                    '#' => Tile::Rock,
-                    '.' => Tile::Ash,
-                    _ => unimplemented!(),
-                })
-                .collect()
-        }),
-    )(input)
+fn parse_pattern(input: &str) -> IResult<&str, Grid<Tile>> {
+    let (rest, (pattern, [])) = Grid::parse(input, [], |c| match c {
+        '#' => Some(Tile::Rock),
+        '.' => Some(Tile::Ash),
+        _ => None,
+    })?;
+    Ok((rest, pattern))
 }
 
-/// Find the honrizontal axis that splits the pattern into two vertical mirror images
+/// The summary of a pattern: the number of columns left of its vertical mirror, or 100 times the number of rows above
+/// its horizontal one
 ///
-/// In the second part, we want to look for patterns which have exactly 1 difference in the reflection
-fn find_vertical_mirror(pattern: &[Vec<Tile>], smudge: bool) -> Option<usize> {
-    // Storing all potential candidates: key = number of diverging tiles, value = x coordinate
-    let mut res = HashMap::<usize, usize>::new();
-    let width = pattern[0].len();
-    let mut x = 1; // to have symmetry, we must have at least 1 col on the left
-    while x < width {
-        let mut symmetrical = true; // we asssume symmetrical until proven otherwise
-        let mut total_diverging = 0; // how many tiles are different between the two halves
-        for row in pattern {
-            let (left, right) = row.split_at(x);
-            // iterate through both halves at the same time, starting at the candidate symmetry axis.
-            // count how many tiles are different between the two halves
-            let diverging = right
-                .iter()
-                .zip(left.iter().rev())
-                .filter(|(a, b)| a != b)
-                .count();
-            // if diverging > 1, it's not possible that this symmetry axis interests us
-            if diverging > 1 {
-                // not symmetrical, we can stop looking at further rows
-                symmetrical = false;
-                break;
-            }
-            // we could potentially have a symmetry axis, but only if the total_diverging is 0 or 1 at the very end
-            total_diverging += diverging;
-        }
-        if !symmetrical {
-            // let's keep looking
-            x += 1;
-            continue;
-        }
-        if total_diverging <= 1 {
-            // we found no evidence of non-symmetry, so let's record our candidate
-            res.insert(total_diverging, x);
-        }
-        // let's keep looking
-        x += 1;
-    }
-    if smudge {
-        // we are looking for exactly 1 tile of difference
-        res.get(&1).copied()
-    } else {
-        res.get(&0).copied()
-    }
+/// In the second part, the mirror is the one across which exactly 1 tile differs from its reflection
+fn summarize(pattern: &Grid<Tile>, tolerated_diffs: usize) -> Option<usize> {
+    // the rows of the transposed pattern are the columns of this one
+    find_reflection(&pattern.transposed(), tolerated_diffs)
+        .map(|axis| axis * 100)
+        .or_else(|| find_reflection(pattern, tolerated_diffs))
 }
 
-/// Find the vertical axis that splits the pattern into two horizontal mirror images
-///
-/// In the second part, we want to look for patterns which have exactly 1 difference in the reflection
-fn find_horizontal_mirror(pattern: &[Vec<Tile>], smudge: bool) -> Option<usize> {
-    // same implementation as above
-    let mut res = HashMap::<usize, usize>::new();
-    let width = pattern[0].len();
-    let height = pattern.len();
-    let mut y = 1;
-    while y < height {
-        let mut symmetrical = true;
-        let mut total_diverging = 0;
-        // we iterate over the columns this time
-        for col in (0..width).map(|x| pattern.iter().map(|row| row[x]).collect_vec()) {
-            let (top, bottom) = col.split_at(y);
-            let diverging = bottom
-                .iter()
-                .zip(top.iter().rev())
-                .filter(|(a, b)| a != b)
-                .count();
-            if diverging > 1 {
-                symmetrical = false;
-                break;
-            }
-            total_diverging += diverging;
-        }
-        if !symmetrical {
-            y += 1;
-            continue;
-        }
-        if total_diverging <= 1 {
-            res.insert(total_diverging, y);
-        }
-        y += 1;
-    }
-    if smudge {
-        res.get(&1).copied()
-    } else {
-        res.get(&0).copied()
-    }
+/// The sum of the summaries of all the patterns
+fn summarize_all(input: &[Grid<Tile>], tolerated_diffs: usize) -> Result<usize, SolveError> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, pattern)| {
+            summarize(pattern, tolerated_diffs)
+                .ok_or_else(|| SolveError::invalid(format!("pattern {} has no symmetry", i + 1)))
+        })
+        .sum()
 }
 
-impl Day for Day13 {
-    type Input = Vec<Vec<Vec<Tile>>>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(tag("\n\n"), parse_pattern)(input)
-    }
+fn parse(input: &str) -> IResult<&str, Vec<Grid<Tile>>> {
+    blocks(parse_pattern)(input)
+}
 
-    type Output1 = usize;
-
-    /// Part 1 took 153.5µs
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
-            .iter()
-            .map(|pattern| {
-                if let Some(axis) = find_horizontal_mirror(pattern, false) {
-                    axis * 100
-                } else if let Some(axis) = find_vertical_mirror(pattern, false) {
-                    axis
-                } else {
-                    unreachable!("all patterns must have symmetry")
-                }
-            })
-            .sum()
-    }
+/// Part 1 took 153.5µs
+fn part_1(input: &[Grid<Tile>], _params: &Params) -> Result<usize, SolveError> {
+    summarize_all(input, 0)
+}
 
-    type Output2 = usize;
-
-    /// Part 2 took 142µs
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        input
-            .iter()
-            .map(|pattern| {
-                if let Some(axis) = find_horizontal_mirror(pattern, true) {
-                    axis * 100
-                } else if let Some(axis) = find_vertical_mirror(pattern, true) {
-                    axis
-                } else {
-                    unreachable!("all patterns must have symmetry")
-                }
-            })
-            .sum()
-    }
+/// Part 2 took 142µs
+fn part_2(input: &[Grid<Tile>], _params: &Params) -> Result<usize, SolveError> {
+    summarize_all(input, 1)
 }
 
+impl_day!(
+    Day13,
+    13,
+    parse -> Vec<Grid<Tile>>,
+    part_1 -> usize,
+    part_2 -> usize,
+    answers = [Some("405"), Some("400")],
+    title = "Point of Incidence",
+    tags = ["grid"],
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "#.##..##.
-..#.##.#.
-##......#
-##......#
-..#.##.#.
-..##..##.
-#.#.##.#.
-
-#...##..#
-#....#..#
-..##..###
-#####.##.
-#####.##.
-..##..###
-#....#..#";
+    use crate::days::Day;
 
     #[test]
     fn test_part1() {
-        let parsed = Day13::parse(INPUT).unwrap().1;
-        assert_eq!(Day13::part_1(&parsed), 405);
+        let parsed = Day13::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day13::part_1(&parsed, &Day13::EXAMPLE_PARAMS), Ok(405));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day13::parse(INPUT).unwrap().1;
-        assert_eq!(Day13::part_2(&parsed), 400);
+        let parsed = Day13::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day13::part_2(&parsed, &Day13::EXAMPLE_PARAMS), Ok(400));
     }
 }
@@ -1,218 +1,179 @@
-use std::collections::{BTreeMap, HashMap};
+//! Parabolic Reflector Dish
+//!
+//! Part 2 skips to the platform after a billion spin cycles with `cycle::state_after`, comparing hashes of the grid
+//! instead of keeping every platform seen.
 
-use itertools::Itertools;
-use nom::{
-    character::complete::{line_ending, one_of},
-    multi::{many1, separated_list0},
-    IResult,
-};
+use std::hash::{DefaultHasher, Hash, Hasher};
 
-use crate::days::Day;
+use itertools::Itertools;
+use nom::IResult;
+use serde::{Deserialize, Serialize};
 
-pub struct Day14;
+use crate::{
+    days::Params,
+    util::{cycle, Direction, Grid, Vec2},
+};
 
-#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-pub struct Point {
-    y: isize, // btreemap is sorted by key, so y is first
-    x: isize,
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Rock {
     Round,
     Cube,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Platform {
-    pub grid: BTreeMap<Point, Rock>,
-    pub width: isize,
-    pub height: isize,
-}
-
-#[derive(Debug, Clone, Copy)]
-pub enum Dir {
-    North,
-    East,
-    South,
-    West,
+    pub grid: Grid<Option<Rock>>,
 }
 
+#[allow(clippy::cast_possible_wrap)]
 impl Platform {
-    fn has_rock(&self, point: &Point) -> Option<&Rock> {
-        self.grid.get(point)
-    }
-
-    fn pos_load(&self, point: &Point, dir: Dir) -> isize {
+    fn pos_load(&self, point: Vec2<isize>, dir: Direction) -> isize {
         match dir {
-            Dir::North => self.height - point.y,
-            Dir::East => point.x + 1,
-            Dir::South => point.y + 1,
-            Dir::West => self.width - point.x,
+            Direction::North => self.grid.height() as isize - point.y,
+            Direction::East => point.x + 1,
+            Direction::South => point.y + 1,
+            Direction::West => self.grid.width() as isize - point.x,
         }
     }
 
-    fn next_in_dir(&self, start: &Point, dir: Dir) -> Point {
-        let pos = match dir {
-            Dir::North => (0..start.y)
-                .rev()
-                .map(|y| Point { y, x: start.x })
-                .collect_vec(),
-            Dir::East => ((start.x + 1)..self.width)
-                .map(|x| Point { y: start.y, x })
-                .collect_vec(),
-            Dir::South => ((start.y + 1)..self.height)
-                .map(|y| Point { y, x: start.x })
-                .collect_vec(),
-            Dir::West => (0..start.x)
-                .rev()
-                .map(|x| Point { y: start.y, x })
-                .collect_vec(),
-        };
-        let empty_space = pos
-            .into_iter()
-            .take_while(|p| self.has_rock(p).is_none())
-            .collect_vec();
-        empty_space.last().cloned().unwrap_or(start.clone())
+    /// The last empty position from `start` in `dir`, before a rock or the edge of the platform
+    fn next_in_dir(&self, start: Vec2<isize>, dir: Direction) -> Vec2<isize> {
+        let mut pos = start;
+        while let Some(None) = self.grid.get(pos.step(dir)) {
+            pos = pos.step(dir);
+        }
+        pos
     }
 
-    fn move_rocks(&mut self, dir: Dir) {
-        let rounds = self
+    fn move_rocks(&mut self, dir: Direction) {
+        // the rocks closest to the edge we tilt towards move first, so that the others pile up behind them
+        let mut rounds = self
             .grid
-            .iter()
-            .filter(|(_, r)| matches!(r, Rock::Round))
-            .sorted_by(|(a, _), (b, _)| match dir {
-                Dir::North => a.y.cmp(&b.y),
-                Dir::East => b.x.cmp(&a.x),
-                Dir::South => b.y.cmp(&a.y),
-                Dir::West => a.x.cmp(&b.x),
-            })
-            .map(|(p, r)| (p.clone(), *r))
+            .positions(|r| *r == Some(Rock::Round))
             .collect_vec();
-        for (p, _) in rounds {
-            let new_p = self.next_in_dir(&p, dir);
-            let r = self.grid.remove(&p).unwrap();
-            self.grid.insert(new_p, r);
+        if matches!(dir, Direction::South | Direction::East) {
+            rounds.reverse();
+        }
+        for p in rounds {
+            let new_p = self.next_in_dir(p, dir);
+            self.grid[p] = None;
+            self.grid[new_p] = Some(Rock::Round);
         }
     }
 
-    fn total_load(&self, dir: Dir) -> isize {
+    fn total_load(&self, dir: Direction) -> isize {
         self.grid
-            .iter()
-            .filter_map(|(p, r)| match r {
-                Rock::Round => Some(self.pos_load(p, dir)),
-                Rock::Cube => None,
-            })
+            .positions(|r| *r == Some(Rock::Round))
+            .map(|p| self.pos_load(p, dir))
             .sum()
     }
 }
 
-impl Day for Day14 {
-    type Input = Platform;
-
-    #[allow(clippy::cast_possible_wrap)]
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        let (rest, elements) = separated_list0(line_ending, many1(one_of(".#O")))(input)?;
-        let mut grid = BTreeMap::new();
-        for (y, row) in elements.iter().enumerate() {
-            for (x, elem) in row.iter().enumerate() {
-                let point = Point {
-                    y: y as isize,
-                    x: x as isize,
-                };
-                match elem {
-                    '.' => {}
-                    '#' => {
-                        grid.insert(point, Rock::Cube);
-                    }
-                    'O' => {
-                        grid.insert(point, Rock::Round);
-                    }
-                    _ => unreachable!(),
-                }
-            }
-        }
-        let width = elements.first().unwrap().len() as isize;
-        let height = elements.len() as isize;
-        Ok((
-            rest,
-            Platform {
-                grid,
-                width,
-                height,
-            },
-        ))
-    }
+fn parse(input: &str) -> IResult<&str, Platform> {
+    let (rest, (grid, [])) = Grid::parse(input, [], |c| match c {
+        '.' => Some(None),
+        '#' => Some(Some(Rock::Cube)),
+        'O' => Some(Some(Rock::Round)),
+        _ => None,
+    })?;
+    Ok((rest, Platform { grid }))
+}
 
-    type Output1 = isize;
+/// Part 1 took 784.744µs
+fn part_1(input: &Platform, _params: &Params) -> isize {
+    let mut platform = input.clone(); // get mutable copy
+    platform.move_rocks(Direction::North);
+    platform.total_load(Direction::North)
+}
 
-    /// Part 1 took 784.744µs
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let mut platform = input.clone(); // get mutable copy
-        platform.move_rocks(Dir::North);
-        platform.total_load(Dir::North)
+/// One spin cycle: tilt the platform north, then west, then south, then east
+fn spin(platform: &Platform) -> Platform {
+    let mut platform = platform.clone();
+    for dir in [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ] {
+        platform.move_rocks(dir);
     }
+    platform
+}
 
-    type Output2 = isize;
-
-    /// Part 2 took 436.819662ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut platform = input.clone(); // get mutable copy
-
-        // There is probably a repeating pattern, where at some point the rocks would return to the same pattern every
-        // N cycles.
-        // Let's keep a cache of the platform state at each iteration.
-        let mut cache = HashMap::<BTreeMap<Point, Rock>, usize>::new();
-        for i in 0.. {
-            platform.move_rocks(Dir::North);
-            platform.move_rocks(Dir::West);
-            platform.move_rocks(Dir::South);
-            platform.move_rocks(Dir::East);
-            if let Some(j) = cache.get(&platform.grid) {
-                // We have seen the current configuration before!
-                let modulo = i - j; // The periodicity
-                let target = (1_000_000_000 - 1) % modulo;
-
-                // Find the last one in the cache that matches the predicate i % modulo == 1B % modulo
-                let elem = cache
-                    .iter()
-                    .sorted_by(|(_, a), (_, &b)| b.cmp(a))
-                    .find(|(_, &ii)| ii % modulo == target)
-                    .unwrap();
-                platform.grid = elem.0.clone(); // restore the state
-                break;
-            }
-            cache.insert(platform.grid.clone(), i);
-        }
-        // Check finally the north support load
-        platform.total_load(Dir::North)
-    }
+/// Hash of the positions of the rocks, to compare the platforms during the cycle detection
+fn digest(platform: &Platform) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    platform.grid.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn part_2(input: &Platform, _params: &Params) -> isize {
+    // The rocks end up returning to the same positions every N cycles, so we only need to simulate the cycles until
+    // the first repetition, plus the remainder of the billion cycles after that
+    let platform = cycle::state_after(input, spin, digest, 1_000_000_000);
+    // Check finally the north support load
+    platform.total_load(Direction::North)
 }
 
+impl_day!(
+    Day14,
+    14,
+    parse -> Platform,
+    part_1 -> isize,
+    part_2 -> isize,
+    answers = [Some("136"), Some("64")],
+    title = "Parabolic Reflector Dish",
+    tags = ["grid", "cycle"],
+    cost = Slow,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "O....#....
-O.OO#....#
-.....##...
-OO.#O....O
-.O.....O#.
-O.#..O.#.#
-..O..#O..O
-.......O..
-#....###..
-#OO..#....";
+    use crate::{days::Day, util::symmetry::find_reflection};
 
     #[test]
     fn test_part1() {
-        let parsed = Day14::parse(INPUT).unwrap().1;
-        assert_eq!(Day14::part_1(&parsed), 136);
+        let parsed = Day14::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day14::part_1(&parsed, &Day14::EXAMPLE_PARAMS), Ok(136));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day14::parse(INPUT).unwrap().1;
-        assert_eq!(Day14::part_2(&parsed), 64);
+        let parsed = Day14::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day14::part_2(&parsed, &Day14::EXAMPLE_PARAMS), Ok(64));
+    }
+
+    #[test]
+    fn test_tilt_symmetry() {
+        // the example next to its mirror image stays symmetric when tilted north or south, but not east
+        let example = Day14::parse(EXAMPLE).unwrap().1;
+        let rows = example
+            .grid
+            .rows()
+            .map(|row| row.iter().chain(row.iter().rev()).copied().collect())
+            .collect();
+        let mut platform = Platform {
+            grid: Grid::from_rows(rows).unwrap(),
+        };
+        assert_eq!(find_reflection(&platform.grid, 0), Some(10));
+        for dir in [Direction::North, Direction::South] {
+            platform.move_rocks(dir);
+            assert_eq!(find_reflection(&platform.grid, 0), Some(10));
+        }
+        platform.move_rocks(Direction::East);
+        assert_eq!(find_reflection(&platform.grid, 0), None);
     }
 }
@@ -1,16 +1,18 @@
 use itertools::Itertools;
 use nom::{
-    character::complete::{alpha1, char, none_of, one_of, u8},
-    combinator::{map, opt},
+    bytes::complete::is_not,
+    character::complete::{alpha1, char, one_of, u8},
+    combinator::opt,
     error::ErrorKind,
-    multi::{many1, separated_list1},
+    multi::separated_list1,
     sequence::tuple,
     IResult,
 };
 
-use crate::days::Day;
+use crate::{days::Params, parser::SolveError};
 
-pub struct Day15;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
 #[derive(Debug)]
 pub enum Action {
@@ -19,38 +21,42 @@ pub enum Action {
 }
 
 #[derive(Debug)]
-pub struct Instruction {
-    label: String,
+pub struct Instruction<'a> {
+    label: &'a str,
     box_id: usize,
     action: Action,
 }
 
 #[derive(Debug)]
-pub struct Lens {
-    label: String,
+pub struct Lens<'a> {
+    label: &'a str,
     focal: usize,
 }
 
-impl Instruction {
+impl<'a> Instruction<'a> {
     /// Parse instruction to extract lens label, box ID and action type
-    fn new(input: &str) -> Self {
-        let (_, (label, action, value)) = tuple((
+    fn new(input: &'a str) -> Result<Self, SolveError> {
+        let invalid = || SolveError::invalid(format!("invalid instruction {input}"));
+        let (rest, (label, action, value)) = tuple((
             alpha1::<&str, _>,
             one_of::<_, _, (&str, ErrorKind)>("-="),
             opt(u8),
         ))(input)
-        .unwrap();
+        .map_err(|_| invalid())?;
+        if !rest.is_empty() {
+            return Err(invalid());
+        }
         let box_id = hash_string(label);
-        let action = match action {
-            '-' => Action::Remove,
-            '=' => Action::Add(value.unwrap() as usize),
-            _ => unimplemented!(),
+        let action = match (action, value) {
+            ('-', None) => Action::Remove,
+            ('=', Some(focal)) => Action::Add(focal as usize),
+            _ => return Err(invalid()),
         };
-        Instruction {
-            label: label.to_string(),
+        Ok(Instruction {
+            label,
             box_id,
             action,
-        }
+        })
     }
 }
 
@@ -64,89 +70,96 @@ fn hash_string(instr: &str) -> usize {
     })
 }
 
-impl Day for Day15 {
-    type Input = Vec<String>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list1(
-            char(','),
-            map(many1(none_of("\n,")), |s| s.into_iter().collect()),
-        )(input)
-    }
+fn parse(input: &str) -> IResult<&str, Vec<&'_ str>> {
+    separated_list1(char(','), is_not("\n,"))(input)
+}
 
-    type Output1 = usize;
+/// Part 1 took 42.9µs
+fn part_1(input: &[&str], _params: &Params) -> usize {
+    input.iter().map(|s| hash_string(s)).sum()
+}
 
-    /// Part 1 took 42.9µs
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input.iter().map(|s| hash_string(s)).sum()
+/// Part 2 took 423µs
+fn part_2(input: &[&str], _params: &Params) -> Result<usize, SolveError> {
+    // parse the input strings into instructions
+    let instructions: Vec<_> = input.iter().map(|s| Instruction::new(s)).try_collect()?;
+    // initialize the boxes with empty VecDeque's
+    let mut boxes: Vec<Vec<Lens>> = Vec::with_capacity(256);
+    for _ in 0..256 {
+        boxes.push(Vec::new());
     }
-
-    type Output2 = usize;
-
-    /// Part 2 took 423µs
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        // parse the input strings into instructions
-        let instructions = input.iter().map(|s| Instruction::new(s)).collect_vec();
-        // initialize the boxes with empty VecDeque's
-        let mut boxes: Vec<Vec<Lens>> = Vec::with_capacity(256);
-        for _ in 0..256 {
-            boxes.push(Vec::new());
-        }
-        // process all instructions
-        for instr in instructions {
-            let b = boxes.get_mut(instr.box_id).unwrap();
-            match instr.action {
-                Action::Remove => {
-                    // only keep lenses which have a label different from the one in the instruction
-                    b.retain(|l| l.label != instr.label);
-                }
-                // when adding, check if there is a lens with the same label already
-                Action::Add(focal) => match b.iter_mut().find(|l| l.label == instr.label) {
-                    Some(lens) => {
-                        // we have a lens with the same label
-                        // replace the lens at the position
-                        *lens = Lens {
-                            label: instr.label.clone(),
-                            focal,
-                        };
-                    }
-                    None => b.push(Lens {
-                        label: instr.label.clone(),
-                        focal,
-                    }),
-                },
+    // process all instructions
+    for instr in instructions {
+        // the hash is always below 256
+        let b = &mut boxes[instr.box_id];
+        match instr.action {
+            Action::Remove => {
+                // only keep lenses which have a label different from the one in the instruction
+                b.retain(|l| l.label != instr.label);
             }
+            // when adding, check if there is a lens with the same label already
+            Action::Add(focal) => match b.iter_mut().find(|l| l.label == instr.label) {
+                Some(lens) => {
+                    // we have a lens with the same label
+                    // replace the lens at the position
+                    *lens = Lens {
+                        label: instr.label,
+                        focal,
+                    };
+                }
+                None => b.push(Lens {
+                    label: instr.label,
+                    focal,
+                }),
+            },
         }
-        // calculate the total focusing power
-        boxes
-            .iter()
-            .enumerate()
-            .map(|(i, lenses)| {
-                lenses
-                    .iter()
-                    .enumerate()
-                    .map(|(j, lens)| (i + 1) * (j + 1) * lens.focal)
-                    .sum::<usize>()
-            })
-            .sum()
     }
+    // calculate the total focusing power
+    Ok(boxes
+        .iter()
+        .enumerate()
+        .map(|(i, lenses)| {
+            lenses
+                .iter()
+                .enumerate()
+                .map(|(j, lens)| (i + 1) * (j + 1) * lens.focal)
+                .sum::<usize>()
+        })
+        .sum())
 }
 
+impl_day!(
+    Day15,
+    15,
+    parse -> Vec<&'a str>,
+    part_1 -> usize,
+    part_2 -> usize,
+    answers = [Some("1320"), Some("145")],
+    title = "Lens Library",
+    tags = ["hashing"],
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+    use crate::days::Day;
 
     #[test]
     fn test_part1() {
-        let parsed = Day15::parse(INPUT).unwrap().1;
-        assert_eq!(Day15::part_1(&parsed), 1320);
+        let parsed = Day15::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day15::part_1(&parsed, &Day15::EXAMPLE_PARAMS), Ok(1320));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day15::parse(INPUT).unwrap().1;
-        assert_eq!(Day15::part_2(&parsed), 145);
+        let parsed = Day15::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day15::part_2(&parsed, &Day15::EXAMPLE_PARAMS), Ok(145));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for input in ["ab=x", "ab", "1=2", "ab-3", "ab=5x"] {
+            assert!(part_2(&[input], &Day15::EXAMPLE_PARAMS).is_err(), "{input}");
+        }
     }
 }
@@ -1,24 +1,26 @@
-use std::collections::{HashMap, VecDeque};
+use std::{collections::VecDeque, iter};
 
-use nom::{
-    character::complete::{line_ending, one_of},
-    multi::{many1, separated_list0},
-    IResult,
-};
-
-use crate::days::Day;
+use nom::IResult;
+use serde::{Deserialize, Serialize};
 
-pub struct Day16;
+use crate::{
+    days::Params,
+    util::{Direction, Grid, Pose, Vec2},
+};
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub enum Dir {
-    Top,
-    Right,
-    Bottom,
-    Left,
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = ".|...\\....
+|.-.\\.....
+.....|-...
+........|.
+..........
+.........\\
+..../.\\\\..
+.-.-/..|..
+.|....-|.\\
+..//.|....";
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Tile {
     Empty,
     Vertical,
@@ -27,245 +29,104 @@ pub enum Tile {
     BackSlash,
 }
 
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
-pub struct Point {
-    x: isize,
-    y: isize,
-}
-
-#[allow(clippy::cast_possible_wrap)]
-impl Point {
-    fn at_dir(&self, dir: Dir, width: usize, height: usize) -> Option<Point> {
-        let at = match dir {
-            Dir::Top => Point {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Dir::Right => Point {
-                x: self.x + 1,
-                y: self.y,
-            },
-            Dir::Bottom => Point {
-                x: self.x,
-                y: self.y + 1,
-            },
-            Dir::Left => Point {
-                x: self.x - 1,
-                y: self.y,
-            },
-        };
-        if at.x < 0 || at.x > (width as isize) - 1 || at.y < 0 || at.y > (height as isize) - 1 {
-            return None;
-        }
-        Some(at)
-    }
-}
-
-#[derive(Debug)]
-pub struct Grid {
-    grid: HashMap<Point, Tile>,
-    width: usize,
-    height: usize,
-}
-
-fn get_beams(grid: &Grid, start_pos: Point, start_dir: Dir) -> HashMap<Point, Vec<Dir>> {
-    let mut beams = HashMap::<Point, Vec<Dir>>::new();
-    let mut stack = VecDeque::<(Point, Dir)>::new();
-    stack.push_front((start_pos, start_dir));
-    while let Some((pos, dir)) = stack.pop_front() {
+/// Number of tiles energized by a beam entering the grid at `start.pos` in `start.dir`
+fn energized(grid: &Grid<Tile>, start: Pose<isize>) -> usize {
+    // the directions in which a beam went through each tile, one bit per direction
+    let mut beams = Grid::new(grid.width(), grid.height(), 0u8);
+    let mut stack = VecDeque::<Pose<isize>>::new();
+    stack.push_front(start);
+    while let Some(beam @ Pose { pos, dir }) = stack.pop_front() {
         // mark visited
-        match beams.get_mut(&pos) {
-            Some(dirs) => {
-                if dirs.contains(&dir) {
-                    continue;
-                }
-                dirs.push(dir);
-            }
-            None => {
-                beams.insert(pos.clone(), vec![dir]);
-            }
+        let seen = &mut beams[pos];
+        if *seen & (1 << dir as u8) != 0 {
+            continue;
         }
-        let tile = grid.grid.get(&pos).unwrap();
-        match (tile, dir) {
-            (Tile::Empty, _)
-            | (Tile::Vertical, Dir::Top | Dir::Bottom)
-            | (Tile::Horizontal, Dir::Left | Dir::Right) => {
-                // continue in the same dir
-                if let Some(next) = pos.at_dir(dir, grid.width, grid.height) {
-                    stack.push_back((next, dir));
-                }
-            }
-            (Tile::Vertical, Dir::Right | Dir::Left) => {
-                // split beam if we come perpendicular
-                if let Some(next) = pos.at_dir(Dir::Top, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Top));
-                }
-                if let Some(next) = pos.at_dir(Dir::Bottom, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Bottom));
-                }
-            }
-            (Tile::Horizontal, Dir::Top | Dir::Bottom) => {
-                // split beam if we come perpendicular
-                if let Some(next) = pos.at_dir(Dir::Left, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Left));
-                }
-                if let Some(next) = pos.at_dir(Dir::Right, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Right));
-                }
-            }
-            (Tile::Slash, Dir::Left) | (Tile::BackSlash, Dir::Right) => {
-                // continue down
-                if let Some(next) = pos.at_dir(Dir::Bottom, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Bottom));
-                }
-            }
-            (Tile::Slash, Dir::Bottom) | (Tile::BackSlash, Dir::Top) => {
-                // continue left
-                if let Some(next) = pos.at_dir(Dir::Left, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Left));
-                }
-            }
-            (Tile::Slash, Dir::Right) | (Tile::BackSlash, Dir::Left) => {
-                // continue up
-                if let Some(next) = pos.at_dir(Dir::Top, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Top));
-                }
-            }
-            (Tile::Slash, Dir::Top) | (Tile::BackSlash, Dir::Bottom) => {
-                // continue right
-                if let Some(next) = pos.at_dir(Dir::Right, grid.width, grid.height) {
-                    stack.push_back((next, Dir::Right));
-                }
+        *seen |= 1 << dir as u8;
+        let (first, second) = match (&grid[pos], dir.is_vertical()) {
+            // continue in the same dir
+            (Tile::Empty, _) | (Tile::Vertical, true) | (Tile::Horizontal, false) => (dir, None),
+            // split beam if we come perpendicular
+            (Tile::Vertical | Tile::Horizontal, _) => (dir.turn_left(), Some(dir.turn_right())),
+            // mirrors, e.g. `/` turns up when going right and right when going up
+            (Tile::Slash, true) | (Tile::BackSlash, false) => (dir.turn_right(), None),
+            (Tile::Slash, false) | (Tile::BackSlash, true) => (dir.turn_left(), None),
+        };
+        for next_dir in iter::once(first).chain(second) {
+            let next = beam.facing(next_dir).step();
+            if grid.contains(next.pos) {
+                stack.push_back(next);
             }
         }
     }
-    beams
+    beams.cells().iter().filter(|&&dirs| dirs != 0).count()
 }
 
-impl Day for Day16 {
-    type Input = Grid;
-
-    #[allow(clippy::cast_possible_wrap)]
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        let (_, tiles) = separated_list0(line_ending, many1(one_of(".|-/\\")))(input)?;
-        let height = tiles.len();
-        let width = tiles.first().unwrap().len();
-        let mut grid = HashMap::<Point, Tile>::new();
-        for (y, row) in tiles.iter().enumerate() {
-            for (x, tile) in row.iter().enumerate() {
-                let point = Point {
-                    x: x as isize,
-                    y: y as isize,
-                };
-                let tile = match tile {
-                    '.' => Tile::Empty,
-                    '|' => Tile::Vertical,
-                    '-' => Tile::Horizontal,
-                    '/' => Tile::Slash,
-                    '\\' => Tile::BackSlash,
-                    _ => unreachable!(),
-                };
-                grid.insert(point, tile);
-            }
-        }
-        Ok((
-            "",
-            Grid {
-                grid,
-                width,
-                height,
-            },
-        ))
-    }
-
-    type Output1 = usize;
-
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let beams = get_beams(input, Point { x: 0, y: 0 }, Dir::Right);
-        beams.len()
-    }
+fn parse(input: &str) -> IResult<&str, Grid<Tile>> {
+    let (rest, (grid, [])) = Grid::parse(input, [], |c| {
+        Some(match c {
+            '.' => Tile::Empty,
+            '|' => Tile::Vertical,
+            '-' => Tile::Horizontal,
+            '/' => Tile::Slash,
+            '\\' => Tile::BackSlash,
+            _ => return None,
+        })
+    })?;
+    Ok((rest, grid))
+}
 
-    type Output2 = usize;
+fn part_1(input: &Grid<Tile>, _params: &Params) -> usize {
+    energized(input, Pose::new(Vec2::new(0, 0), Direction::East))
+}
 
-    #[allow(clippy::cast_possible_wrap)]
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut energized = Vec::<usize>::new();
-        for y in 0..input.height {
-            energized.push(
-                get_beams(
-                    input,
-                    Point {
-                        x: 0,
-                        y: y as isize,
-                    },
-                    Dir::Right,
-                )
-                .len(),
-            );
-            energized.push(
-                get_beams(
-                    input,
-                    Point {
-                        x: (input.width as isize) - 1,
-                        y: y as isize,
-                    },
-                    Dir::Left,
-                )
-                .len(),
-            );
-        }
-        for x in 0..input.width {
-            energized.push(
-                get_beams(
-                    input,
-                    Point {
-                        x: x as isize,
-                        y: 0,
-                    },
-                    Dir::Bottom,
-                )
-                .len(),
-            );
-            energized.push(
-                get_beams(
-                    input,
-                    Point {
-                        x: x as isize,
-                        y: (input.height as isize) - 1,
-                    },
-                    Dir::Top,
-                )
-                .len(),
-            );
-        }
-        energized.iter().max().copied().unwrap()
-    }
+#[allow(clippy::cast_possible_wrap)]
+fn part_2(input: &Grid<Tile>, _params: &Params) -> usize {
+    let (width, height) = (input.width() as isize, input.height() as isize);
+    // the beam can enter from any tile of the edges, towards the opposite edge
+    let rows = (0..height).flat_map(|y| {
+        [
+            Pose::new(Vec2::new(0, y), Direction::East),
+            Pose::new(Vec2::new(width - 1, y), Direction::West),
+        ]
+    });
+    let cols = (0..width).flat_map(|x| {
+        [
+            Pose::new(Vec2::new(x, 0), Direction::South),
+            Pose::new(Vec2::new(x, height - 1), Direction::North),
+        ]
+    });
+    rows.chain(cols)
+        .map(|start| energized(input, start))
+        .max()
+        .unwrap()
 }
 
+impl_day!(
+    Day16,
+    16,
+    parse -> Grid<Tile>,
+    part_1 -> usize,
+    part_2 -> usize,
+    answers = [Some("46"), Some("51")],
+    title = "The Floor Will Be Lava",
+    tags = ["grid", "bfs"],
+    cost = Medium,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = ".|...\\....
-|.-.\\.....
-.....|-...
-........|.
-..........
-.........\\
-..../.\\\\..
-.-.-/..|..
-.|....-|.\\
-..//.|....";
+    use crate::days::Day;
 
     #[test]
     fn test_part1() {
-        let parsed = Day16::parse(INPUT).unwrap().1;
-        assert_eq!(Day16::part_1(&parsed), 46);
+        let parsed = Day16::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day16::part_1(&parsed, &Day16::EXAMPLE_PARAMS), Ok(46));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day16::parse(INPUT).unwrap().1;
-        assert_eq!(Day16::part_2(&parsed), 51);
+        let parsed = Day16::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day16::part_2(&parsed, &Day16::EXAMPLE_PARAMS), Ok(51));
     }
 }
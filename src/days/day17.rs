@@ -1,390 +1,216 @@
 use itertools::Itertools;
-use nom::{
-    character::complete::{digit1, line_ending},
-    combinator::map,
-    multi::separated_list0,
-    IResult,
+use nom::IResult;
+use owo_colors::Style;
+use tracing::trace;
+
+use crate::{
+    color,
+    days::Params,
+    util::{search::astar, Direction, Grid, GridDisplay, Pose, Vec2},
 };
-use owo_colors::OwoColorize;
-use pathfinding::prelude::astar;
 
-use crate::days::Day;
-
-static SIZE: i64 = if cfg!(test) { 13 } else { 141 };
-
-pub struct Day17;
-
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub enum Dir {
-    Up(i64),
-    Right(i64),
-    Down(i64),
-    Left(i64),
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
 
-impl Dir {
-    fn steps(&self) -> i64 {
-        match self {
-            Dir::Up(steps) | Dir::Right(steps) | Dir::Down(steps) | Dir::Left(steps) => *steps,
-        }
-    }
+/// Position of the bottom-right block, where the crucible must go
+///
+/// It's derived from the size of the grid, so that the example and the real input don't need a parameter.
+#[allow(clippy::cast_possible_wrap)]
+fn goal_pos(grid: &Grid<i64>) -> Vec2<i64> {
+    Vec2::new(grid.width() as i64 - 1, grid.height() as i64 - 1)
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct Candidate {
-    pub dir: Dir,
-    pub x: i64,
-    pub y: i64,
+    pub pose: Pose<i64>,
+    /// Number of steps taken in a straight line in `pose.dir` to get here
+    pub steps: i64,
 }
 
 impl Candidate {
     /// Manhattan distance * minimum cost of 1 step = dist * 1
     fn distance(&self, other: &Self) -> i64 {
-        (self.x.max(other.x) - self.x.min(other.x)) + (self.y.max(other.y) - self.y.min(other.y))
+        self.pose.pos.manhattan(other.pose.pos)
+    }
+
+    /// The candidate one step away in `dir`, having taken `steps` steps in that direction
+    fn step(&self, dir: Direction, steps: i64) -> Candidate {
+        Candidate {
+            pose: self.pose.facing(dir).step(),
+            steps,
+        }
     }
 
-    /// Super ugly but works
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-    fn successors(&self, grid: &[Vec<i64>]) -> Vec<(Candidate, i64)> {
-        // initially add all the possible moves (straight, turn left, turn right)
-        let candidates = match self.dir {
-            Dir::Up(steps) => [
-                Candidate {
-                    dir: Dir::Up(steps + 1),
-                    x: self.x,
-                    y: self.y - 1,
-                },
-                Candidate {
-                    dir: Dir::Left(1),
-                    x: self.x - 1,
-                    y: self.y,
-                },
-                Candidate {
-                    dir: Dir::Right(1),
-                    x: self.x + 1,
-                    y: self.y,
-                },
-            ],
-            Dir::Right(steps) => [
-                Candidate {
-                    dir: Dir::Right(steps + 1),
-                    x: self.x + 1,
-                    y: self.y,
-                },
-                Candidate {
-                    dir: Dir::Up(1),
-                    x: self.x,
-                    y: self.y - 1,
-                },
-                Candidate {
-                    dir: Dir::Down(1),
-                    x: self.x,
-                    y: self.y + 1,
-                },
-            ],
-            Dir::Down(steps) => [
-                Candidate {
-                    dir: Dir::Down(steps + 1),
-                    x: self.x,
-                    y: self.y + 1,
-                },
-                Candidate {
-                    dir: Dir::Right(
-                        1 + i64::from(
-                            self.x == 0 && self.y == 0 && matches!(self.dir, Dir::Down(_)),
-                        ),
-                    ), // special case for start
-                    x: self.x + 1,
-                    y: self.y,
-                },
-                Candidate {
-                    dir: Dir::Left(1),
-                    x: self.x - 1,
-                    y: self.y,
-                },
-            ],
-            Dir::Left(steps) => [
-                Candidate {
-                    dir: Dir::Left(steps + 1),
-                    x: self.x - 1,
-                    y: self.y,
-                },
-                Candidate {
-                    dir: Dir::Up(1),
-                    x: self.x,
-                    y: self.y - 1,
-                },
-                Candidate {
-                    dir: Dir::Down(1),
-                    x: self.x,
-                    y: self.y + 1,
-                },
-            ],
-        };
-        // remove invalid moves (out of grid, straight path too long)
+    /// At the start, the crucible can go right as well as down, which counts as its second step
+    fn is_start(&self) -> bool {
+        self.pose == Pose::new(Vec2::new(0, 0), Direction::South)
+    }
+
+    /// Keep the candidates inside of the grid, with the heat loss of their block
+    #[allow(clippy::cast_possible_truncation)]
+    fn in_grid(candidates: Vec<Candidate>, grid: &Grid<i64>) -> Vec<(Candidate, i64)> {
         candidates
             .into_iter()
             .filter_map(|c| {
-                if c.dir.steps() <= 3 && c.x >= 0 && c.x < SIZE && c.y >= 0 && c.y < SIZE {
-                    let x = c.x as usize;
-                    let y = c.y as usize;
-                    Some((c, grid[y][x]))
-                } else {
-                    None
-                }
+                let pos = c.pose.pos;
+                let heat_loss = *grid.get(Vec2::new(pos.x as isize, pos.y as isize))?;
+                Some((c, heat_loss))
             })
             .collect_vec()
     }
 
-    /// Super ugly but works
-    #[allow(
-        clippy::cast_possible_truncation,
-        clippy::cast_sign_loss,
-        clippy::too_many_lines
-    )]
-    fn successors2(&self, grid: &[Vec<i64>]) -> Vec<(Candidate, i64)> {
-        // Register the candidates (for now we allow out of grid)
+    fn successors(&self, grid: &Grid<i64>) -> Vec<(Candidate, i64)> {
+        // all the possible moves (straight, turn left, turn right)
+        let dir = self.pose.dir;
+        let left = dir.turn_left();
+        let candidates = vec![
+            self.step(dir, self.steps + 1),
+            self.step(
+                left,
+                1 + i64::from(self.is_start() && left == Direction::East),
+            ),
+            self.step(dir.turn_right(), 1),
+        ];
+        // remove the straight paths which are too long
+        let candidates = candidates.into_iter().filter(|c| c.steps <= 3).collect();
+        Self::in_grid(candidates, grid)
+    }
+
+    fn successors2(&self, grid: &Grid<i64>) -> Vec<(Candidate, i64)> {
         let mut candidates = Vec::with_capacity(3);
-        match self.dir {
-            Dir::Up(steps) => {
-                // Straight. We can go at most 10 steps in the same direction
-                if steps < 10 {
-                    candidates.push(Candidate {
-                        dir: Dir::Up(steps + 1),
-                        x: self.x,
-                        y: self.y - 1,
-                    });
-                }
-                // Turn. We must go at least 4 steps in the same direction
-                if steps >= 4 {
-                    candidates.push(Candidate {
-                        dir: Dir::Left(1),
-                        x: self.x - 1,
-                        y: self.y,
-                    });
-                    candidates.push(Candidate {
-                        dir: Dir::Right(1),
-                        x: self.x + 1,
-                        y: self.y,
-                    });
-                }
-            }
-            Dir::Right(steps) => {
-                if steps < 10 {
-                    candidates.push(Candidate {
-                        dir: Dir::Right(steps + 1),
-                        x: self.x + 1,
-                        y: self.y,
-                    });
-                }
-                if steps >= 4 {
-                    candidates.push(Candidate {
-                        dir: Dir::Up(1),
-                        x: self.x,
-                        y: self.y - 1,
-                    });
-                    candidates.push(Candidate {
-                        dir: Dir::Down(1),
-                        x: self.x,
-                        y: self.y + 1,
-                    });
-                }
-            }
-            Dir::Down(steps) => {
-                // special case for start, where it could be either Dir::Down or Dir::Right
-                if self.x == 0 && self.y == 0 && matches!(self.dir, Dir::Down(_)) {
-                    candidates.push(Candidate {
-                        dir: Dir::Right(2),
-                        x: self.x + 1,
-                        y: self.y,
-                    });
-                }
-                if steps < 10 {
-                    candidates.push(Candidate {
-                        dir: Dir::Down(steps + 1),
-                        x: self.x,
-                        y: self.y + 1,
-                    });
-                }
-                if steps >= 4 {
-                    candidates.push(Candidate {
-                        dir: Dir::Right(1),
-                        x: self.x + 1,
-                        y: self.y,
-                    });
-                    candidates.push(Candidate {
-                        dir: Dir::Left(1),
-                        x: self.x - 1,
-                        y: self.y,
-                    });
-                }
-            }
-            Dir::Left(steps) => {
-                if steps < 10 {
-                    candidates.push(Candidate {
-                        dir: Dir::Left(steps + 1),
-                        x: self.x - 1,
-                        y: self.y,
-                    });
-                }
-                if steps >= 4 {
-                    candidates.push(Candidate {
-                        dir: Dir::Up(1),
-                        x: self.x,
-                        y: self.y - 1,
-                    });
-                    candidates.push(Candidate {
-                        dir: Dir::Down(1),
-                        x: self.x,
-                        y: self.y + 1,
-                    });
-                }
-            }
-        };
-        // filter out out of grid candidates
-        candidates
-            .into_iter()
-            .filter_map(|c| {
-                if c.x >= 0 && c.x < SIZE && c.y >= 0 && c.y < SIZE {
-                    let x = c.x as usize;
-                    let y = c.y as usize;
-                    Some((c, grid[y][x]))
-                } else {
-                    None
-                }
-            })
-            .collect_vec()
+        if self.is_start() {
+            candidates.push(self.step(Direction::East, 2));
+        }
+        // Straight. We can go at most 10 steps in the same direction
+        let dir = self.pose.dir;
+        if self.steps < 10 {
+            candidates.push(self.step(dir, self.steps + 1));
+        }
+        // Turn. We must go at least 4 steps in the same direction
+        if self.steps >= 4 {
+            candidates.push(self.step(dir.turn_left(), 1));
+            candidates.push(self.step(dir.turn_right(), 1));
+        }
+        Self::in_grid(candidates, grid)
     }
 }
 
-/// Print a colorful representation of the path in the grid
+/// Draw a colorful representation of the path in the grid
 #[allow(
     clippy::cast_possible_wrap,
     clippy::cast_possible_truncation,
-    clippy::cast_sign_loss,
-    unused
+    clippy::cast_sign_loss
 )]
-fn print_path(path: &[Candidate], grid: &[Vec<i64>]) {
+fn draw_path(path: &[Candidate], grid: &Grid<i64>) -> String {
+    let step_at = |pos: Vec2<isize>| {
+        path.iter()
+            .find(|c| c.pose.pos == Vec2::new(pos.x as i64, pos.y as i64))
+    };
     let gradient = colorous::PLASMA;
-    for (y, row) in grid.iter().enumerate() {
-        for (x, value) in row.iter().enumerate() {
-            let color = gradient.eval_rational(*value as usize, 9);
-            if let Some(pos) = path.iter().find(|c| c.x == (x as i64) && c.y == (y as i64)) {
-                let symbol = match pos.dir {
-                    Dir::Up(_) => "^",
-                    Dir::Right(_) => ">",
-                    Dir::Down(_) => "v",
-                    Dir::Left(_) => "<",
-                };
-                print!(
-                    "{}",
-                    symbol
-                        .on_truecolor(color.r, color.g, color.b)
-                        .truecolor(255, 255, 255)
-                );
-            } else {
-                print!("{}", " ".on_truecolor(color.r, color.g, color.b));
-            }
+    GridDisplay::with_glyphs(grid, |pos, value| match step_at(pos) {
+        Some(c) => c.pose.dir.arrow(),
+        None if color::enabled() => ' ',
+        // without colors, show the heat loss instead of the gradient
+        None => char::from_digit(*value as u32, 10).unwrap_or('?'),
+    })
+    .styled(|pos, value| {
+        let color = gradient.eval_rational(*value as usize, 9);
+        let style = Style::new().on_truecolor(color.r, color.g, color.b);
+        if step_at(pos).is_some() {
+            style.truecolor(255, 255, 255)
+        } else {
+            style
         }
-        println!();
-    }
+    })
+    .to_string()
 }
 
-impl Day for Day17 {
-    type Input = Vec<Vec<i64>>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
-            line_ending,
-            map(digit1, |s: &str| {
-                s.chars()
-                    .map(|c| i64::from(c.to_digit(10).unwrap()))
-                    .collect_vec()
-            }),
-        )(input)
-    }
-
-    type Output1 = i64;
-
-    /// Part 1 took 43.9389ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        // We use A* to find the optimal path.
-        // To see my implementation, see AoC 2022 day 12.
-        // Here I used a library.
-        let start = Candidate {
-            dir: Dir::Down(1),
-            x: 0,
-            y: 0,
-        };
-        let goal = Candidate {
-            dir: Dir::Down(0),
-            x: SIZE - 1,
-            y: SIZE - 1,
-        };
-        let result = astar(
-            &start,
-            |c| c.successors(input),
-            |c| c.distance(&goal),
-            |c| c.x == goal.x && c.y == goal.y,
-        )
-        .unwrap();
-        print_path(&result.0, input);
-        result.1
-    }
+fn parse(input: &str) -> IResult<&str, Grid<i64>> {
+    let (rest, (grid, [])) = Grid::parse(input, [], |c| c.to_digit(10).map(i64::from))?;
+    Ok((rest, grid))
+}
 
-    type Output2 = i64;
+/// Part 1 took 43.9389ms
+fn part_1(input: &Grid<i64>, _params: &Params) -> i64 {
+    // We use A* to find the optimal path.
+    let start = Candidate {
+        pose: Pose::new(Vec2::new(0, 0), Direction::South),
+        steps: 1,
+    };
+    let goal = Candidate {
+        pose: Pose::new(goal_pos(input), Direction::South),
+        steps: 0,
+    };
+    let result = astar(
+        &start,
+        |c| c.successors(input),
+        |c| c.distance(&goal),
+        |c| c.pose.pos == goal.pose.pos,
+    )
+    .unwrap();
+    trace!("best path:\n{}", draw_path(&result.0, input));
+    result.1
+}
 
-    /// Part 2 took 197.254202ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let start = Candidate {
-            dir: Dir::Down(1),
-            x: 0,
-            y: 0,
-        };
-        let goal = Candidate {
-            dir: Dir::Down(0),
-            x: SIZE - 1,
-            y: SIZE - 1,
-        };
-        let result = astar(
-            &start,
-            |c| c.successors2(input),
-            |c| c.distance(&goal),
-            |c| c.x == goal.x && c.y == goal.y,
-        )
-        .unwrap();
-        print_path(&result.0, input);
-        result.1
-    }
+/// Part 2 took 197.254202ms
+fn part_2(input: &Grid<i64>, _params: &Params) -> i64 {
+    let start = Candidate {
+        pose: Pose::new(Vec2::new(0, 0), Direction::South),
+        steps: 1,
+    };
+    let goal = Candidate {
+        pose: Pose::new(goal_pos(input), Direction::South),
+        steps: 0,
+    };
+    let result = astar(
+        &start,
+        |c| c.successors2(input),
+        |c| c.distance(&goal),
+        |c| c.pose.pos == goal.pose.pos,
+    )
+    .unwrap();
+    trace!("best path:\n{}", draw_path(&result.0, input));
+    result.1
 }
 
+impl_day!(
+    Day17,
+    17,
+    parse -> Grid<i64>,
+    part_1 -> i64,
+    part_2 -> i64,
+    answers = [Some("102"), Some("94")],
+    title = "Clumsy Crucible",
+    tags = ["pathfinding", "grid"],
+    cost = Medium,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "2413432311323
-3215453535623
-3255245654254
-3446585845452
-4546657867536
-1438598798454
-4457876987766
-3637877979653
-4654967986887
-4564679986453
-1224686865563
-2546548887735
-4322674655533";
+    use crate::days::Day;
 
     #[test]
     fn test_part1() {
-        let parsed = Day17::parse(INPUT).unwrap().1;
-        assert_eq!(Day17::part_1(&parsed), 102);
+        let parsed = Day17::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day17::part_1(&parsed, &Day17::EXAMPLE_PARAMS), Ok(102));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day17::parse(INPUT).unwrap().1;
-        assert_eq!(Day17::part_2(&parsed), 94);
+        let parsed = Day17::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day17::part_2(&parsed, &Day17::EXAMPLE_PARAMS), Ok(94));
     }
 }
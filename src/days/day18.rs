@@ -1,163 +1,202 @@
 use nom::{
     bytes::complete::tag,
-    character::complete::{char, hex_digit1, i64, line_ending, one_of, space1},
-    combinator::map,
-    multi::separated_list0,
+    character::complete::{char, hex_digit1, i64, one_of, space1},
+    combinator::{map, map_opt},
     sequence::{preceded, terminated, tuple},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::days::Day;
-
-pub struct Day18;
+use crate::{
+    days::Params,
+    parser::helpers::lines_of,
+    util::{Direction, Polygon, Pose, Vec2},
+};
 
-#[derive(Debug)]
-pub enum Dir {
-    Up,
-    Right,
-    Down,
-    Left,
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Instruction {
-    dir: Dir,
+    dir: Direction,
     dist: i64,
     color: String,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
-pub struct Point {
-    y: i64,
-    x: i64,
-}
-
 #[allow(clippy::cast_possible_wrap)]
-fn get_trench_coordinates(input: &[Instruction], part2: bool) -> (Vec<Point>, i64) {
-    let mut trench = Vec::<Point>::new();
-    // save the total distance
-    let mut total_dist = 0i64;
-    let mut x = 0i64;
-    let mut y = 0i64;
+fn get_trench_coordinates(input: &[Instruction], part2: bool) -> Polygon<i64> {
+    let mut trench = Vec::<Vec2<i64>>::new();
+    let mut digger = Pose::new(Vec2::<i64>::default(), Direction::North);
     for instr in input {
-        let mut dist = instr.dist;
-        if part2 {
-            dist = u64::from_str_radix(&instr.color[..5], 16).unwrap() as i64;
-        }
-        total_dist += dist;
-        if part2 {
-            match &instr.color.chars().nth(5).unwrap() {
-                '3' => y -= dist,
-                '0' => x += dist,
-                '1' => y += dist,
-                '2' => x -= dist,
+        let (dir, dist) = if part2 {
+            // the last hex digit is the direction, clockwise from the right
+            let dir = match instr.color.as_bytes()[5] {
+                b'0' => Direction::East,
+                b'1' => Direction::South,
+                b'2' => Direction::West,
+                b'3' => Direction::North,
                 _ => unreachable!(),
-            }
+            };
+            (
+                dir,
+                u64::from_str_radix(&instr.color[..5], 16).unwrap() as i64,
+            )
         } else {
-            match instr.dir {
-                Dir::Up => y -= dist,
-                Dir::Right => x += dist,
-                Dir::Down => y += dist,
-                Dir::Left => x -= dist,
-            }
-        }
-        trench.push(Point { y, x });
+            (instr.dir, instr.dist)
+        };
+        digger = digger.facing(dir).advance(dist);
+        trench.push(digger.pos);
     }
-    (trench, total_dist)
+    Polygon::new(trench)
 }
 
-fn pool_area(trench: &[Point], perimeter: i64) -> i64 {
-    let mut double_area = 0i64;
-    let len = trench.len();
-    // algorithm to find double of the area of a non-intersecting polygon without holes
-    // the area can be negative depending on the clockwise or anti-clockwise direction of the sequence
-    for i in 0..len {
-        let j = (i + 1) % len;
-        let this = trench.get(i).unwrap();
-        let other = trench.get(j).unwrap();
-        double_area += this.x * other.y;
-        double_area -= this.y * other.x;
-    }
-    // the area is counting half of the trench volumes only, so we need to add the other half
-    // is the +1 always needed?
-    double_area.abs() / 2 + perimeter / 2 + 1
+/// The number of cubic meters of the pool: the trench itself plus the points inside of it
+fn pool_area(trench: &Polygon<i64>) -> i64 {
+    trench.interior_points() + trench.boundary_points()
 }
 
-impl Day for Day18 {
-    type Input = Vec<Instruction>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
-            line_ending,
-            map(
-                tuple((
-                    one_of("UDLR"),
-                    space1,
-                    i64,
-                    space1,
-                    preceded(tag("(#"), terminated(hex_digit1, char(')'))),
-                )),
-                |(dir, _, dist, _, color): (char, &str, i64, &str, &str)| {
-                    let dir = match dir {
-                        'U' => Dir::Up,
-                        'R' => Dir::Right,
-                        'D' => Dir::Down,
-                        'L' => Dir::Left,
-                        _ => unreachable!(),
-                    };
-                    Instruction {
-                        dir,
-                        dist,
-                        color: color.to_string(),
-                    }
-                },
-            ),
-        )(input)
-    }
-
-    type Output1 = i64;
-
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let (trench, perimeter) = get_trench_coordinates(input, false);
-        pool_area(&trench, perimeter)
-    }
+fn parse(input: &str) -> IResult<&str, Vec<Instruction>> {
+    lines_of(map(
+        tuple((
+            map_opt(one_of("UDLR"), Direction::from_char),
+            space1,
+            i64,
+            space1,
+            preceded(tag("(#"), terminated(hex_digit1, char(')'))),
+        )),
+        |(dir, _, dist, _, color): (Direction, &str, i64, &str, &str)| Instruction {
+            dir,
+            dist,
+            color: color.to_string(),
+        },
+    ))(input)
+}
 
-    type Output2 = i64;
+fn part_1(input: &[Instruction], _params: &Params) -> i64 {
+    pool_area(&get_trench_coordinates(input, false))
+}
 
-    #[allow(clippy::cast_possible_wrap)]
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let (trench, perimeter) = get_trench_coordinates(input, true);
-        pool_area(&trench, perimeter)
-    }
+#[allow(clippy::cast_possible_wrap)]
+fn part_2(input: &[Instruction], _params: &Params) -> i64 {
+    pool_area(&get_trench_coordinates(input, true))
 }
 
+impl_day!(
+    Day18,
+    18,
+    parse -> Vec<Instruction>,
+    part_1 -> i64,
+    part_2 -> i64,
+    answers = [Some("62"), Some("952408144115")],
+    title = "Lavaduct Lagoon",
+    tags = ["geometry"],
+);
+
 #[cfg(test)]
 mod tests {
-    use super::*;
-    const INPUT: &str = "R 6 (#70c710)
-D 5 (#0dc571)
-L 2 (#5713f0)
-D 2 (#d2c081)
-R 2 (#59c680)
-D 2 (#411b91)
-L 5 (#8ceee2)
-U 2 (#caa173)
-L 1 (#1b58a2)
-U 2 (#caa171)
-R 2 (#7807d2)
-U 3 (#a77fa3)
-L 2 (#015232)
-U 2 (#7a21e3)";
+    use itertools::Itertools;
 
+    use super::*;
+    use crate::{
+        days::Day,
+        util::{
+            flood::{flood_fill, Connectivity},
+            Aabb, CompressedAxis, Grid, SparseGrid,
+        },
+    };
     #[test]
     fn test_part1() {
-        let parsed = Day18::parse(INPUT).unwrap().1;
-        assert_eq!(Day18::part_1(&parsed), 62);
+        let parsed = Day18::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day18::part_1(&parsed, &Day18::EXAMPLE_PARAMS), Ok(62));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day18::parse(INPUT).unwrap().1;
-        assert_eq!(Day18::part_2(&parsed), 952_408_144_115);
+        let parsed = Day18::parse(EXAMPLE).unwrap().1;
+        assert_eq!(
+            Day18::part_2(&parsed, &Day18::EXAMPLE_PARAMS),
+            Ok(952_408_144_115)
+        );
+    }
+
+    /// Dig the trench of the first part on a grid and fill the ground outside of it, as a check of Pick's theorem
+    #[test]
+    #[allow(clippy::cast_possible_truncation)]
+    fn test_flood_fill() {
+        let parsed = Day18::parse(EXAMPLE).unwrap().1;
+        let trench = get_trench_coordinates(&parsed, false);
+        let vertices = trench.vertices();
+        let mut dug = SparseGrid::new();
+        for (i, &from) in vertices.iter().enumerate() {
+            let to = vertices[(i + 1) % vertices.len()];
+            let step = Vec2::new((to.x - from.x).signum(), (to.y - from.y).signum());
+            let mut pos = from;
+            while pos != to {
+                dug.insert(Vec2::new(pos.x as isize, pos.y as isize), true);
+                pos += step;
+            }
+        }
+        // leave a border of one cell around the trench, so that the outside is a single region
+        let Aabb { min, max } = dug.bounds().unwrap();
+        dug.insert(Vec2::from(min) - Vec2::new(1, 1), false);
+        dug.insert(Vec2::from(max) + Vec2::new(1, 1), false);
+        let (dug, _) = dug.to_grid(false).unwrap();
+        let outside = flood_fill(&dug, Vec2::new(0, 0), Connectivity::Four, |&d| !d);
+        assert_eq!(dug.cells().len() - outside.len(), 62);
+    }
+
+    /// The area of the pool by filling the outside of the trench on a grid compressed around its corners, which stays
+    /// small for the second part too
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn compressed_area(trench: &Polygon<i64>) -> i64 {
+        let vertices = trench.vertices();
+        // each corner gets a row and a column of its own, and a border of one cell is left around the trench
+        let axis = |coord: fn(&Vec2<i64>) -> i64| -> CompressedAxis {
+            let (min, max) = vertices.iter().map(coord).minmax().into_option().unwrap();
+            vertices
+                .iter()
+                .flat_map(|v| [coord(v), coord(v) + 1])
+                .chain([min - 1, max + 2])
+                .collect()
+        };
+        let (xs, ys) = (axis(|v| v.x), axis(|v| v.y));
+        let mut dug = Grid::new(xs.cells(), ys.cells(), false);
+        for (i, &from) in vertices.iter().enumerate() {
+            let to = vertices[(i + 1) % vertices.len()];
+            let x_range = xs.index(from.x.min(to.x)).unwrap()..=xs.index(from.x.max(to.x)).unwrap();
+            let y_range = ys.index(from.y.min(to.y)).unwrap()..=ys.index(from.y.max(to.y)).unwrap();
+            for (x, y) in x_range.cartesian_product(y_range) {
+                dug[Vec2::new(x as isize, y as isize)] = true;
+            }
+        }
+        let outside = flood_fill(&dug, Vec2::new(0, 0), Connectivity::Four, |&d| !d);
+        let total = xs.gaps().sum::<i64>() * ys.gaps().sum::<i64>();
+        total
+            - outside
+                .iter()
+                .map(|pos| xs.gap(pos.x as usize) * ys.gap(pos.y as usize))
+                .sum::<i64>()
+    }
+
+    #[test]
+    fn test_compressed_area() {
+        let parsed = Day18::parse(EXAMPLE).unwrap().1;
+        for (part2, area) in [(false, 62), (true, 952_408_144_115)] {
+            let trench = get_trench_coordinates(&parsed, part2);
+            assert_eq!(compressed_area(&trench), area);
+        }
     }
 }
@@ -1,20 +1,43 @@
-use std::collections::HashMap;
-
 use nom::{
+    branch::alt,
     bytes::complete::tag,
-    character::complete::{alpha1, char, line_ending, one_of, u64},
-    combinator::{map, opt},
+    character::complete::{alpha1, char, u64},
+    combinator::{map, opt, value},
     multi::separated_list1,
     sequence::{preceded, separated_pair, terminated, tuple},
     IResult,
 };
-use ranges::{GenericRange, OperationResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    days::Params,
+    parser::{
+        helpers::{blank_line, lines_of},
+        SolveError,
+    },
+    util::{Interner, Interval},
+};
 
-use crate::days::Day;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
 
-pub struct Day19;
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Part {
     x: u64,
     m: u64,
@@ -22,31 +45,95 @@ pub struct Part {
     s: u64,
 }
 
-#[derive(Debug, Clone)]
+/// What happens to a part, where `Goto` holds the id of the next workflow
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Action {
-    Goto(String),
+    Goto(u32),
     Accepted,
     Rejected,
 }
 
+/// One of the four parameters of a part
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Param {
+    X,
+    M,
+    A,
+    S,
+}
+
 /// A condition, either greater than or less than, with the first item being the parameter (x, m, a or s) and second
 /// being the value to use for the comparison
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum Condition {
-    Lt(char, u64),
-    Gt(char, u64),
+    Lt(Param, u64),
+    Gt(Param, u64),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rule {
     cond: Option<Condition>,
     action: Action,
 }
 
-#[derive(Debug, Clone)]
-pub struct Workflow {
-    name: String,
-    rules: Vec<Rule>,
+/// The rules of each workflow, indexed by the id of its name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workflows<'a> {
+    #[serde(borrow)]
+    names: Interner<'a>,
+    rules: Vec<Vec<Rule>>,
+}
+
+/// A workflow as written in the input, with its name and the condition and target of each rule
+type WorkflowDef<'a> = (&'a str, Vec<(Option<Condition>, &'a str)>);
+
+impl<'a> Workflows<'a> {
+    fn new(defs: &[WorkflowDef<'a>]) -> Self {
+        // the workflows get the first ids so that they index the rules
+        let mut names: Interner = defs.iter().map(|(name, _)| *name).collect();
+        let mut rules = vec![Vec::new(); names.len()];
+        for (name, defs) in defs {
+            rules[names.intern(name) as usize] = defs
+                .iter()
+                .map(|&(cond, target)| {
+                    let action = match target {
+                        "A" => Action::Accepted,
+                        "R" => Action::Rejected,
+                        name => Action::Goto(names.intern(name)),
+                    };
+                    Rule { cond, action }
+                })
+                .collect();
+        }
+        Self { names, rules }
+    }
+
+    /// The id of the "in" workflow, where every part starts
+    fn start(&self) -> Result<u32, SolveError> {
+        self.names
+            .get("in")
+            .ok_or_else(|| SolveError::invalid("there is no workflow named in"))
+    }
+
+    /// The rules of a workflow, `depth` being the number of workflows that the part went through before
+    ///
+    /// A part can't go through more workflows than there are without looping forever.
+    fn rules(&self, workflow: u32, depth: usize) -> Result<&[Rule], SolveError> {
+        if depth > self.rules.len() {
+            return Err(SolveError::invalid(
+                "the workflows send parts around in a loop",
+            ));
+        }
+        self.rules
+            .get(workflow as usize)
+            .map(Vec::as_slice)
+            .ok_or_else(|| {
+                SolveError::invalid(format!(
+                    "there is no workflow named {}",
+                    self.names.name(workflow)
+                ))
+            })
+    }
 }
 
 impl Part {
@@ -57,27 +144,29 @@ impl Part {
 }
 
 /// Check if a part is accepted after being processed by all the workflows
-fn is_part_accepted(workflow: &str, part: &Part, workflows: &HashMap<String, Workflow>) -> bool {
-    let workflow = workflows.get(workflow).unwrap();
+fn is_part_accepted(
+    workflow: u32,
+    depth: usize,
+    part: &Part,
+    workflows: &Workflows,
+) -> Result<bool, SolveError> {
     // apply each rule until the part is accepted or rejected
-    for rule in &workflow.rules {
+    for rule in workflows.rules(workflow, depth)? {
         // check if there is a condition, and if yes, whether it's true
         let cond_true = match rule.cond {
             Some(cond) => match cond {
                 // condider on which value we need to branch and perform the comparison
                 Condition::Lt(param, value) => match param {
-                    'x' => part.x < value,
-                    'm' => part.m < value,
-                    'a' => part.a < value,
-                    's' => part.s < value,
-                    _ => unimplemented!(),
+                    Param::X => part.x < value,
+                    Param::M => part.m < value,
+                    Param::A => part.a < value,
+                    Param::S => part.s < value,
                 },
                 Condition::Gt(param, value) => match param {
-                    'x' => part.x > value,
-                    'm' => part.m > value,
-                    'a' => part.a > value,
-                    's' => part.s > value,
-                    _ => unimplemented!(),
+                    Param::X => part.x > value,
+                    Param::M => part.m > value,
+                    Param::A => part.a > value,
+                    Param::S => part.s > value,
                 },
             },
             // when no condition, it's always true
@@ -85,144 +174,93 @@ fn is_part_accepted(workflow: &str, part: &Part, workflows: &HashMap<String, Wor
         };
         if cond_true {
             // in case the condition was matched (or we are at the last rule), then categorize accordingly
-            return match &rule.action {
-                Action::Goto(w) => is_part_accepted(w, part, workflows), // recursively find out if accepted
-                Action::Accepted => true,
-                Action::Rejected => false,
+            return match rule.action {
+                Action::Goto(w) => is_part_accepted(w, depth + 1, part, workflows), // recursively find out if accepted
+                Action::Accepted => Ok(true),
+                Action::Rejected => Ok(false),
             };
         }
     }
-    false
-}
-
-/// Calculate the overlap of two ranges, returning an empty range if none
-fn range_overlap(a: GenericRange<u64>, b: GenericRange<u64>) -> GenericRange<u64> {
-    match a & b {
-        OperationResult::Empty | OperationResult::Double(_, _) => GenericRange::from(0..0),
-        OperationResult::Single(r) => r,
-    }
-}
-
-/// Calculate how many values are in a range
-fn len(range: GenericRange<u64>) -> u64 {
-    range.into_iter().count() as u64
+    Ok(false)
 }
 
 /// Check how many combinations of values are accepted considering the set of workflows
 ///
-/// Ranges for each parameter are passed to the function, and are initialized at 1..=4000
+/// Ranges for each parameter are passed to the function, and are initialized at 1..4001
 #[allow(clippy::too_many_lines)]
 fn combinations(
-    workflow: &str,
-    workflows: &HashMap<String, Workflow>,
-    x: GenericRange<u64>,
-    m: GenericRange<u64>,
-    a: GenericRange<u64>,
-    s: GenericRange<u64>,
-) -> u64 {
+    workflow: u32,
+    depth: usize,
+    workflows: &Workflows,
+    x: Interval<u64>,
+    m: Interval<u64>,
+    a: Interval<u64>,
+    s: Interval<u64>,
+) -> Result<u64, SolveError> {
     // get mutable copies of our input ranges
     let (mut x, mut m, mut a, mut s) = (x, m, a, s);
-    let workflow = workflows.get(workflow).unwrap();
     // sum of all combinations
     let mut sum: u64 = 0;
-    for rule in &workflow.rules {
+    for rule in workflows.rules(workflow, depth)? {
         // first set for "true" condition, second set for "false"
         let (x1, m1, a1, s1, x2, m2, a2, s2) = if let Some(cond) = rule.cond {
             match cond {
                 // check the overlap between the acceptable range, and the range that was filtered in previous steps
                 // we return two disjoint ranges, one if the condition was true, the other if it was false
                 Condition::Lt(param, value) => match param {
-                    'x' => (
-                        range_overlap(x, GenericRange::new_less_than(value)),
-                        m,
-                        a,
-                        s,
-                        range_overlap(x, GenericRange::new_at_least(value)),
-                        m,
-                        a,
-                        s,
-                    ),
-                    'm' => (
-                        x,
-                        range_overlap(m, GenericRange::new_less_than(value)),
-                        a,
-                        s,
-                        x,
-                        range_overlap(m, GenericRange::new_at_least(value)),
-                        a,
-                        s,
-                    ),
-                    'a' => (
-                        x,
-                        m,
-                        range_overlap(a, GenericRange::new_less_than(value)),
-                        s,
-                        x,
-                        m,
-                        range_overlap(a, GenericRange::new_at_least(value)),
-                        s,
-                    ),
-                    's' => (
-                        x,
-                        m,
-                        a,
-                        range_overlap(s, GenericRange::new_less_than(value)),
-                        x,
-                        m,
-                        a,
-                        range_overlap(s, GenericRange::new_at_least(value)),
-                    ),
-                    _ => unimplemented!(),
+                    Param::X => (x.split_at(value).0, m, a, s, x.split_at(value).1, m, a, s),
+                    Param::M => (x, m.split_at(value).0, a, s, x, m.split_at(value).1, a, s),
+                    Param::A => (x, m, a.split_at(value).0, s, x, m, a.split_at(value).1, s),
+                    Param::S => (x, m, a, s.split_at(value).0, x, m, a, s.split_at(value).1),
                 },
                 Condition::Gt(param, value) => match param {
-                    'x' => (
-                        range_overlap(x, GenericRange::new_greater_than(value)),
+                    Param::X => (
+                        x.split_at(value + 1).1,
                         m,
                         a,
                         s,
-                        range_overlap(x, GenericRange::new_at_most(value)),
+                        x.split_at(value + 1).0,
                         m,
                         a,
                         s,
                     ),
-                    'm' => (
+                    Param::M => (
                         x,
-                        range_overlap(m, GenericRange::new_greater_than(value)),
+                        m.split_at(value + 1).1,
                         a,
                         s,
                         x,
-                        range_overlap(m, GenericRange::new_at_most(value)),
+                        m.split_at(value + 1).0,
                         a,
                         s,
                     ),
-                    'a' => (
+                    Param::A => (
                         x,
                         m,
-                        range_overlap(a, GenericRange::new_greater_than(value)),
+                        a.split_at(value + 1).1,
                         s,
                         x,
                         m,
-                        range_overlap(a, GenericRange::new_at_most(value)),
+                        a.split_at(value + 1).0,
                         s,
                     ),
-                    's' => (
+                    Param::S => (
                         x,
                         m,
                         a,
-                        range_overlap(s, GenericRange::new_greater_than(value)),
+                        s.split_at(value + 1).1,
                         x,
                         m,
                         a,
-                        range_overlap(s, GenericRange::new_at_most(value)),
+                        s.split_at(value + 1).0,
                     ),
-                    _ => unimplemented!(),
                 },
             }
         } else {
             // we had no condition, so we are at the last filter and simply consider each case
-            let comb = match &rule.action {
-                Action::Goto(wf) => combinations(wf, workflows, x, m, a, s),
-                Action::Accepted => len(x) * len(m) * len(a) * len(s),
+            let comb = match rule.action {
+                Action::Goto(wf) => combinations(wf, depth + 1, workflows, x, m, a, s)?,
+                Action::Accepted => x.len() * m.len() * a.len() * s.len(),
                 Action::Rejected => 0,
             };
             sum += comb;
@@ -230,57 +268,52 @@ fn combinations(
         };
         // here we had a condition so we split into two range sets
         // the first set is for when the condition was true and we process with the rule's action
-        let true_comb = match &rule.action {
-            Action::Goto(wf) => combinations(wf, workflows, x1, m1, a1, s1),
-            Action::Accepted => len(x1) * len(m1) * len(a1) * len(s1),
+        let true_comb = match rule.action {
+            Action::Goto(wf) => combinations(wf, depth + 1, workflows, x1, m1, a1, s1)?,
+            Action::Accepted => x1.len() * m1.len() * a1.len() * s1.len(),
             Action::Rejected => 0,
         };
         sum += true_comb;
         // the second set continues to get processed by the following rules
         (x, m, a, s) = (x2, m2, a2, s2);
     }
-    sum
+    Ok(sum)
+}
+
+fn parse_param(input: &str) -> IResult<&str, Param> {
+    alt((
+        value(Param::X, char('x')),
+        value(Param::M, char('m')),
+        value(Param::A, char('a')),
+        value(Param::S, char('s')),
+    ))(input)
 }
 
 fn parse_condition(input: &str) -> IResult<&str, Condition> {
-    let (rest, (param, comp, value)) = tuple((one_of("xmas"), one_of("<>"), u64))(input)?;
-    let cond = match comp {
-        '<' => Condition::Lt(param, value),
-        '>' => Condition::Gt(param, value),
-        _ => unimplemented!(),
-    };
-    Ok((rest, cond))
+    alt((
+        map(
+            separated_pair(parse_param, char('<'), u64),
+            |(param, value)| Condition::Lt(param, value),
+        ),
+        map(
+            separated_pair(parse_param, char('>'), u64),
+            |(param, value)| Condition::Gt(param, value),
+        ),
+    ))(input)
 }
 
-fn parse_rule(input: &str) -> IResult<&str, Rule> {
-    map(
-        tuple((opt(terminated(parse_condition, char(':'))), alpha1)),
-        |(cond, action)| {
-            let action = match action {
-                "A" => Action::Accepted,
-                "R" => Action::Rejected,
-                s => Action::Goto(s.to_string()),
-            };
-            Rule { cond, action }
-        },
-    )(input)
+fn parse_rule(input: &str) -> IResult<&str, (Option<Condition>, &str)> {
+    tuple((opt(terminated(parse_condition, char(':'))), alpha1))(input)
 }
 
-fn parse_workflow(input: &str) -> IResult<&str, Workflow> {
-    let (rest, (name, rules)) = tuple((
+fn parse_workflow(input: &str) -> IResult<&str, WorkflowDef<'_>> {
+    tuple((
         alpha1,
         preceded(
             char('{'),
             terminated(separated_list1(char(','), parse_rule), char('}')),
         ),
-    ))(input)?;
-    Ok((
-        rest,
-        Workflow {
-            name: name.to_string(),
-            rules,
-        },
-    ))
+    ))(input)
 }
 
 fn parse_part(input: &str) -> IResult<&str, Part> {
@@ -299,82 +332,85 @@ fn parse_part(input: &str) -> IResult<&str, Part> {
     Ok((rest, Part { x, m, a, s }))
 }
 
-impl Day for Day19 {
-    type Input = (HashMap<String, Workflow>, Vec<Part>);
+/// The workflows by name, and the parts to sort
+type Input<'a> = (Workflows<'a>, Vec<Part>);
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_pair(
-            map(separated_list1(line_ending, parse_workflow), |workflows| {
-                workflows.into_iter().map(|w| (w.name.clone(), w)).collect()
-            }),
-            tag("\n\n"),
-            separated_list1(line_ending, parse_part),
-        )(input)
-    }
-
-    type Output1 = u64;
-
-    /// Part 1 took 35.646µs
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let (workflows, parts) = input;
-        parts
-            .iter()
-            .filter_map(|p| {
-                if is_part_accepted("in", p, workflows) {
-                    Some(p.score())
-                } else {
-                    None
-                }
-            })
-            .sum()
-    }
+fn parse(input: &str) -> IResult<&str, Input<'_>> {
+    separated_pair(
+        map(lines_of(parse_workflow), |defs| Workflows::new(&defs)),
+        blank_line,
+        lines_of(parse_part),
+    )(input)
+}
 
-    type Output2 = u64;
+/// Part 1 took 35.646µs
+fn part_1(input: &Input<'_>, _params: &Params) -> Result<u64, SolveError> {
+    let (workflows, parts) = input;
+    let start = workflows.start()?;
+    parts
+        .iter()
+        .filter_map(|p| match is_part_accepted(start, 0, p, workflows) {
+            Ok(true) => Some(Ok(p.score())),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .sum()
+}
 
-    /// Part 2 took 2.352422ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let (workflows, _) = input;
-        combinations(
-            "in",
-            workflows,
-            GenericRange::from(1..=4000),
-            GenericRange::from(1..=4000),
-            GenericRange::from(1..=4000),
-            GenericRange::from(1..=4000),
-        )
-    }
+/// Part 2 took 2.352422ms
+fn part_2(input: &Input<'_>, _params: &Params) -> Result<u64, SolveError> {
+    let (workflows, _) = input;
+    combinations(
+        workflows.start()?,
+        0,
+        workflows,
+        Interval::new(1, 4001),
+        Interval::new(1, 4001),
+        Interval::new(1, 4001),
+        Interval::new(1, 4001),
+    )
 }
 
+impl_day!(
+    Day19,
+    19,
+    parse -> Input<'a>,
+    part_1 -> u64,
+    part_2 -> u64,
+    answers = [Some("19114"), Some("167409079868000")],
+    title = "Aplenty",
+    tags = ["ranges"],
+    parse_version = 2,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "px{a<2006:qkq,m>2090:A,rfg}
-pv{a>1716:R,A}
-lnx{m>1548:A,A}
-rfg{s<537:gd,x>2440:R,A}
-qs{s>3448:A,lnx}
-qkq{x<1416:A,crn}
-crn{x>2662:A,R}
-in{s<1351:px,qqz}
-qqz{s>2770:qs,m<1801:hdj,R}
-gd{a>3333:R,R}
-hdj{m>838:A,pv}
-
-{x=787,m=2655,a=1222,s=2876}
-{x=1679,m=44,a=2067,s=496}
-{x=2036,m=264,a=79,s=2244}
-{x=2461,m=1339,a=466,s=291}
-{x=2127,m=1623,a=2188,s=1013}";
-
+    use crate::days::Day;
     #[test]
     fn test_part1() {
-        let parsed = Day19::parse(INPUT).unwrap().1;
-        assert_eq!(Day19::part_1(&parsed), 19114);
+        let parsed = Day19::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day19::part_1(&parsed, &Day19::EXAMPLE_PARAMS), Ok(19114));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day19::parse(INPUT).unwrap().1;
-        assert_eq!(Day19::part_2(&parsed), 167_409_079_868_000);
+        let parsed = Day19::parse(EXAMPLE).unwrap().1;
+        assert_eq!(
+            Day19::part_2(&parsed, &Day19::EXAMPLE_PARAMS),
+            Ok(167_409_079_868_000)
+        );
+    }
+
+    #[test]
+    fn test_invalid_workflows() {
+        for input in [
+            "in{x<10:A,ab}\n\n{x=787,m=2655,a=1222,s=2876}",
+            "in{x<10:A,ab}\nab{in}\n\n{x=787,m=2655,a=1222,s=2876}",
+        ] {
+            let parsed = parse(input).unwrap().1;
+            assert!(part_1(&parsed, &Day19::EXAMPLE_PARAMS).is_err(), "{input}");
+            assert!(part_2(&parsed, &Day19::EXAMPLE_PARAMS).is_err(), "{input}");
+        }
     }
 }
@@ -1,29 +1,45 @@
-use std::collections::{BTreeMap, HashMap, VecDeque};
+//! Pulse Propagation
+//!
+//! Like day 8, part 2 takes the least common multiple of the cycles of the modules feeding `rx`, which have no offset.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write as _,
+};
 
 use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{alpha1, line_ending, one_of},
+    character::complete::{alpha1, char},
     combinator::map,
-    multi::{separated_list0, separated_list1},
-    sequence::{separated_pair, tuple},
+    multi::separated_list1,
+    sequence::{preceded, separated_pair},
     IResult,
 };
-use num::Integer;
 use petgraph::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::days::Day;
+use crate::{
+    days::Params,
+    parser::{helpers::lines_of, SolveError},
+    util::{numtheory, Interner},
+};
 
-pub struct Day20;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
 
-#[derive(Debug, Clone, Copy, Hash)]
+#[derive(Debug, Clone, Copy, Hash, Serialize, Deserialize)]
 pub enum Pulse {
     High,
     Low,
 }
 
-#[derive(Debug, Clone, Copy, Hash)]
+#[derive(Debug, Clone, Copy, Hash, Serialize, Deserialize)]
 pub enum State {
     On,
     Off,
@@ -36,19 +52,17 @@ pub struct Instruction {
     pub pulse: Pulse,
 }
 
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct FlipFlopModule {
-    pub name: String,
     pub state: State,
 }
 
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub struct ConjunctionModule {
-    pub name: String,
     pub input_states: BTreeMap<NodeIndex, Pulse>,
 }
 
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Hash, Serialize, Deserialize)]
 pub enum Module {
     FlipFlop(FlipFlopModule),
     Conjunction(ConjunctionModule),
@@ -57,6 +71,39 @@ pub enum Module {
     Output,
 }
 
+/// The modules and the ids of their outputs, indexed by the id of their name
+///
+/// The names which are only ever an output ("rx") are output modules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Network<'a> {
+    #[serde(borrow)]
+    names: Interner<'a>,
+    modules: Vec<Module>,
+    outputs: Vec<Vec<u32>>,
+}
+
+impl<'a> Network<'a> {
+    fn new(defs: Vec<(&'a str, Module, Vec<&'a str>)>) -> Self {
+        // the modules get the first ids so that they index the other fields
+        let mut names: Interner = defs.iter().map(|(name, ..)| *name).collect();
+        let mut modules = vec![Module::Output; names.len()];
+        let mut outputs = vec![Vec::new(); names.len()];
+        for (name, module, outs) in defs {
+            let id = names.intern(name) as usize;
+            modules[id] = module;
+            outputs[id] = outs.into_iter().map(|out| names.intern(out)).collect();
+        }
+        // the names which are only outputs got the last ids
+        modules.resize(names.len(), Module::Output);
+        outputs.resize(names.len(), Vec::new());
+        Self {
+            names,
+            modules,
+            outputs,
+        }
+    }
+}
+
 impl FlipFlopModule {
     /// This module outputs a pulse when flipped from On to Off or reverse
     fn flip(&mut self) -> Pulse {
@@ -90,53 +137,35 @@ impl ConjunctionModule {
     }
 }
 
-impl Module {
-    fn name(&self) -> String {
-        match self {
-            Self::FlipFlop(m) => m.name.clone(),
-            Self::Conjunction(m) => m.name.clone(),
-            Self::Broadcaster => "broadcaster".to_string(),
-            Self::Button => "button".to_string(),
-            Self::Output => "rx".to_string(),
-        }
-    }
-}
-
 /// Construct the graph and return the indices of the input (button) and output (rx) nodes
+///
+/// The index of the node of each module is its id.
 fn create_graph(
-    modules: &HashMap<String, Module>,
-    outputs_map: HashMap<String, Vec<String>>,
-) -> (Graph<Module, ()>, NodeIndex, NodeIndex) {
+    network: &Network,
+) -> Result<(Graph<Module, ()>, NodeIndex, NodeIndex), SolveError> {
     let mut graph = Graph::<Module, ()>::new();
-    let mut node_indices = HashMap::new();
-    let mut node_outputs = HashMap::new();
-    let mut button_idx = None;
-    let mut output_idx = None;
-    // create all nodes
-    for (name, outputs) in outputs_map {
-        let module = modules.get(&name).unwrap().clone();
-        let idx = graph.add_node(module);
-        // we add the button manually since it's not part of the input
-        if name == "broadcaster" {
-            button_idx = Some(graph.add_node(Module::Button));
-            graph.add_edge(button_idx.unwrap(), idx, ());
-        }
-        node_indices.insert(name, idx);
-        node_outputs.insert(idx, outputs);
+    for module in &network.modules {
+        graph.add_node(module.clone());
     }
-    // create all edges
-    for (node, outputs) in node_outputs {
-        for output in outputs {
-            if let Some(output_node) = node_indices.get(&output) {
-                graph.add_edge(node, *output_node, ());
-            } else {
-                // if we have no definition for this module's name, it means it's "rx"
-                output_idx = Some(graph.add_node(Module::Output));
-                graph.add_edge(node, output_idx.unwrap(), ());
-            }
+    for (node, outputs) in network.outputs.iter().enumerate() {
+        for &output in outputs {
+            graph.add_edge(NodeIndex::new(node), NodeIndex::new(output as usize), ());
         }
     }
-    (graph, button_idx.unwrap(), output_idx.unwrap())
+    // we add the button manually since it's not part of the input
+    let broadcaster = network
+        .names
+        .get("broadcaster")
+        .ok_or_else(|| SolveError::invalid("there is no broadcaster module"))?;
+    let button_idx = graph.add_node(Module::Button);
+    graph.add_edge(button_idx, NodeIndex::new(broadcaster as usize), ());
+    // if we have no definition for a module's name, it means it's "rx"
+    let output_idx = network
+        .modules
+        .iter()
+        .position(|module| matches!(module, Module::Output))
+        .ok_or_else(|| SolveError::invalid("there is no output module"))?;
+    Ok((graph, button_idx, NodeIndex::new(output_idx)))
 }
 
 /// Reset the state of all nodes in the graph
@@ -162,12 +191,14 @@ fn press_button(
     let mut output_high = false;
     // queue for instructions, we push at the back
     let mut instr = VecDeque::<Instruction>::new();
-    // press the button first
-    instr.push_back(Instruction {
-        from: button_idx,
-        dest: graph.neighbors(button_idx).next().unwrap(), // we only have 1 child = the broadcaster
-        pulse: Pulse::Low,
-    });
+    // press the button first, its only child is the broadcaster
+    for n in graph.neighbors(button_idx) {
+        instr.push_back(Instruction {
+            from: button_idx,
+            dest: n,
+            pulse: Pulse::Low,
+        });
+    }
     while let Some(i) = instr.pop_front() {
         // record that one pulse was sent
         match i.pulse {
@@ -235,115 +266,151 @@ fn press_button(
     (low, high, output_high)
 }
 
-fn parse_module(input: &str) -> IResult<&str, Module> {
+/// The name of a module and the module itself
+fn parse_module(input: &str) -> IResult<&str, (&str, Module)> {
     alt((
-        map(tag("broadcaster"), |_| Module::Broadcaster),
-        map(
-            tuple((one_of("%&"), alpha1::<&str, _>)),
-            |(t, name)| match t {
-                '%' => Module::FlipFlop(FlipFlopModule {
-                    name: name.to_string(),
-                    state: State::Off,
-                }),
-                '&' => Module::Conjunction(ConjunctionModule {
-                    name: name.to_string(),
+        map(tag("broadcaster"), |name| (name, Module::Broadcaster)),
+        map(preceded(char('%'), alpha1), |name| {
+            (name, Module::FlipFlop(FlipFlopModule { state: State::Off }))
+        }),
+        map(preceded(char('&'), alpha1), |name| {
+            (
+                name,
+                Module::Conjunction(ConjunctionModule {
                     input_states: BTreeMap::new(),
                 }),
-                _ => unimplemented!(),
-            },
-        ),
+            )
+        }),
     ))(input)
 }
 
-fn parse_outputs(input: &str) -> IResult<&str, Vec<String>> {
-    separated_list1(tag(", "), map(alpha1::<&str, _>, ToString::to_string))(input)
+fn parse_outputs(input: &str) -> IResult<&str, Vec<&str>> {
+    separated_list1(tag(", "), alpha1)(input)
 }
 
-impl Day for Day20 {
-    type Input = (HashMap<String, Module>, HashMap<String, Vec<String>>);
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        let (rest, items) = separated_list0(
-            line_ending,
-            map(
-                separated_pair(parse_module, tag(" -> "), parse_outputs),
-                |(module, outputs)| ((module.name(), module.clone()), (module.name(), outputs)),
-            ),
-        )(input)?;
-        Ok((rest, items.into_iter().unzip()))
-    }
+type Input<'a> = Network<'a>;
 
-    type Output1 = usize;
+fn parse(input: &str) -> IResult<&str, Input<'_>> {
+    map(
+        lines_of(map(
+            separated_pair(parse_module, tag(" -> "), parse_outputs),
+            |((name, module), outputs)| (name, module, outputs),
+        )),
+        Network::new,
+    )(input)
+}
 
-    /// Part 1 took 1.6558ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let (modules, outputs) = input;
-        let (mut graph, button_idx, _) = create_graph(modules, outputs.clone());
-        let mut low_pulses = 0;
-        let mut high_pulses = 0;
-        // press the button 1000 times
-        for _ in 0..1000 {
-            let (low, high, _) = press_button(&mut graph, button_idx, None);
-            low_pulses += low;
-            high_pulses += high;
-        }
-        low_pulses * high_pulses
+/// Part 1 took 1.6558ms
+fn part_1(input: &Input<'_>, _params: &Params) -> Result<usize, SolveError> {
+    let (mut graph, button_idx, _) = create_graph(input)?;
+    let mut low_pulses = 0;
+    let mut high_pulses = 0;
+    // press the button 1000 times
+    for _ in 0..1000 {
+        let (low, high, _) = press_button(&mut graph, button_idx, None);
+        low_pulses += low;
+        high_pulses += high;
     }
+    Ok(low_pulses * high_pulses)
+}
 
-    type Output2 = usize;
+/// Name of each source of the collector, with the number of button presses until it outputs "high" (see `part_2`)
+fn source_cycles<'a>(input: &Input<'a>) -> Result<Vec<(&'a str, usize)>, SolveError> {
+    // From checking a graph representation of the input, we know that the output node ("rx") is connected to a
+    // single parent Conjunction module (the "collector"), which has 4 "source" inputs, each also a Conjunction
+    // module.
+    // Since we need the collector to output "low", each of the 4 sources needs to output "high".
+    let (mut graph, button_idx, output_idx) = create_graph(input)?;
+    // the parent of the output (rx node) -> the collector
+    let collector_idx = graph
+        .neighbors_directed(output_idx, Direction::Incoming)
+        .next()
+        .ok_or_else(|| SolveError::invalid("nothing is connected to the rx module"))?;
+    // the four nodes that feed into the collector -> the sources
+    let sources = graph
+        .neighbors_directed(collector_idx, Direction::Incoming)
+        .collect_vec();
+    // the examples don't have this structure, and pressing the button would never end
+    if !sources
+        .iter()
+        .all(|source| matches!(graph[*source], Module::Conjunction(_)))
+    {
+        return Err(SolveError::invalid(
+            "the sources of the collector should all be conjunction modules",
+        ));
+    }
+    // for each source, we want to know how many button presses are needed until we see a "high" output
+    let presses = sources
+        .iter()
+        .map(|source| {
+            #[allow(clippy::cast_possible_truncation)]
+            let name = input.names.name(source.index() as u32);
+            // important, we need to reset the graph, as we consider each of the 4 sources independently
+            reset_graph(&mut graph);
+            let mut presses = 1; // the while loop will stop before the first high output, so we record its press
+                                 // by starting at 1
 
-    /// Part 2 took 26.5538ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        // From checking a graph representation of the input, we know that the output node ("rx") is connected to a
-        // single parent Conjunction module (the "collector"), which has 4 "source" inputs, each also a Conjunction
-        // module.
-        // Since we need the collector to output "low", each of the 4 sources needs to output "high".
-        let (modules, outputs) = input;
-        let (mut graph, button_idx, output_idx) = create_graph(modules, outputs.clone());
-        // the parent of the output (rx node) -> the collector
-        let collector_idx = graph
-            .neighbors_directed(output_idx, Direction::Incoming)
-            .next()
-            .unwrap();
-        // the four nodes that feed into the collector -> the sources
-        let sources = graph
-            .neighbors_directed(collector_idx, Direction::Incoming)
-            .collect_vec();
-        // for each source, we want to know how many button presses are needed until we see a "high" output
-        let presses = sources
-            .iter()
-            .map(|source| {
-                // important, we need to reset the graph, as we consider each of the 4 sources independently
-                reset_graph(&mut graph);
-                let mut presses = 1; // the while loop will stop before the first high output, so we record its press
-                                     // by starting at 1
+            // press until the source outputs high
+            while !press_button(&mut graph, button_idx, Some(*source)).2 {
+                presses += 1;
+            }
+            (name, presses)
+        })
+        .collect_vec();
+    Ok(presses)
+}
 
-                // press until the source outputs high
-                while !press_button(&mut graph, button_idx, Some(*source)).2 {
-                    presses += 1;
-                }
-                presses
-            })
-            .collect_vec();
+/// Part 2 took 26.5538ms
+fn part_2(input: &Input<'_>, _params: &Params) -> Result<usize, SolveError> {
+    // The least common multiplier of the presses for the 4 sources is how many presses are needed to turn on the
+    // machine
+    numtheory::lcm_all(
+        source_cycles(input)?
+            .into_iter()
+            .map(|(_, presses)| presses),
+    )
+    .ok_or_else(|| SolveError::invalid("the collector has no sources"))
+}
 
-        // The least common multiplier of the presses for the 4 sources is how many presses are needed to turn on the
-        // machine
-        presses.into_iter().reduce(|acc, e| acc.lcm(&e)).unwrap()
+fn explain(input: &Input<'_>, params: &Params) -> Result<String, SolveError> {
+    let mut text = "The rx module is fed by a single conjunction module, the collector, which sends it a low pulse once \
+                    all of its sources sent it a high pulse. Each source first sends a high pulse after:"
+        .to_string();
+    for (name, presses) in source_cycles(input)? {
+        let _ = write!(text, "\n  {name}: {presses} button presses");
     }
+    let _ = write!(
+        text,
+        "\nThe sources cycle independently, so rx first gets a low pulse after the least common multiple of these: \
+         {} presses.",
+        part_2(input, params)?
+    );
+    Ok(text)
 }
 
+// the puzzle gives no example for part 2
+impl_day!(
+    Day20,
+    20,
+    parse -> Input<'a>,
+    part_1 -> usize,
+    part_2 -> usize,
+    answers = [Some("11687500"), None],
+    title = "Pulse Propagation",
+    tags = ["graph", "simulation", "math"],
+    explain = explain,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "broadcaster -> a
-%a -> inv, con
-&inv -> b
-%b -> con
-&con -> output";
-
+    use crate::days::Day;
     #[test]
     fn test_part1() {
-        let parsed = Day20::parse(INPUT).unwrap().1;
-        assert_eq!(Day20::part_1(&parsed), 11_687_500);
+        let parsed = Day20::parse(EXAMPLE).unwrap().1;
+        assert_eq!(
+            Day20::part_1(&parsed, &Day20::EXAMPLE_PARAMS),
+            Ok(11_687_500)
+        );
     }
 }
@@ -1,85 +1,200 @@
-use std::collections::BTreeSet;
+//! Step Counter
+//!
+//! Part 1 returns the distances to the plots along with its answer, so that `part_2_with` doesn't explore the garden
+//! again.
+
+use std::fmt::{self, Display};
 
 use itertools::Itertools;
-use nom::{
-    character::complete::{line_ending, not_line_ending},
-    multi::separated_list0,
-    IResult,
+use nom::IResult;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    answer::Answer,
+    days::{Cost, Day, Params},
+    parser::SolveError,
+    util::{search::dijkstra_all, Grid, Vec2},
 };
-use pathfinding::{directed::bfs::bfs_reach, grid::Grid};
 
-use crate::days::Day;
+pub struct Day21;
 
-const STEPS_PART1: usize = if cfg!(test) { 6 } else { 64 };
-const STEPS_PART2: usize = 26_501_365;
+/// The garden, as a grid where the plots are `true`
+#[derive(Serialize, Deserialize)]
+pub struct Garden {
+    pub grid: Grid<bool>,
+    pub start: Vec2<isize>,
+    pub width: usize,
+    pub height: usize,
+}
 
-pub struct Day21;
+/// Example input from the puzzle description
+const EXAMPLE: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct Pos {
-    coord: (usize, usize),
+    coord: Vec2<isize>,
     dist: usize,
 }
 
-fn count_pos(grid: &Grid, start_pos: &Pos, steps: usize) -> usize {
-    bfs_reach(*start_pos, |n| {
-        grid.neighbours(n.coord)
-            .into_iter()
-            .filter_map(|c| {
-                if n.dist >= steps {
-                    return None;
-                }
-                Some(Pos {
-                    coord: c,
-                    dist: n.dist + 1,
-                })
-            })
-            .collect_vec()
+/// Answer to part 1, along with the distances to the plots which part 2 reuses
+pub struct Reachable {
+    pub count: usize,
+    distances: Vec<Pos>,
+}
+
+impl Display for Reachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.count)
+    }
+}
+
+impl From<Reachable> for Answer {
+    fn from(reachable: Reachable) -> Self {
+        reachable.count.into()
+    }
+}
+
+/// Find the shortest number of steps to each reachable plot of the garden
+fn distances(garden: &Garden) -> Vec<Pos> {
+    let Garden { grid, start, .. } = garden;
+    dijkstra_all(start, |pos: &Vec2<isize>| {
+        grid.neighbors(*pos).filter(|&n| grid[n]).map(|n| (n, 1))
     })
-    .filter(|n| n.dist == steps)
-    .count()
+    .into_iter()
+    .map(|(coord, dist)| Pos { coord, dist })
+    .collect_vec()
+}
+
+/// Count the plots where the elf can be after exactly `steps` steps
+///
+/// The elf can go back and forth between two plots, so any plot at a distance of at most `steps` with the same parity
+/// can be reached.
+fn count_pos(all_moves: &[Pos], steps: usize) -> usize {
+    all_moves
+        .iter()
+        .filter(|m| m.dist <= steps && m.dist % 2 == steps % 2)
+        .count()
+}
+
+/// Number of plots of a tile reachable with an even and an odd number of moves, in the whole tile and in its corners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Parities {
+    even_moves: usize,
+    odd_moves: usize,
+    even_corners: usize,
+    odd_corners: usize,
+}
+
+fn parities(all_moves: &[Pos], width: usize) -> Parities {
+    // how many tiles are reachable by even moves
+    let even_moves = all_moves.iter().filter(|m| m.dist % 2 == 0).count();
+
+    // how many tiles are reachable by odd moves
+    let odd_moves = all_moves.iter().filter(|m| m.dist % 2 == 1).count();
+
+    // how many tiles are reachable by even moves and lie in the corners of the tile
+    let even_corners = all_moves
+        .iter()
+        .filter(|m| m.dist % 2 == 0 && m.dist > width / 2)
+        .count();
+
+    // how many tiles are reachable by odd moves and lie in the corners of the tile
+    let odd_corners = all_moves
+        .iter()
+        .filter(|m| m.dist % 2 == 1 && m.dist > width / 2)
+        .count();
+
+    Parities {
+        even_moves,
+        odd_moves,
+        even_corners,
+        odd_corners,
+    }
+}
+
+/// Count the plots where the elf can be after `steps` steps in the infinitely repeating garden (see [`Day21::part_2`])
+fn count_pos_infinite(all_moves: &[Pos], width: usize, steps: usize) -> usize {
+    let Parities {
+        even_moves,
+        odd_moves,
+        even_corners,
+        odd_corners,
+    } = parities(all_moves, width);
+
+    let dim = steps / width; // how many units of the grid we would be traversing if going in a straight direction for the total number of steps (= half of the diamond width)
+                             // this is equal to 202_300 in our case
+
+    // The width of the "diamond" would be twice that value (+1). So the total number of tiles is roughly half of
+    // those of a square with same width/heigth:
+    // 0.5 * (2*dim+1) * (2*dim+1) = 2 * (dim + 1/2) * (dim + 1/2). The larger half of those is Odd, while the
+    // other half is even.
+
+    // all the odd tiles + all the even tiles - the missing corners of some of the odd tiles + the extra corners of
+    // the incomplete even tiles
+    ((dim + 1) * (dim + 1)) * odd_moves + (dim * dim) * even_moves - (dim + 1) * odd_corners
+        + dim * even_corners
 }
 
 impl Day for Day21 {
-    type Input = (Grid, (usize, usize), usize, usize);
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        let (rest, rows) = separated_list0(line_ending, not_line_ending)(input)?;
-        let mut start_x = 0;
-        let mut start_y = 0;
-        let mut width = 0;
-        let height = rows.len();
-        let mut plots = vec![];
-        for (y, row) in rows.into_iter().enumerate() {
-            if width == 0 {
-                width = row.len();
-            }
-            for (x, c) in row.chars().enumerate() {
-                match c {
-                    '.' => plots.push((x, y)),
-                    'S' => {
-                        plots.push((x, y));
-                        start_x = x;
-                        start_y = y;
-                    }
-                    _ => {}
-                }
-            }
-        }
-        let grid = plots.into_iter().collect::<Grid>();
-        Ok((rest, (grid, (start_x, start_y), width, height)))
+    const DAY: u8 = 21;
+    const EXAMPLE: &'static str = EXAMPLE;
+    // the example uses fewer steps than the real input
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("16"), None];
+    const EXAMPLE_PARAMS: Params = Params {
+        steps_part1: 6,
+        ..Params::PUZZLE
+    };
+
+    fn title() -> &'static str {
+        "Step Counter"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["grid", "bfs", "math"]
+    }
+
+    fn cost_hint() -> Cost {
+        Cost::Medium
     }
 
-    type Output1 = usize;
+    type Input<'a> = Garden;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        let (rest, (grid, [start])) = Grid::parse(input, ['S'], |c| match c {
+            '.' | 'S' => Some(true),
+            '#' => Some(false),
+            _ => None,
+        })?;
+        Ok((
+            rest,
+            Garden {
+                width: grid.width(),
+                height: grid.height(),
+                grid,
+                start,
+            },
+        ))
+    }
+
+    type Output1 = Reachable;
 
     /// Part 1 took 7.78602ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let (grid, start, _, _) = input;
-        let start_pos = Pos {
-            coord: *start,
-            dist: 0,
-        };
-        count_pos(grid, &start_pos, STEPS_PART1)
+    fn part_1(input: &Self::Input<'_>, params: &Params) -> Result<Self::Output1, SolveError> {
+        let distances = distances(input);
+        Ok(Reachable {
+            count: count_pos(&distances, params.steps_part1),
+            distances,
+        })
     }
 
     type Output2 = usize;
@@ -130,84 +245,149 @@ impl Day for Day21 {
     /// tiles.
     ///
     /// Part 2 took 5.797118ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let (grid, start, width, _) = input;
-        let start_pos = Pos {
-            coord: *start,
-            dist: 0,
-        };
+    fn part_2(input: &Self::Input<'_>, params: &Params) -> Result<Self::Output2, SolveError> {
+        Ok(count_pos_infinite(
+            &distances(input),
+            input.width,
+            params.steps_part2,
+        ))
+    }
 
-        // how many tiles are reachable
-        let mut visited = BTreeSet::<(usize, usize)>::new();
-        visited.insert(*start);
-        let all_moves = bfs_reach(start_pos, |n| {
-            grid.neighbours(n.coord)
-                .into_iter()
-                .filter_map(|c| {
-                    if visited.contains(&c) {
-                        return None;
-                    }
-                    visited.insert(c);
-                    Some(Pos {
-                        coord: c,
-                        dist: n.dist + 1,
-                    })
-                })
-                .collect_vec()
-        })
-        .collect_vec();
-
-        // how many tiles are reachable by even moves
-        let even_moves = all_moves.iter().filter(|m| m.dist % 2 == 0).count();
-
-        // how many tiles are reachable by odd moves
-        let odd_moves = all_moves.iter().filter(|m| m.dist % 2 == 1).count();
-
-        // how many tiles are reachable by even moves and lie in the corners of the tile
-        let even_corners = all_moves
-            .iter()
-            .filter(|m| m.dist % 2 == 0 && m.dist > width / 2)
-            .count();
-
-        // how many tiles are reachable by odd moves and lie in the corners of the tile
-        let odd_corners = all_moves
-            .iter()
-            .filter(|m| m.dist % 2 == 1 && m.dist > width / 2)
-            .count();
-
-        let dim = STEPS_PART2 / width; // how many units of the grid we would be traversing if going in a straight direction for the total number of steps (= half of the diamond width)
-                                       // this is equal to 202_300 in our case
-
-        // The width of the "diamond" would be twice that value (+1). So the total number of tiles is roughly half of
-        // those of a square with same width/heigth:
-        // 0.5 * (2*dim+1) * (2*dim+1) = 2 * (dim + 1/2) * (dim + 1/2). The larger half of those is Odd, while the
-        // other half is even.
-
-        // all the odd tiles + all the even tiles - the missing corners of some of the odd tiles + the extra corners of
-        // the incomplete even tiles
-        ((dim + 1) * (dim + 1)) * odd_moves + (dim * dim) * even_moves - (dim + 1) * odd_corners
-            + dim * even_corners
+    /// Both parts count plots from the same distances, so we only explore the garden once
+    fn part_2_with(
+        input: &Self::Input<'_>,
+        params: &Params,
+        part1: &Self::Output1,
+    ) -> Result<Self::Output2, SolveError> {
+        Ok(count_pos_infinite(
+            &part1.distances,
+            input.width,
+            params.steps_part2,
+        ))
+    }
+
+    fn explain(input: &Self::Input<'_>, params: &Params) -> Result<Option<String>, SolveError> {
+        let distances = distances(input);
+        let Parities {
+            even_moves,
+            odd_moves,
+            even_corners,
+            odd_corners,
+        } = parities(&distances, input.width);
+        let dim = params.steps_part2 / input.width;
+        Ok(Some(format!(
+            "A breadth-first search from the start finds {} reachable plots, {} of them within {} steps with the \
+             same parity.\n\
+             The rows and columns of the start are free of rocks, so after {} steps the elf covers a diamond {dim} \
+             gardens wide on each side of the first one. In a garden:\n  \
+             {odd_moves} plots are an odd number of steps away ({odd_corners} of them in the corners)\n  \
+             {even_moves} plots are an even number of steps away ({even_corners} of them in the corners)\n\
+             The diamond holds ({dim} + 1)² odd gardens and {dim}² even ones, minus {} odd corners plus {dim} even \
+             corners: {} plots.",
+            distances.len(),
+            count_pos(&distances, params.steps_part1),
+            params.steps_part1,
+            params.steps_part2,
+            dim + 1,
+            count_pos_infinite(&distances, input.width, params.steps_part2),
+        )))
+    }
+}
+
+/// Slow solutions which follow the puzzle literally, to cross-check the ones above
+#[cfg(test)]
+mod reference {
+    use super::Garden;
+    use crate::util::{FastSet, Vec2};
+
+    /// Move the elf one step at a time in every direction, in a garden which repeats infinitely when `infinite` (the
+    /// number of plots to visit grows with the square of the steps)
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn plots_after(garden: &Garden, steps: usize, infinite: bool) -> usize {
+        let size = Vec2::new(garden.width as isize, garden.height as isize);
+        let is_plot = |pos: Vec2<isize>| {
+            if infinite {
+                garden.grid[Vec2::new(pos.x.rem_euclid(size.x), pos.y.rem_euclid(size.y))]
+            } else {
+                garden.grid.get(pos).copied().unwrap_or(false)
+            }
+        };
+        let mut plots = FastSet::from_iter([garden.start]);
+        for _ in 0..steps {
+            plots = plots
+                .iter()
+                .flat_map(|pos| pos.neighbors())
+                .filter(|&pos| is_plot(pos))
+                .collect();
+        }
+        plots.len()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "...........
-.....###.#.
-.###.##..#.
-..#.#...#..
-....#.#....
-.##..S####.
-.##..#...#.
-.......##..
-.##.#.####.
-.##..##.##.
-...........";
+    use crate::util::gen::Rng;
 
     #[test]
     fn test_part1() {
-        let parsed = Day21::parse(INPUT).unwrap().1;
-        assert_eq!(Day21::part_1(&parsed), 16);
+        let parsed = Day21::parse(EXAMPLE).unwrap().1;
+        assert_eq!(
+            Day21::part_1(&parsed, &Day21::EXAMPLE_PARAMS).map(|r| r.count),
+            Ok(16)
+        );
+    }
+
+    /// A garden of an odd size with the start in the middle, where the row and the column of the start and the border
+    /// are free and the rocks don't touch each other, even diagonally or in the next garden, so every plot can be
+    /// reached by its shortest path
+    fn random_garden(size: usize, rng: &mut Rng) -> String {
+        let mut rows = vec![vec!['.'; size]; size];
+        let middle = size / 2;
+        for y in 1..size - 1 {
+            for x in 1..size - 1 {
+                let touches_rock = (y.saturating_sub(1)..=y + 1).any(|ny| {
+                    (x.saturating_sub(1)..=x + 1)
+                        .any(|nx| rows.get(ny).and_then(|row| row.get(nx)) == Some(&'#'))
+                });
+                if x != middle && y != middle && !touches_rock && rng.chance(0.4) {
+                    rows[y][x] = '#';
+                }
+            }
+        }
+        rows[middle][middle] = 'S';
+        rows.into_iter()
+            .map(String::from_iter)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_reference() {
+        let mut rng = Rng::new(21);
+        for _ in 0..20 {
+            // like the real garden (131 wide), half the size is odd, so that the number of steps of part 2 is odd
+            let size = 4 * (1 + rng.below(3)) + 3;
+            let input = random_garden(size, &mut rng);
+            let garden = Day21::parse(&input).unwrap().1;
+            let steps = rng.below(2 * size);
+            let params = Params {
+                steps_part1: steps,
+                // the shape of the real number of steps, which ends on the edges of the gardens
+                steps_part2: 2 * size * (1 + rng.below(2)) + size / 2,
+                ..Params::PUZZLE
+            };
+            let part1 = Day21::part_1(&garden, &params).unwrap();
+            assert_eq!(
+                part1.count,
+                reference::plots_after(&garden, steps, false),
+                "{input}"
+            );
+            assert_eq!(
+                Day21::part_2_with(&garden, &params, &part1),
+                Ok(reference::plots_after(&garden, params.steps_part2, true)),
+                "{input}"
+            );
+        }
     }
 }
@@ -1,370 +1,305 @@
-use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+//! Sand Slabs
+//!
+//! The heights occupied in each column are kept in an `IntervalSet` to find where a falling brick lands. The settled
+//! blocks are then indexed in a `KdTree`, whose range queries find the bricks right above each brick, and the bricks
+//! are walked in the order of `toposort_stable`, so that all the supports of a brick are decided before it.
 
-use itertools::Itertools;
 use nom::{
-    character::complete::{char, line_ending, u64},
+    character::complete::{char, u64},
     combinator::map,
-    multi::separated_list0,
     sequence::{separated_pair, tuple},
     IResult,
 };
-use petgraph::prelude::*;
-
-use crate::days::Day;
+use petgraph::{
+    dot::{Config, Dot},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{enabled, trace, Level};
+
+use crate::{
+    days::{Cost, Day, Params},
+    parser::{helpers::lines_of, SolveError},
+    timing::sub_phase,
+    util::{
+        arena::{with_arena, ArenaVec},
+        graph::toposort_stable,
+        Aabb, Grid, Grid3, GridDisplay, Interval, IntervalSet, KdTree, Vec2, Vec3,
+    },
+};
 
 pub struct Day22;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Voxel {
-    pub z: usize,
-    pub x: usize,
-    pub y: usize,
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
 
-#[derive(Debug, Clone, Copy)]
-pub enum Dir {
-    X,
-    Y,
-    Z,
-}
+/// A block of a brick, the blocks sort by height first so that the bricks sort from the lowest ones
+pub type Voxel = Vec3<usize>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Brick {
     pub begin: Voxel,
     pub end: Voxel,
 }
 
 impl Brick {
-    /// Since bricks are a single row of blocks, we can define a main direction
-    fn dir(&self) -> Dir {
-        if self.begin.x != self.end.x {
-            Dir::X
-        } else if self.begin.y != self.end.y {
-            Dir::Y
-        } else {
-            Dir::Z
-        }
+    /// The box between the two ends of the brick
+    fn extent(&self) -> Aabb<usize, 3> {
+        Aabb::new(self.begin.into(), self.end.into())
     }
-}
 
-/// Iterator over the blocks of a brick
-pub struct BrickIntoIterator {
-    brick: Brick,
-    dir: Dir,
-    current: Voxel,
-}
-
-impl IntoIterator for Brick {
-    type Item = Voxel;
-
-    type IntoIter = BrickIntoIterator;
+    /// The blocks of the brick, which is a single row of blocks
+    fn voxels(&self) -> impl Iterator<Item = Voxel> {
+        self.extent().points().map(Voxel::from)
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        BrickIntoIterator {
-            brick: self,
-            dir: self.dir(),
-            current: self.begin,
-        }
+    /// The box of the blocks right on top of the brick
+    #[allow(clippy::cast_possible_wrap)]
+    fn top(&self) -> Aabb<i64, 3> {
+        let above = |v: Voxel| Vec3::new(v.x, v.y, v.z + 1).map(|c| c as i64).into();
+        Aabb::new(above(self.begin), above(self.end))
     }
 }
 
-impl Iterator for BrickIntoIterator {
-    type Item = Voxel;
-
-    /// Get the next voxel (block) in a brick iterator
-    fn next(&mut self) -> Option<Self::Item> {
-        let curr = self.current;
-        if curr > self.brick.end {
-            return None;
-        }
-        let next = match self.dir {
-            Dir::X => Voxel {
-                x: self.current.x + 1,
-                y: self.current.y,
-                z: self.current.z,
-            },
-            Dir::Y => Voxel {
-                x: self.current.x,
-                y: self.current.y + 1,
-                z: self.current.z,
-            },
-            Dir::Z => Voxel {
-                x: self.current.x,
-                y: self.current.y,
-                z: self.current.z + 1,
-            },
-        };
-        self.current = next;
-        Some(curr)
-    }
+/// The blocks of the settled bricks, with the index of the brick each one belongs to
+#[allow(clippy::cast_possible_wrap)]
+fn index_voxels(bricks: &[Brick]) -> KdTree<usize, 3> {
+    bricks
+        .iter()
+        .enumerate()
+        .flat_map(|(i, brick)| brick.voxels().map(move |v| (v.map(|c| c as i64), i)))
+        .collect()
 }
 
 #[allow(clippy::cast_possible_truncation)]
 fn parse_voxel(input: &str) -> IResult<&str, Voxel> {
     let (rest, (x, _, y, _, z)) = tuple((u64, char(','), u64, char(','), u64))(input)?;
-    Ok((
-        rest,
-        Voxel {
-            x: x as usize,
-            y: y as usize,
-            z: z as usize,
-        },
-    ))
+    Ok((rest, Vec3::new(x, y, z).map(|v| v as usize)))
+}
+
+/// Draw the settled bricks seen from the front (x, z) and from the side (y, z) like in the puzzle description, with
+/// the number of blocks behind each position
+#[allow(clippy::cast_possible_wrap)]
+fn draw_bricks(grid: &Grid3<Option<usize>>) -> String {
+    let (width, depth) = (grid.width(), grid.depth());
+    // the two views side by side, from the highest layer with a block at the top down to z = 1 above the ground
+    let layers = (1..grid.height())
+        .rev()
+        .find(|&z| (0..width).any(|x| (0..depth).any(|y| grid[(x, y, z)].is_some())))
+        .unwrap_or(0);
+    let mut views = Grid::new(width + 3 + depth, layers, ' ');
+    let glyph = |count: usize| match count {
+        0 => '.',
+        _ => u32::try_from(count)
+            .ok()
+            .and_then(|c| char::from_digit(c, 10))
+            .unwrap_or('+'),
+    };
+    for z in 1..=layers {
+        let row = layers - z;
+        for x in 0..width {
+            let count = (0..depth).filter(|&y| grid[(x, y, z)].is_some()).count();
+            views[Vec2::new(x as isize, row as isize)] = glyph(count);
+        }
+        for y in 0..depth {
+            let count = (0..width).filter(|&x| grid[(x, y, z)].is_some()).count();
+            views[Vec2::new((width + 3 + y) as isize, row as isize)] = glyph(count);
+        }
+    }
+    GridDisplay::new(&views).to_string()
 }
 
-// fn print_bricks(grid: &BTreeSet<Voxel>) {
-//     let max_z = grid.iter().map(|v| v.z).max().unwrap();
-//     let max_x = grid.iter().map(|v| v.x).max().unwrap();
-//     let max_y = grid.iter().map(|v| v.y).max().unwrap();
-//     for z in (1..=max_z).rev() {
-//         for x in 0..=max_x {
-//             let count = grid.iter().filter(|v| v.x == x && v.z == z).count();
-//             if count > 0 {
-//                 print!("{count}");
-//             } else {
-//                 print!(".");
-//             }
-//         }
-//         print!("   ");
-//         for y in 0..=max_y {
-//             let count = grid.iter().filter(|v| v.y == y && v.z == z).count();
-//             if count > 0 {
-//                 print!("{count}");
-//             } else {
-//                 print!(".");
-//             }
-//         }
-//         println!();
-//     }
-//     println!("-----------------");
-// }
-
-/// Make the bricks fall
-fn settle(bricks: &mut [Brick], grid: &mut BTreeSet<Voxel>) {
+/// Make the bricks fall, lowest first, in a space of `width` by `depth` columns
+fn settle(bricks: &mut [Brick], width: usize, depth: usize) {
+    // the heights occupied by the settled bricks in each column (x, y)
+    let mut heights = vec![IntervalSet::new(); width * depth];
     for brick in bricks.iter_mut() {
-        // Check how far we can move down on the Z axis before reaching an obstacle
-        // We start at an offset of 1 and continue until an obstacle is reached
-        let move_z = (1..1000)
-            .take_while(|i| match brick.dir() {
-                // for a vertical brick, we only need to consider the "begin" voxel
-                Dir::Z => {
-                    let Some(new_z) = brick.begin.z.checked_sub(*i) else {
-                        return false;
-                    };
-                    if new_z == 0 {
-                        return false;
-                    }
-                    !grid.contains(&Voxel {
-                        x: brick.begin.x,
-                        y: brick.begin.y,
-                        z: new_z,
-                    })
-                }
-                // for horizontal bricks, we need to check how far can each block go
-                Dir::X | Dir::Y => brick.into_iter().all(|v| {
-                    let Some(new_z) = v.z.checked_sub(*i) else {
-                        return false;
-                    };
-                    if new_z == 0 {
-                        return false;
-                    }
-                    !grid.contains(&Voxel {
-                        x: v.x,
-                        y: v.y,
-                        z: v.z - i,
-                    })
-                }),
+        // The brick stops on top of the highest block below any of its blocks, or on the ground at z = 0
+        let floor = brick
+            .voxels()
+            .map(|v| {
+                heights[v.y * width + v.x]
+                    .end_before(brick.begin.z)
+                    .unwrap_or(1)
             })
-            .count();
-        // shift the brick down by the calculated amount, reflecting the changes in the global grid
-        for voxel in brick.into_iter() {
-            grid.remove(&voxel);
-        }
+            .max()
+            .unwrap_or(brick.begin.z);
+        let move_z = brick.begin.z - floor;
         brick.begin.z -= move_z;
         brick.end.z -= move_z;
-        grid.extend(brick.into_iter());
+        for v in brick.voxels() {
+            heights[v.y * width + v.x].insert(Interval::new(v.z, v.z + 1));
+        }
     }
-    // Sort the bricks so we can still iterate from low-Z to high-Z
-    bricks.sort();
 }
 
 /// Create a graph where the nodes are bricks, and the edges represent "support". If a brick has contact to a brick
 /// one layer up, then a directed edge joins them (from bottom brick to to brick).
-fn get_graph<'a>(
-    bricks: &'a [Brick],
-    grid: &'a BTreeSet<Voxel>,
-) -> (Graph<&'a Brick, ()>, HashMap<&'a Brick, NodeIndex>) {
+///
+/// The node of each brick has the same index as the brick in `bricks`, and `voxels` gives the brick of each block.
+fn get_graph<'a>(bricks: &'a [Brick], voxels: &KdTree<usize, 3>) -> Graph<&'a Brick, ()> {
     let mut graph = Graph::<&Brick, ()>::new();
-    let mut node_indices = HashMap::<&Brick, NodeIndex>::new();
     // add all brick references to the graph as nodes
     for brick in bricks {
-        let idx = graph.add_node(brick);
-        node_indices.insert(brick, idx);
+        graph.add_node(brick);
     }
-    // for each brick, check the blocks above, identify which brick they belong to, and create the edges
-    for brick in bricks {
-        match brick.dir() {
-            // for vertical bricks, we only need to consider the "end" block
-            Dir::Z => {
-                // check if the block above is populated
-                let above = Voxel {
-                    x: brick.end.x,
-                    y: brick.end.y,
-                    z: brick.end.z + 1,
-                };
-                if grid.contains(&above) {
-                    // find which brick it belongs to
-                    let other = bricks
-                        .iter()
-                        .skip_while(|b| b.begin.z < above.z)
-                        .find(|b| b.into_iter().any(|v| v.x == above.x && v.y == above.y))
-                        .unwrap();
-
-                    graph.add_edge(
-                        *node_indices.get(brick).unwrap(),
-                        *node_indices.get(other).unwrap(),
-                        (),
-                    );
-                }
+    // for each brick, find the blocks above and create an edge to the brick they belong to
+    for (i, brick) in bricks.iter().enumerate() {
+        for (_, &other) in voxels.range(&brick.top()) {
+            // the block above a vertical brick belongs to the brick itself, and a horizontal brick can touch the
+            // same brick with several of its blocks
+            if other != i {
+                graph.update_edge(NodeIndex::new(i), NodeIndex::new(other), ());
             }
-            // for horizontal bricks, we consider each block
-            Dir::X | Dir::Y => {
-                // check if the blocks above are populated
-                let voxels = brick.into_iter().collect_vec();
-                for voxel in &voxels {
-                    let above = Voxel {
-                        x: voxel.x,
-                        y: voxel.y,
-                        z: voxel.z + 1,
-                    };
-                    if grid.contains(&above) {
-                        // find which brick it belongs to
-                        let other = bricks
-                            .iter()
-                            .skip_while(|b| b.begin.z < above.z)
-                            .find(|b| b.into_iter().any(|v| v.x == above.x && v.y == above.y))
-                            .unwrap();
-                        let a = *node_indices.get(brick).unwrap();
-                        let b = *node_indices.get(other).unwrap();
-                        if !graph.contains_edge(a, b) {
-                            // only add an edge if one is not already existing
-                            graph.add_edge(a, b, ());
-                        }
-                    }
+        }
+    }
+    graph
+}
+
+/// Settle the bricks, build the graph of which brick supports which, and pass it to `f`
+fn with_supports<T>(input: &[Brick], f: impl FnOnce(&Graph<&Brick, ()>) -> T) -> T {
+    with_arena(|arena| {
+        let mut bricks = ArenaVec::from_iter_in(input.iter().copied(), arena);
+        bricks.sort_unstable();
+        let bounds = bricks.iter().flat_map(|b| [b.extent().min, b.extent().max]);
+        let [width, depth, height] =
+            Aabb::from_points(bounds).map_or([0; 3], |aabb| aabb.max.map(|v| v + 1));
+        sub_phase("settle", || settle(&mut bricks, width, depth));
+        if enabled!(Level::TRACE) {
+            let mut grid = Grid3::new(width, depth, height, None);
+            for (i, brick) in bricks.iter().enumerate() {
+                for v in brick.voxels() {
+                    grid[(v.x, v.y, v.z)] = Some(i);
                 }
             }
+            trace!("settled bricks:\n{}", draw_bricks(&grid));
+        }
+        let voxels = sub_phase("index of the blocks", || index_voxels(&bricks));
+        let supports = sub_phase("graph of the supports", || get_graph(&bricks, &voxels));
+        trace!(
+            "graph of the supports:\n{:?}",
+            Dot::with_config(&supports, &[Config::EdgeNoLabel])
+        );
+        f(&supports)
+    })
+}
+
+/// Count the bricks that could be disintegrated without any other brick falling
+fn safe_to_disintegrate(supports: &Graph<&Brick, ()>) -> usize {
+    // Check which bricks only have children with more than 1 parent (i.e. they would not move if removed)
+    supports
+        .node_indices()
+        .filter(|&brick_idx| {
+            supports
+                .neighbors_directed(brick_idx, Direction::Outgoing)
+                .all(|child| {
+                    let parents = supports.edges_directed(child, Direction::Incoming).count();
+                    parents > 1
+                })
+        })
+        .count()
+}
+
+/// Sum the number of other bricks that would fall when disintegrating each brick
+fn falling_bricks(supports: &Graph<&Brick, ()>) -> Result<usize, SolveError> {
+    // the bricks in an order where each brick comes after the bricks supporting it, so that we know whether all of
+    // them fall when we reach it
+    let order = toposort_stable(supports)
+        .ok_or_else(|| SolveError::invalid("the bricks support each other in a cycle"))?;
+    let mut falling = vec![false; supports.node_count()];
+    let mut total = 0;
+
+    // check how many bricks would fall for each brick that we would remove
+    for (i, brick_idx) in order.iter().enumerate() {
+        falling.fill(false);
+        falling[brick_idx.index()] = true;
+        // only the bricks after the removed one can fall
+        for n in &order[i + 1..] {
+            let mut parents = supports
+                .neighbors_directed(*n, Direction::Incoming)
+                .peekable();
+            // a brick on the ground doesn't fall, the others fall if all of their parents do
+            if parents.peek().is_some() && parents.all(|p| falling[p.index()]) {
+                falling[n.index()] = true;
+                total += 1;
+            }
         }
     }
-    (graph, node_indices)
+    Ok(total)
 }
 
 impl Day for Day22 {
-    type Input = Vec<Brick>;
-
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(
-            line_ending,
-            map(
-                separated_pair(parse_voxel, char('~'), parse_voxel),
-                |(begin, end)| Brick { begin, end },
-            ),
-        )(input)
+    const DAY: u8 = 22;
+    const EXAMPLE: &'static str = EXAMPLE;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("5"), Some("7")];
+
+    fn title() -> &'static str {
+        "Sand Slabs"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["graph", "simulation"]
+    }
+
+    fn cost_hint() -> Cost {
+        Cost::Medium
+    }
+
+    type Input<'a> = Vec<Brick>;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        lines_of(map(
+            separated_pair(parse_voxel, char('~'), parse_voxel),
+            |(begin, end)| Brick { begin, end },
+        ))(input)
     }
 
     type Output1 = usize;
 
     /// Part 1 took 7.5043ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let mut bricks = input.iter().sorted().copied().collect_vec();
-        let mut grid = BTreeSet::<Voxel>::new();
-        for brick in &bricks {
-            grid.extend(brick.into_iter());
-        }
-
-        settle(&mut bricks, &mut grid);
-        let (supports, node_indices) = get_graph(&bricks, &grid);
-
-        // println!("{:?}", Dot::with_config(&supports, &[Config::EdgeNoLabel]));
-
-        // Check which bricks only have children with more than 1 parent (i.e. they would not move if removed)
-        node_indices
-            .iter()
-            .filter(|(_, &brick_idx)| {
-                supports
-                    .neighbors_directed(brick_idx, Direction::Outgoing)
-                    .all(|child| {
-                        let parents = supports.edges_directed(child, Direction::Incoming).count();
-                        parents > 1
-                    })
-            })
-            .count()
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
+        Ok(with_supports(input, safe_to_disintegrate))
     }
 
     type Output2 = usize;
 
     /// Part 2 took 15.0054ms
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let mut bricks = input.iter().sorted().copied().collect_vec();
-        let mut grid = BTreeSet::<Voxel>::new();
-        for brick in &bricks {
-            grid.extend(brick.into_iter());
-        }
-        settle(&mut bricks, &mut grid);
-        let (supports, node_indices) = get_graph(&bricks, &grid);
-
-        // println!("{:?}", Dot::with_config(&supports, &[Config::EdgeNoLabel]));
-
-        let mut total = 0;
-
-        // check how many bricks would fall for each brick that we would remove
-        for (_, brick_idx) in node_indices {
-            // BFS to visit all nodes starting at the considered brick
-            let mut falling = HashSet::<NodeIndex>::new();
-            let mut stack = VecDeque::<NodeIndex>::new();
-            stack.push_back(brick_idx);
-            while let Some(nx) = stack.pop_front() {
-                if !falling.insert(nx) {
-                    continue; // was already visited
-                }
-                // consider all of the children
-                for n in supports.neighbors_directed(nx, Direction::Outgoing) {
-                    // for this child, check if its parents would fall
-                    if !supports
-                        .neighbors_directed(n, Direction::Incoming)
-                        .all(|i| falling.contains(&i))
-                    {
-                        // one or more of its parents would not fall, so this child would not fall either
-                        continue;
-                    }
-                    // this child would fall too, let's mark it
-                    stack.push_back(n);
-                }
-            }
-            // since the start node (brick that we are considering) should not be counted, we subtract one
-            total += falling.len() - 1;
-        }
-        total
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
+        with_supports(input, falling_bricks)
+    }
+
+    const SHARES_WORK: bool = true;
+
+    /// Settling the bricks and building the graph is most of the work, so we only do it once for both parts
+    fn solve_both(
+        input: &Self::Input<'_>,
+        _params: &Params,
+    ) -> Result<(Self::Output1, Self::Output2), SolveError> {
+        with_supports(input, |supports| {
+            Ok((
+                sub_phase("safe bricks", || safe_to_disintegrate(supports)),
+                sub_phase("chain reactions", || falling_bricks(supports))?,
+            ))
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    const INPUT: &str = "1,0,1~1,2,1
-0,0,2~2,0,2
-0,2,3~2,2,3
-0,0,4~0,2,4
-2,0,5~2,2,5
-0,1,6~2,1,6
-1,1,8~1,1,9";
-
     #[test]
     fn test_iter() {
         let brick = Brick {
             begin: Voxel { x: 2, y: 0, z: 5 },
             end: Voxel { x: 2, y: 2, z: 5 },
         };
-        let mut iter = brick.into_iter();
+        let mut iter = brick.voxels();
         assert_eq!(iter.next(), Some(Voxel { x: 2, y: 0, z: 5 }));
         assert_eq!(iter.next(), Some(Voxel { x: 2, y: 1, z: 5 }));
         assert_eq!(iter.next(), Some(Voxel { x: 2, y: 2, z: 5 }));
@@ -373,12 +308,21 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let parsed = Day22::parse(INPUT).unwrap().1;
-        assert_eq!(Day22::part_1(&parsed), 5);
+        let parsed = Day22::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day22::part_1(&parsed, &Day22::EXAMPLE_PARAMS), Ok(5));
     }
     #[test]
     fn test_part2() {
-        let parsed = Day22::parse(INPUT).unwrap().1;
-        assert_eq!(Day22::part_2(&parsed), 7);
+        let parsed = Day22::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day22::part_2(&parsed, &Day22::EXAMPLE_PARAMS), Ok(7));
+    }
+
+    #[test]
+    fn test_solve_both() {
+        let parsed = Day22::parse(EXAMPLE).unwrap().1;
+        assert_eq!(
+            Day22::solve_both(&parsed, &Day22::EXAMPLE_PARAMS),
+            Ok((5, 7))
+        );
     }
 }
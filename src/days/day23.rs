@@ -1,273 +1,270 @@
-use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+//! A Long Walk
+//!
+//! The corridors between the intersections are merged into weighted edges. With the slopes, each corridor can only be
+//! walked one way, so part 1 finds the longest hike in a DAG with `longest_path`. Part 2 searches every hike between
+//! the intersections, keeping the ones visited in a `VisitedMask`.
 
 use itertools::Itertools;
-use nom::{
-    character::complete::{line_ending, not_line_ending},
-    multi::separated_list0,
-    IResult,
-};
-use pathfinding::grid::Grid;
+use nom::IResult;
 use petgraph::{
-    algo::all_simple_paths,
     dot::{Config, Dot},
     prelude::*,
 };
+use serde::{Deserialize, Serialize};
+use tracing::trace;
 
-use crate::days::Day;
+use crate::{
+    days::Params,
+    parser::SolveError,
+    timing::sub_phase,
+    util::{
+        arena::{with_arena, ArenaVec},
+        graph::{longest_path, KeyedGraph},
+        Direction, FastMap, Grid, Vec2, VisitedMask,
+    },
+};
 
-pub struct Day23;
+/// The trails, with the start and end positions
+#[derive(Serialize, Deserialize)]
+pub struct Trails {
+    pub grid: Grid<Tile>,
+    pub start: Vec2<isize>,
+    pub end: Vec2<isize>,
+}
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub enum Slope {
-    Up,
-    Right,
-    Down,
-    Left,
+/// Example input from the puzzle description
+const EXAMPLE: &str = "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Tile {
+    Forest,
+    Path,
+    /// A path which can only be walked down the slope, in its direction
+    Slope(Direction),
 }
 
-/// Create directed graph with authorized moves
-fn get_graph(
-    grid: &Grid,
-    slopes: &HashMap<(usize, usize), Slope>,
-) -> (Graph<(), ()>, BTreeMap<(usize, usize), NodeIndex>) {
-    let mut graph = Graph::<(), ()>::new();
-    let mut path_indices = BTreeMap::<(usize, usize), NodeIndex>::new();
-    for path in grid {
-        let idx = graph.add_node(());
-        path_indices.insert(path, idx);
+/// Create directed graph with authorized moves, whose nodes are keyed by the position of their path
+///
+/// Without `slopes`, the slopes are ordinary paths and each path can be walked both ways.
+fn get_graph(grid: &Grid<Tile>, slopes: bool) -> KeyedGraph<Vec2<isize>, (), ()> {
+    let mut graph = KeyedGraph::new();
+    let paths = grid.positions(|&tile| tile != Tile::Forest).collect_vec();
+    for &path in &paths {
+        graph.get_or_insert_node(path, || ());
     }
-    for path in grid {
-        let a = path_indices.get(&path).unwrap();
-        // when encountering a slope, we have only 1 move option (go down the slope)
-        if let Some(slope) = slopes.get(&path) {
-            let n = match slope {
-                Slope::Up => (path.0, path.1 - 1),
-                Slope::Right => (path.0 + 1, path.1),
-                Slope::Down => (path.0, path.1 + 1),
-                Slope::Left => (path.0 - 1, path.1),
-            };
-            if let Some(b) = path_indices.get(&n) {
-                graph.add_edge(*a, *b, ());
+    for &path in &paths {
+        let a = graph.index(&path).unwrap();
+        match grid[path] {
+            // when encountering a slope, we have only 1 move option (go down the slope)
+            Tile::Slope(dir) if slopes => {
+                if let Some(b) = graph.index(&path.step(dir)) {
+                    graph.add_edge(a, b, ());
+                }
             }
-        } else {
-            #[allow(clippy::cast_possible_wrap)]
-            let path_isize = (path.0 as isize, path.1 as isize);
-            // check all neighbors
-            for n in grid.neighbours(path) {
-                #[allow(clippy::cast_possible_wrap)]
-                let n_isize = (n.0 as isize, n.1 as isize);
-                // if the neighbor is a slope, we can only visit it if the slope is pointing away from us
-                if let Some(slope) = slopes.get(&n) {
-                    // check if slope of n points towards path
-                    match ((n_isize.0 - path_isize.0, n_isize.1 - path_isize.1), slope) {
-                        ((-1, 0), Slope::Right)
-                        | ((0, -1), Slope::Down)
-                        | ((1, 0), Slope::Left)
-                        | ((0, 1), Slope::Up) => {
-                            // those moves are unallowed
+            _ => {
+                for n in grid.neighbors(path) {
+                    let Some(b) = graph.index(&n) else {
+                        continue;
+                    };
+                    // a slope can only be entered if it points away from us
+                    if let Tile::Slope(dir) = grid[n] {
+                        if slopes && n.step(dir) == path {
                             continue;
                         }
-                        _ => {}
                     }
-                }
-                let b = path_indices.get(&n).unwrap();
-                if !graph.contains_edge(*a, *b) {
-                    graph.add_edge(*a, *b, ());
+                    graph.add_edge(a, b, ());
                 }
             }
         }
     }
-    (graph, path_indices)
+    graph
 }
 
-/// Create undirected graph with long segments between intersections merged into one edge
-fn get_graph2(
-    grid: &Grid,
-    start: &(usize, usize),
-) -> (UnGraph<(), usize>, BTreeMap<(usize, usize), NodeIndex>) {
-    // first, create graph with all possible moves
-    let mut graph = UnGraph::<(), usize>::new_undirected();
-    let mut path_indices = BTreeMap::<(usize, usize), NodeIndex>::new();
-    for path in grid {
-        let idx = graph.add_node(());
-        path_indices.insert(path, idx);
-    }
-    let start_node = path_indices.get(start).unwrap();
-    for path in grid {
-        let a = path_indices.get(&path).unwrap();
-        for n in grid.neighbours(path) {
-            let b = path_indices.get(&n).unwrap();
-            if !graph.contains_edge(*a, *b) {
-                graph.add_edge(*a, *b, 1);
-            }
-        }
-    }
-    // modify graph so that segments between intersections are merged into one edge with a higher weight
-    let mut stack = VecDeque::<NodeIndex>::new();
-    let mut visited = BTreeSet::<NodeIndex>::new();
-    stack.push_back(*start_node);
-    while let Some(path) = stack.pop_front() {
-        visited.insert(path);
-        // add each unvisited neighbor to the stack
-        for n in graph.neighbors(path) {
-            if !visited.contains(&n) {
-                stack.push_back(n);
+/// Merge the corridors of the directed graph into single edges between the intersections (and the start and end),
+/// weighted by their length, and return this graph along with the nodes of the start and end in it
+///
+/// With the slopes around the intersections, each corridor can only be walked one way and this graph is a DAG.
+/// Without them, each corridor gives an edge in both directions.
+fn get_junction_graph(
+    graph: &Graph<(), ()>,
+    start: NodeIndex,
+    end: NodeIndex,
+) -> (Graph<(), usize>, NodeIndex, NodeIndex) {
+    let junctions: FastMap<NodeIndex, u32> = graph
+        .node_indices()
+        .filter(|&node| {
+            node == start || node == end || graph.neighbors_undirected(node).unique().count() > 2
+        })
+        .zip(0..)
+        .collect();
+    // the edges are only collected to size the merged graph, so they can live in the scratch arena
+    let merged = with_arena(|arena| {
+        let mut edges = ArenaVec::new_in(arena);
+        for (&junction, &from) in &junctions {
+            for first in graph.neighbors(junction) {
+                // follow the corridor up to the next intersection, unless it is a dead end
+                let (mut prev, mut node, mut steps) = (junction, first, 1);
+                while !junctions.contains_key(&node) {
+                    let Some(next) = graph.neighbors(node).find(|&n| n != prev) else {
+                        break;
+                    };
+                    (prev, node) = (node, next);
+                    steps += 1;
+                }
+                if let Some(&to) = junctions.get(&node) {
+                    edges.push((from, to, steps));
+                }
             }
         }
-        // check if we have only two neighbors = straight segment
-        if let Some((n1, n2)) = graph.neighbors(path).collect_tuple() {
-            // if we have two neighbors exactly, then let's create a new edge that skips over the current node, and
-            // remove existing edges
-
-            // get existing edges
-            let ((e1_id, e1_weight), (e2_id, e2_weight)) = graph
-                .edges(path)
-                .map(|e| (e.id(), *(e.weight())))
-                .collect_tuple()
-                .unwrap();
-            // add shortcut edge with the sum of both edge weights
-            graph.add_edge(n1, n2, e1_weight + e2_weight);
-            // disconnect the current node
-            graph.remove_edge(e1_id);
-            graph.remove_edge(e2_id);
+        let mut merged = Graph::with_capacity(junctions.len(), edges.len());
+        for _ in 0..junctions.len() {
+            merged.add_node(());
         }
-    }
-
-    (graph, path_indices)
+        merged.extend_with_edges(edges);
+        merged
+    });
+    let index = |node| NodeIndex::new(junctions[&node] as usize);
+    (merged, index(start), index(end))
 }
 
-impl Day for Day23 {
-    type Input = (
-        Grid,
-        HashMap<(usize, usize), Slope>,
-        (usize, usize),
-        (usize, usize),
+fn parse(input: &str) -> IResult<&str, Trails> {
+    let (rest, (grid, [])) = Grid::parse(input, [], |c| match c {
+        '#' => Some(Tile::Forest),
+        '.' => Some(Tile::Path),
+        '^' | '>' | 'v' | '<' => Direction::from_char(c).map(Tile::Slope),
+        _ => None,
+    })?;
+    // the start and the end are the paths on the first and last rows
+    let path_in =
+        |row: Option<&[Tile]>| row.and_then(|row| row.iter().position(|&t| t == Tile::Path));
+    let (Some(start_x), Some(end_x)) = (path_in(grid.rows().next()), path_in(grid.rows().last()))
+    else {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Verify,
+        )));
+    };
+    #[allow(clippy::cast_possible_wrap)]
+    let (start, end) = (
+        Vec2::new(start_x as isize, 0),
+        Vec2::new(end_x as isize, grid.height() as isize - 1),
     );
+    Ok((rest, Trails { grid, start, end }))
+}
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        let (rest, rows) = separated_list0(line_ending, not_line_ending)(input)?;
-        let height = rows.len();
-        let mut start_x = 0;
-        let mut end_x = 0;
-        let mut path = vec![];
-        let mut slopes = HashMap::<(usize, usize), Slope>::new();
-        for (y, row) in rows.into_iter().enumerate() {
-            for (x, c) in row.chars().enumerate() {
-                match c {
-                    '.' => {
-                        path.push((x, y));
-                        if y == 0 {
-                            start_x = x;
-                        } else if y == height - 1 {
-                            end_x = x;
-                        }
-                    }
-                    '^' => {
-                        path.push((x, y));
-                        slopes.insert((x, y), Slope::Up);
-                    }
-                    '>' => {
-                        path.push((x, y));
-                        slopes.insert((x, y), Slope::Right);
-                    }
-                    'v' => {
-                        path.push((x, y));
-                        slopes.insert((x, y), Slope::Down);
-                    }
-                    '<' => {
-                        path.push((x, y));
-                        slopes.insert((x, y), Slope::Left);
-                    }
-                    _ => {}
-                }
-            }
-        }
-        let grid = path.into_iter().collect::<Grid>();
-        Ok((rest, (grid, slopes, (start_x, 0), (end_x, height - 1))))
-    }
-
-    type Output1 = usize;
+/// Part 1 took 12.724701ms
+fn part_1(input: &Trails, _params: &Params) -> Result<usize, SolveError> {
+    let Trails { grid, start, end } = input;
+    // create directed graph
+    let graph = get_graph(grid, true);
+    let start_node = graph.index(start).unwrap();
+    let end_node = graph.index(end).unwrap();
+    let (dag, start_node, end_node) = get_junction_graph(graph.graph(), start_node, end_node);
 
-    /// Part 1 took 12.724701ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        let (grid, slopes, start, end) = input;
-        // create directed graph
-        let (graph, path_indices) = get_graph(grid, slopes);
-        let start_node = path_indices.get(start).unwrap();
-        let end_node = path_indices.get(end).unwrap();
+    // the slopes make the longest path easy to find, from the intersections in topological order
+    longest_path(&dag, start_node, end_node, |edge| *edge.weight()).ok_or_else(|| {
+        SolveError::invalid(
+            "the end can't be reached, or the slopes don't prevent walking in a loop",
+        )
+    })
+}
 
-        // check all possible paths that visit each node at most once and check which is longest
-        all_simple_paths::<Vec<_>, _>(&graph, *start_node, *end_node, 10, None)
-            .map(|path| path.len() - 1)
-            .max()
-            .unwrap()
+/// The length of the longest hike from `node` to `end` which doesn't go through the nodes already `visited`, `None`
+/// if `end` can't be reached
+fn longest_hike(
+    graph: &Graph<(), usize>,
+    node: NodeIndex,
+    end: NodeIndex,
+    visited: VisitedMask,
+) -> Option<usize> {
+    if node == end {
+        return Some(0);
     }
+    let visited = visited.with(node.index());
+    graph
+        .edges(node)
+        .filter(|edge| !visited.contains(edge.target().index()))
+        .filter_map(|edge| Some(edge.weight() + longest_hike(graph, edge.target(), end, visited)?))
+        .max()
+}
 
-    type Output2 = usize;
-
-    /// Part 2 took 1.859898321s
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        let (grid, _, start, end) = input;
-        // create undirected graph with segments between intersections merged into one edge with steps as the weigth
-        let (graph, path_indices) = get_graph2(grid, start);
-        let start_node = path_indices.get(start).unwrap();
-        let end_node = path_indices.get(end).unwrap();
+/// Part 2 took 1.859898321s
+fn part_2(input: &Trails, _params: &Params) -> Result<usize, SolveError> {
+    let Trails { grid, start, end } = input;
+    // the slopes are ordinary paths now, so every corridor between two intersections can be walked both ways
+    let (graph, start_node, end_node) = sub_phase("graph of the intersections", || {
+        let graph = get_graph(grid, false);
+        let start_node = graph.index(start).unwrap();
+        let end_node = graph.index(end).unwrap();
+        get_junction_graph(graph.graph(), start_node, end_node)
+    });
 
-        if cfg!(test) {
-            println!("{:?}", Dot::with_config(&graph, &[Config::NodeIndexLabel]));
-        }
+    trace!(
+        "graph of the intersections:\n{:?}",
+        Dot::with_config(&graph, &[Config::NodeIndexLabel])
+    );
 
-        // retrieve all paths that visit each node at most once and check which is longest
-        all_simple_paths::<Vec<_>, _>(&graph, *start_node, *end_node, 0, None)
-            .map(|path| {
-                // get path length by summing the edge weights
-                path.iter()
-                    .tuple_windows()
-                    .map(|(a, b)| graph.edge_weight(graph.find_edge(*a, *b).unwrap()).unwrap())
-                    .sum()
-            })
-            .max()
-            .unwrap()
+    // the intersections visited by a hike fit in a mask, which the search copies instead of undoing its steps
+    if graph.node_count() > VisitedMask::CAPACITY {
+        return Err(SolveError::invalid(format!(
+            "{} intersections are too many to search every hike",
+            graph.node_count()
+        )));
     }
+    sub_phase("longest path", || {
+        longest_hike(&graph, start_node, end_node, VisitedMask::default())
+            .ok_or_else(|| SolveError::invalid("the end can't be reached"))
+    })
 }
 
+impl_day!(
+    Day23,
+    23,
+    parse -> Trails,
+    part_1 -> usize,
+    part_2 -> usize,
+    answers = [Some("94"), Some("154")],
+    title = "A Long Walk",
+    tags = ["graph", "grid"],
+    cost = Slow,
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const INPUT: &str = "#.#####################
-#.......#########...###
-#######.#########.#.###
-###.....#.>.>.###.#.###
-###v#####.#v#.###.#.###
-###.>...#.#.#.....#...#
-###v###.#.#.#########.#
-###...#.#.#.......#...#
-#####.#.#.#######.#.###
-#.....#.#.#.......#...#
-#.#####.#.#.#########v#
-#.#...#...#...###...>.#
-#.#.#v#######v###.###v#
-#...#.>.#...>.>.#.###.#
-#####v#.#.###v#.#.###.#
-#.....#...#...#.#.#...#
-#.#########.###.#.#.###
-#...###...#...#...#.###
-###.###.#.###v#####v###
-#...#...#.#.>.>.#.>.###
-#.###.###.#.###.#.#v###
-#.....###...###...#...#
-#####################.#";
+    use crate::days::Day;
 
     #[test]
     fn test_part1() {
-        let parsed = Day23::parse(INPUT).unwrap().1;
-        assert_eq!(Day23::part_1(&parsed), 94);
+        let parsed = Day23::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day23::part_1(&parsed, &Day23::EXAMPLE_PARAMS), Ok(94));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = Day23::parse(INPUT).unwrap().1;
-        assert_eq!(Day23::part_2(&parsed), 154);
+        let parsed = Day23::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day23::part_2(&parsed, &Day23::EXAMPLE_PARAMS), Ok(154));
     }
 }
@@ -1,242 +1,415 @@
-use std::collections::HashSet;
+//! Never Tell Me The Odds
+//!
+//! The crossings of the paths are computed exactly as `Ratio`s instead of rounded floats. Part 2 solves the linear
+//! system given by the cross products of the paths of three hailstones with `linalg`.
 
 use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
-    character::complete::{i64, line_ending, space1},
-    multi::{separated_list0, separated_list1},
+    character::complete::{i64, space1},
+    combinator::map,
     sequence::{separated_pair, tuple},
     IResult,
 };
+use num::{BigInt, BigRational, ToPrimitive};
+use serde::{Deserialize, Serialize};
 
-use crate::days::Day;
-
-const AREA_MIN: f64 = if cfg!(test) { 7. } else { 200_000_000_000_000. };
-const AREA_MAX: f64 = if cfg!(test) {
-    27.
-} else {
-    400_000_000_000_000.
+use crate::{
+    days::{Day, Params},
+    parser::{
+        self,
+        helpers::lines_of,
+        scan::{ScanError, Scanner},
+        SolveError,
+    },
+    util::{
+        geom::{ray_intersection, Intersection},
+        linalg::Matrix,
+        ratio, Aabb, Ratio, Vec2, Vec3,
+    },
 };
 
 pub struct Day24;
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub struct V3 {
-    x: i64,
-    y: i64,
-    z: i64,
-}
+/// Example input from the puzzle description
+const EXAMPLE: &str = "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HailStone {
-    pos: V3,
-    vel: V3,
+    pos: Vec3<i64>,
+    vel: Vec3<i64>,
+}
+
+/// The X and Y coordinates, large enough for the cross products of positions and velocities
+fn xy(v: Vec3<i64>) -> Vec2<i128> {
+    v.map(i128::from).xy()
 }
 
 impl HailStone {
-    #[allow(clippy::cast_precision_loss, clippy::similar_names)]
-    fn intersection_with(&self, other: &HailStone) -> Option<(f64, f64)> {
-        let (p1x, p1y, v1x, v1y) = (
-            self.pos.x as f64,
-            self.pos.y as f64,
-            self.vel.x as f64,
-            self.vel.y as f64,
-        );
-        let (p2x, p2y, v2x, v2y) = (
-            other.pos.x as f64,
-            other.pos.y as f64,
-            other.vel.x as f64,
-            other.vel.y as f64,
-        );
+    /// Where the paths of the two hailstones cross in the X-Y plane, if they cross in the future of both of them
+    fn intersection_with(&self, other: &HailStone) -> Option<(Ratio, Ratio)> {
+        match ray_intersection(xy(self.pos), xy(self.vel), xy(other.pos), xy(other.vel)) {
+            Intersection::Point { at, .. } => Some((at.x, at.y)),
+            _ => None,
+        }
+    }
+}
 
-        let t2 = ((p2y - p1y) * v1x - (p2x - p1x) * v1y) / (v2x * v1y - v2y * v1x);
-        let t1 = (p2x - p1x + t2 * v2x) / v1x;
+fn parse_vec(input: &[u8]) -> IResult<&[u8], Vec3<i64>> {
+    let separator = || tuple((tag(","), space1));
+    map(
+        tuple((i64, separator(), i64, separator(), i64)),
+        |(x, _, y, _, z)| Vec3::new(x, y, z),
+    )(input)
+}
 
-        if !t1.is_sign_positive() || !t2.is_sign_positive() {
-            return None;
-        }
+fn parse_hailstone(input: &[u8]) -> IResult<&[u8], HailStone> {
+    map(
+        separated_pair(parse_vec, tuple((space1, tag("@"), space1)), parse_vec),
+        |(pos, vel)| HailStone { pos, vel },
+    )(input)
+}
 
-        let x = p1x + t1 * v1x;
-        let y = p1y + t1 * v1y;
-        Some((x, y))
-    }
+fn scan_vec<'a>(line: &mut Scanner<'a>) -> Result<Vec3<i64>, ScanError<'a>> {
+    let x = line.next_int()?;
+    line.tag(b",")?;
+    let y = line.next_int()?;
+    line.tag(b",")?;
+    let z = line.next_int()?;
+    Ok(Vec3::new(x, y, z))
 }
 
-fn parse_vec(input: &str) -> IResult<&str, V3> {
-    let (rest, coords) = separated_list1(tuple((tag(","), space1)), i64)(input)?;
-    let (x, y, z) = coords.into_iter().collect_tuple().unwrap();
-    Ok((rest, V3 { x, y, z }))
+fn scan_hailstone(mut line: Scanner<'_>) -> Result<HailStone, ScanError<'_>> {
+    let pos = scan_vec(&mut line)?;
+    line.skip_spaces();
+    line.tag(b"@")?;
+    let vel = scan_vec(&mut line)?;
+    line.finish()?;
+    Ok(HailStone { pos, vel })
 }
 
-fn parse_hailstone(input: &str) -> IResult<&str, HailStone> {
-    let (rest, (position, speed)) =
-        separated_pair(parse_vec, tuple((space1, tag("@"), space1)), parse_vec)(input)?;
-    Ok((
-        rest,
-        HailStone {
-            pos: position,
-            vel: speed,
-        },
-    ))
+/// Parse the hailstones with nom, to compare with `scan_hailstones` (see the `nom-parsers` feature)
+fn parse_hailstones(input: &[u8]) -> IResult<&[u8], Vec<HailStone>> {
+    lines_of(parse_hailstone)(input)
+}
+
+/// Read the hailstones with `parser::scan`
+fn scan_hailstones(input: &[u8]) -> IResult<&[u8], Vec<HailStone>> {
+    let mut scanner = Scanner::new(input);
+    let hailstones = scanner
+        .lines()
+        .map(scan_hailstone)
+        .collect::<Result<_, _>>()?;
+    Ok((scanner.rest(), hailstones))
 }
 
 impl Day for Day24 {
-    type Input = Vec<HailStone>;
+    const DAY: u8 = 24;
+    const EXAMPLE: &'static str = EXAMPLE;
+    // part 1 uses a smaller test area for the example
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("2"), Some("47")];
+    const EXAMPLE_PARAMS: Params = Params {
+        area_min: 7.,
+        area_max: 27.,
+        ..Params::PUZZLE
+    };
 
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        separated_list0(line_ending, parse_hailstone)(input)
+    fn title() -> &'static str {
+        "Never Tell Me The Odds"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["geometry", "math"]
+    }
+
+    type Input<'a> = Vec<HailStone>;
+
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        parser::from_bytes(input, Self::parse_bytes(input.as_bytes()))
+    }
+
+    fn parse_bytes(input: &[u8]) -> IResult<&[u8], Self::Input<'_>> {
+        if cfg!(feature = "nom-parsers") {
+            parse_hailstones(input)
+        } else {
+            scan_hailstones(input)
+        }
     }
 
     type Output1 = usize;
 
     /// Part 1 took 129.3µs
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        input
+    fn part_1(input: &Self::Input<'_>, params: &Params) -> Result<Self::Output1, SolveError> {
+        let bound = |value: f64| {
+            ratio::from_f64(value)
+                .ok_or_else(|| SolveError::invalid(format!("invalid test area bound {value}")))
+        };
+        let (min, max) = (bound(params.area_min)?, bound(params.area_max)?);
+        let area = Aabb::new([min, min], [max, max]);
+        Ok(input
             .iter()
             .tuple_combinations()
             .filter(|(a, b)| {
                 let Some((x, y)) = a.intersection_with(b) else {
                     return false;
                 };
-                (AREA_MIN..=AREA_MAX).contains(&x) && (AREA_MIN..=AREA_MAX).contains(&y)
+                area.contains([x, y])
             })
-            .count()
+            .count())
     }
 
     type Output2 = i64;
 
     /// Part 2 took 999.1µs
-    #[allow(
-        clippy::similar_names,
-        clippy::cast_precision_loss,
-        clippy::cast_possible_truncation
-    )]
-    fn part_2(input: &Self::Input) -> Self::Output2 {
-        // let's find the velocity that our rock must have, by considering pairs of hailstones that have the same
-        // (large) velocity on one axis
-        let mut vel_x: Option<HashSet<i64>> = None;
-        let mut vel_y: Option<HashSet<i64>> = None;
-        let mut vel_z: Option<HashSet<i64>> = None;
-        for (a, b) in input.iter().tuple_combinations().filter(|(a, b)| {
-            (a.vel.x == b.vel.x && a.vel.x.abs() > 100)
-                || (a.vel.y == b.vel.y && a.vel.y.abs() > 100)
-                || (a.vel.z == b.vel.z && a.vel.z.abs() > 100)
-        }) {
-            // a and b go at the same velocity on at least one axis, so that means their distance on that axis is
-            // constant. Then, the velocity of the rock on that axis must satisfy: dist % (rock.vel - hail.vel) == 0
-            // in order for it to encounter both hailstones
-            let (dist, hailstone_vel, velocities) = match (a.vel.x == b.vel.x, a.vel.y == b.vel.y) {
-                (true, _) => (b.pos.x - a.pos.x, a.vel.x, &mut vel_x),
-                (false, true) => (b.pos.y - a.pos.y, a.vel.y, &mut vel_y),
-                (false, false) => (b.pos.z - a.pos.z, a.vel.z, &mut vel_z),
-            };
-            let mut candidates = HashSet::<i64>::new();
-            // let's check velocities in a realistic range that match the equation
-            for v in -1000..1000 {
-                if v == hailstone_vel {
-                    // would divide by zero
-                    continue;
-                }
-                if dist % (v - hailstone_vel) == 0 {
-                    candidates.insert(v);
-                }
-            }
-            // add the candidates to the set, or compute the intersection with previous candidates
-            if let Some(velocities) = velocities {
-                *velocities = velocities.intersection(&candidates).copied().collect();
-            } else {
-                *velocities = Some(candidates);
-            }
+    fn part_2(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
+        // any three hailstones whose paths are not too much alike pin down the rock
+        let Some(rock) = input
+            .iter()
+            .tuple_combinations()
+            .find_map(|(a, b, c)| rock_from(a, b, c))
+        else {
+            return Err(SolveError::invalid(
+                "no three hailstones give a single starting position for the rock",
+            ));
+        };
+        let sum = &rock.x + &rock.y + &rock.z;
+        if !sum.is_integer() {
+            return Err(SolveError::invalid(format!(
+                "the rock would start at {rock}, which is not on the grid"
+            )));
         }
-        // we now know the velocity of the rock
-        let (rvx, rvy, rvz) = (
-            vel_x.unwrap().into_iter().next().unwrap() as f64,
-            vel_y.unwrap().into_iter().next().unwrap() as f64,
-            vel_z.unwrap().into_iter().next().unwrap() as f64,
-        );
+        sum.to_integer().to_i64().ok_or_else(|| {
+            SolveError::invalid(format!("the sum of the coordinates {sum} is too large"))
+        })
+    }
+}
 
-        // we can take any two hailstones and subtract the rock velocity to each to find two lines where our rock
-        // starting position could lie. The intersection of the two lines is our rock starting position.
-        let a = input.first().unwrap();
-        let b = input.get(2).unwrap();
-        // find intersection on X-Y
-        let ma = (a.vel.y as f64 - rvy) / (a.vel.x as f64 - rvx);
-        let mb = (b.vel.y as f64 - rvy) / (b.vel.x as f64 - rvx);
-        let ca = a.pos.y as f64 - (ma * a.pos.x as f64);
-        let cb = b.pos.y as f64 - (mb * b.pos.x as f64);
-        let rx = ((cb - ca) / (ma - mb)).round();
-        let ry = (ma * rx + ca).round();
-        // check at which time we intersect with a
-        let time = (rx - a.pos.x as f64) / (a.vel.x as f64 - rvx);
-        // what was the z position at time zero?
-        let rz = a.pos.z as f64 + (a.vel.z as f64 - rvz) * time;
-        rx as i64 + ry as i64 + rz as i64
+/// The starting position of the rock which hits the three hailstones, `None` if they don't pin down a single one
+///
+/// The rock starting at `P` with a velocity `V` hits a hailstone at `p` going at `v` when `P - p` and `V - v` are
+/// parallel, so when `(P - p) × (V - v) = 0`. The term `P × V` is the same for all the hailstones, and subtracting the
+/// equations of two hailstones `i` and `j` leaves three equations which are linear in `P` and `V`:
+/// `P × (vj - vi) + (pj - pi) × V = pj × vj - pi × vi`.
+///
+/// The elimination multiplies several of these large coefficients together, which overflows a [`Ratio`], so the
+/// system is solved with a [`BigRational`].
+fn rock_from(a: &HailStone, b: &HailStone, c: &HailStone) -> Option<Vec3<BigRational>> {
+    let wide = |v: Vec3<i64>| v.map(i128::from);
+    let big = |value: i128| BigRational::from_integer(BigInt::from(value));
+    let units = [Vec3::new(1, 0, 0), Vec3::new(0, 1, 0), Vec3::new(0, 0, 1)];
+    let (mut rows, mut rhs) = (Vec::with_capacity(6), Vec::with_capacity(6));
+    for (i, j) in [(a, b), (a, c)] {
+        let (pi, vi, pj, vj) = (wide(i.pos), wide(i.vel), wide(j.pos), wide(j.vel));
+        // the columns of the cross products with `P` and `V`, which are the cross products with the unit vectors
+        let (dv, dp) = (vj - vi, pj - pi);
+        let columns: Vec<[i128; 3]> = units
+            .iter()
+            .map(|&e| e.cross(dv).into())
+            .chain(units.iter().map(|&e| dp.cross(e).into()))
+            .collect();
+        let target: [i128; 3] = (pj.cross(vj) - pi.cross(vi)).into();
+        for k in 0..3 {
+            rows.push(columns.iter().map(|col| big(col[k])).collect());
+            rhs.push(big(target[k]));
+        }
     }
+    let mut solution = Matrix::from_rows(rows)?.solve(&rhs)?.into_iter();
+    Some(Vec3::new(
+        solution.next()?,
+        solution.next()?,
+        solution.next()?,
+    ))
 }
 
+/// Slow solutions which follow the puzzle literally, to cross-check the ones above
 #[cfg(test)]
-mod tests {
-    use approx::assert_relative_eq;
+mod reference {
+    use itertools::Itertools;
 
-    use super::*;
+    use super::HailStone;
+    use crate::util::Vec3;
 
-    const INPUT: &str = "19, 13, 30 @ -2,  1, -2
-18, 19, 22 @ -1, -1, -2
-20, 25, 34 @ -2, -2, -4
-12, 31, 28 @ -1, -2, -1
-20, 19, 15 @  1, -5, -3";
+    fn wide(v: Vec3<i64>) -> [i128; 3] {
+        v.map(i128::from).into()
+    }
+
+    /// Solve the crossing of each pair of paths with floats, which is only exact for small coordinates
+    #[allow(clippy::cast_precision_loss)]
+    pub fn crossings_in_area(hailstones: &[HailStone], min: f64, max: f64) -> usize {
+        hailstones
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b)| {
+                let ([ax, ay, _], [avx, avy, _]) = (wide(a.pos), wide(a.vel));
+                let ([bx, by, _], [bvx, bvy, _]) = (wide(b.pos), wide(b.vel));
+                // a + a_vel * t = b + b_vel * u, with Cramer's rule
+                let det = avx * bvy - avy * bvx;
+                if det == 0 {
+                    return false;
+                }
+                let t = ((bx - ax) * bvy - (by - ay) * bvx) as f64 / det as f64;
+                let u = ((bx - ax) * avy - (by - ay) * avx) as f64 / det as f64;
+                let x = ax as f64 + avx as f64 * t;
+                let y = ay as f64 + avy as f64 * t;
+                t >= 0. && u >= 0. && (min..=max).contains(&x) && (min..=max).contains(&y)
+            })
+            .count()
+    }
+
+    /// Try every velocity of the rock on X and Y in `-max..=max`: relative to the rock, the paths of the first two
+    /// hailstones cross where it starts, and the velocity is the right one if it then hits all the hailstones
+    pub fn rock_coordinates_sum(hailstones: &[HailStone], max: i64) -> Option<i64> {
+        let [a, b, ..] = hailstones else {
+            return None;
+        };
+        let ([ax, ay, az], [avx, avy, avz]) = (wide(a.pos), wide(a.vel));
+        let ([bx, by, bz], [bvx, bvy, bvz]) = (wide(b.pos), wide(b.vel));
+        let hits = |rock: [i128; 3], vel: [i128; 3], stone: &HailStone| {
+            let (pos, stone_vel) = (wide(stone.pos), wide(stone.vel));
+            let mut time = None;
+            (0..3).all(|i| {
+                let (dist, speed) = (pos[i] - rock[i], vel[i] - stone_vel[i]);
+                if speed == 0 {
+                    return dist == 0;
+                }
+                dist % speed == 0
+                    && dist / speed >= 0
+                    && *time.get_or_insert(dist / speed) == dist / speed
+            })
+        };
+        (-max..=max)
+            .cartesian_product(-max..=max)
+            .find_map(|(vx, vy)| {
+                let (vx, vy) = (i128::from(vx), i128::from(vy));
+                let (dax, day, dbx, dby) = (avx - vx, avy - vy, bvx - vx, bvy - vy);
+                let det = dax * dby - day * dbx;
+                if det == 0 {
+                    return None;
+                }
+                // the times when the rock hits a and b
+                let t = ((bx - ax) * dby - (by - ay) * dbx) / det;
+                let u = ((bx - ax) * day - (by - ay) * dax) / det;
+                // both hailstones are at the same place relative to the rock, and on z too
+                if ax + dax * t != bx + dbx * u || ay + day * t != by + dby * u || t == u {
+                    return None;
+                }
+                let vz = (bz + bvz * u - az - avz * t) / (u - t);
+                let rock = [ax + dax * t, ay + day * t, az + (avz - vz) * t];
+                hailstones
+                    .iter()
+                    .all(|stone| hits(rock, [vx, vy, vz], stone))
+                    .then(|| rock.iter().sum::<i128>())
+            })
+            .and_then(|sum| i64::try_from(sum).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::gen::{self, Rng};
 
     #[test]
     fn test_part1() {
-        let parsed = Day24::parse(INPUT).unwrap().1;
-        assert_eq!(Day24::part_1(&parsed), 2);
+        let parsed = Day24::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day24::part_1(&parsed, &Day24::EXAMPLE_PARAMS), Ok(2));
+    }
+
+    #[test]
+    fn test_part2() {
+        let parsed = Day24::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day24::part_2(&parsed, &Day24::EXAMPLE_PARAMS), Ok(47));
     }
 
     #[test]
     fn test_intersection() {
         let a = HailStone {
-            pos: V3 {
+            pos: Vec3 {
                 x: 19,
                 y: 13,
                 z: 30,
             },
-            vel: V3 { x: -2, y: 1, z: -2 },
+            vel: Vec3 { x: -2, y: 1, z: -2 },
         };
         let b = HailStone {
-            pos: V3 {
+            pos: Vec3 {
                 x: 18,
                 y: 19,
                 z: 22,
             },
-            vel: V3 {
+            vel: Vec3 {
                 x: -1,
                 y: -1,
                 z: -2,
             },
         };
         let (x, y) = a.intersection_with(&b).unwrap();
-        assert_relative_eq!(x, 14.333_333_333_333_334);
-        assert_relative_eq!(y, 15.333_333_333_333_332);
+        assert_eq!(x, Ratio::new(43, 3));
+        assert_eq!(y, Ratio::new(46, 3));
     }
 
     #[test]
     fn test_intersection_past() {
         let a = HailStone {
-            pos: V3 {
+            pos: Vec3 {
                 x: 19,
                 y: 13,
                 z: 30,
             },
-            vel: V3 { x: -2, y: 1, z: -2 },
+            vel: Vec3 { x: -2, y: 1, z: -2 },
         };
         let b = HailStone {
-            pos: V3 {
+            pos: Vec3 {
                 x: 20,
                 y: 19,
                 z: 15,
             },
-            vel: V3 { x: 1, y: -5, z: -3 },
+            vel: Vec3 { x: 1, y: -5, z: -3 },
         };
         assert_eq!(a.intersection_with(&b), None);
     }
+
+    #[test]
+    fn test_reference() {
+        let mut rng = Rng::new(24);
+        for _ in 0..20 {
+            let hailstones = (0..20)
+                .map(|_| HailStone {
+                    pos: Vec3 {
+                        x: rng.range(0..=30),
+                        y: rng.range(0..=30),
+                        z: 0,
+                    },
+                    vel: Vec3 {
+                        x: rng.range(-5..=5),
+                        y: rng.range(-5..=5),
+                        z: 1,
+                    },
+                })
+                .collect_vec();
+            assert_eq!(
+                Day24::part_1(&hailstones, &Day24::EXAMPLE_PARAMS),
+                Ok(reference::crossings_in_area(&hailstones, 7., 27.))
+            );
+        }
+        for seed in 0..2 {
+            let input = gen::hailstones(300, &mut Rng::new(seed));
+            let hailstones = Day24::parse(&input).unwrap().1;
+            let sum = reference::rock_coordinates_sum(&hailstones, 200).unwrap();
+            assert_eq!(Day24::part_2(&hailstones, &Params::PUZZLE), Ok(sum));
+        }
+    }
+
+    #[test]
+    fn test_parsers() {
+        let input = EXAMPLE.as_bytes();
+        assert_eq!(
+            parse_hailstones(input).unwrap().1,
+            scan_hailstones(input).unwrap().1
+        );
+    }
 }
@@ -1,67 +1,301 @@
-use std::collections::HashMap;
+//! Snowverload
+//!
+//! The cut can be found in three ways, see `ALGORITHMS`, e.g. `cargo run -- viz 25 --algorithm karger` draws the
+//! partition found by Karger's algorithm.
 
+use std::collections::VecDeque;
+
+use itertools::Itertools;
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha1, line_ending, space1},
-    multi::{separated_list0, separated_list1},
+    character::complete::{alpha1, space1},
+    multi::separated_list1,
     sequence::separated_pair,
     IResult,
 };
 use petgraph::prelude::*;
 use rustworkx_core::connectivity::stoer_wagner_min_cut;
 
-use crate::days::Day;
+use crate::{
+    days::{Cost, Day, Params},
+    parser::{helpers::lines_of, SolveError},
+    stars,
+    util::{
+        arena::{arena_vec, with_arena, ArenaVec},
+        graph::KeyedGraph,
+        Dsu, FastSet,
+    },
+    viz::DotGraph,
+};
 
 pub struct Day25;
 
+/// Example input from the puzzle description
+const EXAMPLE: &str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+/// An algorithm that finds the three wires to cut in order to split the graph into two groups
+pub type CutFinder = fn(&UnGraph<String, ()>) -> Result<Vec<EdgeIndex>, SolveError>;
+
+/// All the algorithms available to find the cut, by name. The first one is used by default.
+pub const ALGORITHMS: [(&str, CutFinder); 3] = [
+    ("betweenness", betweenness_cut),
+    ("stoer-wagner", stoer_wagner_cut),
+    ("karger", karger_cut),
+];
+
+/// Look up a cut-finding algorithm by name
+pub fn algorithm(name: &str) -> Option<CutFinder> {
+    ALGORITHMS
+        .iter()
+        .find_map(|(n, finder)| (*n == name).then_some(*finder))
+}
+
+/// Use a minimum cut algorithm to partition the graph into two, and return the edges that cross the partition
+fn stoer_wagner_cut(graph: &UnGraph<String, ()>) -> Result<Vec<EdgeIndex>, SolveError> {
+    let (_, partition): (usize, Vec<_>) =
+        stoer_wagner_min_cut::<_, _, _, anyhow::Error>(graph, |_| Ok(1))
+            .map_err(|e| SolveError::invalid(format!("the minimum cut failed: {e}")))?
+            .ok_or_else(|| SolveError::invalid("the graph has fewer than 2 components"))?;
+    let left: FastSet<NodeIndex> = partition.into_iter().collect();
+    Ok(graph
+        .edge_references()
+        .filter(|e| left.contains(&e.source()) != left.contains(&e.target()))
+        .map(|e| e.id())
+        .collect())
+}
+
+/// Deterministic alternative to the min cut: the three wires joining the two groups must be traversed by a lot of
+/// the shortest paths in the graph.
+///
+/// We run a BFS from a sample of source nodes, count how many times each edge is used by the shortest paths to all
+/// other nodes, and remove the most used edge. Repeating this three times (recounting after each removal) gives the
+/// cut.
+fn betweenness_cut(graph: &UnGraph<String, ()>) -> Result<Vec<EdgeIndex>, SolveError> {
+    let node_count = graph.node_count();
+    // sampling about 50 sources is plenty to make the bridges stand out
+    let stride = (node_count / 50).max(1);
+    let mut removed = vec![false; graph.edge_count()];
+    let mut cut = Vec::with_capacity(3);
+    for _ in 0..3 {
+        let mut usage = vec![0usize; graph.edge_count()];
+        for source in graph.node_indices().step_by(stride) {
+            with_arena(|arena| {
+                // BFS from the source, recording the edge used to reach each node
+                let mut parent_edge: ArenaVec<Option<EdgeIndex>> =
+                    arena_vec![in arena; None; node_count];
+                let mut visited = arena_vec![in arena; false; node_count];
+                // the queue is never popped, the nodes are read in order from it instead
+                let mut queue = arena_vec![in arena; source];
+                visited[source.index()] = true;
+                let mut head = 0;
+                while let Some(&node) = queue.get(head) {
+                    head += 1;
+                    for edge in graph.edges(node) {
+                        let next = edge.target();
+                        if removed[edge.id().index()] || visited[next.index()] {
+                            continue;
+                        }
+                        visited[next.index()] = true;
+                        parent_edge[next.index()] = Some(edge.id());
+                        queue.push(next);
+                    }
+                }
+                // walk back the shortest path from each node to the source and count the edges used
+                for node in graph.node_indices() {
+                    let mut current = node;
+                    while let Some(edge) = parent_edge[current.index()] {
+                        usage[edge.index()] += 1;
+                        let (a, b) = graph.edge_endpoints(edge).unwrap();
+                        current = if a == current { b } else { a };
+                    }
+                }
+            });
+        }
+        let (most_used, _) = usage
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !removed[*i])
+            .max_by_key(|(i, count)| (**count, std::cmp::Reverse(*i)))
+            .ok_or_else(|| SolveError::invalid("the graph has fewer than 3 wires"))?;
+        removed[most_used] = true;
+        cut.push(EdgeIndex::new(most_used));
+    }
+    Ok(cut)
+}
+
+/// Randomized alternative: Karger's algorithm merges the ends of the wires in a random order until only two groups
+/// are left, which gives a minimum cut with a small probability, so we try again until the cut has three wires.
+///
+/// The order comes from a fixed xorshift sequence so that the runs are reproducible. After enough attempts, the
+/// smallest cut found is returned.
+#[allow(clippy::cast_possible_truncation)]
+fn karger_cut(graph: &UnGraph<String, ()>) -> Result<Vec<EdgeIndex>, SolveError> {
+    if graph.edge_count() == 0 {
+        return Err(SolveError::invalid("the graph has no wires"));
+    }
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut edges = graph.edge_indices().collect_vec();
+    let mut best: Option<Vec<EdgeIndex>> = None;
+    for _ in 0..10_000 {
+        // Fisher-Yates shuffle of the wires
+        for i in (1..edges.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            edges.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+        let mut groups = Dsu::new(graph.node_count());
+        for &edge in &edges {
+            if groups.sets() <= 2 {
+                break;
+            }
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            groups.union(a.index(), b.index());
+        }
+        let cut = graph
+            .edge_references()
+            .filter(|e| !groups.same(e.source().index(), e.target().index()))
+            .map(|e| e.id())
+            .collect_vec();
+        if cut.len() <= 3 {
+            return Ok(cut);
+        }
+        if best.as_ref().is_none_or(|best| cut.len() < best.len()) {
+            best = Some(cut);
+        }
+    }
+    Ok(best.unwrap_or_default())
+}
+
+/// Find the group of nodes connected to the first node when the cut edges are removed
+///
+/// Returns `None` if the cut doesn't split the graph into two groups.
+fn split(graph: &UnGraph<String, ()>, cut: &[EdgeIndex]) -> Option<Vec<NodeIndex>> {
+    let start = graph.node_indices().next()?;
+    let mut visited = FastSet::from_iter([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for edge in graph.edges(node) {
+            if cut.contains(&edge.id()) {
+                continue;
+            }
+            if visited.insert(edge.target()) {
+                queue.push_back(edge.target());
+            }
+        }
+    }
+    (visited.len() < graph.node_count()).then(|| visited.into_iter().collect())
+}
+
+/// Size of both groups after cutting the wires found by the given algorithm
+fn group_sizes(
+    graph: &UnGraph<String, ()>,
+    finder: CutFinder,
+) -> Result<(usize, usize), SolveError> {
+    let cut = finder(graph)?;
+    // double-check that the number of cut edges is 3
+    if cut.len() != 3 {
+        return Err(SolveError::invalid(format!(
+            "the minimum cut has {} wires instead of 3",
+            cut.len()
+        )));
+    }
+    let group = split(graph, &cut)
+        .ok_or_else(|| SolveError::invalid("cutting 3 wires doesn't split the graph in two"))?;
+    Ok((group.len(), graph.node_count() - group.len()))
+}
+
+/// Render the wiring graph as DOT, coloring each partition and highlighting the cut edges
+pub fn partition_dot(graph: &UnGraph<String, ()>, finder: CutFinder) -> Result<String, SolveError> {
+    let cut = finder(graph)?;
+    let left: FastSet<NodeIndex> = split(graph, &cut)
+        .ok_or_else(|| SolveError::invalid("the cut doesn't split the graph in two"))?
+        .into_iter()
+        .collect();
+    let mut dot = DotGraph::default();
+    for idx in graph.node_indices() {
+        let color = if left.contains(&idx) {
+            "#8ecae6"
+        } else {
+            "#ffb703"
+        };
+        dot.node(idx.index(), &graph[idx], color);
+    }
+    for edge in graph.edge_references() {
+        dot.edge(
+            edge.source().index(),
+            edge.target().index(),
+            cut.contains(&edge.id()),
+        );
+    }
+    Ok(dot.render())
+}
+
 impl Day for Day25 {
-    type Input = UnGraph<(), ()>;
+    const DAY: u8 = 25;
+    const EXAMPLE: &'static str = EXAMPLE;
+    // part 2 depends on the stars collected on the other days
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("54"), None];
+
+    fn title() -> &'static str {
+        "Snowverload"
+    }
+
+    fn tags() -> &'static [&'static str] {
+        &["graph"]
+    }
+
+    fn cost_hint() -> Cost {
+        Cost::Slow
+    }
+
+    type Input<'a> = UnGraph<String, ()>;
 
     /// Get a graph of the connected components
-    fn parse(input: &str) -> IResult<&str, Self::Input> {
-        let (rest, components) = separated_list0(
-            line_ending,
-            separated_pair(alpha1, tag(": "), separated_list1(space1, alpha1)),
-        )(input)?;
-        let mut graph = UnGraph::<(), ()>::new_undirected();
-        let mut node_indices = HashMap::<String, NodeIndex>::new();
-        for (name, _) in &components {
-            let idx = graph.add_node(());
-            node_indices.insert((*name).to_string(), idx);
-        }
-        for (name, conn) in &components {
-            let this = *(node_indices.get(*name).unwrap());
+    fn parse(input: &str) -> IResult<&str, Self::Input<'_>> {
+        let (rest, components) = lines_of(separated_pair(
+            alpha1,
+            tag(": "),
+            separated_list1(space1, alpha1),
+        ))(input)?;
+        let mut graph = KeyedGraph::<&str, String, (), Undirected>::new();
+        for (name, conn) in components {
+            let this = graph.get_or_insert_node(name, || name.to_string());
             for c in conn {
-                let other = if let Some(other) = node_indices.get(*c) {
-                    *other
-                } else {
-                    graph.add_node(())
-                };
-                node_indices.insert((*c).to_string(), other);
+                let other = graph.get_or_insert_node(c, || c.to_string());
                 graph.add_edge(this, other, ());
             }
         }
-        Ok((rest, graph))
+        Ok((rest, graph.into_graph()))
     }
 
     type Output1 = usize;
 
-    /// Part 1 took 234.191498ms
-    fn part_1(input: &Self::Input) -> Self::Output1 {
-        // Use a minimum cut algorithm to partition the graph into two
-        let (min_cut, partition): (usize, Vec<_>) =
-            stoer_wagner_min_cut::<_, _, _, anyhow::Error>(input, |_| Ok(1))
-                .unwrap()
-                .unwrap();
-        // double-check that the number of cut edges is 3
-        assert_eq!(min_cut, 3);
-        partition.len() * (input.node_count() - partition.len())
+    fn part_1(input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output1, SolveError> {
+        let (_, finder) = ALGORITHMS[0];
+        let (a, b) = group_sizes(input, finder)?;
+        Ok(a * b)
     }
 
-    type Output2 = usize;
+    type Output2 = String;
 
-    fn part_2(_input: &Self::Input) -> Self::Output2 {
-        0
+    /// The last star is awarded once all the other ones were collected, so we check that the answers of all the other
+    /// days and of part 1 were verified during this run (i.e. when using `--all` with `--check` or a filled answer
+    /// cache). The runner fills it in again once part 1 is checked, see `stars::refresh_completion`.
+    fn part_2(_input: &Self::Input<'_>, _params: &Params) -> Result<Self::Output2, SolveError> {
+        Ok(stars::completion(&stars::verified(), 25))
     }
 }
 
@@ -69,23 +303,40 @@ impl Day for Day25 {
 mod tests {
     use super::*;
 
-    const INPUT: &str = "jqt: rhn xhk nvd
-rsh: frs pzl lsr
-xhk: hfx
-cmg: qnr nvd lhk bvb
-rhn: xhk bvb hfx
-bvb: xhk hfx
-pzl: lsr hfx nvd
-qnr: nvd
-ntq: jqt hfx bvb xhk
-nvd: lhk
-lsr: lhk
-rzs: qnr cmg lsr rsh
-frs: qnr lhk lsr";
-
     #[test]
     fn test_part1() {
-        let parsed = Day25::parse(INPUT).unwrap().1;
-        assert_eq!(Day25::part_1(&parsed), 54);
+        let parsed = Day25::parse(EXAMPLE).unwrap().1;
+        assert_eq!(Day25::part_1(&parsed, &Day25::EXAMPLE_PARAMS), Ok(54));
+    }
+
+    #[test]
+    fn test_algorithms() {
+        let parsed = Day25::parse(EXAMPLE).unwrap().1;
+        for (name, finder) in ALGORITHMS {
+            let (a, b) = group_sizes(&parsed, finder).unwrap();
+            assert_eq!(a * b, 54, "algorithm {name}");
+        }
+        assert!(algorithm("stoer-wagner").is_some());
+        assert!(algorithm("karger").is_some());
+        assert!(algorithm("kruskal").is_none());
+    }
+
+    #[test]
+    fn test_no_wires() {
+        let mut graph = UnGraph::new_undirected();
+        graph.add_node("jqt".to_string());
+        graph.add_node("rhn".to_string());
+        for (name, finder) in ALGORITHMS {
+            assert!(group_sizes(&graph, finder).is_err(), "algorithm {name}");
+        }
+        assert!(partition_dot(&graph, betweenness_cut).is_err());
+    }
+
+    #[test]
+    fn test_partition_dot() {
+        let parsed = Day25::parse(EXAMPLE).unwrap().1;
+        let dot = partition_dot(&parsed, betweenness_cut).unwrap();
+        assert_eq!(dot.matches("color=red").count(), 3);
+        assert!(dot.contains("label=\"jqt\""));
     }
 }
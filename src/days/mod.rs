@@ -1,78 +1,570 @@
-use crate::parser::MyErr;
-use crate::Instant;
-use nom::IResult;
+// the helpers shared by some days are unused when these days are compiled out
+#![cfg_attr(not(feature = "all-days"), allow(dead_code, unused_macros))]
+
+use crate::answer::Answer;
+use crate::parser::{self, MyErr, ParseError, SolveError};
+use crate::stats::Stats;
+use crate::timing::{Phase, PhaseTimer, SubPhase};
+use crate::{config, inputs, parse_cache};
+use nom::{error::ErrorKind, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
-use std::fs::read_to_string;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Duration;
+use tracing::{debug, debug_span, Span};
+
+/// Implement [`Day`] for a new unit struct from the free functions of a day's module, and add a test which checks the
+/// answers to the examples like `self-test` does:
+///
+/// ```ignore
+/// impl_day!(
+///     Day06,
+///     6,
+///     parse -> Vec<Race>,
+///     part_1 -> u64,
+///     part_2 -> u64,
+///     answers = [Some("288"), Some("71503")],
+///     title = "Wait For It",
+///     tags = ["math"],
+/// );
+/// ```
+///
+/// The module must define `EXAMPLE`, the parser as `fn(&str) -> IResult<&str, Input>` and the parts as
+/// `fn(&Input, &Params) -> Output`, or `-> Result<Output, SolveError>` for the parts which can fail (see
+/// [`PartResult`]). A day can add `cost = Slow` after the tags to implement [`Day::cost_hint`], `parse_version = 2` to
+/// set [`Day::PARSE_VERSION`], and `explain = explain` to implement [`Day::explain`] with a
+/// `fn(&Input, &Params) -> Result<String, SolveError>`. Days which override other items of the trait implement it by
+/// hand.
+macro_rules! impl_day {
+    (
+        $day:ident,
+        $number:literal,
+        $parse:ident -> $input:ty,
+        $part1:ident -> $output1:ty,
+        $part2:ident -> $output2:ty,
+        answers = $answers:expr,
+        title = $title:literal,
+        tags = [$($tag:literal),* $(,)?]
+        $(, cost = $cost:ident)?
+        $(, parse_version = $parse_version:literal)?
+        $(, explain = $explain:ident)? $(,)?
+    ) => {
+        pub struct $day;
+
+        impl $crate::days::Day for $day {
+            const DAY: u8 = $number;
+            const EXAMPLE: &'static str = EXAMPLE;
+            const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = $answers;
+
+            fn title() -> &'static str {
+                $title
+            }
+
+            fn tags() -> &'static [&'static str] {
+                &[$($tag),*]
+            }
+
+            $(
+                fn cost_hint() -> $crate::days::Cost {
+                    $crate::days::Cost::$cost
+                }
+            )?
+
+            type Input<'a> = $input;
+
+            $(const PARSE_VERSION: u32 = $parse_version;)?
+
+            fn parse(input_string: &str) -> nom::IResult<&str, Self::Input<'_>> {
+                $parse(input_string)
+            }
+
+            type Output1 = $output1;
+
+            fn part_1(
+                input: &Self::Input<'_>,
+                params: &$crate::days::Params,
+            ) -> Result<Self::Output1, $crate::parser::SolveError> {
+                $crate::days::PartResult::into_result($part1(input, params))
+            }
 
+            type Output2 = $output2;
+
+            fn part_2(
+                input: &Self::Input<'_>,
+                params: &$crate::days::Params,
+            ) -> Result<Self::Output2, $crate::parser::SolveError> {
+                $crate::days::PartResult::into_result($part2(input, params))
+            }
+
+            $(
+                fn explain(
+                    input: &Self::Input<'_>,
+                    params: &$crate::days::Params,
+                ) -> Result<Option<String>, $crate::parser::SolveError> {
+                    $explain(input, params).map(Some)
+                }
+            )?
+        }
+
+        #[cfg(test)]
+        mod examples {
+            use $crate::selftest::test_day;
+
+            #[test]
+            fn test_examples() {
+                for outcome in test_day::<super::$day>() {
+                    assert!(!outcome.is_failure(), "{outcome:?}");
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "day01")]
 pub mod day01;
+#[cfg(feature = "day02")]
 pub mod day02;
+#[cfg(feature = "day03")]
 pub mod day03;
+#[cfg(feature = "day04")]
 pub mod day04;
+#[cfg(feature = "day05")]
 pub mod day05;
+#[cfg(feature = "day06")]
 pub mod day06;
+#[cfg(feature = "day07")]
 pub mod day07;
+#[cfg(feature = "day08")]
 pub mod day08;
+#[cfg(feature = "day09")]
 pub mod day09;
+#[cfg(feature = "day10")]
 pub mod day10;
+#[cfg(feature = "day11")]
 pub mod day11;
+#[cfg(feature = "day12")]
 pub mod day12;
+#[cfg(feature = "day13")]
 pub mod day13;
+#[cfg(feature = "day14")]
 pub mod day14;
+#[cfg(feature = "day15")]
 pub mod day15;
+#[cfg(feature = "day16")]
 pub mod day16;
+#[cfg(feature = "day17")]
 pub mod day17;
+#[cfg(feature = "day18")]
 pub mod day18;
+#[cfg(feature = "day19")]
 pub mod day19;
+#[cfg(feature = "day20")]
 pub mod day20;
+#[cfg(feature = "day21")]
 pub mod day21;
+#[cfg(feature = "day22")]
 pub mod day22;
+#[cfg(feature = "day23")]
 pub mod day23;
+#[cfg(feature = "day24")]
 pub mod day24;
+#[cfg(feature = "day25")]
 pub mod day25;
 
-pub trait Day {
-    type Input;
-
-    fn parse(input_string: &str) -> IResult<&str, Self::Input>;
-
-    type Output1: Display;
-
-    fn part_1(input: &Self::Input) -> Self::Output1;
-
-    type Output2: Display;
-
-    fn part_2(input: &Self::Input) -> Self::Output2;
-
-    fn parse_file(fp: &str) -> Result<(Self::Input, Duration), MyErr> {
-        let input_string = read_to_string(fp)?;
-        let before_parse = Instant::now();
-        let (_, input) = Self::parse(&input_string)?;
-        #[allow(clippy::cast_precision_loss)]
-        let parsing_elapsed = before_parse.elapsed();
-        Ok((input, parsing_elapsed))
-    }
-
-    #[allow(clippy::cast_precision_loss)]
-    fn run_day(fp: &str) {
-        match Self::parse_file(fp) {
-            Err(e) => println!("{e:?}"),
-            Ok((input, parsing_elapsed)) => {
-                let before1 = Instant::now();
-                println!("Part 1: {}", Self::part_1(&input));
-                let part1_elapsed = before1.elapsed();
-                println!(
-                    "Part 1 took {part1_elapsed:?} ({:?} with parsing)",
-                    part1_elapsed + parsing_elapsed
-                );
-                let before2 = Instant::now();
-                println!("Part 2: {}", Self::part_2(&input));
-                let part2_elapsed = before2.elapsed();
-                println!(
-                    "Part 2 took {part2_elapsed:?} ({:?} with parsing)",
-                    part2_elapsed + parsing_elapsed
-                );
+/// What the parts given to [`impl_day!`] return: the answer itself, or a `Result` for the parts which can fail
+pub trait PartResult<O> {
+    fn into_result(self) -> Result<O, SolveError>;
+}
+
+impl<O> PartResult<O> for Result<O, SolveError> {
+    fn into_result(self) -> Result<O, SolveError> {
+        self
+    }
+}
+
+macro_rules! impl_part_result {
+    ($($t:ty),*) => {
+        $(
+            impl PartResult<$t> for $t {
+                fn into_result(self) -> Result<$t, SolveError> {
+                    Ok(self)
+                }
             }
+        )*
+    };
+}
+
+impl_part_result!(i32, i64, i128, isize, u32, u64, u128, usize, String);
+
+/// Number of unmeasured runs before the measured ones
+static WARMUP: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the number of unmeasured runs before the measured ones, for the rest of the run
+pub fn set_warmup(iterations: usize) {
+    WARMUP.store(iterations, Ordering::Relaxed);
+}
+
+/// Whether both parts are solved at the same time on two threads
+static CONCURRENT_PARTS: AtomicBool = AtomicBool::new(false);
+
+/// Solve part 1 and part 2 concurrently for the rest of the run, which only measures the time of the slower one
+pub fn set_concurrent_parts(concurrent: bool) {
+    CONCURRENT_PARTS.store(concurrent, Ordering::Relaxed);
+}
+
+/// Solve both parts of a day on two threads. Part 2 can't reuse the output of part 1, so it's solved by
+/// [`Day::part_2`].
+fn solve_concurrently<D: Day + ?Sized>(
+    input: &D::Input<'_>,
+    params: &Params,
+) -> Result<(D::Output1, D::Output2), SolveError> {
+    let parent = Span::current();
+    let (output1, output2) = rayon::join(
+        || debug_span!(parent: &parent, "part_1").in_scope(|| D::part_1(input, params)),
+        || debug_span!(parent: &parent, "part_2").in_scope(|| D::part_2(input, params)),
+    );
+    Ok((output1?, output2?))
+}
+
+/// Parameters of the puzzles which differ between the example and the real input, passed to the parts
+///
+/// Each field is only used by one day, and can be set in the table of that day in the configuration file (e.g.
+/// `expansion` in `[day11]`) or with `--set day11.expansion=10`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    /// Day 11: how many times larger the empty rows and columns get in part 2
+    pub expansion: isize,
+    /// Day 21: number of steps taken by the elf in part 1
+    pub steps_part1: usize,
+    /// Day 21: number of steps taken by the elf in part 2
+    pub steps_part2: usize,
+    /// Day 24: lower bound of the test area, on both axes
+    pub area_min: f64,
+    /// Day 24: upper bound of the test area, on both axes
+    pub area_max: f64,
+}
+
+impl Params {
+    /// The values the puzzle descriptions give for the real input
+    pub const PUZZLE: Params = Params {
+        expansion: 1_000_000,
+        steps_part1: 64,
+        steps_part2: 26_501_365,
+        area_min: 200_000_000_000_000.,
+        area_max: 400_000_000_000_000.,
+    };
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self::PUZZLE
+    }
+}
+
+/// Rough expected runtime of a day on a real input, which `--all --parallel` uses to start the slowest days first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cost {
+    /// A few milliseconds at most
+    Fast,
+    /// Tens of milliseconds
+    Medium,
+    /// The days which dominate the time of a full run
+    Slow,
+}
+
+/// Time spent in each phase of a day
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+    /// Both parts were solved together by [`Day::solve_both`] or concurrently, so `part1` is zero and `part2` is their
+    /// combined time
+    pub shared: bool,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part1 + self.part2
+    }
+}
+
+/// Timing statistics for each phase of a day, over several iterations
+#[derive(Debug, Clone, Copy)]
+pub struct BenchStats {
+    pub iterations: usize,
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+    pub shared: bool,
+}
+
+impl BenchStats {
+    fn new(samples: &[Timings]) -> Self {
+        let phase =
+            |f: fn(&Timings) -> Duration| Stats::new(&samples.iter().map(f).collect::<Vec<_>>());
+        Self {
+            iterations: samples.len(),
+            parse: phase(|t| t.parse),
+            part1: phase(|t| t.part1),
+            part2: phase(|t| t.part2),
+            shared: samples.iter().any(|t| t.shared),
         }
     }
+
+    pub fn medians(&self) -> Timings {
+        Timings {
+            parse: self.parse.median,
+            part1: self.part1.median,
+            part2: self.part2.median,
+            shared: self.shared,
+        }
+    }
+}
+
+/// Answers to both parts of a day and the time taken to get them, which the caller formats as it sees fit
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    /// The answers to part 1 and part 2
+    pub answers: [Answer; 2],
+    pub timings: Timings,
+    /// Steps of the phases reported by the solution
+    pub sub_phases: Vec<SubPhase>,
+}
+
+pub trait Day {
+    /// Number of the day in the calendar
+    const DAY: u8;
+
+    /// Year of the event the puzzle belongs to
+    const YEAR: u16 = crate::YEAR;
+
+    /// Example input from the puzzle description, used instead of the input file with `--example`
+    const EXAMPLE: &'static str;
+
+    /// Example input for part 2, when the puzzle description gives a different one than for part 1
+    const EXAMPLE_PART2: &'static str = Self::EXAMPLE;
+
+    /// Answers to the examples given in the puzzle description, `None` when a part can't be checked on its example
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2];
+
+    /// Parameters to solve the examples with, when the puzzle description gives different ones for the example
+    const EXAMPLE_PARAMS: Params = Params::PUZZLE;
+
+    /// The parsed input, which can borrow from the input string to avoid allocations
+    ///
+    /// It is deserialized from the parse cache with `--parse-cache`, in which case it borrows from the cached bytes.
+    ///
+    /// It is shared between the threads solving the two parts with `--concurrent-parts`.
+    type Input<'a>: Serialize + Deserialize<'a> + Sync;
+
+    fn parse(input_string: &str) -> IResult<&str, Self::Input<'_>>;
+
+    /// Version of the parser, to bump when a change to the parser gives a different input of the same type, so that
+    /// the inputs cached with `--parse-cache` are parsed again
+    const PARSE_VERSION: u32 = 1;
+
+    /// Parse the raw bytes of the input, which is what the runner calls
+    ///
+    /// The default validates the bytes as UTF-8 and goes through [`Day::parse`]. Days with a hot parser override it
+    /// with byte-level combinators and implement [`Day::parse`] with [`crate::parser::from_bytes`] instead.
+    fn parse_bytes(input: &[u8]) -> IResult<&[u8], Self::Input<'_>> {
+        let input_string = std::str::from_utf8(input)
+            .map_err(|_| nom::Err::Error(nom::error::Error::new(input, ErrorKind::Char)))?;
+        let (rest, parsed) = Self::parse(input_string)
+            .map_err(|e| e.map(|e| nom::error::Error::new(e.input.as_bytes(), e.code)))?;
+        Ok((rest.as_bytes(), parsed))
+    }
+
+    type Output1: Display + Into<Answer> + Send;
+
+    /// Solve part 1, failing if the input doesn't have the shape the solution relies on
+    fn part_1(input: &Self::Input<'_>, params: &Params) -> Result<Self::Output1, SolveError>;
+
+    type Output2: Display + Into<Answer> + Send;
+
+    /// Solve part 2, failing if the input doesn't have the shape the solution relies on
+    fn part_2(input: &Self::Input<'_>, params: &Params) -> Result<Self::Output2, SolveError>;
+
+    /// Solve part 2 with the output of part 1 at hand, so that a day whose `Output1` carries some intermediate state
+    /// along with the answer can reuse it
+    fn part_2_with(
+        input: &Self::Input<'_>,
+        params: &Params,
+        _part1: &Self::Output1,
+    ) -> Result<Self::Output2, SolveError> {
+        Self::part_2(input, params)
+    }
+
+    /// Whether the day overrides [`Day::solve_both`], in which case the runner solves both parts in one go and only
+    /// measures their combined time
+    const SHARES_WORK: bool = false;
+
+    /// Solve both parts at once, so that part 2 can reuse the expensive work of part 1
+    fn solve_both(
+        input: &Self::Input<'_>,
+        params: &Params,
+    ) -> Result<(Self::Output1, Self::Output2), SolveError> {
+        let output1 = Self::part_1(input, params)?;
+        let output2 = Self::part_2_with(input, params, &output1)?;
+        Ok((output1, output2))
+    }
+
+    /// Parameters for this run: the ones of the example with `--example`, overridden by the configuration
+    fn params() -> Params {
+        let defaults = if inputs::use_example() {
+            Self::EXAMPLE_PARAMS
+        } else {
+            Params::PUZZLE
+        };
+        config::get().params(usize::from(Self::DAY), defaults)
+    }
+
+    /// Explanation of the approach of the solution, with the key intermediate values it finds for this input, which
+    /// `--explain` prints after the answers. `None` for the days which don't explain themselves.
+    fn explain(_input: &Self::Input<'_>, _params: &Params) -> Result<Option<String>, SolveError> {
+        Ok(None)
+    }
+
+    /// Title of the puzzle, shown next to the day number
+    fn title() -> &'static str {
+        ""
+    }
+
+    /// Techniques used by the solution, like `graph` or `grid`, which `--tag` filters the days on
+    fn tags() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// How long the day is expected to take, so that the parallel runner can start the slowest days first
+    fn cost_hint() -> Cost {
+        Cost::Fast
+    }
+
+    /// Address of the puzzle description on adventofcode.com
+    fn url() -> String {
+        format!("https://adventofcode.com/{}/day/{}", Self::YEAR, Self::DAY)
+    }
+
+    /// Read the input file, decompressing it if it ends with `.gz` or `.zst`, or get the example input when running
+    /// with `--example`
+    fn read_input(fp: impl AsRef<Path>) -> Result<String, MyErr> {
+        if inputs::use_example() {
+            return Ok(Self::EXAMPLE.to_string());
+        }
+        Ok(inputs::read(fp)?)
+    }
+
+    /// Parse the input string, which must outlive the parsed input since it can borrow from it
+    ///
+    /// Unless `--lenient` is given, the parser must consume the whole input besides trailing whitespace.
+    fn parse_input(input_string: &str) -> Result<Self::Input<'_>, MyErr> {
+        let (rest, input) = Self::parse_bytes(input_string.as_bytes())
+            .map_err(|e| ParseError::new(Self::DAY, input_string, &e))?;
+        parser::check_rest(rest).map_err(|e| ParseError::new(Self::DAY, input_string, &e))?;
+        Ok(input)
+    }
+
+    /// Decode the input from the bytes of the parse cache, or parse the input string if there are none or they are
+    /// stale. Returns whether the input came from the cache.
+    fn load_input<'a>(
+        input_string: &'a str,
+        cached: Option<&'a [u8]>,
+    ) -> Result<(Self::Input<'a>, bool), MyErr> {
+        if let Some(input) = cached.and_then(parse_cache::decode) {
+            return Ok((input, true));
+        }
+        Ok((Self::parse_input(input_string)?, false))
+    }
+
+    /// Parse the input and solve both parts, measuring the time taken by each phase
+    ///
+    /// With `--parse-cache`, the parse phase decodes the cached input instead when there is one. Reading the cache file
+    /// isn't measured, like reading the input file.
+    fn measure(input_string: &str) -> Result<(Self::Output1, Self::Output2, PhaseTimer), MyErr> {
+        let params = Self::params();
+        let cached =
+            parse_cache::read::<Self::Input<'_>>(Self::DAY, Self::PARSE_VERSION, input_string);
+        let mut timer = PhaseTimer::new();
+        let (input, from_cache) = timer.time(Phase::Parse, || {
+            debug_span!("parse").in_scope(|| Self::load_input(input_string, cached.as_deref()))
+        })?;
+        debug!(elapsed = ?timer.timings().parse, from_cache, "parsed the input");
+        if parse_cache::enabled() && !from_cache {
+            parse_cache::write(Self::DAY, Self::PARSE_VERSION, input_string, &input);
+        }
+        if Self::SHARES_WORK || CONCURRENT_PARTS.load(Ordering::Relaxed) {
+            let (output1, output2) = timer.time(Phase::Both, || {
+                if Self::SHARES_WORK {
+                    debug_span!("both_parts").in_scope(|| Self::solve_both(&input, &params))
+                } else {
+                    solve_concurrently::<Self>(&input, &params)
+                }
+            })?;
+            debug!(elapsed = ?timer.timings().part2, answer1 = %output1, answer2 = %output2, "solved both parts");
+            return Ok((output1, output2, timer));
+        }
+        let output1 = timer.time(Phase::Part1, || {
+            debug_span!("part_1").in_scope(|| Self::part_1(&input, &params))
+        })?;
+        debug!(elapsed = ?timer.timings().part1, answer = %output1, "solved part 1");
+        let output2 = timer.time(Phase::Part2, || {
+            debug_span!("part_2").in_scope(|| Self::part_2_with(&input, &params, &output1))
+        })?;
+        debug!(elapsed = ?timer.timings().part2, answer = %output2, "solved part 2");
+        Ok((output1, output2, timer))
+    }
+
+    /// Parse the input and solve both parts a few times without measuring anything, so that the measured runs don't
+    /// pay for cold caches and page faults
+    fn warm_up(input_string: &str) -> Result<(), MyErr> {
+        for _ in 0..WARMUP.load(Ordering::Relaxed) {
+            Self::measure(input_string)?;
+        }
+        Ok(())
+    }
+
+    /// Read the input file and solve both parts, without printing anything
+    fn run_day(fp: &str) -> Result<RunResult, MyErr> {
+        let input_string = Self::read_input(fp)?;
+        Self::warm_up(&input_string)?;
+        let (output1, output2, timer) = Self::measure(&input_string)?;
+        let (timings, sub_phases) = timer.finish();
+        Ok(RunResult {
+            answers: [output1.into(), output2.into()],
+            timings,
+            sub_phases,
+        })
+    }
+
+    /// Parse the input and solve both parts `iterations` times, collecting statistics about each phase
+    fn bench(
+        input_string: &str,
+        iterations: usize,
+    ) -> Result<(Self::Output1, Self::Output2, BenchStats), MyErr> {
+        Self::warm_up(input_string)?;
+        let mut samples = Vec::with_capacity(iterations);
+        let (mut output1, mut output2, timer) = Self::measure(input_string)?;
+        samples.push(timer.timings());
+        for _ in 1..iterations {
+            let timer;
+            (output1, output2, timer) = Self::measure(input_string)?;
+            samples.push(timer.timings());
+        }
+        Ok((output1, output2, BenchStats::new(&samples)))
+    }
+
+    /// Read the input file and solve both parts `iterations` times, returning the answers with the median timings
+    /// along with the statistics about each phase
+    fn bench_day(fp: &str, iterations: usize) -> Result<(RunResult, BenchStats), MyErr> {
+        let input_string = Self::read_input(fp)?;
+        let (output1, output2, stats) = Self::bench(&input_string, iterations)?;
+        let result = RunResult {
+            answers: [output1.into(), output2.into()],
+            timings: stats.medians(),
+            sub_phases: Vec::new(),
+        };
+        Ok((result, stats))
+    }
 }
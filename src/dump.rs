@@ -0,0 +1,37 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+
+use crate::{days::Day, inputs};
+
+/// Where the parsed input is written: next to the input file, with a `.parsed.json` extension
+fn dump_path(fp: &str) -> PathBuf {
+    let path = PathBuf::from(fp);
+    if inputs::use_example() {
+        path.with_extension("example.parsed.json")
+    } else {
+        path.with_extension("parsed.json")
+    }
+}
+
+/// Parse the input of a day and write the result as pretty-printed JSON, returning the path of the file
+pub fn dump_parsed<D: Day>(fp: &str) -> Result<PathBuf> {
+    let input_string = D::read_input(fp).map_err(|e| anyhow!("{e}"))?;
+    let input = D::parse_input(&input_string).map_err(|e| anyhow!("{e}"))?;
+    let path = dump_path(fp);
+    fs::write(&path, serde_json::to_string_pretty(&input)?)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_path() {
+        assert_eq!(
+            dump_path("inputs/day12.txt"),
+            PathBuf::from("inputs/day12.parsed.json")
+        );
+    }
+}
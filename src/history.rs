@@ -0,0 +1,113 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+    process::Command,
+};
+
+use chrono::Local;
+
+use crate::days::Timings;
+
+/// File where the timings of each recorded run are appended
+const HISTORY_FILE: &str = "timings.csv";
+
+const HEADER: &str = "timestamp,git_rev,day,part,nanos";
+
+/// Short hash of the current commit, with a `-dirty` suffix if there are uncommitted changes
+fn git_rev() -> String {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let Some(rev) = git(&["rev-parse", "--short", "HEAD"]) else {
+        return "unknown".to_string();
+    };
+    match git(&["status", "--porcelain", "--untracked-files=no"]) {
+        Some(status) if !status.is_empty() => format!("{rev}-dirty"),
+        _ => rev,
+    }
+}
+
+/// CSV rows for the timings of a run, one per day and phase. Days that were not solved are skipped.
+///
+/// When both parts were solved together, their combined time is a single row whose part is `both`.
+fn rows(timestamp: &str, rev: &str, timings: &[(usize, Option<Timings>)]) -> Vec<String> {
+    timings
+        .iter()
+        .filter_map(|(day, t)| t.map(|t| (day, t)))
+        .flat_map(|(day, t)| {
+            let parts = if t.shared {
+                vec![("parse", t.parse), ("both", t.part2)]
+            } else {
+                vec![("parse", t.parse), ("1", t.part1), ("2", t.part2)]
+            };
+            parts.into_iter().map(move |(part, duration)| {
+                format!("{timestamp},{rev},{day},{part},{}", duration.as_nanos())
+            })
+        })
+        .collect()
+}
+
+/// Append the timings of this run to `timings.csv`, writing the header first if the file is new
+pub fn record(timings: &[(usize, Option<Timings>)]) -> io::Result<()> {
+    let is_new = !Path::new(HISTORY_FILE).exists();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY_FILE)?;
+    if is_new {
+        writeln!(file, "{HEADER}")?;
+    }
+    let timestamp = Local::now().to_rfc3339();
+    for row in rows(&timestamp, &git_rev(), timings) {
+        writeln!(file, "{row}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        let timings = [
+            (
+                1,
+                Some(Timings {
+                    parse: Duration::from_nanos(10),
+                    part1: Duration::from_nanos(20),
+                    part2: Duration::from_nanos(30),
+                    shared: false,
+                }),
+            ),
+            (2, None),
+            (
+                3,
+                Some(Timings {
+                    parse: Duration::from_nanos(10),
+                    part1: Duration::ZERO,
+                    part2: Duration::from_nanos(40),
+                    shared: true,
+                }),
+            ),
+        ];
+        assert_eq!(
+            rows("2023-12-25T06:00:00+01:00", "abc1234", &timings),
+            vec![
+                "2023-12-25T06:00:00+01:00,abc1234,1,parse,10",
+                "2023-12-25T06:00:00+01:00,abc1234,1,1,20",
+                "2023-12-25T06:00:00+01:00,abc1234,1,2,30",
+                "2023-12-25T06:00:00+01:00,abc1234,3,parse,10",
+                "2023-12-25T06:00:00+01:00,abc1234,3,both,40",
+            ]
+        );
+    }
+}
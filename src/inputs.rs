@@ -0,0 +1,239 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+
+use crate::registry;
+
+/// Directory where the puzzle inputs are cached by default
+pub const DEFAULT_DIR: &str = "inputs";
+
+/// Default template for the path of an input file
+///
+/// `{dir}` is replaced by the input directory, `{NN}` by the zero-padded day number and `{N}` by the day number.
+pub const DEFAULT_TEMPLATE: &str = "{dir}/day{NN}.txt";
+
+/// Where to find the input files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputLocation {
+    pub dir: String,
+    pub template: String,
+}
+
+impl Default for InputLocation {
+    fn default() -> Self {
+        Self {
+            dir: DEFAULT_DIR.to_string(),
+            template: DEFAULT_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl InputLocation {
+    /// Path of the input file for a day
+    pub fn path(&self, day: usize) -> String {
+        self.template
+            .replace("{dir}", &self.dir)
+            .replace("{NN}", &format!("{day:02}"))
+            .replace("{N}", &day.to_string())
+    }
+}
+
+/// The input location chosen on the command line, if any
+static LOCATION: OnceLock<InputLocation> = OnceLock::new();
+
+/// Set the location of the input files for the rest of the run
+///
+/// Can only be called once, subsequent calls are ignored.
+pub fn set_location(location: InputLocation) {
+    let _ = LOCATION.set(location);
+}
+
+/// Whether to use the example from the puzzle description instead of the input files
+static USE_EXAMPLE: AtomicBool = AtomicBool::new(false);
+
+/// Use the examples from the puzzle descriptions instead of the input files for the rest of the run
+pub fn set_use_example(use_example: bool) {
+    USE_EXAMPLE.store(use_example, Ordering::Relaxed);
+}
+
+pub fn use_example() -> bool {
+    USE_EXAMPLE.load(Ordering::Relaxed)
+}
+
+/// How the input at `path` is named in messages, which is the example instead when running with `--example`
+pub fn describe(path: &str) -> String {
+    if use_example() {
+        "the example".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Path of the input file for a day
+pub fn input_path(day: usize) -> String {
+    LOCATION.get_or_init(InputLocation::default).path(day)
+}
+
+/// Extensions of the compressed inputs which are decompressed transparently
+const COMPRESSED_EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+/// The input file at `path`, or its compressed version (`path.gz` or `path.zst`) if only that one exists
+pub fn find(path: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = path.as_ref();
+    if path.exists() {
+        return Some(path.to_path_buf());
+    }
+    COMPRESSED_EXTENSIONS.iter().find_map(|extension| {
+        let mut compressed = path.as_os_str().to_owned();
+        compressed.push(format!(".{extension}"));
+        let compressed = PathBuf::from(compressed);
+        compressed.exists().then_some(compressed)
+    })
+}
+
+/// Turn CRLF line endings into LF and remove the whitespace at the end of the lines and of the input, so that the
+/// parsers see the input like it was downloaded, even if it was saved on Windows or by an editor
+pub fn normalize(input: &str) -> String {
+    let mut normalized = String::with_capacity(input.len());
+    for line in input.trim_end().lines() {
+        normalized.push_str(line.trim_end());
+        normalized.push('\n');
+    }
+    normalized.pop();
+    normalized
+}
+
+/// Read an input file, decompressing it first if it ends with `.gz` or `.zst`, and [`normalize`] it
+pub fn read(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    let mut reader: Box<dyn Read> = match path.extension().and_then(|e| e.to_str()) {
+        Some("gz") => Box::new(GzDecoder::new(file)),
+        Some("zst") => Box::new(zstd::Decoder::new(file)?),
+        _ => Box::new(file),
+    };
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(normalize(&input))
+}
+
+/// Session cookie used to authenticate with adventofcode.com
+///
+/// Taken from the `AOC_SESSION` environment variable, or from the `.session` file at the root of the project.
+pub fn session() -> Result<String> {
+    if let Ok(session) = env::var("AOC_SESSION") {
+        return Ok(session.trim().to_string());
+    }
+    let session = fs::read_to_string(".session")
+        .context("Could not find a session cookie in AOC_SESSION or the .session file")?;
+    Ok(session.trim().to_string())
+}
+
+/// Download the input for a day and write it to the cache, returning its path
+pub fn download(day: usize) -> Result<String> {
+    let Some(entry) = registry::get(day) else {
+        bail!("Provided unsupported day {day}");
+    };
+    let session = session()?;
+    let url = format!("{}/input", entry.solver.url());
+    let client = reqwest::blocking::Client::new();
+    let response = client
+        .get(url)
+        .header("cookie", format!("session={session};"))
+        .send()?;
+
+    if !response.status().is_success() {
+        bail!(
+            "Could not get input for day {day} ({}). Is your session cookie correct?",
+            response.status()
+        );
+    }
+    let mut text = response.text()?;
+    // Remove trailing newline
+    text.pop();
+    let path = input_path(day);
+    if let Some(parent) = Path::new(&path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// Path of the input for a day, downloading it first if it's not in the cache yet
+///
+/// A compressed input (`day04.txt.gz` or `day04.txt.zst`) is used when the uncompressed one doesn't exist. If the
+/// download fails, the error is reported and the path is returned anyway so that the caller fails to read it.
+pub fn ensure(day: usize) -> String {
+    let path = input_path(day);
+    if use_example() {
+        return path;
+    }
+    if let Some(found) = find(&path) {
+        return found.to_string_lossy().into_owned();
+    }
+    eprintln!("Input for day {day} not found, downloading it");
+    match download(day) {
+        Ok(path) => eprintln!("Successfully downloaded input to {path}"),
+        Err(e) => eprintln!("{e:#}"),
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_path() {
+        let location = InputLocation::default();
+        assert_eq!(location.path(3), "inputs/day03.txt");
+        assert_eq!(location.path(25), "inputs/day25.txt");
+        let location = InputLocation {
+            dir: "samples/friend".to_string(),
+            template: "{dir}/{N}/input-{NN}".to_string(),
+        };
+        assert_eq!(location.path(7), "samples/friend/7/input-07");
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("#.#\r\n..# \r\n\r\n"), "#.#\n..#");
+        assert_eq!(normalize("a\n\nb\t\n"), "a\n\nb");
+        assert_eq!(normalize("already\nclean"), "already\nclean");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn test_read_compressed() {
+        use std::io::Write;
+
+        let dir = env::temp_dir().join(format!("aoc-inputs-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = "467..114..\n...*......\n";
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(input.as_bytes()).unwrap();
+        fs::write(dir.join("day03.txt.gz"), gz.finish().unwrap()).unwrap();
+        fs::write(
+            dir.join("day04.txt.zst"),
+            zstd::encode_all(input.as_bytes(), 0).unwrap(),
+        )
+        .unwrap();
+        fs::write(dir.join("day05.txt"), input).unwrap();
+        for day in 3..=5 {
+            let found = find(dir.join(format!("day{day:02}.txt"))).unwrap();
+            assert_eq!(read(found).unwrap(), input.trim_end());
+        }
+        assert_eq!(find(dir.join("day06.txt")), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
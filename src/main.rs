@@ -1,33 +1,255 @@
 #![warn(clippy::pedantic)]
-use std::{fs, time::Instant};
+use std::{
+    panic::{self, UnwindSafe},
+    sync::Mutex,
+    time::Duration,
+};
 
-use chrono::{Datelike, Local};
-use clap::{Parser, Subcommand};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 
+use check::Answers;
+use color::ColorChoice;
 #[allow(clippy::wildcard_imports)]
 use days::*;
+use inputs::InputLocation;
+use output::OutputFormat;
+use registry::Entry;
+use selftest::Outcome;
+use serde_json::Value;
+use tracing::{debug_span, Level};
 
+mod answer;
+mod bench_diff;
+mod cache;
+mod check;
+mod color;
+mod config;
 mod days;
+mod dump;
+mod history;
+mod inputs;
+mod output;
+mod parse_cache;
 mod parser;
+mod profile;
+mod registry;
+mod report;
+mod selftest;
+mod stars;
+mod stats;
+mod submit;
+mod timing;
+mod tui;
+mod util;
+mod viz;
+mod watch;
 
-const YEAR: usize = 2023;
+const YEAR: u16 = 2023;
 
 #[derive(Parser)]
 #[command(author, version)]
 #[command(propagate_version = true)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
+    /// Arguments for the `run` subcommand, which is the default when no subcommand is given
+    #[command(flatten)]
+    run: RunArgs,
+    #[arg(
+        long,
+        global = true,
+        env = "AOC_INPUT_DIR",
+        default_value = inputs::DEFAULT_DIR,
+        help = "Directory containing the input files"
+    )]
+    input_dir: String,
+    #[arg(
+        long,
+        global = true,
+        default_value = inputs::DEFAULT_TEMPLATE,
+        help = "Path of the input files, where {dir} is the input directory, {NN} the zero-padded day and {N} the day"
+    )]
+    input_template: String,
+    #[arg(
+        long,
+        global = true,
+        help = "Uses the example from the puzzle description instead of the input file"
+    )]
+    example: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Ignores the input left over by the parser of a day instead of failing"
+    )]
+    lenient: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t,
+        help = "When to use colors in the drawings (auto respects NO_COLOR)"
+    )]
+    color: ColorChoice,
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        help = "Number of threads used by the parallel code (one per CPU by default)"
+    )]
+    threads: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Configuration file with the runtime parameters of the days (aoc.toml by default, if it exists)"
+    )]
+    config: Option<String>,
+    #[arg(
+        long = "set",
+        global = true,
+        value_name = "DAYNN.NAME=VALUE",
+        help = "Sets a runtime parameter of a day, overriding the configuration file, e.g. --set day11.expansion=10"
+    )]
+    set: Vec<String>,
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Prints diagnostics to stderr: the timings of each phase with -v, and the drawings of some days with -vv"
+    )]
+    verbose: u8,
+}
+
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)] // each flag is a command-line switch
+struct RunArgs {
+    #[arg(
+        value_name = "DAY",
+        help = "The number of the day you want to run, or a list of days and ranges like 1-10,15,20-25"
+    )]
+    day: Option<String>,
+    #[arg(
+        value_name = "FILE",
+        requires = "day",
+        conflicts_with_all = ["all", "bench", "output", "check", "watch", "profile", "record"],
+        help = "Solves the day for each of these input files instead of yours and prints a table of the results"
+    )]
+    files: Vec<String>,
+    #[arg(
+        short,
+        long,
+        help = "Runs all days sequentially and prints a summary of the timings"
+    )]
+    all: bool,
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Runs each day N times and prints statistics about the timings"
+    )]
+    bench: Option<usize>,
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        help = "Runs each day N times without measuring it before the measured runs"
+    )]
+    warmup: usize,
+    #[arg(
+        long,
+        help = "Caches the parsed inputs in outputs/parse-cache and reuses them in the next runs instead of parsing"
+    )]
+    parse_cache: bool,
+    #[arg(
+        long,
+        conflicts_with = "bench",
+        help = "Solves part 1 and part 2 at the same time on two threads and reports the time of the slower one"
+    )]
+    concurrent_parts: bool,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        requires = "all",
+        conflicts_with_all = ["bench", "parallel"],
+        help = "Stops running days once their total time exceeds DURATION (like 500ms, 5s or 1m) and lists the skipped days"
+    )]
+    budget: Option<Duration>,
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t,
+        help = "Format used to print the answers and timings"
+    )]
+    output: OutputFormat,
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "answers.toml",
+        conflicts_with_all = ["bench", "output"],
+        help = "Compares the answers with the expected ones in FILE (answers.toml by default) and exits with an error on mismatch"
+    )]
+    check: Option<String>,
+    #[arg(
+        long,
+        requires = "all",
+        conflicts_with_all = ["bench", "check"],
+        help = "Runs all days concurrently and prints the results in order"
+    )]
+    parallel: bool,
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["all", "check"],
+        help = "Runs the day again every time its input file changes"
+    )]
+    watch: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["all", "bench", "check", "watch"],
+        help = "Writes a flamegraph of each part to the outputs folder (requires the `profile` feature)"
+    )]
+    profile: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["all", "bench", "output", "check", "watch", "profile"],
+        help = "Writes the parsed input as JSON next to the input file instead of solving the day"
+    )]
+    dump_parsed: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["all", "output", "watch", "profile", "dump_parsed"],
+        help = "Explains how the solution solves the input after printing the answers, for the days which can"
+    )]
+    explain: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["profile", "dump_parsed"],
+        help = "Appends the timings of each day and part to timings.csv"
+    )]
+    record: bool,
+    #[arg(
+        long,
+        value_name = "TAG",
+        requires = "all",
+        conflicts_with = "parallel",
+        help = "Only runs the days whose solution is tagged with TAG, like graph or grid"
+    )]
+    tag: Option<String>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    Run {
-        #[arg(value_name = "DAY", help = "The number of the day you want to run")]
-        day: Option<String>,
-        #[arg(short, long, help = "Runs all days sequentially")]
-        all: bool,
-    },
+    /// Solve one day or all of them (the default when no subcommand is given)
+    Run(RunArgs),
+    /// Download the input of one day or all of them
     GetInput {
         #[arg(
             value_name = "DAY",
@@ -37,136 +259,791 @@ enum Commands {
         #[arg(short, long, help = "Downloads input for all days sequentially")]
         all: bool,
     },
+    /// Export a visualization of the input of a day to the outputs folder
+    Viz {
+        #[arg(
+            value_name = "DAY",
+            help = "The number of the day you want to export a visualization for"
+        )]
+        day: String,
+        #[arg(
+            long,
+            default_value = "betweenness",
+            help = "Algorithm used to find the cut for day 25 (betweenness, stoer-wagner, karger)"
+        )]
+        algorithm: String,
+    },
+    /// Solve all days and write the answers and timings to `outputs/report.md`
+    Report {
+        #[arg(long, help = "Also writes the report as an HTML page")]
+        html: bool,
+    },
+    /// Solve every day on the examples from the puzzle description and compare with the known answers
+    SelfTest,
+    /// Show a dashboard of all days which fills up as they are solved
+    Tui,
+    /// Solve a day and submit the answer to one of its parts
+    Submit {
+        #[arg(value_name = "DAY", help = "The number of the day you want to submit")]
+        day: String,
+        #[arg(
+            value_name = "PART",
+            value_parser = clap::value_parser!(u8).range(1..=2),
+            help = "The part whose answer is submitted"
+        )]
+        part: u8,
+    },
+    /// Print a random input for a day to stdout, to stress-test its solution on larger inputs than the real one
+    Gen {
+        #[arg(
+            value_name = "DAY",
+            help = "The number of the day to generate an input for (10, 19, 22 or 24)"
+        )]
+        day: String,
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "How many times as large as the real input the generated one is"
+        )]
+        scale: usize,
+        #[arg(long, default_value_t = 0, help = "Seed of the random generator")]
+        seed: u64,
+    },
+    /// Compare the timings of two runs saved with `--output json`, e.g. before and after an optimization
+    BenchDiff {
+        #[arg(value_name = "BEFORE", help = "Output of the first run")]
+        before: String,
+        #[arg(value_name = "AFTER", help = "Output of the second run")]
+        after: String,
+        #[arg(
+            long,
+            default_value_t = 5.0,
+            help = "Highlights the phases which got slower by more than this percentage"
+        )]
+        threshold: f64,
+    },
+    /// Print a completion script for a shell to stdout
+    Completions {
+        #[arg(value_enum, help = "The shell to generate the completions for")]
+        shell: Shell,
+    },
 }
 
-fn main() {
+fn main() -> Result<()> {
     let cli = Cli::parse();
+    inputs::set_location(InputLocation {
+        dir: cli.input_dir,
+        template: cli.input_template,
+    });
+    inputs::set_use_example(cli.example);
+    parser::set_strict(!cli.lenient);
+    color::init(cli.color);
+    init_tracing(cli.verbose);
+    config::init(cli.config.as_deref(), &cli.set)?;
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .context("could not configure the thread pool")?;
+    }
 
-    match &cli.command {
-        Commands::Run { day, all } => {
+    match &cli.command.unwrap_or(Commands::Run(cli.run)) {
+        Commands::Run(args) => run(args),
+        Commands::GetInput { day, all } => {
             if *all {
-                run_all_days();
+                download_all_input()
+            } else if let Some(day) = day {
+                download_input(parse_day(day)?)
             } else {
-                match day {
-                    Some(day) => run_day(parse_day(day)),
-                    None => {
-                        println!("No day parameter specified, attempting to run today");
-                        let now_day = get_today();
-                        println!("Running day {now_day}");
-                        run_day(now_day);
-                    }
-                }
+                let now_day = get_today()?;
+                println!(
+                    "No day specified, getting the input for day {now_day} (today's puzzle in America/New_York)"
+                );
+                download_input(now_day)
             }
         }
-        Commands::GetInput { day, all } => {
-            if *all {
-                download_all_input();
-            } else {
-                match day {
-                    Some(day) => download_input(parse_day(day)),
-                    None => {
-                        println!(
-                            "No day parameter specified, attempting to download today's input"
-                        );
-                        let now_day = get_today();
-                        println!("Getting input for day {now_day}");
-                        download_input(now_day);
+        Commands::Viz { day, algorithm } => visualize(parse_day(day)?, algorithm),
+        Commands::Report { html } => write_report(*html),
+        Commands::SelfTest => self_test(),
+        Commands::Tui => tui::run(),
+        Commands::Submit { day, part } => submit_answer(parse_day(day)?, *part),
+        Commands::Gen { day, scale, seed } => {
+            let day = parse_day(day)?;
+            let input = util::gen::generate(day, *scale, &mut util::gen::Rng::new(*seed))
+                .ok_or_else(|| anyhow!("There is no input generator for day {day}"))?;
+            print!("{input}");
+            Ok(())
+        }
+        Commands::BenchDiff {
+            before,
+            after,
+            threshold,
+        } => bench_diff::print(before, after, *threshold),
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                *shell,
+                &mut Cli::command(),
+                env!("CARGO_PKG_NAME"),
+                &mut std::io::stdout(),
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Solve the requested days according to the `run` arguments
+fn run(args: &RunArgs) -> Result<()> {
+    let RunArgs {
+        day,
+        files,
+        all,
+        bench,
+        warmup,
+        parse_cache,
+        concurrent_parts,
+        budget,
+        output,
+        check,
+        parallel,
+        watch,
+        profile,
+        dump_parsed,
+        explain,
+        record,
+        tag,
+    } = args;
+    days::set_warmup(*warmup);
+    parse_cache::set_enabled(*parse_cache);
+    days::set_concurrent_parts(*concurrent_parts);
+    let answers = check.as_deref().map(Answers::load).transpose()?;
+    let answers = answers.as_ref();
+    let timings = if *all && *parallel {
+        run_all_days_parallel(*output)
+    } else if *all {
+        let days: Vec<_> = registry::DAYS
+            .iter()
+            .filter(|entry| tag.as_ref().is_none_or(|tag| entry.has_tag(tag)))
+            .map(|entry| entry.day)
+            .collect();
+        if days.is_empty() {
+            bail!(
+                "No day is tagged with {}",
+                tag.as_deref().unwrap_or_default()
+            );
+        }
+        run_all_days(&days, *bench, *budget, *output, answers)
+    } else if let Some(days) = day.as_deref().filter(|day| day.contains([',', '-'])) {
+        if *watch || *profile || *dump_parsed || *explain || !files.is_empty() {
+            bail!("--watch, --profile, --dump-parsed, --explain and input files can only be used with a single day");
+        }
+        run_all_days(&parse_days(days)?, *bench, None, *output, answers)
+    } else {
+        let day = if let Some(day) = day {
+            parse_day(day)?
+        } else {
+            let now_day = get_today()?;
+            eprintln!(
+                "No day specified, running day {now_day} (today's puzzle in America/New_York)"
+            );
+            now_day
+        };
+        if !files.is_empty() {
+            return run_batch(day, files);
+        }
+        if *profile {
+            return run_profile(day);
+        }
+        if *dump_parsed {
+            return run_dump(day);
+        }
+        if *watch {
+            // make sure the input is downloaded before we start watching it
+            let input_fp = inputs::ensure(day);
+            watch::watch(&input_fp, || {
+                let timings = report_error(run_day(day, *bench, *output, answers));
+                if *record {
+                    if let Err(e) = history::record(&[(day, timings)]) {
+                        eprintln!("Error: could not record the timings: {e}");
                     }
                 }
-            }
+            });
+        }
+        let timings = report_error(run_day(day, *bench, *output, answers));
+        if *explain && timings.is_some() {
+            print_explanation(day)?;
         }
+        vec![(day, timings)]
+    };
+    if *record {
+        history::record(&timings).context("could not record the timings")?;
     }
+    let failed = timings.iter().filter(|(_, t)| t.is_none()).count();
+    if failed > 0 {
+        bail!("{failed} day(s) could not be solved");
+    }
+    if answers.is_some() {
+        let failures = check::failures();
+        if failures > 0 {
+            bail!("{failures} answer(s) did not match");
+        }
+        println!("All answers match");
+    }
+    Ok(())
+}
+
+/// Print the diagnostics up to the level selected by the number of `-v` flags to stderr
+fn init_tracing(verbosity: u8) {
+    let level = match verbosity {
+        0 => Level::WARN,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(color::enabled())
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+/// The latest puzzle released at `now`, if it's December. Days after the 25th give the last puzzle.
+fn aoc_day(now: DateTime<Utc>) -> Option<usize> {
+    // puzzles unlock at midnight in America/New_York, which is always on EST (UTC-5) in December
+    let est = FixedOffset::west_opt(5 * 3600).unwrap();
+    let now = now.with_timezone(&est);
+    (now.month() == 12).then(|| now.day().clamp(1, 25) as usize)
+}
+
+fn get_today() -> Result<usize> {
+    let Some(day) = aoc_day(Utc::now()) else {
+        bail!("Today is not during Advent of Code. Please specify a day");
+    };
+    Ok(day)
 }
 
-fn get_today() -> usize {
-    let now = Local::now();
-    let now_day = now.day();
-    if now.month() == 12 && (1..=25).contains(&now_day) {
-        now_day.try_into().unwrap()
+fn parse_day(day: &str) -> Result<usize> {
+    let i = day
+        .parse()
+        .with_context(|| format!("Please provide a number. {day} is not a valid day"))?;
+    if (1..=25).contains(&i) {
+        Ok(i)
     } else {
-        panic!("Today is not a valid Advent of Code day. Please specify a day");
+        bail!("{i} is not a valid day. Only days 1-25 are allowed.")
     }
 }
 
-fn parse_day(day: &str) -> usize {
-    match day.parse() {
-        Ok(i) => {
-            if (i..=25).contains(&i) {
-                i
-            } else {
-                panic!("{i} is not a valid day. Only days 1-25 are allowed.")
+/// Parse a list of days and ranges of days like `1-10,15,20-25`, returning the days in order without duplicates
+fn parse_days(days: &str) -> Result<Vec<usize>> {
+    let mut selected = Vec::new();
+    for item in days.split(',').map(str::trim) {
+        if let Some((first, last)) = item.split_once('-') {
+            let (first, last) = (parse_day(first.trim())?, parse_day(last.trim())?);
+            if first > last {
+                bail!("{item} is not a valid range of days");
             }
+            selected.extend(first..=last);
+        } else {
+            selected.push(parse_day(item)?);
         }
-        Err(e) => {
-            panic!("Please provide a number. {day} is not a valid day: {e:?}")
-        }
-    }
-}
-
-fn run_all_days() {
-    (1..=25).for_each(run_day);
-}
-// Panics if you provide a value outside the range of 1 to 25
-fn run_day(day: usize) {
-    println!("======== DAY {day} ========");
-    // I'd like to do this with a macro, but I'm not sure how to do it.
-    let input_fp = &format!("inputs/day{day:02}.txt");
-    match day {
-        1 => day01::Day01::run_day(input_fp),
-        2 => day02::Day02::run_day(input_fp),
-        3 => day03::Day03::run_day(input_fp),
-        4 => day04::Day04::run_day(input_fp),
-        5 => day05::Day05::run_day(input_fp),
-        6 => day06::Day06::run_day(input_fp),
-        7 => day07::Day07::run_day(input_fp),
-        8 => day08::Day08::run_day(input_fp),
-        9 => day09::Day09::run_day(input_fp),
-        10 => day10::Day10::run_day(input_fp),
-        11 => day11::Day11::run_day(input_fp),
-        12 => day12::Day12::run_day(input_fp),
-        13 => day13::Day13::run_day(input_fp),
-        14 => day14::Day14::run_day(input_fp),
-        15 => day15::Day15::run_day(input_fp),
-        16 => day16::Day16::run_day(input_fp),
-        17 => day17::Day17::run_day(input_fp),
-        18 => day18::Day18::run_day(input_fp),
-        19 => day19::Day19::run_day(input_fp),
-        20 => day20::Day20::run_day(input_fp),
-        21 => day21::Day21::run_day(input_fp),
-        22 => day22::Day22::run_day(input_fp),
-        23 => day23::Day23::run_day(input_fp),
-        24 => day24::Day24::run_day(input_fp),
-        25 => day25::Day25::run_day(input_fp),
-        d => panic!("Provided unsupported day {d}"),
-    }
-}
-
-fn download_all_input() {
-    (1..=25).for_each(download_input);
-}
-
-fn download_input(day: usize) {
-    // Read session cookie from .session file
-    let session = fs::read_to_string(".session").expect("Could not find .session file");
-    let session = session.trim();
-    let url = format!("https://adventofcode.com/{YEAR}/day/{day}/input");
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .get(url)
-        .header("cookie", format!("session={session};"))
-        .send()
-        .unwrap();
-
-    if response.status().is_success() {
-        let mut text = response.text().unwrap();
-        // Remove trailing newline
-        text.pop();
-        let path = format!("inputs/day{day:02}.txt");
-        fs::write(&path, text).unwrap();
-        println!("Successfully downloaded input to {}", &path);
+    }
+    selected.sort_unstable();
+    selected.dedup();
+    Ok(selected)
+}
+
+/// Parse a duration made of a number and a unit among `ms`, `s` and `m`, like `500ms` or `1.5s`
+fn parse_duration(duration: &str) -> Result<Duration> {
+    let duration = duration.trim();
+    let split = duration
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(|| anyhow!("{duration} has no unit, use ms, s or m"))?;
+    let (value, unit) = duration.split_at(split);
+    let value: f64 = value
+        .trim()
+        .parse()
+        .with_context(|| format!("{value} is not a valid number"))?;
+    let seconds = match unit {
+        "ms" => value / 1000.,
+        "s" => value,
+        "m" => value * 60.,
+        _ => bail!("{unit} is not a valid unit, use ms, s or m"),
+    };
+    Duration::try_from_secs_f64(seconds)
+        .with_context(|| format!("{duration} is not a valid duration"))
+}
+
+/// Look up a day in the registry
+fn entry(day: usize) -> Result<&'static Entry> {
+    registry::get(day).ok_or_else(|| anyhow!("Provided unsupported day {day}"))
+}
+
+/// Print the error that prevented solving a day, if any, and return the timings otherwise
+fn report_error(result: Result<Timings>) -> Option<Timings> {
+    result.map_err(|e| eprintln!("Error: {e:#}")).ok()
+}
+
+/// Call `f`, turning panics into errors when running on the examples
+///
+/// Some solutions rely on properties of the real input which the examples don't have, in which case we want to
+/// report the day as failed and carry on with the others.
+fn catch_example_panic<T>(day: usize, f: impl FnOnce() -> Result<T> + UnwindSafe) -> Result<T> {
+    if !inputs::use_example() {
+        return f();
+    }
+    panic::catch_unwind(f)
+        .unwrap_or_else(|_| Err(anyhow!("day {day} panicked on the example input")))
+}
+
+/// Run the given days one after the other, then print a summary of the timings
+///
+/// With a `budget`, the remaining days are skipped once the total time of the solved days exceeds it.
+fn run_all_days(
+    days: &[usize],
+    bench: Option<usize>,
+    budget: Option<Duration>,
+    output: OutputFormat,
+    answers: Option<&Answers>,
+) -> Vec<(usize, Option<Timings>)> {
+    let mut spent = Duration::ZERO;
+    let mut timings = Vec::new();
+    for &day in days {
+        if budget.is_some_and(|budget| spent > budget) {
+            break;
+        }
+        let timing = report_error(run_day(day, bench, output, answers));
+        spent += timing.as_ref().map_or(Duration::ZERO, Timings::total);
+        timings.push((day, timing));
+    }
+    if output == OutputFormat::Text {
+        print_summary(&timings);
+    }
+    if let Some(budget) = budget {
+        print_budget(budget, spent, &timings, &days[timings.len()..]);
+    }
+    timings
+}
+
+/// Print which days were solved within the time budget and which ones were skipped
+fn print_budget(
+    budget: Duration,
+    spent: Duration,
+    timings: &[(usize, Option<Timings>)],
+    skipped: &[usize],
+) {
+    let list = |days: &[usize]| {
+        days.iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let solved: Vec<_> = timings.iter().map(|(day, _)| *day).collect();
+    eprintln!(
+        "Ran {} day(s) in {spent:.3?} out of a budget of {budget:.3?}: {}",
+        solved.len(),
+        list(&solved)
+    );
+    if skipped.is_empty() {
+        eprintln!("No day was skipped");
     } else {
-        panic!(
-            "Could not get input for day {day}. Is your correct session cookie in your .session file?",
+        eprintln!(
+            "Skipped {} day(s) after the budget ran out: {}",
+            skipped.len(),
+            list(skipped)
+        );
+    }
+}
+
+/// Solve all days concurrently, then print the results in order
+fn run_all_days_parallel(output: OutputFormat) -> Vec<(usize, Option<Timings>)> {
+    // the slowest days are started first, in a FIFO scope so that the threads pick the days in that order
+    let solutions = Mutex::new(Vec::with_capacity(registry::DAYS.len()));
+    rayon::scope_fifo(|scope| {
+        for entry in registry::by_cost(registry::DAYS) {
+            let solutions = &solutions;
+            scope.spawn_fifo(move |_| {
+                let solution = solve_day(entry.day);
+                solutions.lock().unwrap().push((entry.day, solution));
+            });
+        }
+    });
+    let mut solutions = solutions.into_inner().unwrap();
+    solutions.sort_by_key(|(day, _)| *day);
+    // day 25 part 2 reports the answers verified on the other days, which are only all known once they are solved
+    if let Some((day, Ok(result))) = solutions.iter_mut().find(|(day, _)| *day == 25) {
+        stars::refresh_completion(*day, result);
+    }
+    let timings: Vec<_> = solutions
+        .into_iter()
+        .map(|(day, solution)| {
+            if output == OutputFormat::Text {
+                println!("======== {} ========", heading(day));
+            }
+            let timings = match (output, solution) {
+                (OutputFormat::Text, Ok(result)) => {
+                    output::print_result(&result);
+                    Ok(result.timings)
+                }
+                (OutputFormat::Json, Ok(result)) => {
+                    output::print_result_json(day, &result, Value::Null);
+                    Ok(result.timings)
+                }
+                (OutputFormat::Text, Err(e)) => Err(e),
+                (OutputFormat::Json, Err(e)) => {
+                    output::print_error_json(day, &e);
+                    Err(e)
+                }
+            };
+            (day, report_error(timings))
+        })
+        .collect();
+    if output == OutputFormat::Text {
+        print_summary(&timings);
+    }
+    timings
+}
+
+/// Solve a day without printing anything
+fn solve_day(day: usize) -> Result<RunResult> {
+    let _span = debug_span!("day", day).entered();
+    let entry = entry(day)?;
+    let input_fp = &inputs::ensure(day);
+    let mut solution = catch_example_panic(day, || {
+        entry.solver.solve(input_fp).with_context(|| {
+            format!(
+                "could not solve day {day} with {}",
+                inputs::describe(input_fp)
+            )
+        })
+    })?;
+    cache::check(day, input_fp, &solution);
+    stars::refresh_completion(day, &mut solution);
+    Ok(solution)
+}
+
+/// Write the parsed input of a day as JSON and report where it was written
+fn run_dump(day: usize) -> Result<()> {
+    let input_fp = &inputs::ensure(day);
+    let path = entry(day)?.solver.dump(input_fp).with_context(|| {
+        format!(
+            "could not parse day {day} with {}",
+            inputs::describe(input_fp)
+        )
+    })?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// Print how the solution of a day solves the input, for the days which explain themselves
+fn print_explanation(day: usize) -> Result<()> {
+    let input_fp = &inputs::ensure(day);
+    let explanation = entry(day)?.solver.explain(input_fp).with_context(|| {
+        format!(
+            "could not explain day {day} with {}",
+            inputs::describe(input_fp)
         )
+    })?;
+    match explanation {
+        Some(explanation) => println!("Explanation:\n{explanation}"),
+        None => println!("Day {day} doesn't explain its solution yet"),
+    }
+    Ok(())
+}
+
+/// Profile both parts of a day and report where the flamegraphs were written
+fn run_profile(day: usize) -> Result<()> {
+    let input_fp = &inputs::ensure(day);
+    let written = entry(day)?.solver.profile(day, input_fp)?;
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Solve a day for each of the given input files and print a table with the answers and total time for each file
+fn run_batch(day: usize, files: &[String]) -> Result<()> {
+    if inputs::use_example() {
+        bail!("--example cannot be combined with input files");
+    }
+    let entry = entry(day)?;
+    let mut failed = 0;
+    let rows: Vec<_> = files
+        .iter()
+        .map(|file| match entry.solver.solve(file) {
+            Ok(s) => {
+                cache::check(day, file, &s);
+                [
+                    file.clone(),
+                    s.answers[0].to_string(),
+                    s.answers[1].to_string(),
+                    format!("{:.3?}", s.timings.total()),
+                ]
+            }
+            Err(e) => {
+                eprintln!("Error: could not solve {file}: {e}");
+                failed += 1;
+                [file.clone(), "-".into(), "-".into(), "-".into()]
+            }
+        })
+        .collect();
+    let header = ["File", "Part 1", "Part 2", "Total"].map(String::from);
+    let widths: Vec<_> = (0..header.len())
+        .map(|col| {
+            rows.iter()
+                .chain([&header])
+                .map(|row| row[col].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    for row in [&header].into_iter().chain(&rows) {
+        println!(
+            "{:<w0$} | {:>w1$} | {:>w2$} | {:>w3$}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        );
+    }
+    if failed > 0 {
+        bail!("{failed} file(s) could not be solved");
+    }
+    Ok(())
+}
+
+/// Print a table with the timings of each phase for all days, and the grand total
+fn print_summary(timings: &[(usize, Option<Timings>)]) {
+    println!("======== SUMMARY ========");
+    println!(
+        "{:>5} | {:>12} | {:>12} | {:>12} | {:>12}",
+        "Day", "Parse", "Part 1", "Part 2", "Total"
+    );
+    let mut grand_total = Timings::default();
+    for (day, timing) in timings {
+        let Some(t) = timing else {
+            println!(
+                "{day:>5} | {:>12} | {:>12} | {:>12} | {:>12}",
+                "-", "-", "-", "-"
+            );
+            continue;
+        };
+        println!(
+            "{day:>5} | {:>12.3?} | {:>12.3?} | {:>12.3?} | {:>12.3?}",
+            t.parse,
+            t.part1,
+            t.part2,
+            t.total()
+        );
+        grand_total.parse += t.parse;
+        grand_total.part1 += t.part1;
+        grand_total.part2 += t.part2;
+    }
+    println!(
+        "{:>5} | {:>12.3?} | {:>12.3?} | {:>12.3?} | {:>12.3?}",
+        "Total",
+        grand_total.parse,
+        grand_total.part1,
+        grand_total.part2,
+        grand_total.total()
+    );
+}
+
+/// Run, check or benchmark a day, printing the results in the requested format
+fn run_day(
+    day: usize,
+    bench: Option<usize>,
+    output: OutputFormat,
+    answers: Option<&Answers>,
+) -> Result<Timings> {
+    if output == OutputFormat::Text {
+        println!("======== {} ========", heading(day));
+    }
+    let _span = debug_span!("day", day).entered();
+    let entry = entry(day)?;
+    let input_fp = &inputs::ensure(day);
+    let result = catch_example_panic(day, || {
+        let result = if let Some(answers) = answers {
+            entry.solver.check(day, input_fp, answers)
+        } else {
+            match (output, bench) {
+                (OutputFormat::Json, _) => entry.solver.json(day, input_fp, bench),
+                (OutputFormat::Text, Some(iterations)) => entry
+                    .solver
+                    .benchmark(input_fp, iterations)
+                    .map(|(result, stats)| {
+                        output::print_bench(&result, &stats);
+                        result
+                    }),
+                (OutputFormat::Text, None) => entry.solver.solve(input_fp).inspect(|result| {
+                    output::print_result(result);
+                }),
+            }
+        };
+        result.with_context(|| {
+            format!(
+                "could not solve day {day} with {}",
+                inputs::describe(input_fp)
+            )
+        })
+    })?;
+    // whichever way the answers were printed, a changed answer is flagged
+    cache::check(day, input_fp, &result);
+    Ok(result.timings)
+}
+
+/// Heading printed before the results of a day
+fn heading(day: usize) -> String {
+    registry::get(day).map_or_else(|| format!("Day {day}"), Entry::name)
+}
+
+// only day 25 has a visualization, which is where the algorithm is used
+#[cfg_attr(not(feature = "day25"), allow(unused_variables))]
+fn visualize(day: usize, algorithm: &str) -> Result<()> {
+    let input_fp = &inputs::ensure(day);
+    let dot: Option<String> = match day {
+        #[cfg(feature = "day25")]
+        25 => {
+            let Some(finder) = day25::algorithm(algorithm) else {
+                bail!("Unknown algorithm {algorithm}");
+            };
+            let input_string = day25::Day25::read_input(input_fp)?;
+            let input = day25::Day25::parse_input(&input_string)
+                .with_context(|| format!("could not parse {}", inputs::describe(input_fp)))?;
+            Some(day25::partition_dot(&input, finder).with_context(|| {
+                format!(
+                    "could not split the graph of {}",
+                    inputs::describe(input_fp)
+                )
+            })?)
+        }
+        _ => None,
+    };
+    let Some(dot) = dot else {
+        bail!("No visualization available for day {day}");
+    };
+    let written = viz::export_dot(&format!("day{day:02}"), &dot)?;
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn write_report(with_html: bool) -> Result<()> {
+    let rows: Vec<_> = registry::DAYS
+        .iter()
+        .map(|&Entry { day, .. }| {
+            let solution = solve_day(day);
+            if let Err(e) = &solution {
+                eprintln!("Error: {e:#}");
+            }
+            (day, solution.ok())
+        })
+        .collect();
+    let written = report::write_report(&rows, with_html)?;
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn submit_answer(day: usize, part: u8) -> Result<()> {
+    if inputs::use_example() {
+        bail!("Answers computed from the example cannot be submitted");
+    }
+    let result = solve_day(day)?;
+    let answer = &result.answers[usize::from(part) - 1];
+    println!("Submitting {answer} for day {day} part {part}");
+    println!("{}", submit::submit(day, part, &answer.to_string())?);
+    Ok(())
+}
+
+fn self_test() -> Result<()> {
+    // panics are reported in the table, silence the default hook which would print them in the middle of it
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let outcomes: Vec<_> = registry::DAYS
+        .iter()
+        .map(|entry| (entry.day, entry.solver.self_test()))
+        .collect();
+    panic::set_hook(hook);
+
+    println!("{:>5} | {:>6} | {:>6}", "Day", "Part 1", "Part 2");
+    for (day, [part1, part2]) in &outcomes {
+        println!("{day:>5} | {part1:>6} | {part2:>6}");
+    }
+    let mut failures = 0;
+    for (day, parts) in &outcomes {
+        for (part, outcome) in (1..).zip(parts) {
+            match outcome {
+                Outcome::Fail { expected, actual } => {
+                    println!("Day {day} part {part}: expected {expected}, got {actual}");
+                }
+                Outcome::Error(e) => println!("Day {day} part {part}: {e}"),
+                _ => {}
+            }
+            failures += usize::from(outcome.is_failure());
+        }
+    }
+    if failures > 0 {
+        bail!("{failures} example(s) failed");
+    }
+    println!("All examples pass");
+    Ok(())
+}
+
+fn download_all_input() -> Result<()> {
+    registry::DAYS
+        .iter()
+        .try_for_each(|entry| download_input(entry.day))
+}
+
+fn download_input(day: usize) -> Result<()> {
+    let path = inputs::download(day)?;
+    println!("Successfully downloaded input to {path}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_aoc_day() {
+        let at = |month, day, hour| Utc.with_ymd_and_hms(2023, month, day, hour, 0, 0).unwrap();
+        // 04:59 UTC is still the previous day in New York
+        assert_eq!(aoc_day(at(12, 1, 4)), None);
+        assert_eq!(aoc_day(at(12, 1, 5)), Some(1));
+        assert_eq!(aoc_day(at(12, 17, 4)), Some(16));
+        assert_eq!(aoc_day(at(12, 28, 12)), Some(25));
+        assert_eq!(aoc_day(at(7, 14, 12)), None);
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(
+            parse_days("1-3,15, 20-22").unwrap(),
+            vec![1, 2, 3, 15, 20, 21, 22]
+        );
+        assert_eq!(parse_days("5,2-4,3").unwrap(), vec![2, 3, 4, 5]);
+        assert!(parse_days("3-1").is_err());
+        assert!(parse_days("20-26").is_err());
+        assert!(parse_days("1,,2").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5s").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("-1s").is_err());
     }
 }
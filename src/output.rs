@@ -0,0 +1,142 @@
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::{
+    days::{BenchStats, Day, RunResult},
+    parser::MyErr,
+    stats::Stats,
+    timing::{self, Phase},
+};
+
+/// How the results of a run are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON document per day and per line
+    Json,
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn nanos(stats: &Stats) -> Value {
+    json!({
+        "min": stats.min.as_nanos() as u64,
+        "median": stats.median.as_nanos() as u64,
+        "mean": stats.mean.as_nanos() as u64,
+        "stddev": stats.stddev.as_nanos() as u64,
+    })
+}
+
+/// Print the answers and timings of a day, with the steps reported by the solution under their phase
+pub fn print_result(result: &RunResult) {
+    let RunResult {
+        answers: [part1, part2],
+        timings,
+        sub_phases,
+    } = result;
+    if sub_phases.iter().any(|s| s.phase == Phase::Parse) {
+        println!("{}", timing::describe(timings, sub_phases, Phase::Parse));
+    }
+    println!("Part 1: {part1}");
+    if !timings.shared {
+        println!("{}", timing::describe(timings, sub_phases, Phase::Part1));
+    }
+    println!("Part 2: {part2}");
+    let phase = if timings.shared {
+        Phase::Both
+    } else {
+        Phase::Part2
+    };
+    println!("{}", timing::describe(timings, sub_phases, phase));
+}
+
+/// Print the answers of a day and the statistics about each phase of the benchmark
+pub fn print_bench(result: &RunResult, stats: &BenchStats) {
+    let [part1, part2] = &result.answers;
+    println!("Part 1: {part1}");
+    println!("Part 2: {part2}");
+    println!("Benchmark over {} iterations:", stats.iterations);
+    println!(
+        "{:>8} | {:>12} | {:>12} | {:>12} | {:>12}",
+        "Phase", "Min", "Median", "Mean", "Std. dev."
+    );
+    let phases = if stats.shared {
+        vec![("Parse", &stats.parse), ("Both", &stats.part2)]
+    } else {
+        vec![
+            ("Parse", &stats.parse),
+            ("Part 1", &stats.part1),
+            ("Part 2", &stats.part2),
+        ]
+    };
+    for (name, phase) in phases {
+        println!(
+            "{name:>8} | {:>12.3?} | {:>12.3?} | {:>12.3?} | {:>12.3?}",
+            phase.min, phase.median, phase.mean, phase.stddev
+        );
+    }
+}
+
+/// Print the answers and timings of a day as a single-line JSON document
+///
+/// Durations are in nanoseconds. `bench` is added to the document if it's not null.
+pub fn print_result_json(day: usize, result: &RunResult, bench: Value) {
+    let RunResult {
+        answers: [part1, part2],
+        timings,
+        sub_phases,
+    } = result;
+    let mut doc = json!({
+        "day": day,
+        "part1": part1.to_string(),
+        "part2": part2.to_string(),
+    });
+    if let (Some(doc), Value::Object(timings)) =
+        (doc.as_object_mut(), timing::json(timings, sub_phases))
+    {
+        doc.extend(timings);
+    }
+    if !bench.is_null() {
+        doc["bench"] = bench;
+    }
+    println!("{doc}");
+}
+
+/// Print the error that prevented solving a day as a single-line JSON document
+pub fn print_error_json(day: usize, error: &impl Display) {
+    println!("{}", json!({ "day": day, "error": format!("{error:#}") }));
+}
+
+/// Solve (or benchmark) a day, returning the answers and timings and the benchmark statistics as JSON
+fn solve<D: Day>(fp: &str, bench: Option<usize>) -> Result<(RunResult, Value), MyErr> {
+    let Some(iterations) = bench else {
+        return Ok((D::run_day(fp)?, Value::Null));
+    };
+    let (result, stats) = D::bench_day(fp, iterations)?;
+    let bench = json!({
+        "iterations": stats.iterations,
+        "parse_ns": nanos(&stats.parse),
+        "part1_ns": nanos(&stats.part1),
+        "part2_ns": nanos(&stats.part2),
+    });
+    Ok((result, bench))
+}
+
+/// Run (or benchmark) a day and print the answers and timings (or the error) as a single-line JSON document
+///
+/// When benchmarking, the `*_ns` fields contain the medians and the `bench` object has the full statistics.
+pub fn print_json<D: Day>(day: usize, fp: &str, bench: Option<usize>) -> Result<RunResult, MyErr> {
+    match solve::<D>(fp, bench) {
+        Err(e) => {
+            print_error_json(day, &e);
+            Err(e)
+        }
+        Ok((result, bench)) => {
+            print_result_json(day, &result, bench);
+            Ok(result)
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    any::type_name,
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::Result;
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::cache;
+
+/// Folder where the parsed inputs are cached
+const CACHE_DIR: &str = "outputs/parse-cache";
+
+/// Whether the parsed inputs are read from and written to the cache
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Cache the parsed inputs on disk for the rest of the run, to skip parsing in the next runs
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Where the parsed input of a day is cached
+///
+/// The entry is named after the hash of the input, so that each input gets its own entry, and after the hash of the
+/// type of the parsed input and the version of the parser, so that an entry written by another parser is never
+/// decoded.
+fn path<T>(day: u8, version: u32, input: &str) -> PathBuf {
+    let layout = format!("{}#{version}", type_name::<T>());
+    PathBuf::from(format!(
+        "{CACHE_DIR}/day{day:02}-{:016x}-{:016x}.bin",
+        cache::hash(layout.as_bytes()),
+        cache::hash(input.as_bytes())
+    ))
+}
+
+/// The cached parsed input of a day, if the cache is enabled and has an entry for this input and this parser
+pub fn read<T>(day: u8, version: u32, input: &str) -> Option<Vec<u8>> {
+    if !enabled() {
+        return None;
+    }
+    fs::read(path::<T>(day, version, input)).ok()
+}
+
+/// Decode a cached parsed input, or `None` if the bytes are corrupted
+pub fn decode<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Option<T> {
+    bincode::DefaultOptions::new()
+        .deserialize(bytes)
+        .inspect_err(|e| debug!("could not decode the cached input: {e}"))
+        .ok()
+}
+
+fn try_write<T: Serialize>(day: u8, version: u32, input: &str, parsed: &T) -> Result<()> {
+    let path = path::<T>(day, version, input);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, bincode::DefaultOptions::new().serialize(parsed)?)?;
+    Ok(())
+}
+
+/// Cache the parsed input of a day. Since the cache only saves time, failing to write it is not an error.
+pub fn write<T: Serialize>(day: u8, version: u32, input: &str, parsed: &T) {
+    if let Err(e) = try_write(day, version, input, parsed) {
+        warn!("could not cache the parsed input of day {day}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let parsed: (Vec<&str>, HashMap<&str, u32>) =
+            (vec!["a", "bc"], [("x", 1), ("y", 2)].into());
+        let bytes = bincode::DefaultOptions::new().serialize(&parsed).unwrap();
+        assert_eq!(decode(&bytes), Some(parsed));
+        assert_eq!(decode::<Vec<u64>>(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(decode::<u8>(&bytes), None);
+    }
+
+    #[test]
+    fn test_path() {
+        assert_ne!(path::<u8>(4, 1, "a"), path::<u8>(4, 1, "b"));
+        assert_ne!(path::<u8>(4, 1, "a"), path::<u8>(5, 1, "a"));
+        assert_ne!(path::<u8>(4, 1, "a"), path::<u8>(4, 2, "a"));
+        assert_ne!(path::<u8>(4, 1, "a"), path::<u16>(4, 1, "a"));
+        assert!(path::<u8>(4, 1, "a").starts_with("outputs/parse-cache"));
+    }
+}
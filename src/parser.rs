@@ -1,19 +1,188 @@
-use nom::{error::Error, Err};
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::{
+    fmt::{self, Display},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use nom::{
+    error::{Error, ErrorKind},
+    Err, IResult,
+};
+
+pub mod helpers;
+pub mod scan;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)] // each variant wraps the error it's named after
 pub enum MyErr {
     FileError(std::io::Error),
-    ParseError(Err<Error<String>>),
+    ParseError(ParseError),
+    SolveError(SolveError),
+}
+
+/// Error returned by a part when the input doesn't have the shape the solution relies on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The input lacks a property the solution needs, like a node that should exist
+    InvalidInput(String),
+    /// The search ended without finding an answer
+    NoSolution,
+}
+
+impl SolveError {
+    pub fn invalid(message: impl Into<String>) -> Self {
+        SolveError::InvalidInput(message.into())
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::InvalidInput(message) => write!(f, "invalid input: {message}"),
+            SolveError::NoSolution => write!(f, "no solution was found"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Whether input left over after parsing is an error. The tests parse bits of the examples, so they are lenient.
+static STRICT: AtomicBool = AtomicBool::new(!cfg!(test));
+
+/// Fail when the parser of a day doesn't consume the whole input, for the rest of the run
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
 }
 
-impl From<Err<Error<&str>>> for MyErr {
-    fn from(e: Err<Error<&str>>) -> MyErr {
-        let inner_err = match e {
-            Err::Incomplete(n) => Err::Incomplete(n),
-            Err::Error(e) => Err::Error(conv_error(&e)),
-            Err::Failure(e) => Err::Failure(conv_error(&e)),
+/// Check that the input left over by a parser is only whitespace
+///
+/// The left over input is reported like a failed [`nom::combinator::all_consuming`], with [`ErrorKind::Eof`].
+fn all_consumed(rest: &[u8]) -> Result<(), Err<Error<&[u8]>>> {
+    if rest.trim_ascii().is_empty() {
+        return Ok(());
+    }
+    let start = rest.len() - rest.trim_ascii_start().len();
+    Err(Err::Error(Error::new(&rest[start..], ErrorKind::Eof)))
+}
+
+/// Check that the parser consumed the whole input, besides trailing whitespace, when parsing is strict
+pub fn check_rest(rest: &[u8]) -> Result<(), Err<Error<&[u8]>>> {
+    if STRICT.load(Ordering::Relaxed) {
+        all_consumed(rest)
+    } else {
+        Ok(())
+    }
+}
+
+/// Maximum number of characters of the offending line shown in parse errors
+const SNIPPET_LEN: usize = 40;
+
+/// Error returned when the input of a day can't be parsed, pointing at the place where the parser failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    /// Line where the parser failed, starting at 1
+    pub line: usize,
+    /// Column where the parser failed, in characters and starting at 1
+    pub column: usize,
+    /// The combinator which failed, or `None` if the parser needed more data than the input had
+    pub kind: Option<ErrorKind>,
+    /// Part of the offending line around the column, with `...` where it was cut
+    pub snippet: String,
+    /// Position of the column in the snippet, in characters
+    caret: usize,
+}
+
+impl ParseError {
+    /// Locate the error of a byte-level parser in the input it was run on
+    pub fn new(day: u8, input: &str, err: &Err<Error<&[u8]>>) -> Self {
+        let (offset, kind) = match err {
+            Err::Incomplete(_) => (input.len(), None),
+            Err::Error(e) | Err::Failure(e) => {
+                (input.len().saturating_sub(e.input.len()), Some(e.code))
+            }
         };
-        MyErr::ParseError(inner_err)
+        let before = &input.as_bytes()[..offset];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let line_end = input.as_bytes()[offset..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(input.len(), |i| offset + i);
+        let column = String::from_utf8_lossy(&before[line_start..])
+            .chars()
+            .count();
+        let text = String::from_utf8_lossy(&input.as_bytes()[line_start..line_end]);
+        let chars: Vec<char> = text.trim_end_matches('\r').chars().collect();
+        // keep the column in view when the line is too long
+        let start = column
+            .saturating_sub(SNIPPET_LEN / 2)
+            .min(chars.len().saturating_sub(SNIPPET_LEN));
+        let end = chars.len().min(start + SNIPPET_LEN);
+        let mut snippet: String = chars[start..end].iter().collect();
+        let mut caret = column - start;
+        if start > 0 {
+            snippet.insert_str(0, "...");
+            caret += 3;
+        }
+        if end < chars.len() {
+            snippet.push_str("...");
+        }
+        Self {
+            day,
+            line: before.split(|&b| b == b'\n').count(),
+            column: column + 1,
+            kind,
+            snippet,
+            caret,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "could not parse the input of day {}: ", self.day)?;
+        match self.kind {
+            Some(ErrorKind::Eof) => write!(f, "unparsed input left")?,
+            Some(kind) => write!(f, "{kind:?} failed")?,
+            None => write!(f, "more data is needed")?,
+        }
+        write!(f, " at line {}, column {}", self.line, self.column)?;
+        if self.snippet.is_empty() {
+            return Ok(());
+        }
+        let gutter = " ".repeat(self.line.to_string().len());
+        write!(
+            f,
+            "\n{} | {}\n{gutter} | {}^",
+            self.line,
+            self.snippet,
+            " ".repeat(self.caret)
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Display for MyErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MyErr::FileError(e) => write!(f, "could not read the input file: {e}"),
+            MyErr::SolveError(e) => write!(f, "could not solve the puzzle: {e}"),
+            MyErr::ParseError(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+// the underlying errors are already part of the message, so we don't expose them as `source`
+impl std::error::Error for MyErr {}
+
+impl From<ParseError> for MyErr {
+    fn from(e: ParseError) -> MyErr {
+        MyErr::ParseError(e)
     }
 }
 
@@ -23,9 +192,95 @@ impl From<std::io::Error> for MyErr {
     }
 }
 
-fn conv_error(e: &Error<&str>) -> Error<String> {
-    Error {
-        input: e.input.to_owned(),
-        code: e.code,
+impl From<SolveError> for MyErr {
+    fn from(e: SolveError) -> MyErr {
+        MyErr::SolveError(e)
+    }
+}
+
+/// Turn the result of a byte-level parser run on `input.as_bytes()` back into a result on `input`, so that days which
+/// override [`crate::days::Day::parse_bytes`] can implement [`crate::days::Day::parse`] with it
+pub fn from_bytes<'a, O>(input: &'a str, result: IResult<&'a [u8], O>) -> IResult<&'a str, O> {
+    // the remaining bytes are always a suffix of the input
+    let rest = |bytes: &[u8]| input.get(input.len() - bytes.len()..).unwrap_or_default();
+    match result {
+        Ok((bytes, output)) => Ok((rest(bytes), output)),
+        Err(e) => Err(e.map(|e| Error::new(rest(e.input), e.code))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{character::complete::digit1, error::ErrorKind};
+
+    use super::*;
+
+    /// Run `digit1` at `offset` in `input` and locate its error
+    fn digit_error(input: &str, offset: usize) -> ParseError {
+        let err = digit1::<&[u8], Error<&[u8]>>(&input.as_bytes()[offset..]).unwrap_err();
+        ParseError::new(4, input, &err)
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = digit_error("12\n34\nab cd\n", 6);
+        assert_eq!((err.line, err.column), (3, 1));
+        assert_eq!(err.kind, Some(ErrorKind::Digit));
+        assert_eq!(
+            err.to_string(),
+            "could not parse the input of day 4: Digit failed at line 3, column 1\n3 | ab cd\n  | ^"
+        );
+        let err = digit_error("Card 1: 41 | 83\r\nCard 2: x", 25);
+        assert_eq!((err.line, err.column), (2, 9));
+        assert_eq!(err.snippet, "Card 2: x");
+        let long = format!("{}x{}", "1".repeat(50), "2".repeat(50));
+        let err = digit_error(&long, 50);
+        assert_eq!(err.column, 51);
+        assert!(err.to_string().ends_with(&format!(
+            "...{}x{}...\n  | {}^",
+            "1".repeat(20),
+            "2".repeat(19),
+            " ".repeat(23)
+        )));
+        let err = ParseError::new(4, "12", &Err::Incomplete(nom::Needed::Unknown));
+        assert_eq!(
+            err.to_string(),
+            "could not parse the input of day 4: more data is needed at line 1, column 3\n1 | 12\n  |   ^"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let err: MyErr = SolveError::invalid("brick 3 is missing").into();
+        assert_eq!(
+            err.to_string(),
+            "could not solve the puzzle: invalid input: brick 3 is missing"
+        );
+    }
+
+    #[test]
+    fn test_all_consumed() {
+        assert_eq!(all_consumed(b""), Ok(()));
+        assert_eq!(all_consumed(b"\n\n  "), Ok(()));
+        let input = "1 2\n3 4\n\n5 x\n";
+        let err = all_consumed(&input.as_bytes()[9..]).unwrap_err();
+        assert_eq!(err, Err::Error(Error::new(&b"5 x\n"[..], ErrorKind::Eof)));
+        assert_eq!(
+            ParseError::new(1, input, &err).to_string(),
+            "could not parse the input of day 1: unparsed input left at line 4, column 1\n4 | 5 x\n  | ^"
+        );
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let input = "12ab";
+        assert_eq!(
+            from_bytes(input, digit1(input.as_bytes())),
+            Ok(("ab", &b"12"[..]))
+        );
+        assert_eq!(
+            from_bytes::<&[u8]>(input, digit1(&input.as_bytes()[2..])),
+            Err(Err::Error(Error::new("ab", ErrorKind::Digit)))
+        );
     }
 }
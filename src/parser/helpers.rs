@@ -0,0 +1,109 @@
+//! Combinators for the shapes which come back in many inputs, working on `&str` as well as on `&[u8]`
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::{
+    ops::{Range, RangeFrom, RangeTo},
+    str::FromStr,
+};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{digit1, line_ending, space1},
+    combinator::{map_res, opt, recognize},
+    error::ParseError,
+    multi::{separated_list0, separated_list1},
+    sequence::pair,
+    AsBytes, AsChar, Compare, IResult, InputIter, InputLength, InputTake, InputTakeAtPosition,
+    Offset, Parser, Slice,
+};
+
+/// The input types the helpers work on, `&str` and `&[u8]`
+pub trait Input:
+    Clone
+    + Copy
+    + AsBytes
+    + Compare<&'static str>
+    + InputIter<Item = Self::Char>
+    + InputLength
+    + InputTake
+    + InputTakeAtPosition<Item = Self::Char>
+    + Offset
+    + Slice<Range<usize>>
+    + Slice<RangeFrom<usize>>
+    + Slice<RangeTo<usize>>
+{
+    type Char: AsChar + Copy;
+}
+
+impl Input for &str {
+    type Char = char;
+}
+
+impl Input for &[u8] {
+    type Char = u8;
+}
+
+/// An empty line between two blocks, which is two line endings in a row, `\n` or `\r\n`
+pub fn blank_line<I: Input, E: ParseError<I>>(input: I) -> IResult<I, I, E> {
+    recognize(pair(line_ending, line_ending))(input)
+}
+
+/// Blocks of lines separated by empty lines, like the sections of an input or several grids
+pub fn blocks<I: Input, O, E: ParseError<I>>(
+    block: impl Parser<I, O, E>,
+) -> impl FnMut(I) -> IResult<I, Vec<O>, E> {
+    separated_list1(blank_line, block)
+}
+
+/// One item per line, without consuming the line ending after the last one
+pub fn lines_of<I: Input, O, E: ParseError<I>>(
+    line: impl Parser<I, O, E>,
+) -> impl FnMut(I) -> IResult<I, Vec<O>, E> {
+    separated_list0(line_ending, line)
+}
+
+/// Numbers separated by spaces, any number of them, e.g. `number_list(u64)` or `number_list(integer::<_, i32, _>)`
+pub fn number_list<I: Input, O, E: ParseError<I>>(
+    number: impl Parser<I, O, E>,
+) -> impl FnMut(I) -> IResult<I, Vec<O>, E> {
+    separated_list1(space1, number)
+}
+
+/// An integer of any type, with an optional `-` or `+` sign
+pub fn integer<I: Input, T: FromStr, E>(input: I) -> IResult<I, T, E>
+where
+    E: ParseError<I> + nom::error::FromExternalError<I, Option<T::Err>>,
+{
+    map_res(
+        recognize(pair(opt(alt((tag("-"), tag("+")))), digit1)),
+        |digits: I| {
+            std::str::from_utf8(digits.as_bytes())
+                .map_err(|_| None)
+                .and_then(|s| s.parse::<T>().map_err(Some))
+        },
+    )(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{character::complete::u64, error::Error};
+
+    use super::*;
+
+    #[test]
+    fn test_helpers() {
+        let lines = lines_of::<_, _, Error<_>>(number_list(u64));
+        assert_eq!(
+            blocks(lines)("1 2\r\n3\r\n\r\n4  5\n"),
+            Ok(("\n", vec![vec![vec![1, 2], vec![3]], vec![vec![4, 5]]]))
+        );
+        let signed = |input| integer::<_, i16, Error<_>>(input);
+        assert_eq!(signed(b"-12 x".as_slice()), Ok((b" x".as_slice(), -12)));
+        assert_eq!(signed(b"+7".as_slice()), Ok((b"".as_slice(), 7)));
+        assert!(signed(b"40000".as_slice()).is_err());
+        assert!(integer::<_, u8, Error<_>>("-1").is_err());
+        assert_eq!(blank_line::<_, Error<_>>("\n\nx"), Ok(("x", "\n\n")));
+        assert!(blank_line::<_, Error<_>>("\nx").is_err());
+    }
+}
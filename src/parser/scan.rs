@@ -0,0 +1,209 @@
+//! A minimal scanner for the inputs made of numbers, for the days where nom is most of the time of parsing
+//!
+//! The lines are found with `memchr`, and the scanner moves through the input byte by byte without backtracking.
+//! Its errors are nom errors on the rest of the whole input, so they are reported at the right line and column like
+//! the ones of the parsers written with nom.
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use memchr::memchr;
+use nom::{
+    error::{Error, ErrorKind},
+    Err,
+};
+
+/// Error of the scanner, a nom error at the place where scanning failed
+pub type ScanError<'a> = Err<Error<&'a [u8]>>;
+
+/// The lines of `input`, without their line endings (`\n` or `\r\n`) and without the blank lines at the end
+pub fn lines_bytes(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut scanner = Scanner::new(input);
+    std::iter::from_fn(move || scanner.next_line().map(|line| line.line_rest()))
+}
+
+/// Reads the input from the start, up to the end of the input or of a line
+#[derive(Debug, Clone, Copy)]
+pub struct Scanner<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// Where the scanned part ends, the end of the input or of a line
+    end: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self {
+            input,
+            pos: 0,
+            end: input.len(),
+        }
+    }
+
+    /// The whole input after the position of the scanner, for the rest returned by a parser
+    pub fn rest(&self) -> &'a [u8] {
+        &self.input[self.pos..]
+    }
+
+    /// The rest of the scanned part, up to the end of the line for a line
+    fn line_rest(&self) -> &'a [u8] {
+        &self.input[self.pos..self.end]
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.pos >= self.end
+    }
+
+    /// An error at the position of the scanner
+    pub fn error(&self, kind: ErrorKind) -> ScanError<'a> {
+        Err::Error(Error::new(self.rest(), kind))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        (self.pos < self.end).then(|| self.input[self.pos])
+    }
+
+    /// The next line, with a scanner of its own, or `None` when only whitespace is left (the scanner then stays
+    /// before it, which a strict parse accepts)
+    pub fn next_line(&mut self) -> Option<Scanner<'a>> {
+        let rest = self.line_rest();
+        if rest.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+        let newline = memchr(b'\n', rest).map_or(self.end, |i| self.pos + i);
+        let end = if newline > self.pos && self.input[newline - 1] == b'\r' {
+            newline - 1
+        } else {
+            newline
+        };
+        let line = Scanner {
+            input: self.input,
+            pos: self.pos,
+            end,
+        };
+        self.pos = (newline + 1).min(self.end);
+        Some(line)
+    }
+
+    /// The lines of the rest of the scanned part, see [`Scanner::next_line`]
+    pub fn lines(&mut self) -> impl Iterator<Item = Scanner<'a>> + '_ {
+        std::iter::from_fn(|| self.next_line())
+    }
+
+    /// Skip the spaces, returning whether there were any
+    pub fn skip_spaces(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek() == Some(b' ') {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    /// Expect `tag` at the position of the scanner and move past it
+    pub fn tag(&mut self, tag: &[u8]) -> Result<(), ScanError<'a>> {
+        if self.line_rest().starts_with(tag) {
+            self.pos += tag.len();
+            Ok(())
+        } else {
+            Err(self.error(ErrorKind::Tag))
+        }
+    }
+
+    /// The integer after the spaces at the position of the scanner, with an optional minus sign
+    ///
+    /// The scanner only moves past the integer when there is one, the error points at where it should be.
+    pub fn next_int<T: TryFrom<i64>>(&mut self) -> Result<T, ScanError<'a>> {
+        let bytes = &self.input[..self.end];
+        let mut pos = self.pos;
+        while bytes.get(pos) == Some(&b' ') {
+            pos += 1;
+        }
+        let start = pos;
+        let error = |kind| Err::Error(Error::new(&self.input[start..], kind));
+        let negative = bytes.get(pos) == Some(&b'-');
+        pos += usize::from(negative);
+        let digits = pos;
+        let mut value: i64 = 0;
+        while let Some(digit @ b'0'..=b'9') = bytes.get(pos) {
+            let digit = i64::from(digit - b'0');
+            let next = value.checked_mul(10).and_then(|v| {
+                if negative {
+                    v.checked_sub(digit)
+                } else {
+                    v.checked_add(digit)
+                }
+            });
+            value = next.ok_or_else(|| error(ErrorKind::TooLarge))?;
+            pos += 1;
+        }
+        if pos == digits {
+            return Err(error(ErrorKind::Digit));
+        }
+        let value = T::try_from(value).map_err(|_| error(ErrorKind::TooLarge))?;
+        self.pos = pos;
+        Ok(value)
+    }
+
+    /// The integers separated by spaces at the position of the scanner, stopping before anything else
+    pub fn int_list<T: TryFrom<i64>, C: FromIterator<T>>(&mut self) -> Result<C, ScanError<'a>> {
+        let first = self.next_int()?;
+        let rest = std::iter::from_fn(|| {
+            (self.peek() == Some(b' '))
+                .then(|| self.next_int().ok())
+                .flatten()
+        });
+        Ok(std::iter::once(first).chain(rest).collect())
+    }
+
+    /// Check that only spaces are left in the scanned part
+    pub fn finish(mut self) -> Result<(), ScanError<'a>> {
+        self.skip_spaces();
+        if self.at_end() {
+            Ok(())
+        } else {
+            Err(self.error(ErrorKind::Eof))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_bytes() {
+        let lines: Vec<_> = lines_bytes(b"1 2\r\n\n-3\n\n").collect();
+        assert_eq!(lines, [&b"1 2"[..], b"", b"-3"]);
+        assert_eq!(lines_bytes(b"").count(), 0);
+        assert_eq!(lines_bytes(b"last").count(), 1);
+    }
+
+    #[test]
+    fn test_scanner() {
+        let input = b"Card  12: 41 -48 | 83\n7 x\n\n";
+        let mut scanner = Scanner::new(input);
+        let mut card = scanner.next_line().unwrap();
+        card.tag(b"Card").unwrap();
+        assert_eq!(card.next_int::<u8>(), Ok(12));
+        card.tag(b":").unwrap();
+        assert_eq!(card.int_list::<i64, Vec<_>>(), Ok(vec![41, -48]));
+        assert!(card.finish().is_err());
+        card.tag(b" |").unwrap();
+        assert_eq!(card.int_list::<u64, Vec<_>>(), Ok(vec![83]));
+        assert!(card.finish().is_ok());
+        let mut line = scanner.next_line().unwrap();
+        assert_eq!(line.next_int::<u8>(), Ok(7));
+        // the errors point at the rest of the whole input
+        let error = line.next_int::<u8>().unwrap_err();
+        assert!(matches!(
+            error,
+            Err::Error(Error {
+                input: b"x\n\n",
+                code: ErrorKind::Digit
+            })
+        ));
+        assert_eq!(line.rest(), b" x\n\n");
+        assert!(Scanner::new(b"300").next_int::<u8>().is_err());
+        assert!(scanner.next_line().is_none());
+        // the blank line at the end is left
+        assert_eq!(scanner.rest(), b"\n");
+    }
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::days::Day;
+
+/// Run each part of a day under the profiler and write one flamegraph per part to `outputs/dayNN_partN.svg`
+///
+/// Parts are run repeatedly for at least [`MIN_DURATION`] so that the profiler collects enough samples. Returns the
+/// paths of the files that were written.
+#[cfg(feature = "profile")]
+pub fn profile_day<D: Day>(day: usize, fp: &str) -> Result<Vec<PathBuf>> {
+    use std::{
+        fs,
+        hint::black_box,
+        time::{Duration, Instant},
+    };
+
+    use anyhow::anyhow;
+    use pprof::ProfilerGuardBuilder;
+
+    use crate::viz::OUTPUT_DIR;
+
+    /// Minimum time spent running each part
+    const MIN_DURATION: Duration = Duration::from_secs(1);
+    /// Sampling frequency of the profiler, in Hz
+    const FREQUENCY: i32 = 10_000;
+
+    let input_string = D::read_input(fp).map_err(|e| anyhow!("{e}"))?;
+    let input = D::parse_input(&input_string).map_err(|e| anyhow!("{e}"))?;
+    let params = D::params();
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let mut written = Vec::with_capacity(2);
+    for part in [1, 2] {
+        let guard = ProfilerGuardBuilder::default()
+            .frequency(FREQUENCY)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()?;
+        let start = Instant::now();
+        let mut runs = 0;
+        while runs == 0 || start.elapsed() < MIN_DURATION {
+            if part == 1 {
+                black_box(D::part_1(&input, &params)?);
+            } else {
+                black_box(D::part_2(&input, &params)?);
+            }
+            runs += 1;
+        }
+        let report = guard.report().build()?;
+        let path = PathBuf::from(format!("{OUTPUT_DIR}/day{day:02}_part{part}.svg"));
+        report.flamegraph(fs::File::create(&path)?)?;
+        eprintln!("Profiled part {part} over {runs} runs");
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(not(feature = "profile"))]
+pub fn profile_day<D: Day>(_day: usize, _fp: &str) -> Result<Vec<PathBuf>> {
+    anyhow::bail!("Profiling is not available, rebuild with `--features profile`")
+}
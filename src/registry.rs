@@ -0,0 +1,236 @@
+use std::{cmp::Reverse, fmt::Write as _, panic::RefUnwindSafe, path::PathBuf};
+
+use anyhow::Result;
+
+#[allow(clippy::wildcard_imports)]
+use crate::days::*;
+use crate::{
+    check,
+    check::Answers,
+    dump, output,
+    parser::MyErr,
+    profile,
+    selftest::{self, Outcome},
+};
+
+/// The entry points of a day with its associated types erased, so that any day can be held as a trait object
+///
+/// Every [`Day`] implements it, with the answers formatted as strings.
+pub trait DynDay: Sync + RefUnwindSafe {
+    /// Title of the puzzle
+    fn title(&self) -> &'static str;
+
+    /// Techniques used by the solution
+    fn tags(&self) -> &'static [&'static str];
+
+    /// Expected runtime of the day
+    fn cost_hint(&self) -> Cost;
+
+    /// Address of the puzzle description on adventofcode.com
+    fn url(&self) -> String;
+
+    /// Solve both parts without printing anything
+    fn solve(&self, fp: &str) -> Result<RunResult, MyErr>;
+
+    /// Benchmark both parts without printing anything, returning the median timings and the statistics of each phase
+    fn benchmark(&self, fp: &str, iterations: usize) -> Result<(RunResult, BenchStats), MyErr>;
+
+    /// Solve (or benchmark) both parts and print the results as JSON
+    fn json(&self, day: usize, fp: &str, bench: Option<usize>) -> Result<RunResult, MyErr>;
+
+    /// Solve both parts and compare the answers with the expected ones
+    fn check(&self, day: usize, fp: &str, answers: &Answers) -> Result<RunResult, MyErr>;
+
+    /// Write a flamegraph of each part
+    fn profile(&self, day: usize, fp: &str) -> Result<Vec<PathBuf>>;
+
+    /// Write the parsed input as JSON
+    fn dump(&self, fp: &str) -> Result<PathBuf>;
+
+    /// Parse the input and explain how the solution solves it, if the day can
+    fn explain(&self, fp: &str) -> Result<Option<String>, MyErr>;
+
+    /// Solve both parts on the examples and compare with the known answers
+    fn self_test(&self) -> [Outcome; 2];
+}
+
+impl<D: Day + Sync + RefUnwindSafe> DynDay for D {
+    fn title(&self) -> &'static str {
+        D::title()
+    }
+
+    fn tags(&self) -> &'static [&'static str] {
+        D::tags()
+    }
+
+    fn cost_hint(&self) -> Cost {
+        D::cost_hint()
+    }
+
+    fn url(&self) -> String {
+        D::url()
+    }
+
+    fn solve(&self, fp: &str) -> Result<RunResult, MyErr> {
+        D::run_day(fp)
+    }
+
+    fn benchmark(&self, fp: &str, iterations: usize) -> Result<(RunResult, BenchStats), MyErr> {
+        D::bench_day(fp, iterations)
+    }
+
+    fn json(&self, day: usize, fp: &str, bench: Option<usize>) -> Result<RunResult, MyErr> {
+        output::print_json::<D>(day, fp, bench)
+    }
+
+    fn check(&self, day: usize, fp: &str, answers: &Answers) -> Result<RunResult, MyErr> {
+        check::check_day::<D>(day, fp, answers)
+    }
+
+    fn profile(&self, day: usize, fp: &str) -> Result<Vec<PathBuf>> {
+        profile::profile_day::<D>(day, fp)
+    }
+
+    fn dump(&self, fp: &str) -> Result<PathBuf> {
+        dump::dump_parsed::<D>(fp)
+    }
+
+    fn explain(&self, fp: &str) -> Result<Option<String>, MyErr> {
+        let input_string = D::read_input(fp)?;
+        let input = D::parse_input(&input_string)?;
+        Ok(D::explain(&input, &D::params())?)
+    }
+
+    fn self_test(&self) -> [Outcome; 2] {
+        selftest::test_day::<D>()
+    }
+}
+
+/// A day and its implementation, so that any day can be looked up by its number
+#[derive(Clone, Copy)]
+pub struct Entry {
+    /// Taken from [`Day::DAY`]
+    pub day: usize,
+    pub solver: &'static dyn DynDay,
+}
+
+impl Entry {
+    /// The day with the title and tags of its puzzle, like `Day 17 — Clumsy Crucible [pathfinding, grid]`
+    pub fn name(&self) -> String {
+        let mut name = format!("Day {}", self.day);
+        let title = self.solver.title();
+        if !title.is_empty() {
+            let _ = write!(name, " — {title}");
+        }
+        let tags = self.solver.tags();
+        if !tags.is_empty() {
+            let _ = write!(name, " [{}]", tags.join(", "));
+        }
+        name
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.solver.tags().contains(&tag)
+    }
+}
+
+macro_rules! registry {
+    ($($feature:literal => $solver:path),* $(,)?) => {
+        /// All the days compiled in, in order
+        pub const DAYS: &[Entry] = &[$(
+            #[cfg(feature = $feature)]
+            Entry { day: <$solver as Day>::DAY as usize, solver: &$solver }
+        ),*];
+    };
+}
+
+registry! {
+    "day01" => day01::Day01,
+    "day02" => day02::Day02,
+    "day03" => day03::Day03,
+    "day04" => day04::Day04,
+    "day05" => day05::Day05,
+    "day06" => day06::Day06,
+    "day07" => day07::Day07,
+    "day08" => day08::Day08,
+    "day09" => day09::Day09,
+    "day10" => day10::Day10,
+    "day11" => day11::Day11,
+    "day12" => day12::Day12,
+    "day13" => day13::Day13,
+    "day14" => day14::Day14,
+    "day15" => day15::Day15,
+    "day16" => day16::Day16,
+    "day17" => day17::Day17,
+    "day18" => day18::Day18,
+    "day19" => day19::Day19,
+    "day20" => day20::Day20,
+    "day21" => day21::Day21,
+    "day22" => day22::Day22,
+    "day23" => day23::Day23,
+    "day24" => day24::Day24,
+    "day25" => day25::Day25,
+}
+
+/// The days in the order the parallel runner starts them: the slowest first, so that a slow day doesn't end up running
+/// alone at the end, and in calendar order among the days of the same cost
+pub fn by_cost(entries: &[Entry]) -> Vec<Entry> {
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|entry| Reverse(entry.solver.cost_hint()));
+    entries
+}
+
+/// Look up a day by its number
+pub fn get(day: usize) -> Option<&'static Entry> {
+    DAYS.iter().find(|entry| entry.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        assert!(DAYS.windows(2).all(|w| w[0].day < w[1].day));
+        assert!(get(26).is_none());
+        if cfg!(feature = "all-days") {
+            assert_eq!(DAYS.len(), 25);
+            for (i, entry) in DAYS.iter().enumerate() {
+                assert_eq!(entry.day, i + 1);
+            }
+            assert_eq!(get(17).unwrap().day, 17);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "day04")]
+    fn test_dyn_day() {
+        let boxed: Box<dyn DynDay> = Box::new(day04::Day04);
+        assert_eq!(boxed.self_test(), [Outcome::Pass, Outcome::Pass]);
+        assert_eq!(get(4).unwrap().solver.self_test(), boxed.self_test());
+        assert_eq!(boxed.url(), "https://adventofcode.com/2023/day/4");
+    }
+
+    #[test]
+    #[cfg(feature = "day17")]
+    fn test_name() {
+        let entry = get(17).unwrap();
+        assert_eq!(entry.name(), "Day 17 — Clumsy Crucible [pathfinding, grid]");
+        assert!(entry.has_tag("grid"));
+        assert!(!entry.has_tag("graph"));
+    }
+
+    #[test]
+    fn test_by_cost() {
+        let order = by_cost(DAYS);
+        assert_eq!(order.len(), DAYS.len());
+        assert!(order
+            .windows(2)
+            .all(|w| w[0].solver.cost_hint() > w[1].solver.cost_hint()
+                || (w[0].solver.cost_hint() == w[1].solver.cost_hint() && w[0].day < w[1].day)));
+        if cfg!(feature = "all-days") {
+            let slowest: Vec<_> = order.iter().take(3).map(|entry| entry.day).collect();
+            assert_eq!(slowest, [14, 23, 25]);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::{fmt::Write as _, fs, io, path::PathBuf};
+
+use crate::{
+    days::{RunResult, Timings},
+    registry,
+    viz::OUTPUT_DIR,
+};
+
+/// The answers of a day, or `None` if it couldn't be solved
+pub type Row = (usize, Option<RunResult>);
+
+const HEADERS: [&str; 8] = [
+    "Day",
+    "Title",
+    "Part 1",
+    "Part 2",
+    "Parse",
+    "Part 1 time",
+    "Part 2 time",
+    "Total",
+];
+
+/// Title of the puzzle of a day, empty if the day isn't in the registry
+fn title(day: usize) -> &'static str {
+    registry::get(day).map_or("", |entry| entry.solver.title())
+}
+
+/// Format all the cells of a row, with `-` for the days that couldn't be solved
+fn cells((day, solution): &Row) -> [String; 8] {
+    match solution {
+        None => [
+            day.to_string(),
+            title(*day).into(),
+            "-".into(),
+            "-".into(),
+            "-".into(),
+            "-".into(),
+            "-".into(),
+            "-".into(),
+        ],
+        Some(RunResult {
+            answers: [part1, part2],
+            timings,
+            ..
+        }) => [
+            day.to_string(),
+            title(*day).into(),
+            part1.to_string(),
+            part2.to_string(),
+            format!("{:.3?}", timings.parse),
+            format!("{:.3?}", timings.part1),
+            format!("{:.3?}", timings.part2),
+            format!("{:.3?}", timings.total()),
+        ],
+    }
+}
+
+/// Sum of the timings of all solved days
+fn grand_total(rows: &[Row]) -> Timings {
+    let mut total = Timings::default();
+    for solution in rows.iter().filter_map(|(_, s)| s.as_ref()) {
+        total.parse += solution.timings.parse;
+        total.part1 += solution.timings.part1;
+        total.part2 += solution.timings.part2;
+    }
+    total
+}
+
+/// Render the answers and timings as a markdown table, followed by the total time
+pub fn markdown(rows: &[Row]) -> String {
+    let mut md = String::new();
+    let _ = writeln!(md, "| {} |", HEADERS.join(" | "));
+    let _ = writeln!(md, "|{}", "---:|".repeat(HEADERS.len()));
+    for row in rows {
+        // pipes would end the cell early
+        let cells = cells(row).map(|c| c.replace('|', "\\|"));
+        let _ = writeln!(md, "| {} |", cells.join(" | "));
+    }
+    let _ = writeln!(md, "\nTotal time: {:.3?}", grand_total(rows).total());
+    md
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Render the answers and timings as a standalone HTML page
+pub fn html(rows: &[Row]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Advent of Code report</title>\n</head>\n<body>\n<table>\n",
+    );
+    let _ = writeln!(html, "<tr><th>{}</th></tr>", HEADERS.join("</th><th>"));
+    for row in rows {
+        let cells = cells(row).map(|c| escape_html(&c));
+        let _ = writeln!(html, "<tr><td>{}</td></tr>", cells.join("</td><td>"));
+    }
+    let _ = write!(
+        html,
+        "</table>\n<p>Total time: {:.3?}</p>\n</body>\n</html>\n",
+        grand_total(rows).total()
+    );
+    html
+}
+
+/// Write the report to `outputs/report.md`, and to `outputs/report.html` if requested
+///
+/// Returns the paths of the files that were written.
+pub fn write_report(rows: &[Row], with_html: bool) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let md_path = PathBuf::from(format!("{OUTPUT_DIR}/report.md"));
+    fs::write(&md_path, markdown(rows))?;
+    let mut written = vec![md_path];
+    if with_html {
+        let html_path = PathBuf::from(format!("{OUTPUT_DIR}/report.html"));
+        fs::write(&html_path, html(rows))?;
+        written.push(html_path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn rows() -> Vec<Row> {
+        vec![
+            (
+                1,
+                Some(RunResult {
+                    answers: ["142".into(), "a|b<c>".into()],
+                    timings: Timings {
+                        parse: Duration::from_micros(1),
+                        part1: Duration::from_micros(2),
+                        part2: Duration::from_micros(3),
+                        shared: false,
+                    },
+                    sub_phases: Vec::new(),
+                }),
+            ),
+            (2, None),
+        ]
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = markdown(&rows());
+        let lines: Vec<_> = md.lines().collect();
+        assert_eq!(
+            lines[0],
+            "| Day | Title | Part 1 | Part 2 | Parse | Part 1 time | Part 2 time | Total |"
+        );
+        assert_eq!(
+            lines[2],
+            format!(
+                "| 1 | {} | 142 | a\\|b<c> | 1.000µs | 2.000µs | 3.000µs | 6.000µs |",
+                title(1)
+            )
+        );
+        assert_eq!(
+            lines[3],
+            format!("| 2 | {} | - | - | - | - | - | - |", title(2))
+        );
+        assert_eq!(lines[5], "Total time: 6.000µs");
+    }
+
+    #[test]
+    fn test_html() {
+        let html = html(&rows());
+        assert!(html.contains("<td>a|b&lt;c&gt;</td>"));
+        assert!(html.contains("<p>Total time: 6.000µs</p>"));
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    any::Any,
+    fmt::{self, Display},
+    panic,
+};
+
+use crate::days::Day;
+
+/// Result of solving a part on its example
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Fail {
+        expected: String,
+        actual: String,
+    },
+    /// The input could not be parsed, or the solution failed or panicked
+    Error(String),
+    /// There is no known answer for this example
+    Skipped,
+}
+
+impl Outcome {
+    fn new(expected: &str, actual: Result<String, String>) -> Self {
+        match actual {
+            Ok(actual) if actual == expected => Self::Pass,
+            Ok(actual) => Self::Fail {
+                expected: expected.to_string(),
+                actual,
+            },
+            Err(e) => Self::Error(e),
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Fail { .. } | Self::Error(_))
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Pass => "PASS",
+            Self::Fail { .. } => "FAIL",
+            Self::Error(_) => "ERROR",
+            Self::Skipped => "skip",
+        })
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Parse an example and solve one part of it, turning parse errors, solve errors and panics into errors
+fn solve<D: Day>(part: u8) -> Result<String, String> {
+    let example = if part == 1 {
+        D::EXAMPLE
+    } else {
+        D::EXAMPLE_PART2
+    };
+    panic::catch_unwind(|| {
+        let input =
+            D::parse_input(example).map_err(|e| format!("could not parse the example: {e}"))?;
+        let answer = if part == 1 {
+            D::part_1(&input, &D::EXAMPLE_PARAMS).map(|output| output.to_string())
+        } else {
+            D::part_2(&input, &D::EXAMPLE_PARAMS).map(|output| output.to_string())
+        };
+        answer.map_err(|e| format!("could not solve the example: {e}"))
+    })
+    .unwrap_or_else(|payload| Err(format!("panicked: {}", panic_message(&*payload))))
+}
+
+/// Solve both parts of a day on the examples from the puzzle description and compare with the known answers
+pub fn test_day<D: Day>() -> [Outcome; 2] {
+    [1, 2].map(|part| match D::EXAMPLE_ANSWERS[usize::from(part) - 1] {
+        Some(expected) => Outcome::new(expected, solve::<D>(part)),
+        None => Outcome::Skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome() {
+        assert_eq!(Outcome::new("13", Ok("13".to_string())), Outcome::Pass);
+        assert!(Outcome::new("13", Ok("12".to_string())).is_failure());
+        assert!(Outcome::new("13", Err("panicked".to_string())).is_failure());
+        assert!(!Outcome::Skipped.is_failure());
+    }
+
+    #[test]
+    #[cfg(feature = "day04")]
+    fn test_day04() {
+        use crate::days::day04::Day04;
+        assert_eq!(test_day::<Day04>(), [Outcome::Pass, Outcome::Pass]);
+    }
+}
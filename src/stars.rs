@@ -0,0 +1,146 @@
+//! The stars verified during this run
+//!
+//! A part earns its star when its answer matches a stored one: the expected answer of the `--check` file, or the
+//! answer cached by a previous run on the same input. Solving a part without anything to compare with doesn't count.
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::{collections::BTreeSet, sync::Mutex};
+
+use crate::{answer::Answer, days::RunResult};
+
+/// The (day, part) pairs whose answer was verified during this run
+static VERIFIED: Mutex<BTreeSet<(usize, u8)>> = Mutex::new(BTreeSet::new());
+
+/// Record that the answer to a part matched a stored answer
+pub fn record(day: usize, part: u8) {
+    VERIFIED.lock().unwrap().insert((day, part));
+}
+
+/// The (day, part) pairs verified so far
+pub fn verified() -> BTreeSet<(usize, u8)> {
+    VERIFIED.lock().unwrap().clone()
+}
+
+/// List the (day, part) pairs which are not in `verified` among the ones needed for part 2 of `day`: both parts of
+/// all the days before it, and its own part 1
+pub fn missing_before(verified: &BTreeSet<(usize, u8)>, day: usize) -> Vec<(usize, u8)> {
+    (1..day)
+        .flat_map(|d| [(d, 1), (d, 2)])
+        .chain([(day, 1)])
+        .filter(|star| !verified.contains(star))
+        .collect()
+}
+
+/// `complete` when all the stars needed for part 2 of `day` are in `verified`, or the missing ones like
+/// `missing: days 1-3`
+pub fn completion(verified: &BTreeSet<(usize, u8)>, day: usize) -> String {
+    let missing = missing_before(verified, day);
+    if missing.is_empty() {
+        "complete".to_string()
+    } else {
+        format!("missing: {}", describe(&missing))
+    }
+}
+
+/// Fill in the answer to day 25 part 2 again with the stars verified so far
+///
+/// It needs the star of its own part 1, which is only verified after the day is solved.
+pub fn refresh_completion(day: usize, result: &mut RunResult) {
+    if day == 25 {
+        result.answers[1] = Answer::Text(completion(&verified(), day));
+    }
+}
+
+/// Describe the missing stars, merging the consecutive days missing both of them, like `days 1-3, day 5 part 2`
+pub fn describe(missing: &[(usize, u8)]) -> String {
+    let days = |(first, last): (usize, usize)| {
+        if first == last {
+            format!("day {first}")
+        } else {
+            format!("days {first}-{last}")
+        }
+    };
+    let mut items = Vec::new();
+    // the current run of days missing both stars
+    let mut whole: Option<(usize, usize)> = None;
+    for parts in missing.chunk_by(|a, b| a.0 == b.0) {
+        let day = parts[0].0;
+        if parts.len() == 2 {
+            match &mut whole {
+                Some((_, last)) if *last + 1 == day => *last = day,
+                _ => {
+                    items.extend(whole.map(days));
+                    whole = Some((day, day));
+                }
+            }
+        } else {
+            items.extend(whole.take().map(days));
+            items.extend(
+                parts
+                    .iter()
+                    .map(|(_, part)| format!("day {day} part {part}")),
+            );
+        }
+    }
+    items.extend(whole.map(days));
+    items.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing() {
+        let mut verified: BTreeSet<_> = (1..25)
+            .flat_map(|d| [(d, 1), (d, 2)])
+            .chain([(25, 1)])
+            .collect();
+        assert_eq!(missing_before(&verified, 25), vec![]);
+        for star in [
+            (1, 1),
+            (1, 2),
+            (2, 1),
+            (2, 2),
+            (3, 1),
+            (5, 2),
+            (7, 1),
+            (7, 2),
+        ] {
+            verified.remove(&star);
+        }
+        let missing = missing_before(&verified, 25);
+        assert_eq!(missing.len(), 8);
+        assert_eq!(
+            describe(&missing),
+            "days 1-2, day 3 part 1, day 5 part 2, day 7"
+        );
+        assert_eq!(
+            describe(&missing_before(&BTreeSet::new(), 25)),
+            "days 1-24, day 25 part 1"
+        );
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut verified: BTreeSet<_> = (1..25)
+            .flat_map(|d| [(d, 1), (d, 2)])
+            .chain([(25, 1)])
+            .collect();
+        assert_eq!(completion(&verified, 25), "complete");
+        verified.remove(&(24, 2));
+        assert_eq!(completion(&verified, 25), "missing: day 24 part 2");
+        assert_eq!(
+            completion(&BTreeSet::new(), 25),
+            "missing: days 1-24, day 25 part 1"
+        );
+    }
+
+    #[test]
+    fn test_completion_without_day25_part1() {
+        let mut verified: BTreeSet<_> = (1..=25).flat_map(|d| [(d, 1), (d, 2)]).collect();
+        verified.remove(&(25, 1));
+        verified.remove(&(25, 2));
+        assert_eq!(completion(&verified, 25), "missing: day 25 part 1");
+    }
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+/// Summary statistics over a set of duration samples
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Compute the statistics for the samples, which are all zero if there are no samples
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let len = sorted.len();
+        let median = if len.is_multiple_of(2) {
+            (sorted[len / 2 - 1] + sorted[len / 2]) / 2
+        } else {
+            sorted[len / 2]
+        };
+        let mean = sorted.iter().map(Duration::as_secs_f64).sum::<f64>() / len as f64;
+        // population standard deviation
+        let variance = sorted
+            .iter()
+            .map(|d| (d.as_secs_f64() - mean).powi(2))
+            .sum::<f64>()
+            / len as f64;
+        Self {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let samples = [2, 4, 4, 4, 5, 5, 7, 9].map(Duration::from_millis);
+        let stats = Stats::new(&samples);
+        assert_eq!(stats.min, Duration::from_millis(2));
+        assert_eq!(
+            stats.median,
+            Duration::from_millis(4) + Duration::from_micros(500)
+        );
+        assert_eq!(stats.mean, Duration::from_millis(5));
+        assert_eq!(stats.stddev, Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_stats_odd() {
+        let samples = [3, 1, 2].map(Duration::from_millis);
+        assert_eq!(Stats::new(&samples).median, Duration::from_millis(2));
+        assert_eq!(Stats::new(&[]), Stats::default());
+    }
+}
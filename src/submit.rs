@@ -0,0 +1,59 @@
+use anyhow::{bail, Result};
+
+use crate::{inputs, registry};
+
+/// Extract the text of the message in the page returned by the website after submitting an answer
+fn message(html: &str) -> Option<String> {
+    let start = html.find("<article>")? + "<article>".len();
+    let end = start + html[start..].find("</article>")?;
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html[start..end].chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Submit the answer to a part of a day, returning the response from the website
+pub fn submit(day: usize, part: u8, answer: &str) -> Result<String> {
+    let Some(entry) = registry::get(day) else {
+        bail!("Provided unsupported day {day}");
+    };
+    let session = inputs::session()?;
+    let url = format!("{}/answer", entry.solver.url());
+    let client = reqwest::blocking::Client::new();
+    let response = client
+        .post(url)
+        .header("cookie", format!("session={session};"))
+        .form(&[("level", part.to_string().as_str()), ("answer", answer)])
+        .send()?;
+
+    if !response.status().is_success() {
+        bail!(
+            "Could not submit the answer for day {day} ({}). Is your session cookie correct?",
+            response.status()
+        );
+    }
+    let html = response.text()?;
+    Ok(message(&html).unwrap_or(html))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message() {
+        let html = "<main>\n<article><p>That's the <em>right answer</em>!  You are <span>one gold star</span> closer.</p></article>\n</main>";
+        assert_eq!(
+            message(html).unwrap(),
+            "That's the right answer! You are one gold star closer."
+        );
+        assert!(message("<main></main>").is_none());
+    }
+}
@@ -0,0 +1,237 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::{
+    cell::RefCell,
+    fmt::{self, Display},
+    mem,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use crate::days::Timings;
+
+/// A phase of the run of a day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+    /// Both parts solved together, by [`crate::days::Day::solve_both`] or concurrently
+    Both,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Phase::Parse => "Parsing",
+            Phase::Part1 => "Part 1",
+            Phase::Part2 => "Part 2",
+            Phase::Both => "Both parts",
+        })
+    }
+}
+
+/// Time spent in a step of a phase, reported by the solution with [`sub_phase`]
+///
+/// A step which ran several times during the phase (e.g. in a loop) is reported once, with the total time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubPhase {
+    pub phase: Phase,
+    pub name: &'static str,
+    pub elapsed: Duration,
+    /// How many times the step ran
+    pub calls: usize,
+}
+
+impl Display for SubPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "  {} took {:?}", self.name, self.elapsed)?;
+        if self.calls > 1 {
+            write!(f, " over {} calls", self.calls)?;
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    /// Steps reported by the solution during the phase running on this thread
+    static REPORTED: RefCell<Vec<(&'static str, Duration)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Time a step of a solution, which is then reported under the phase it ran in
+///
+/// Only the steps which run on the thread of the runner are collected, so not the ones of `--concurrent-parts` or of a
+/// parallel iterator.
+pub fn sub_phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let output = f();
+    let elapsed = start.elapsed();
+    REPORTED.with_borrow_mut(|reported| reported.push((name, elapsed)));
+    output
+}
+
+/// Measures the phases of a day, along with the steps the solution reports with [`sub_phase`]
+#[derive(Debug, Default)]
+pub struct PhaseTimer {
+    timings: Timings,
+    sub_phases: Vec<SubPhase>,
+}
+
+impl PhaseTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run a phase and record how long it took, returning its output
+    pub fn time<T>(&mut self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        REPORTED.with_borrow_mut(Vec::clear);
+        let start = Instant::now();
+        let output = f();
+        let elapsed = start.elapsed();
+        match phase {
+            Phase::Parse => self.timings.parse += elapsed,
+            Phase::Part1 => self.timings.part1 += elapsed,
+            Phase::Part2 => self.timings.part2 += elapsed,
+            Phase::Both => {
+                self.timings.part2 += elapsed;
+                self.timings.shared = true;
+            }
+        }
+        for (name, elapsed) in REPORTED.with_borrow_mut(mem::take) {
+            match self
+                .sub_phases
+                .iter_mut()
+                .find(|s| s.phase == phase && s.name == name)
+            {
+                Some(sub_phase) => {
+                    sub_phase.elapsed += elapsed;
+                    sub_phase.calls += 1;
+                }
+                None => self.sub_phases.push(SubPhase {
+                    phase,
+                    name,
+                    elapsed,
+                    calls: 1,
+                }),
+            }
+        }
+        output
+    }
+
+    pub fn timings(&self) -> Timings {
+        self.timings
+    }
+
+    pub fn finish(self) -> (Timings, Vec<SubPhase>) {
+        (self.timings, self.sub_phases)
+    }
+}
+
+/// How long a part took, with and without parsing, followed by the steps reported during that part
+pub fn describe(timings: &Timings, sub_phases: &[SubPhase], phase: Phase) -> String {
+    let elapsed = match phase {
+        Phase::Parse => timings.parse,
+        Phase::Part1 => timings.part1,
+        Phase::Part2 | Phase::Both => timings.part2,
+    };
+    let mut text = if phase == Phase::Parse {
+        format!("{phase} took {elapsed:?}")
+    } else {
+        format!(
+            "{phase} took {elapsed:?} ({:?} with parsing)",
+            elapsed + timings.parse
+        )
+    };
+    for sub_phase in sub_phases.iter().filter(|s| s.phase == phase) {
+        text.push('\n');
+        text.push_str(&sub_phase.to_string());
+    }
+    text
+}
+
+/// The timings as JSON fields, with the durations in nanoseconds and the reported steps in `sub_phases` if there are
+/// any (with the number of `calls` of the steps which ran more than once)
+#[allow(clippy::cast_possible_truncation)]
+pub fn json(timings: &Timings, sub_phases: &[SubPhase]) -> Value {
+    let mut doc = json!({
+        "parse_ns": timings.parse.as_nanos() as u64,
+        "part1_ns": timings.part1.as_nanos() as u64,
+        "part2_ns": timings.part2.as_nanos() as u64,
+    });
+    if !sub_phases.is_empty() {
+        doc["sub_phases"] = sub_phases
+            .iter()
+            .map(|s| {
+                let mut step = json!({ "phase": s.phase.to_string(), "name": s.name, "ns": s.elapsed.as_nanos() as u64 });
+                if s.calls > 1 {
+                    step["calls"] = s.calls.into();
+                }
+                step
+            })
+            .collect();
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_timer() {
+        let mut timer = PhaseTimer::new();
+        let parsed = timer.time(Phase::Parse, || sub_phase("split lines", || 21));
+        let answer = timer.time(Phase::Part1, || parsed * 2);
+        assert_eq!(answer, 42);
+        sub_phase("outside of a phase", || ());
+        timer.time(Phase::Part2, || {
+            for _ in 0..3 {
+                sub_phase("inner loop", || ());
+            }
+        });
+        let (timings, sub_phases) = timer.finish();
+        assert!(!timings.shared);
+        assert_eq!(sub_phases.len(), 2);
+        assert_eq!(sub_phases[0].phase, Phase::Parse);
+        assert_eq!(sub_phases[0].name, "split lines");
+        assert_eq!(sub_phases[0].calls, 1);
+        assert!(sub_phases[0].elapsed <= timings.parse);
+        assert_eq!(
+            (sub_phases[1].phase, sub_phases[1].calls),
+            (Phase::Part2, 3)
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let timings = Timings {
+            parse: Duration::from_micros(1),
+            part2: Duration::from_micros(3),
+            ..Timings::default()
+        };
+        let sub_phases = [SubPhase {
+            phase: Phase::Part2,
+            name: "find cycles",
+            elapsed: Duration::from_micros(2),
+            calls: 1,
+        }];
+        assert_eq!(
+            describe(&timings, &sub_phases, Phase::Part2),
+            "Part 2 took 3µs (4µs with parsing)\n  find cycles took 2µs"
+        );
+        assert_eq!(
+            describe(&timings, &sub_phases, Phase::Part1),
+            "Part 1 took 0ns (1µs with parsing)"
+        );
+        let repeated = SubPhase {
+            calls: 4,
+            ..sub_phases[0].clone()
+        };
+        assert_eq!(repeated.to_string(), "  find cycles took 2µs over 4 calls");
+        assert_eq!(
+            json(&timings, &sub_phases).to_string(),
+            r#"{"parse_ns":1000,"part1_ns":0,"part2_ns":3000,"sub_phases":[{"name":"find cycles","ns":2000,"phase":"Part 2"}]}"#
+        );
+    }
+}
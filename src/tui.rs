@@ -0,0 +1,365 @@
+use std::{
+    io::{self, stdout},
+    panic,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+
+use crate::{color, days::RunResult, registry, solve_day};
+
+/// How long to wait for a key press before redrawing, so that the timer of the running day stays live
+const TICK: Duration = Duration::from_millis(100);
+
+const HELP: &str = "↑/↓ select  r rerun  1/2 toggle part  q quit";
+
+enum Status {
+    /// Waiting in the worker's queue
+    Pending,
+    Running(Instant),
+    Solved(RunResult),
+    Failed(String),
+}
+
+/// Progress reported by the worker thread
+enum Update {
+    Started(usize),
+    Finished(usize, Result<RunResult, String>),
+}
+
+/// Puts the terminal in raw mode on the alternate screen, and restores it when dropped (including when unwinding)
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> io::Result<Self> {
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// Redirects stderr into a pipe while it is alive, so that what the days print while they are solved in the background
+/// doesn't draw over the dashboard, and prints what was captured once dropped
+///
+/// The last line captured is shown in the footer.
+#[cfg(unix)]
+struct StderrCapture {
+    /// The original stderr, restored when dropped
+    stderr: std::os::fd::OwnedFd,
+    lines: Arc<Mutex<Vec<String>>>,
+    reader: Option<thread::JoinHandle<()>>,
+}
+
+#[cfg(unix)]
+impl StderrCapture {
+    fn new() -> io::Result<Self> {
+        use std::{
+            io::{BufRead, BufReader},
+            os::fd::AsFd,
+        };
+
+        let stderr = io::stderr().as_fd().try_clone_to_owned()?;
+        let (pipe, writer) = io::pipe()?;
+        rustix::stdio::dup2_stderr(&writer)?;
+        // stderr is now the only writing end, so the reader stops once the original stderr is restored
+        drop(writer);
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let reader = thread::spawn({
+            let lines = Arc::clone(&lines);
+            move || {
+                for line in BufReader::new(pipe).split(b'\n').map_while(Result::ok) {
+                    let line = String::from_utf8_lossy(&line).into_owned();
+                    lines.lock().unwrap().push(line);
+                }
+            }
+        });
+        Ok(Self {
+            stderr,
+            lines,
+            reader: Some(reader),
+        })
+    }
+
+    fn last_line(&self) -> Option<String> {
+        self.lines.lock().unwrap().last().cloned()
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StderrCapture {
+    fn drop(&mut self) {
+        if rustix::stdio::dup2_stderr(&self.stderr).is_err() {
+            return;
+        }
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+        for line in self.lines.lock().unwrap().iter() {
+            eprintln!("{line}");
+        }
+    }
+}
+
+/// Capturing stderr needs to redirect its file descriptor, which is only done on unix
+#[cfg(not(unix))]
+struct StderrCapture;
+
+#[cfg(not(unix))]
+impl StderrCapture {
+    fn new() -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    fn last_line(&self) -> Option<String> {
+        None
+    }
+}
+
+struct App {
+    /// The status of each day, indexed like [`registry::DAYS`]
+    statuses: Vec<Status>,
+    table: TableState,
+    show_part1: bool,
+    show_part2: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            statuses: registry::DAYS.iter().map(|_| Status::Pending).collect(),
+            table: TableState::default().with_selected((!registry::DAYS.is_empty()).then_some(0)),
+            show_part1: true,
+            show_part2: true,
+        }
+    }
+
+    fn index(day: usize) -> usize {
+        registry::DAYS
+            .iter()
+            .position(|entry| entry.day == day)
+            .expect("the worker only solves registered days")
+    }
+
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::Started(day) => {
+                self.statuses[Self::index(day)] = Status::Running(Instant::now());
+            }
+            Update::Finished(day, result) => {
+                self.statuses[Self::index(day)] = match result {
+                    Ok(solution) => Status::Solved(solution),
+                    Err(e) => Status::Failed(e),
+                };
+            }
+        }
+    }
+
+    fn select(&mut self, offset: isize) {
+        let Some(last) = self.statuses.len().checked_sub(1) else {
+            return;
+        };
+        let selected = self.table.selected().unwrap_or(0);
+        self.table
+            .select(Some(selected.saturating_add_signed(offset).min(last)));
+    }
+
+    /// Queue the selected day again, unless it is already waiting or running
+    fn rerun(&mut self, jobs: &Sender<usize>) -> Result<()> {
+        let Some(i) = self.table.selected() else {
+            return Ok(());
+        };
+        if matches!(self.statuses[i], Status::Solved(_) | Status::Failed(_)) {
+            self.statuses[i] = Status::Pending;
+            jobs.send(registry::DAYS[i].day)?;
+        }
+        Ok(())
+    }
+
+    /// Draw the table of the days, and a footer with the error of the selected day or the help, and the last line
+    /// printed on stderr
+    fn draw(&mut self, frame: &mut Frame, stderr: Option<String>) {
+        let [table_area, footer_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(4)]).areas(frame.area());
+
+        let mut header = vec!["Day", "Status"];
+        let mut widths = vec![Constraint::Length(5), Constraint::Length(12)];
+        for (shown, title) in [(self.show_part1, "Part 1"), (self.show_part2, "Part 2")] {
+            if shown {
+                header.push(title);
+                widths.push(Constraint::Fill(1));
+            }
+        }
+        header.push("Total");
+        widths.push(Constraint::Length(12));
+
+        let rows = registry::DAYS
+            .iter()
+            .zip(&self.statuses)
+            .map(|(entry, status)| self.row(entry.day, status));
+        let table = Table::new(rows, widths)
+            .header(Row::new(header).style(Style::new().add_modifier(Modifier::BOLD)))
+            .block(Block::bordered().title(" Advent of Code 2023 "))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, table_area, &mut self.table);
+
+        let message = match self.table.selected().map(|i| &self.statuses[i]) {
+            Some(Status::Failed(e)) => Line::styled(e.as_str(), style(Color::Red)),
+            _ => Line::raw(HELP),
+        };
+        let stderr = Line::styled(stderr.unwrap_or_default(), style(Color::DarkGray));
+        frame.render_widget(
+            Paragraph::new(vec![message, stderr]).block(Block::bordered()),
+            footer_area,
+        );
+    }
+
+    fn row(&self, day: usize, status: &Status) -> Row<'static> {
+        let (label, color) = match status {
+            Status::Pending => ("pending".to_string(), Color::DarkGray),
+            Status::Running(start) => (format!("running {:.1?}", start.elapsed()), Color::Yellow),
+            Status::Solved(_) => ("solved".to_string(), Color::Green),
+            Status::Failed(_) => ("failed".to_string(), Color::Red),
+        };
+        let mut cells = vec![
+            Line::raw(day.to_string()),
+            Line::styled(label, style(color)),
+        ];
+        let parts = match status {
+            Status::Solved(result) => [
+                format!("{} ({:.3?})", result.answers[0], result.timings.part1),
+                format!("{} ({:.3?})", result.answers[1], result.timings.part2),
+            ],
+            _ => [String::new(), String::new()],
+        };
+        for (shown, part) in [self.show_part1, self.show_part2].into_iter().zip(parts) {
+            if shown {
+                cells.push(Line::raw(part));
+            }
+        }
+        cells.push(match status {
+            Status::Solved(result) => Line::raw(format!("{:.3?}", result.timings.total())),
+            _ => Line::default(),
+        });
+        Row::new(cells)
+    }
+}
+
+/// The style for a status, unless colors are disabled
+fn style(color: Color) -> Style {
+    if color::enabled() {
+        Style::new().fg(color)
+    } else {
+        Style::new()
+    }
+}
+
+/// Solve the days sent on the returned channel one after the other, reporting progress on `updates`
+fn spawn_worker(updates: Sender<Update>) -> Sender<usize> {
+    let (jobs, queue) = mpsc::channel();
+    thread::spawn(move || {
+        for day in queue {
+            if updates.send(Update::Started(day)).is_err() {
+                break;
+            }
+            let result = panic::catch_unwind(|| solve_day(day))
+                .unwrap_or_else(|_| Err(anyhow!("day {day} panicked")));
+            let result = result.map_err(|e| format!("{e:#}"));
+            if updates.send(Update::Finished(day, result)).is_err() {
+                break;
+            }
+        }
+    });
+    jobs
+}
+
+/// Show a table of all days which fills up as they are solved in the background, until the user quits
+pub fn run() -> Result<()> {
+    // dropped after the terminal is restored, so that the captured lines are printed on the normal screen
+    let stderr = StderrCapture::new()?;
+    let (updates_tx, updates): (_, Receiver<Update>) = mpsc::channel();
+    let jobs = spawn_worker(updates_tx);
+    for entry in registry::DAYS {
+        jobs.send(entry.day)?;
+    }
+
+    let mut app = App::new();
+    let _guard = TerminalGuard::new()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    loop {
+        for update in updates.try_iter() {
+            app.apply(update);
+        }
+        terminal.draw(|frame| app.draw(frame, stderr.last_line()))?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => app.select(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.select(1),
+            KeyCode::Char('r') | KeyCode::Enter => app.rerun(&jobs)?,
+            KeyCode::Char('1') => app.show_part1 = !app.show_part1,
+            KeyCode::Char('2') => app.show_part2 = !app.show_part2,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let mut app = App::new();
+        app.select(-1);
+        assert_eq!(
+            app.table.selected(),
+            (!registry::DAYS.is_empty()).then_some(0)
+        );
+        app.select(3);
+        assert_eq!(
+            app.table.selected(),
+            registry::DAYS.len().checked_sub(1).map(|last| 3.min(last))
+        );
+        app.select(100);
+        assert_eq!(app.table.selected(), registry::DAYS.len().checked_sub(1));
+        // no day is compiled in
+        app.statuses.clear();
+        app.table.select(None);
+        app.select(1);
+        assert_eq!(app.table.selected(), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::{array, iter, ops::Add};
+
+use num::One;
+
+use super::Vec2;
+
+/// An axis-aligned box in `N` dimensions, between the corners `min` and `max` which are both inside of it
+///
+/// The coordinates of a point are given in an array, `[x, y]` or `[x, y, z]`. A box whose `min` is above its `max` on
+/// some axis is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Aabb<T, const N: usize> {
+    pub min: [T; N],
+    pub max: [T; N],
+}
+
+impl<T: Copy + PartialOrd, const N: usize> Aabb<T, N> {
+    pub const fn new(min: [T; N], max: [T; N]) -> Self {
+        Self { min, max }
+    }
+
+    /// The box holding a single point
+    pub const fn point(point: [T; N]) -> Self {
+        Self::new(point, point)
+    }
+
+    /// The smallest box holding all the points, `None` if there are none
+    pub fn from_points(points: impl IntoIterator<Item = [T; N]>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let mut aabb = Self::point(points.next()?);
+        for point in points {
+            aabb.expand(point);
+        }
+        Some(aabb)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|axis| self.min[axis] > self.max[axis])
+    }
+
+    pub fn contains(&self, point: [T; N]) -> bool {
+        (0..N).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+
+    /// Grow the box just enough to hold `point`
+    pub fn expand(&mut self, point: [T; N]) {
+        for (axis, value) in point.into_iter().enumerate() {
+            if value < self.min[axis] {
+                self.min[axis] = value;
+            }
+            if value > self.max[axis] {
+                self.max[axis] = value;
+            }
+        }
+    }
+
+    /// The box of the points inside of both boxes, `None` if they don't overlap
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let pick = |a: T, b: T, larger: bool| if (a < b) == larger { b } else { a };
+        let aabb = Self::new(
+            array::from_fn(|axis| pick(self.min[axis], other.min[axis], true)),
+            array::from_fn(|axis| pick(self.max[axis], other.max[axis], false)),
+        );
+        (!aabb.is_empty()).then_some(aabb)
+    }
+
+    /// The integer points of the box, with the first axis changing the fastest, so row by row in two dimensions
+    pub fn points(&self) -> impl Iterator<Item = [T; N]>
+    where
+        T: One + Add<Output = T>,
+    {
+        let Self { min, max } = *self;
+        let first = (!self.is_empty()).then_some(min);
+        iter::successors(first, move |&point| {
+            let mut next = point;
+            // increment the first coordinate which isn't at its maximum, and go back to the minimum for the ones before
+            for axis in 0..N {
+                if next[axis] < max[axis] {
+                    next[axis] = next[axis] + T::one();
+                    return Some(next);
+                }
+                next[axis] = min[axis];
+            }
+            None
+        })
+    }
+}
+
+impl<T> From<Vec2<T>> for [T; 2] {
+    fn from(Vec2 { x, y }: Vec2<T>) -> Self {
+        [x, y]
+    }
+}
+
+impl<T> From<[T; 2]> for Vec2<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Self::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aabb() {
+        let mut aabb = Aabb::from_points([[1, 5], [3, 2]]).unwrap();
+        assert_eq!(aabb, Aabb::new([1, 2], [3, 5]));
+        assert!(aabb.contains([3, 5]) && !aabb.contains([0, 3]));
+        aabb.expand(Vec2::new(0, 3).into());
+        assert_eq!(aabb.min, [0, 2]);
+        assert_eq!(
+            aabb.intersect(&Aabb::new([2, 4], [9, 9])),
+            Some(Aabb::new([2, 4], [3, 5]))
+        );
+        assert_eq!(aabb.intersect(&Aabb::point([4, 4])), None);
+        assert_eq!(Aabb::<i32, 2>::from_points([]), None);
+        // points in reading order
+        assert_eq!(
+            Aabb::new([0, 0], [1, 1])
+                .points()
+                .map(Vec2::from)
+                .collect::<Vec<_>>(),
+            [
+                Vec2::new(0, 0),
+                Vec2::new(1, 0),
+                Vec2::new(0, 1),
+                Vec2::new(1, 1)
+            ]
+        );
+        let cube = Aabb::new([0u8, 0, 0], [2, 1, 3]);
+        assert_eq!(cube.points().count(), 24);
+        assert!(cube.points().all(|p| cube.contains(p)));
+        assert_eq!(Aabb::new([1, 1, 1], [0, 5, 5]).points().count(), 0);
+    }
+}
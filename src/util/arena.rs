@@ -0,0 +1,45 @@
+//! Scratch memory for the short-lived vectors of a part
+//!
+//! A part which builds a vector for each BFS, removed brick or merged node can allocate them in a bump arena instead
+//! of the global allocator: an allocation is a pointer increment, and everything is freed at once at the end of the
+//! scope. Each thread keeps its arena between the scopes, so only the first run pays for its memory.
+use std::cell::RefCell;
+
+pub use bumpalo::{collections::Vec as ArenaVec, vec as arena_vec, Bump};
+
+thread_local! {
+    static ARENA: RefCell<Bump> = RefCell::new(Bump::new());
+}
+
+/// Run `f` with a scratch arena, all of whose allocations are freed when it returns
+///
+/// The result can't borrow from the arena. A scope opened inside of another one gets an arena of its own.
+pub fn with_arena<T>(f: impl FnOnce(&Bump) -> T) -> T {
+    ARENA.with(|arena| {
+        let Ok(mut arena) = arena.try_borrow_mut() else {
+            // the arena of the thread is used by an enclosing scope
+            return f(&Bump::new());
+        };
+        let result = f(&arena);
+        arena.reset();
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_arena() {
+        let sum = with_arena(|arena| {
+            let mut values = ArenaVec::new_in(arena);
+            values.extend(1..=10);
+            let nested = with_arena(|arena| arena_vec![in arena; 2; 5].iter().sum::<i32>());
+            values.iter().sum::<i32>() + nested
+        });
+        assert_eq!(sum, 65);
+        // the arena is available again once the scope is closed
+        assert_eq!(with_arena(|arena| *arena.alloc(7)), 7);
+    }
+}
@@ -0,0 +1,41 @@
+use std::ops::Range;
+
+use num::PrimInt;
+
+/// The first value of `range` for which `predicate` is false, by binary search
+///
+/// Like `slice::partition_point`, the predicate must be true for the values at the start of the range and false for
+/// the rest. Returns `range.end` when it's true for the whole range (and `range.start` when the range is empty).
+pub fn partition_point_by<T: PrimInt>(range: Range<T>, mut predicate: impl FnMut(T) -> bool) -> T {
+    let (mut low, mut high) = (range.start, range.end);
+    while low < high {
+        let mid = low + (high - low) / (T::one() + T::one());
+        if predicate(mid) {
+            low = mid + T::one();
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_point_by() {
+        // the integer square root is the last value whose square fits
+        let isqrt = |n: u64| partition_point_by(0..1 << 32, |x| x * x <= n) - 1;
+        assert_eq!([0, 1, 15, 16, 17].map(isqrt), [0, 1, 3, 4, 4]);
+        let values = [-7, -3, 0, 2, 2, 9];
+        for target in -8..10 {
+            assert_eq!(
+                partition_point_by(0..values.len(), |i| values[i] < target),
+                values.partition_point(|&v| v < target)
+            );
+        }
+        assert_eq!(partition_point_by(-10i32..-5, |_| true), -5);
+        assert_eq!(partition_point_by(4u8..4, |_| true), 4);
+    }
+}
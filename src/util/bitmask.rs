@@ -0,0 +1,177 @@
+//! Sets of up to 64 small indices packed in a `u64`, for the dynamic programming over subsets of a few nodes
+//!
+//! Unlike [`BitSet`](super::BitSet), a mask never allocates and is `Copy`, so it can be part of the state of a search
+//! or the key of a memo.
+
+use std::iter;
+
+use serde::{Deserialize, Serialize};
+
+/// Every subset of `mask`, including the empty one and `mask` itself, from the largest value down
+pub fn subsets(mask: u64) -> impl Iterator<Item = u64> {
+    let mut next = Some(mask);
+    iter::from_fn(move || {
+        let subset = next?;
+        next = (subset != 0).then(|| (subset - 1) & mask);
+        Some(subset)
+    })
+}
+
+/// Every mask of `k` bits among the `n` lowest ones, in increasing order (Gosper's hack)
+///
+/// # Panics
+/// If `n` is over 63, since the masks must fit in a `u64` along with the carry of the last one.
+pub fn combinations(n: u32, k: u32) -> impl Iterator<Item = u64> {
+    assert!(n < u64::BITS, "too many bits for a mask");
+    let end = 1u64 << n;
+    let mut next = (k <= n).then(|| (1u64 << k) - 1);
+    iter::from_fn(move || {
+        let mask = next?;
+        next = (mask != 0)
+            .then(|| {
+                let lowest = mask.isolate_lowest_one();
+                let carry = mask + lowest;
+                carry | (((mask ^ carry) >> 2) / lowest)
+            })
+            .filter(|&m| m < end);
+        Some(mask)
+    })
+}
+
+/// The number of ones of each mask of `bits` bits, so that a table indexed by mask can be walked by size
+///
+/// # Panics
+/// If the table would have more than `2^32` entries.
+pub fn popcounts(bits: u32) -> Vec<u8> {
+    assert!(bits <= 32, "too many bits for a table");
+    let mut counts = vec![0u8; 1 << bits];
+    for mask in 1..counts.len() {
+        counts[mask] = counts[mask >> 1] + u8::from(mask & 1 == 1);
+    }
+    counts
+}
+
+/// A set of the indices `0..64`, e.g. the nodes already visited by a path
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct VisitedMask(pub u64);
+
+impl VisitedMask {
+    /// The number of indices the mask can hold
+    pub const CAPACITY: usize = u64::BITS as usize;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The mask of the indices `0..n`
+    ///
+    /// # Panics
+    /// If `n` is over the capacity.
+    pub fn first(n: usize) -> Self {
+        assert!(n <= Self::CAPACITY, "too many indices for a mask");
+        if n == Self::CAPACITY {
+            Self(u64::MAX)
+        } else {
+            Self((1 << n) - 1)
+        }
+    }
+
+    fn bit(index: usize) -> u64 {
+        assert!(index < Self::CAPACITY, "index {index} out of a mask");
+        1 << index
+    }
+
+    pub fn contains(self, index: usize) -> bool {
+        self.0 & Self::bit(index) != 0
+    }
+
+    /// Add an index, returning whether it was not in the mask yet
+    pub fn insert(&mut self, index: usize) -> bool {
+        let absent = !self.contains(index);
+        self.0 |= Self::bit(index);
+        absent
+    }
+
+    /// Remove an index, returning whether it was in the mask
+    pub fn remove(&mut self, index: usize) -> bool {
+        let present = self.contains(index);
+        self.0 &= !Self::bit(index);
+        present
+    }
+
+    /// A copy of the mask with `index` added, to go one step further in a search without undoing it afterwards
+    #[must_use]
+    pub fn with(self, index: usize) -> Self {
+        Self(self.0 | Self::bit(index))
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The indices in the mask, in increasing order
+    pub fn iter(self) -> impl Iterator<Item = usize> {
+        let mut rest = self.0;
+        iter::from_fn(move || {
+            let index = rest.trailing_zeros() as usize;
+            // clear the lowest bit
+            rest &= rest.wrapping_sub(1);
+            (index < Self::CAPACITY).then_some(index)
+        })
+    }
+}
+
+impl FromIterator<usize> for VisitedMask {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut mask = Self::new();
+        for index in iter {
+            mask.insert(index);
+        }
+        mask
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsets() {
+        assert_eq!(
+            subsets(0b1010).collect::<Vec<_>>(),
+            [0b1010, 0b1000, 0b0010, 0]
+        );
+        assert_eq!(subsets(0).collect::<Vec<_>>(), [0]);
+        assert_eq!(
+            combinations(4, 2).collect::<Vec<_>>(),
+            [0b0011, 0b0101, 0b0110, 0b1001, 0b1010, 0b1100]
+        );
+        assert_eq!(combinations(3, 0).collect::<Vec<_>>(), [0]);
+        assert_eq!(combinations(3, 4).count(), 0);
+        assert_eq!(combinations(63, 63).count(), 1);
+        let counts = popcounts(10);
+        assert!((0..1024).all(|mask: u32| u32::from(counts[mask as usize]) == mask.count_ones()));
+    }
+
+    #[test]
+    fn test_visited_mask() {
+        let mut visited = VisitedMask::new();
+        assert!(visited.insert(63));
+        assert!(!visited.insert(63));
+        let next = visited.with(5);
+        assert!(next.contains(5) && !visited.contains(5));
+        assert_eq!(next.iter().collect::<Vec<_>>(), [5, 63]);
+        assert!(visited.remove(63));
+        assert!(visited.is_empty());
+        assert_eq!([1, 2, 1].into_iter().collect::<VisitedMask>().len(), 2);
+        assert_eq!(VisitedMask::first(3), VisitedMask(0b111));
+        assert_eq!(VisitedMask::first(64).len(), 64);
+        assert_eq!(VisitedMask::first(0), VisitedMask::new());
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    hash::{Hash, Hasher},
+    ops::{BitAnd, BitOr},
+};
+
+use serde::{Deserialize, Serialize};
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of small integers, stored as one bit per value in 64-bit words which are added as larger values are inserted
+///
+/// Two sets are equal if they hold the same values, no matter how many words they allocated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty set which can hold the values below `bits` without growing
+    pub fn with_capacity(bits: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(bits.div_ceil(BITS)),
+        }
+    }
+
+    /// Add a value, returning whether it was not in the set yet
+    pub fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / BITS, 1 << (value % BITS));
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let absent = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        absent
+    }
+
+    /// Remove a value, returning whether it was in the set
+    pub fn remove(&mut self, value: usize) -> bool {
+        let present = self.contains(value);
+        if present {
+            self.words[value / BITS] &= !(1 << (value % BITS));
+        }
+        present
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.words
+            .get(value / BITS)
+            .is_some_and(|word| word & (1 << (value % BITS)) != 0)
+    }
+
+    /// The number of values in the set
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    /// The number of values in both sets, without building their intersection
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// Is every value of this set also in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(i, w)| w & !other.words.get(i).unwrap_or(&0) == 0)
+    }
+
+    /// Add all the values of `other` to this set
+    pub fn union_with(&mut self, other: &Self) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    /// Only keep the values which are also in `other`
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.words.truncate(other.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+    }
+
+    /// The values in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                (rest != 0).then(|| {
+                    let bit = rest.trailing_zeros() as usize;
+                    rest &= rest - 1;
+                    i * BITS + bit
+                })
+            })
+        })
+    }
+
+    /// The words without the empty ones at the end, which don't change the set
+    fn trimmed(&self) -> &[u64] {
+        let len = self
+            .words
+            .iter()
+            .rposition(|&w| w != 0)
+            .map_or(0, |i| i + 1);
+        &self.words[..len]
+    }
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl Eq for BitSet {}
+
+impl Hash for BitSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trimmed().hash(state);
+    }
+}
+
+impl BitAnd for &BitSet {
+    type Output = BitSet;
+
+    fn bitand(self, rhs: Self) -> BitSet {
+        let mut set = self.clone();
+        set.intersect_with(rhs);
+        set
+    }
+}
+
+impl BitOr for &BitSet {
+    type Output = BitSet;
+
+    fn bitor(self, rhs: Self) -> BitSet {
+        let mut set = self.clone();
+        set.union_with(rhs);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut a: BitSet = [3, 64, 200].into_iter().collect();
+        assert!(a.contains(200) && !a.contains(199) && !a.contains(1000));
+        assert_eq!(a.len(), 3);
+        assert!(!a.insert(64));
+        assert!(a.insert(130));
+        assert_eq!(a.iter().collect::<Vec<_>>(), [3, 64, 130, 200]);
+        let b: BitSet = [64, 130, 500].into_iter().collect();
+        assert_eq!(a.intersection_len(&b), 2);
+        assert_eq!((&a & &b).iter().collect::<Vec<_>>(), [64, 130]);
+        assert_eq!((&a | &b).len(), 5);
+        assert!((&a & &b).is_subset(&b));
+        assert!(!a.is_subset(&b));
+        // the words left empty at the end don't matter
+        assert!(a.remove(200));
+        assert!(!a.remove(200));
+        assert_eq!(a, [3, 64, 130].into_iter().collect());
+        a.clear();
+        assert!(a.is_empty());
+        assert_eq!(a, BitSet::with_capacity(1000));
+    }
+}
@@ -0,0 +1,75 @@
+/// The distinct values of a sparse set of coordinates, to index the cells between them instead of every coordinate
+///
+/// Cell `i` spans the coordinates from `coord(i)` up to `coord(i + 1)`, excluded, which is `gap(i)` of them. Adding
+/// both `c` and `c + 1` for a coordinate `c` gives it a cell of its own, with a gap of one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompressedAxis {
+    /// The sorted distinct coordinates
+    coords: Vec<i64>,
+}
+
+impl CompressedAxis {
+    pub fn new(coords: impl IntoIterator<Item = i64>) -> Self {
+        let mut coords: Vec<_> = coords.into_iter().collect();
+        coords.sort_unstable();
+        coords.dedup();
+        Self { coords }
+    }
+
+    /// The number of distinct coordinates
+    pub fn len(&self) -> usize {
+        self.coords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coords.is_empty()
+    }
+
+    /// The number of cells between consecutive coordinates, one fewer than the coordinates
+    pub fn cells(&self) -> usize {
+        self.len().saturating_sub(1)
+    }
+
+    /// The compressed index of `coord`, `None` if it wasn't one of the coordinates
+    pub fn index(&self, coord: i64) -> Option<usize> {
+        self.coords.binary_search(&coord).ok()
+    }
+
+    /// The coordinate at the compressed index `index`
+    pub fn coord(&self, index: usize) -> i64 {
+        self.coords[index]
+    }
+
+    /// How many coordinates cell `index` spans, up to the next coordinate
+    pub fn gap(&self, index: usize) -> i64 {
+        self.coords[index + 1] - self.coords[index]
+    }
+
+    /// The width of each cell, in the order of the indices
+    pub fn gaps(&self) -> impl Iterator<Item = i64> + '_ {
+        self.coords.windows(2).map(|w| w[1] - w[0])
+    }
+}
+
+impl FromIterator<i64> for CompressedAxis {
+    fn from_iter<I: IntoIterator<Item = i64>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compressed_axis() {
+        let axis: CompressedAxis = [1_000_000, -5, 7, 8, 7].into_iter().collect();
+        assert_eq!((axis.len(), axis.cells()), (4, 3));
+        assert_eq!(axis.index(7), Some(1));
+        assert_eq!(axis.index(6), None);
+        assert_eq!(axis.coord(3), 1_000_000);
+        assert_eq!(axis.gap(1), 1);
+        assert_eq!(axis.gaps().collect::<Vec<_>>(), [12, 1, 999_992]);
+        assert_eq!(CompressedAxis::new([3]).cells(), 0);
+    }
+}
@@ -0,0 +1,86 @@
+//! Cycle detection for simulations which end up repeating, to skip to a state far in the future
+
+/// Find the cycle of the sequence `initial`, `step(initial)`, `step(step(initial))`... with Brent's algorithm,
+/// returning `(offset, period)`: the index of the first state of the cycle and its length
+///
+/// The states are compared with the keys returned by `key`, which can be the state itself, a part of it or a hash of
+/// it. Only a couple of states are kept in memory, at the cost of computing the start of the sequence a few times.
+/// The sequence must end up repeating, or this never returns.
+pub fn find_cycle<T: Clone, K: Eq>(
+    initial: &T,
+    step: impl FnMut(&T) -> T,
+    key: impl FnMut(&T) -> K,
+) -> (usize, usize) {
+    let (offset, period, _) = brent(initial, step, key);
+    (offset, period)
+}
+
+/// The state after `n` steps from `initial`, which skips the repetitions of the cycle found by [`find_cycle`]
+pub fn state_after<T: Clone, K: Eq>(
+    initial: &T,
+    mut step: impl FnMut(&T) -> T,
+    key: impl FnMut(&T) -> K,
+    n: usize,
+) -> T {
+    let (offset, period, first) = brent(initial, &mut step, key);
+    if n < offset {
+        return (0..n).fold(initial.clone(), |state, _| step(&state));
+    }
+    (0..(n - offset) % period).fold(first, |state, _| step(&state))
+}
+
+/// Brent's algorithm, returning the offset and period of the cycle along with the first state of the cycle
+fn brent<T: Clone, K: Eq>(
+    initial: &T,
+    mut step: impl FnMut(&T) -> T,
+    mut key: impl FnMut(&T) -> K,
+) -> (usize, usize, T) {
+    // find the period: the tortoise jumps to the hare each time the hare went a power of two steps further, until the
+    // hare catches up with it
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = key(initial);
+    let mut hare = step(initial);
+    while key(&hare) != tortoise {
+        if power == period {
+            tortoise = key(&hare);
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+    // start again with the hare one period ahead, they meet at the start of the cycle
+    let mut tortoise = initial.clone();
+    let mut hare = (0..period).fold(initial.clone(), |state, _| step(&state));
+    let mut offset = 0;
+    while key(&tortoise) != key(&hare) {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        offset += 1;
+    }
+    (offset, period, tortoise)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_cycle() {
+        // 3, 10, 5, 16, 8, 4, 2, 1, 4, 2, 1...
+        let collatz = |n: &u64| {
+            if n.is_multiple_of(2) {
+                n / 2
+            } else {
+                3 * n + 1
+            }
+        };
+        assert_eq!(find_cycle(&3, collatz, |n| *n), (5, 3));
+        assert_eq!(state_after(&3, collatz, |n| *n, 1_000_000_000), 1);
+        assert_eq!(state_after(&3, collatz, |n| *n, 2), 5);
+        // a cycle from the start, compared on a part of the state only
+        let rotate = |v: &Vec<u8>| [&v[1..], &v[..1]].concat();
+        assert_eq!(find_cycle(&vec![1, 2, 3, 4], rotate, |v| v[0]), (0, 4));
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+/// One of the four directions of a grid, with the `y` axis pointing down like the lines of an input
+///
+/// The directions are ordered clockwise starting from the north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// All the directions, clockwise from the north
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Parse a direction from `URDL`, `^>v<` or `NESW`
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'U' | '^' | 'N' => Some(Direction::North),
+            'R' | '>' | 'E' => Some(Direction::East),
+            'D' | 'v' | 'S' => Some(Direction::South),
+            'L' | '<' | 'W' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    /// The `(x, y)` offset of one step in this direction
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    /// Take `steps` steps from `(x, y)` in this direction
+    pub fn step(self, (x, y): (isize, isize), steps: isize) -> (isize, isize) {
+        let (dx, dy) = self.delta();
+        (x + dx * steps, y + dy * steps)
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    /// The direction after a quarter turn counterclockwise
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    /// The direction after a quarter turn clockwise
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::North | Direction::South)
+    }
+
+    /// The arrow pointing in this direction, as used in the inputs
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+            assert_eq!(Direction::from_char(dir.arrow()), Some(dir));
+            assert_eq!(dir.step(dir.opposite().step((3, 4), 2), 2), (3, 4));
+        }
+        assert_eq!(Direction::from_char('U'), Some(Direction::North));
+        assert_eq!(Direction::from_char('W'), Some(Direction::West));
+        assert_eq!(Direction::from_char('x'), None);
+        assert_eq!(Direction::South.delta(), (0, 1));
+        assert!(Direction::ALL.is_sorted());
+    }
+}
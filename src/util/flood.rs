@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+use super::{Grid, Vec2};
+
+/// Which cells around a cell a fill spreads to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// The four orthogonal neighbors
+    Four,
+    /// The eight neighbors including the diagonals
+    Eight,
+}
+
+/// The positions of the region of `passable` cells connected to `start`, in the order of a breadth-first search
+///
+/// The region is empty if `start` is outside of the grid or not passable.
+pub fn flood_fill<T>(
+    grid: &Grid<T>,
+    start: Vec2<isize>,
+    connectivity: Connectivity,
+    mut passable: impl FnMut(&T) -> bool,
+) -> Vec<Vec2<isize>> {
+    let mut region = Vec::new();
+    if !grid.get(start).is_some_and(&mut passable) {
+        return region;
+    }
+    let mut filled = Grid::new(grid.width(), grid.height(), false);
+    let mut queue = VecDeque::from([start]);
+    filled[start] = true;
+    while let Some(pos) = queue.pop_front() {
+        region.push(pos);
+        let neighbors = match connectivity {
+            Connectivity::Four => pos.neighbors().to_vec(),
+            Connectivity::Eight => pos.neighbors8().to_vec(),
+        };
+        for next in neighbors {
+            if grid.get(next).is_some_and(&mut passable) && !filled[next] {
+                filled[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    region
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flood_fill() {
+        let (_, (grid, [start])) = Grid::parse(
+            "S.#..\n\
+             ##.#.\n\
+             ..#..",
+            ['S'],
+            |c| Some(c != '#'),
+        )
+        .unwrap();
+        let four = flood_fill(&grid, start, Connectivity::Four, |&open| open);
+        assert_eq!(four, [Vec2::new(0, 0), Vec2::new(1, 0)]);
+        // through the diagonal gaps of the walls, every open cell is reached
+        let eight = flood_fill(&grid, start, Connectivity::Eight, |&open| open);
+        assert_eq!(eight.len(), grid.positions(|&open| open).count());
+        assert_eq!(eight[2], Vec2::new(2, 1));
+        assert_eq!(
+            flood_fill(&grid, Vec2::new(2, 0), Connectivity::Four, |&open| open),
+            []
+        );
+        assert_eq!(
+            flood_fill(&grid, Vec2::new(-1, 0), Connectivity::Four, |_| true),
+            []
+        );
+    }
+}
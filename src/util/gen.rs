@@ -0,0 +1,300 @@
+//! Random puzzle inputs, to stress-test the solutions on inputs much larger than the real ones
+//!
+//! Each generator takes a size, and [`generate`] scales the size of the real input of a day. The inputs have the
+//! properties the solutions rely on, e.g. a rock which hits every hailstone for day 24.
+use std::{fmt::Write, ops::RangeInclusive};
+
+use super::{FastSet, Grid, GridDisplay, Vec2};
+
+/// A small reproducible random number generator (xorshift64*), good enough for puzzle inputs
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state must not be zero, and close seeds should give unrelated sequences
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Self((state ^ (state >> 31)).max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0..n`, which must not be empty
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// A number in `range`, which must not be empty
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "empty range");
+        let span = end.wrapping_sub(start) as u64;
+        match span.checked_add(1) {
+            Some(len) => start.wrapping_add((self.next_u64() % len) as i64),
+            None => self.next_u64() as i64,
+        }
+    }
+
+    /// True with the probability `p`
+    #[allow(clippy::cast_precision_loss)]
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// A random item of a slice, which must not be empty
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// An input of `day` about `scale` times as large as the real one, `None` if there is no generator for that day
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn generate(day: usize, scale: usize, rng: &mut Rng) -> Option<String> {
+    match day {
+        // a 140x140 grid, the side grows with the square root of the scale so that the area scales
+        10 => Some(pipe_loop(
+            (140. * (scale as f64).sqrt()).round() as usize,
+            rng,
+        )),
+        19 => Some(workflows(550 * scale, 200 * scale, rng)),
+        22 => Some(bricks(1400 * scale, rng)),
+        24 => Some(hailstones(300 * scale, rng)),
+        _ => None,
+    }
+}
+
+/// Day 10: a `size` by `size` grid of pipes where a loop goes through the start, among random pipes
+///
+/// The loop is the outline of a shape made of columns of cells, where each column overlaps the previous one so that
+/// the outline never touches itself.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+pub fn pipe_loop(size: usize, rng: &mut Rng) -> String {
+    let size = size.max(4);
+    // the cells of the shape are between the corners of the tiles, leave a margin of one tile around it
+    let cells = size - 3;
+    let mut spans: Vec<(usize, usize)> = Vec::with_capacity(cells);
+    for _ in 0..cells {
+        let (top, bottom) = match spans.last() {
+            None => {
+                let top = rng.below(cells);
+                (top, top + 1)
+            }
+            Some(&(top, bottom)) => {
+                // large steps give a long outline which winds across the grid
+                let max_step = (cells as i64 / 10).max(2);
+                let step = |rng: &mut Rng, v: usize| {
+                    let v = v as i64 + rng.range(-max_step..=max_step);
+                    v.clamp(0, cells as i64) as usize
+                };
+                // the span shares at least one row with the previous one
+                let new_top = step(rng, top).min(bottom - 1);
+                let new_bottom = step(rng, bottom).max(top + 1).max(new_top + 1);
+                (new_top, new_bottom)
+            }
+        };
+        spans.push((top, bottom));
+    }
+    let inside = |x: isize, y: isize| {
+        x >= 0
+            && (x as usize) < cells
+            && y >= 0
+            && (spans[x as usize].0..spans[x as usize].1).contains(&(y as usize))
+    };
+    // the corner at (x, y) is the tile at (x + 1, y + 1), and an edge is on the outline if one of its sides is inside
+    let mut tiles = Grid::new(size, size, '.');
+    let mut start = None;
+    for y in 0..=cells as isize {
+        for x in 0..=cells as isize {
+            let north = inside(x - 1, y - 1) != inside(x, y - 1);
+            let south = inside(x - 1, y) != inside(x, y);
+            let west = inside(x - 1, y - 1) != inside(x - 1, y);
+            let east = inside(x, y - 1) != inside(x, y);
+            let pipe = match (north, east, south, west) {
+                (true, false, true, false) => '|',
+                (false, true, false, true) => '-',
+                (true, true, false, false) => 'L',
+                (true, false, false, true) => 'J',
+                (false, false, true, true) => '7',
+                (false, true, true, false) => 'F',
+                _ => continue,
+            };
+            let pos = Vec2::new(x + 1, y + 1);
+            tiles[pos] = pipe;
+            start.get_or_insert(pos);
+        }
+    }
+    let start = start.expect("the shape has at least one cell");
+    for y in 0..size as isize {
+        for x in 0..size as isize {
+            // random pipes around the loop, but none of them connects to the start
+            let pos = Vec2::new(x, y);
+            if tiles[pos] == '.' && pos.manhattan(start) != 1 && rng.chance(0.7) {
+                tiles[pos] = *rng.choose(&['|', '-', 'L', 'J', '7', 'F']);
+            }
+        }
+    }
+    tiles[start] = 'S';
+    GridDisplay::new(&tiles).to_string() + "\n"
+}
+
+/// Day 19: a tree of `count` workflows starting from `in`, and `parts` parts to sort
+#[allow(clippy::cast_possible_truncation)]
+pub fn workflows(count: usize, parts: usize, rng: &mut Rng) -> String {
+    // the names are the base 26 numbers from `aa`, skipping `in`
+    let names: Vec<String> = (26..)
+        .map(|mut n: usize| {
+            let mut name = Vec::new();
+            while n > 0 {
+                name.push(b'a' + (n % 26) as u8);
+                n /= 26;
+            }
+            String::from_utf8(name).unwrap()
+        })
+        .filter(|name| name != "in")
+        .take(count.saturating_sub(1))
+        .collect();
+    // each workflow but the first is the target of a rule of an earlier one
+    let mut targets: Vec<Vec<&str>> = vec![Vec::new(); names.len() + 1];
+    for (i, name) in names.iter().enumerate() {
+        targets[rng.below(i + 1)].push(name);
+    }
+    let mut lines = Vec::with_capacity(count + parts + 1);
+    for (i, children) in targets.iter().enumerate() {
+        let name = if i == 0 { "in" } else { &names[i - 1] };
+        let mut rules = String::new();
+        let mut children = children.iter();
+        // the rules without a child workflow send to A or R, and the last one has no condition
+        let rule_count = children.len().max(1 + rng.below(3));
+        for r in 0..rule_count {
+            let target = children
+                .next()
+                .copied()
+                .unwrap_or_else(|| *rng.choose(&["A", "R"]));
+            if r + 1 < rule_count {
+                let category = rng.choose(&['x', 'm', 'a', 's']);
+                let op = rng.choose(&['<', '>']);
+                let value = rng.range(1..=4000);
+                write!(rules, "{category}{op}{value}:{target},").unwrap();
+            } else {
+                rules.push_str(target);
+            }
+        }
+        lines.push(format!("{name}{{{rules}}}"));
+    }
+    rng.shuffle(&mut lines);
+    lines.push(String::new());
+    for _ in 0..parts {
+        let [x, m, a, s] = [(); 4].map(|()| rng.range(1..=4000));
+        lines.push(format!("{{x={x},m={m},a={a},s={s}}}"));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Day 22: `count` bricks of up to four blocks in a column of 10 by 10 blocks, which don't overlap
+#[allow(clippy::cast_possible_wrap)]
+pub fn bricks(count: usize, rng: &mut Rng) -> String {
+    // the real input has about one brick for each 0.2 layer
+    let height = (count as i64 / 5).max(10);
+    let mut occupied = FastSet::default();
+    let mut lines = Vec::with_capacity(count);
+    while lines.len() < count {
+        let begin = [rng.range(0..=9), rng.range(0..=9), rng.range(1..=height)];
+        let (axis, length) = (rng.below(3), rng.range(0..=3));
+        let mut end = begin;
+        end[axis] += length;
+        if end[0] > 9 || end[1] > 9 {
+            continue;
+        }
+        let blocks = (0..=length)
+            .map(|i| {
+                let mut block = begin;
+                block[axis] += i;
+                block
+            })
+            .collect::<Vec<_>>();
+        if blocks.iter().any(|block| occupied.contains(block)) {
+            continue;
+        }
+        occupied.extend(blocks);
+        let [x1, y1, z1] = begin;
+        let [x2, y2, z2] = end;
+        lines.push(format!("{x1},{y1},{z1}~{x2},{y2},{z2}"));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Day 24: `count` hailstones which are all hit by a rock thrown from a random position with a random velocity
+///
+/// The velocities are small like in the real input, so many hailstones share a velocity along an axis.
+pub fn hailstones(count: usize, rng: &mut Rng) -> String {
+    let rock = [(); 3].map(|()| rng.range(100_000_000_000_000..=400_000_000_000_000));
+    let rock_velocity = [(); 3].map(|()| rng.range(-200..=200));
+    let mut times = FastSet::default();
+    let mut lines = Vec::with_capacity(count);
+    while lines.len() < count {
+        // each hailstone is hit at a different time
+        let time = rng.range(100_000_000_000..=1_000_000_000_000);
+        let velocity = [(); 3].map(|()| rng.range(-300..=300));
+        if velocity.iter().zip(&rock_velocity).any(|(v, r)| v == r) || !times.insert(time) {
+            continue;
+        }
+        let [x, y, z] = [0, 1, 2].map(|i| rock[i] + (rock_velocity[i] - velocity[i]) * time);
+        let [vx, vy, vz] = velocity;
+        lines.push(format!("{x}, {y}, {z} @ {vx}, {vy}, {vz}"));
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(0);
+        let values: Vec<_> = (0..1000).map(|_| rng.range(-3..=3)).collect();
+        assert!(values.iter().all(|v| (-3..=3).contains(v)));
+        assert!((-3..=3).all(|v| values.contains(&v)));
+        // the same seed gives the same sequence
+        assert_eq!(Rng::new(42).next_u64(), Rng::new(42).clone().next_u64());
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+        let mut items = [1, 2, 3, 4, 5];
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_pipe_loop() {
+        let input = pipe_loop(12, &mut Rng::new(7));
+        let lines: Vec<_> = input.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert!(lines.iter().all(|line| line.len() == 12));
+        assert_eq!(input.matches('S').count(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+//! Crossings of lines, rays and segments in the plane, exact for integer coordinates
+use num::Num;
+
+use super::Vec2;
+
+/// The z component of the cross product of two vectors, which is zero when they are parallel
+pub fn cross<T: Num + Copy>(a: Vec2<T>, b: Vec2<T>) -> T {
+    a.x * b.y - a.y * b.x
+}
+
+/// How two lines, rays or segments meet
+///
+/// A line, or a ray, is given by a point and a direction, and its points are `point + dir * t` for any `t` (for a ray,
+/// `t >= 0`). A segment is given by its two ends.
+///
+/// The functions below take the coordinates as integers or floats, decide how the two meet with them, and only convert
+/// to the type `F` of the result to divide: a [`Ratio`] gives the exact crossing point of integer coordinates, and
+/// `f64` a float one.
+///
+/// [`Ratio`]: super::Ratio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intersection<F> {
+    /// They cross at the point `at`, which is at the parameter `t` along the first one and `u` along the second one
+    Point { at: Vec2<F>, t: F, u: F },
+    /// They are parallel and apart, so they never meet (or a direction is zero)
+    Parallel,
+    /// They lie on the same line, whether they overlap isn't checked for rays and segments
+    Collinear,
+    /// The lines through them cross, but outside of one of the rays or segments
+    Outside,
+}
+
+/// Where the lines `a + a_dir * t` and `b + b_dir * u` cross
+pub fn line_intersection<T, F>(
+    a: Vec2<T>,
+    a_dir: Vec2<T>,
+    b: Vec2<T>,
+    b_dir: Vec2<T>,
+) -> Intersection<F>
+where
+    T: Num + Copy,
+    F: Num + Copy + From<T>,
+{
+    match crossing(a, a_dir, b, b_dir) {
+        Ok(params) => point(a, a_dir, params),
+        Err(intersection) => intersection,
+    }
+}
+
+/// Where the rays starting at `a` and `b` in the directions `a_dir` and `b_dir` cross
+pub fn ray_intersection<T, F>(
+    a: Vec2<T>,
+    a_dir: Vec2<T>,
+    b: Vec2<T>,
+    b_dir: Vec2<T>,
+) -> Intersection<F>
+where
+    T: Num + Copy + PartialOrd,
+    F: Num + Copy + From<T>,
+{
+    match crossing(a, a_dir, b, b_dir) {
+        Ok(params @ (t, u, denom)) => {
+            let zero = T::zero();
+            let ahead = |numer: T| numer.is_zero() || (numer > zero) == (denom > zero);
+            if ahead(t) && ahead(u) {
+                point(a, a_dir, params)
+            } else {
+                Intersection::Outside
+            }
+        }
+        Err(intersection) => intersection,
+    }
+}
+
+/// Where the segments from `a` to `a_end` and from `b` to `b_end` cross, their ends included
+pub fn segment_intersection<T, F>(
+    a: Vec2<T>,
+    a_end: Vec2<T>,
+    b: Vec2<T>,
+    b_end: Vec2<T>,
+) -> Intersection<F>
+where
+    T: Num + Copy + PartialOrd,
+    F: Num + Copy + From<T>,
+{
+    let a_dir = a_end - a;
+    match crossing(a, a_dir, b, b_end - b) {
+        Ok(params @ (t, u, denom)) => {
+            // the parameter is between 0 and 1 when its numerator is between 0 and the denominator
+            let zero = T::zero();
+            let inside = |numer: T| {
+                if denom > zero {
+                    zero <= numer && numer <= denom
+                } else {
+                    denom <= numer && numer <= zero
+                }
+            };
+            if inside(t) && inside(u) {
+                point(a, a_dir, params)
+            } else {
+                Intersection::Outside
+            }
+        }
+        Err(intersection) => intersection,
+    }
+}
+
+/// The numerators of the parameters where the lines cross and their common denominator, or how they don't cross
+fn crossing<T, F>(
+    a: Vec2<T>,
+    a_dir: Vec2<T>,
+    b: Vec2<T>,
+    b_dir: Vec2<T>,
+) -> Result<(T, T, T), Intersection<F>>
+where
+    T: Num + Copy,
+{
+    let offset = b - a;
+    let denom = cross(a_dir, b_dir);
+    if denom.is_zero() {
+        return Err(
+            if cross(offset, a_dir).is_zero() && cross(offset, b_dir).is_zero() {
+                Intersection::Collinear
+            } else {
+                Intersection::Parallel
+            },
+        );
+    }
+    // solve a + a_dir * t = b + b_dir * u by taking the cross product of both sides with b_dir, then with a_dir
+    Ok((cross(offset, b_dir), cross(offset, a_dir), denom))
+}
+
+/// The crossing point, with the coordinates and the parameters divided in the type of the result
+fn point<T, F>(a: Vec2<T>, a_dir: Vec2<T>, (t, u, denom): (T, T, T)) -> Intersection<F>
+where
+    T: Num + Copy,
+    F: Num + Copy + From<T>,
+{
+    // the numerators of the coordinates are `a * denom + a_dir * t`, so that each value is divided only once
+    let divide = |numer: T| F::from(numer) / F::from(denom);
+    Intersection::Point {
+        at: Vec2::new(
+            divide(a.x * denom + a_dir.x * t),
+            divide(a.y * denom + a_dir.y * t),
+        ),
+        t: divide(t),
+        u: divide(u),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Ratio;
+
+    #[test]
+    fn test_intersections() {
+        let v = Vec2::<i128>::new;
+        // the first two hailstones of day 24
+        let Intersection::<Ratio>::Point { at, t, u } =
+            ray_intersection(v(19, 13), v(-2, 1), v(18, 19), v(-1, -1))
+        else {
+            panic!("the paths should cross");
+        };
+        assert_eq!(at, Vec2::new(Ratio::new(43, 3), Ratio::new(46, 3)));
+        assert_eq!((t, u), (Ratio::new(7, 3), Ratio::new(11, 3)));
+        assert_eq!(
+            ray_intersection::<_, Ratio>(v(19, 13), v(-2, 1), v(20, 19), v(1, -5)),
+            Intersection::Outside
+        );
+        assert_eq!(
+            line_intersection::<_, Ratio>(v(0, 0), v(1, 1), v(1, 0), v(2, 2)),
+            Intersection::Parallel
+        );
+        assert_eq!(
+            segment_intersection::<_, Ratio>(v(0, 0), v(1, 1), v(2, 2), v(3, 3)),
+            Intersection::Collinear
+        );
+        assert_eq!(
+            segment_intersection::<_, Ratio>(v(0, 0), v(2, 0), v(1, 1), v(1, 5)),
+            Intersection::Outside
+        );
+        // a touching end counts, with floats
+        assert_eq!(
+            segment_intersection(
+                Vec2::new(0., 0.),
+                Vec2::new(2., 0.),
+                Vec2::new(1., -1.),
+                Vec2::new(1., 0.),
+            ),
+            Intersection::Point {
+                at: Vec2::new(1., 0.),
+                t: 0.5,
+                u: 1.
+            }
+        );
+    }
+}
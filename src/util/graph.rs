@@ -0,0 +1,215 @@
+use std::{borrow::Borrow, cmp::Reverse, collections::BinaryHeap, hash::Hash};
+
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::{EdgeRef, IntoEdges, IntoNeighborsDirected, IntoNodeIdentifiers, NodeIndexable},
+    Directed,
+    Direction::Outgoing,
+    EdgeType, Graph,
+};
+
+use super::FastMap;
+
+/// A petgraph graph whose nodes are created from keys, e.g. names or positions, along with the map from the keys to
+/// the indices of the nodes
+///
+/// There is at most one edge between two nodes (in each direction for a directed graph).
+#[derive(Debug, Clone)]
+pub struct KeyedGraph<K, N, E, Ty: EdgeType = Directed> {
+    graph: Graph<N, E, Ty>,
+    indices: FastMap<K, NodeIndex>,
+}
+
+impl<K, N, E, Ty: EdgeType> Default for KeyedGraph<K, N, E, Ty> {
+    fn default() -> Self {
+        Self {
+            graph: Graph::default(),
+            indices: FastMap::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq, N, E, Ty: EdgeType> KeyedGraph<K, N, E, Ty> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The node of `key`, which is added with the weight `weight()` the first time
+    pub fn get_or_insert_node(&mut self, key: K, weight: impl FnOnce() -> N) -> NodeIndex {
+        *self
+            .indices
+            .entry(key)
+            .or_insert_with(|| self.graph.add_node(weight()))
+    }
+
+    /// The node of `key`, `None` if it was never added
+    pub fn index<Q>(&self, key: &Q) -> Option<NodeIndex>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.indices.get(key).copied()
+    }
+
+    /// Add an edge from `a` to `b` unless there is already one, whose weight is then left as it is
+    pub fn add_edge(&mut self, a: NodeIndex, b: NodeIndex, weight: E) -> EdgeIndex {
+        match self.graph.find_edge(a, b) {
+            Some(edge) => edge,
+            None => self.graph.add_edge(a, b, weight),
+        }
+    }
+
+    /// Add an edge between the nodes of two keys, adding the nodes with the default weight if needed
+    pub fn connect(&mut self, a: K, b: K, weight: E) -> EdgeIndex
+    where
+        N: Default,
+    {
+        let a = self.get_or_insert_node(a, N::default);
+        let b = self.get_or_insert_node(b, N::default);
+        self.add_edge(a, b, weight)
+    }
+
+    pub fn graph(&self) -> &Graph<N, E, Ty> {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> Graph<N, E, Ty> {
+        self.graph
+    }
+
+    /// The graph and the node of each key, e.g. to change the edges of the graph and still find the nodes
+    pub fn into_parts(self) -> (Graph<N, E, Ty>, FastMap<K, NodeIndex>) {
+        (self.graph, self.indices)
+    }
+}
+
+/// The nodes of a directed graph ordered so that each edge goes from a node to a later one, `None` if there is a cycle
+///
+/// Unlike `petgraph::algo::toposort`, the order only depends on the graph and not on the way it is traversed: of the
+/// nodes whose predecessors are all placed, the one with the lowest index comes first.
+pub fn toposort_stable<G>(graph: G) -> Option<Vec<G::NodeId>>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected + NodeIndexable,
+{
+    // the number of edges coming into each node from the nodes which aren't placed yet
+    let mut in_degree = vec![0usize; graph.node_bound()];
+    let mut node_count = 0;
+    for node in graph.node_identifiers() {
+        node_count += 1;
+        for next in graph.neighbors_directed(node, Outgoing) {
+            in_degree[graph.to_index(next)] += 1;
+        }
+    }
+    let mut ready: BinaryHeap<_> = graph
+        .node_identifiers()
+        .map(|node| graph.to_index(node))
+        .filter(|&i| in_degree[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(node_count);
+    while let Some(Reverse(i)) = ready.pop() {
+        let node = graph.from_index(i);
+        order.push(node);
+        for next in graph.neighbors_directed(node, Outgoing) {
+            let j = graph.to_index(next);
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+    // the nodes on a cycle never have all their predecessors placed
+    (order.len() == node_count).then_some(order)
+}
+
+/// The length of the longest path from `start` to `end` in a directed acyclic graph, where `weight` gives the length
+/// of each edge
+///
+/// The nodes are relaxed in topological order, so this is linear in the size of the graph, while it is NP-hard with
+/// cycles. `None` if `end` can't be reached from `start`, or if there is a cycle.
+pub fn longest_path<G>(
+    graph: G,
+    start: G::NodeId,
+    end: G::NodeId,
+    mut weight: impl FnMut(G::EdgeRef) -> usize,
+) -> Option<usize>
+where
+    G: IntoNodeIdentifiers + IntoNeighborsDirected + IntoEdges + NodeIndexable,
+{
+    let order = toposort_stable(graph)?;
+    // the length of the longest path from the start to each node, `None` for the nodes it doesn't reach
+    let mut lengths = vec![None; graph.node_bound()];
+    lengths[graph.to_index(start)] = Some(0);
+    for node in order {
+        let Some(length) = lengths[graph.to_index(node)] else {
+            continue;
+        };
+        for edge in graph.edges(node) {
+            let next = &mut lengths[graph.to_index(edge.target())];
+            *next = (*next).max(Some(length + weight(edge)));
+        }
+    }
+    lengths[graph.to_index(end)]
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::{graph::DiGraph, Undirected};
+
+    use super::*;
+
+    #[test]
+    fn test_toposort_stable() {
+        let mut graph = DiGraph::<(), ()>::from_edges([(3, 1), (0, 1), (1, 2), (3, 2), (0, 2)]);
+        graph.add_node(());
+        assert_eq!(
+            toposort_stable(&graph),
+            Some([0, 3, 1, 2, 4].map(NodeIndex::new).to_vec())
+        );
+        graph.add_edge(NodeIndex::new(2), NodeIndex::new(3), ());
+        assert_eq!(toposort_stable(&graph), None);
+        assert_eq!(toposort_stable(&DiGraph::<(), ()>::new()), Some(vec![]));
+    }
+
+    #[test]
+    fn test_longest_path() {
+        let mut graph = DiGraph::<(), usize>::from_edges([
+            (0, 1, 1),
+            (1, 3, 1),
+            (0, 2, 5),
+            (2, 3, 1),
+            (0, 3, 4),
+        ]);
+        graph.add_node(());
+        let node = NodeIndex::new;
+        assert_eq!(
+            longest_path(&graph, node(0), node(3), |e| *e.weight()),
+            Some(6)
+        );
+        assert_eq!(longest_path(&graph, node(1), node(3), |_| 1), Some(1));
+        assert_eq!(longest_path(&graph, node(0), node(0), |_| 1), Some(0));
+        assert_eq!(longest_path(&graph, node(3), node(0), |_| 1), None);
+        assert_eq!(longest_path(&graph, node(0), node(4), |_| 1), None);
+        graph.add_edge(node(3), node(1), 1);
+        assert_eq!(longest_path(&graph, node(0), node(3), |_| 1), None);
+    }
+
+    #[test]
+    fn test_keyed_graph() {
+        let mut graph = KeyedGraph::<&str, String, u32, Undirected>::new();
+        let a = graph.get_or_insert_node("a", || "first".to_string());
+        let b = graph.get_or_insert_node("b", String::new);
+        assert_eq!(graph.get_or_insert_node("a", || unreachable!()), a);
+        let edge = graph.add_edge(a, b, 1);
+        // the edge between a and b already exists, in either direction since the graph is undirected
+        assert_eq!(graph.add_edge(b, a, 2), edge);
+        graph.connect("c", "a", 3);
+        assert_eq!(graph.index("c"), Some(NodeIndex::new(2)));
+        assert_eq!(graph.index("d"), None);
+        let (graph, indices) = graph.into_parts();
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 2));
+        assert_eq!(graph[a], "first");
+        assert_eq!(graph[edge], 1);
+        assert_eq!(indices.len(), 3);
+    }
+}
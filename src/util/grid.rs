@@ -0,0 +1,350 @@
+use std::ops::{Index, IndexMut};
+
+use nom::{
+    error::{Error, ErrorKind},
+    IResult,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{neighbors::orthogonal_neighbors, Vec2};
+
+/// A rectangular grid stored row by row in a single `Vec`, indexed by [`Vec2`] positions
+///
+/// The positions are signed so that the neighbors of a cell on the border can be computed and then rejected by
+/// [`Grid::get`], which returns `None` outside of the grid. Indexing with `grid[pos]` panics instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid with every cell set to `fill`
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Build a grid from its rows, or `None` if they don't all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parse the lines of a grid, converting each character with `tile`, along with the position of each of the
+    /// `markers`, e.g. `Grid::parse(input, ['S'], |c| ...)` for a grid with a start tile
+    ///
+    /// The grid ends at the first empty line or at the end of the input, and the line ending after its last row is not
+    /// consumed, so that several grids separated by empty lines can be parsed with `separated_list1`. It fails on a
+    /// character for which `tile` returns `None`, on a row which doesn't have the same length as the first one, and if
+    /// one of the markers is missing. A marker which appears several times is at its first position.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn parse<const N: usize>(
+        input: &str,
+        markers: [char; N],
+        mut tile: impl FnMut(char) -> Option<T>,
+    ) -> IResult<&str, (Self, [Vec2<isize>; N])> {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut found = [None; N];
+        let mut rest = input;
+        let mut height = 0;
+        loop {
+            let line = &rest[..rest.find(['\r', '\n']).unwrap_or(rest.len())];
+            let row_start = cells.len();
+            for (x, (i, c)) in line.char_indices().enumerate() {
+                if let Some(m) = markers.iter().position(|&m| m == c) {
+                    found[m].get_or_insert(Vec2::new(x as isize, height as isize));
+                }
+                let cell =
+                    tile(c).ok_or(nom::Err::Error(Error::new(&line[i..], ErrorKind::Char)))?;
+                cells.push(cell);
+            }
+            let len = cells.len() - row_start;
+            if len == 0 || *width.get_or_insert(len) != len {
+                return Err(nom::Err::Error(Error::new(rest, ErrorKind::Verify)));
+            }
+            rest = &rest[line.len()..];
+            height += 1;
+            // stop before the line ending if the next line is empty
+            match rest
+                .strip_prefix("\r\n")
+                .or_else(|| rest.strip_prefix('\n'))
+            {
+                Some(next) if !next.is_empty() && !next.starts_with(['\r', '\n']) => rest = next,
+                _ => break,
+            }
+        }
+        let Some(found) = found.into_iter().collect::<Option<Vec<_>>>() else {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)));
+        };
+        let grid = Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        };
+        Ok((rest, (grid, found.try_into().unwrap())))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Is the position inside of the grid
+    #[allow(clippy::cast_sign_loss)]
+    pub fn contains(&self, pos: Vec2<isize>) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height
+    }
+
+    /// The orthogonal neighbors of a position which are inside of the grid, clockwise from the north
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn neighbors(&self, pos: Vec2<isize>) -> impl Iterator<Item = Vec2<isize>> {
+        orthogonal_neighbors(pos, Vec2::new(self.width as isize, self.height as isize))
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn index_of(&self, pos: Vec2<isize>) -> Option<usize> {
+        self.contains(pos)
+            .then(|| pos.y as usize * self.width + pos.x as usize)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn position_of(&self, index: usize) -> Vec2<isize> {
+        Vec2::new((index % self.width) as isize, (index / self.width) as isize)
+    }
+
+    pub fn get(&self, pos: Vec2<isize>) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: Vec2<isize>) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.cells[i])
+    }
+
+    /// The cells with their positions, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2<isize>, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (self.position_of(i), cell))
+    }
+
+    /// The positions of the cells for which `predicate` is true, row by row
+    pub fn positions<'a>(
+        &'a self,
+        mut predicate: impl FnMut(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Vec2<isize>> + 'a {
+        self.iter()
+            .filter_map(move |(pos, cell)| predicate(cell).then_some(pos))
+    }
+
+    /// The cells, row by row, without their positions
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// A grid of the given size whose cell at `(x, y)` is the one at index `cell(x, y)` of this grid
+    fn remap(&self, width: usize, height: usize, cell: impl Fn(usize, usize) -> usize) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.cells[cell(x, y)].clone())
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// The grid mirrored along its diagonal, so that its rows are the columns of this one
+    pub fn transposed(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| x * self.width + y)
+    }
+
+    /// The grid after a quarter turn clockwise
+    pub fn rotated_right(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| {
+            (self.height - 1 - x) * self.width + y
+        })
+    }
+
+    /// The grid after a quarter turn counterclockwise
+    pub fn rotated_left(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| {
+            x * self.width + self.width - 1 - y
+        })
+    }
+
+    /// The grid mirrored left to right, each row being reversed
+    pub fn flipped_horizontally(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.width, self.height, |x, y| {
+            y * self.width + self.width - 1 - x
+        })
+    }
+
+    /// The grid mirrored top to bottom, the order of the rows being reversed
+    pub fn flipped_vertically(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.width, self.height, |x, y| {
+            (self.height - 1 - y) * self.width + x
+        })
+    }
+}
+
+/// Transpose rows of the same length, so that the rows of the result are the columns of the input
+///
+/// # Panics
+/// If the rows don't all have the same length.
+pub fn transpose<T: Clone>(rows: &[Vec<T>]) -> Vec<Vec<T>> {
+    let width = rows.first().map_or(0, Vec::len);
+    assert!(
+        rows.iter().all(|row| row.len() == width),
+        "the rows don't have the same length"
+    );
+    (0..width)
+        .map(|x| rows.iter().map(|row| row[x].clone()).collect())
+        .collect()
+}
+
+/// Rotate rows of the same length by a quarter turn clockwise
+///
+/// # Panics
+/// If the rows don't all have the same length.
+pub fn rotate_right<T: Clone>(rows: &[Vec<T>]) -> Vec<Vec<T>> {
+    let mut rotated = transpose(rows);
+    for row in &mut rotated {
+        row.reverse();
+    }
+    rotated
+}
+
+/// Rotate rows of the same length by a quarter turn counterclockwise
+///
+/// # Panics
+/// If the rows don't all have the same length.
+pub fn rotate_left<T: Clone>(rows: &[Vec<T>]) -> Vec<Vec<T>> {
+    let mut rotated = transpose(rows);
+    rotated.reverse();
+    rotated
+}
+
+impl<T> Index<Vec2<isize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Vec2<isize>) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside of the grid"))
+    }
+}
+
+impl<T> IndexMut<Vec2<isize>> for Grid<T> {
+    fn index_mut(&mut self, pos: Vec2<isize>) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos} is outside of the grid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid() {
+        let mut grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(Vec2::new(2, 1)), Some(&6));
+        assert_eq!(grid.get(Vec2::new(3, 0)), None);
+        assert_eq!(grid.get(Vec2::new(0, -1)), None);
+        grid[Vec2::new(1, 0)] = 7;
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 7, 3], [4, 5, 6]]);
+        assert_eq!(
+            grid.positions(|&c| c > 5).collect::<Vec<_>>(),
+            [Vec2::new(1, 0), Vec2::new(2, 1)]
+        );
+        assert_eq!(grid.iter().nth(3), Some((Vec2::new(0, 1), &4)));
+        assert_eq!(Grid::new(2, 2, '.').cells(), ['.'; 4]);
+        assert_eq!(Grid::from_rows(vec![vec![1, 2], vec![3]]), None);
+        assert_eq!(grid.neighbors(Vec2::new(2, 0)).count(), 2);
+    }
+
+    #[test]
+    fn test_parse() {
+        let (rest, (grid, [start, end])) =
+            Grid::parse("#S.\n.E#\n\n...", ['S', 'E'], |c| Some(c == '#')).unwrap();
+        assert_eq!(rest, "\n\n...");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!((start, end), (Vec2::new(1, 0), Vec2::new(1, 1)));
+        assert!(grid[Vec2::new(2, 1)]);
+        let (rest, (grid, [])) = Grid::parse("ab\r\ncd\n", [], Some).unwrap();
+        assert_eq!(rest, "\n");
+        assert_eq!(grid.cells(), ['a', 'b', 'c', 'd']);
+        // unknown character, rows of different lengths, missing marker and empty grid
+        let digit = |c: char| c.to_digit(10);
+        assert!(Grid::parse("12\n3x", [], digit).is_err());
+        assert!(Grid::parse("12\n3", [], digit).is_err());
+        assert!(Grid::parse("12\n34", ['S'], digit).is_err());
+        assert!(Grid::parse("", [], digit).is_err());
+    }
+
+    #[test]
+    fn test_transform() {
+        let grid = Grid::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        let rows = |grid: &Grid<i32>| grid.rows().map(<[_]>::to_vec).collect::<Vec<_>>();
+        assert_eq!(rows(&grid.transposed()), [[1, 4], [2, 5], [3, 6]]);
+        assert_eq!(rows(&grid.rotated_right()), [[4, 1], [5, 2], [6, 3]]);
+        assert_eq!(rows(&grid.rotated_left()), [[3, 6], [2, 5], [1, 4]]);
+        assert_eq!(rows(&grid.flipped_horizontally()), [[3, 2, 1], [6, 5, 4]]);
+        assert_eq!(rows(&grid.flipped_vertically()), [[4, 5, 6], [1, 2, 3]]);
+        assert_eq!(grid.rotated_right().rotated_left(), grid);
+        assert_eq!(
+            grid.rotated_right().rotated_right(),
+            grid.flipped_horizontally().flipped_vertically()
+        );
+        // the same on rows
+        let rows = rows(&grid);
+        assert_eq!(transpose(&rows), [[1, 4], [2, 5], [3, 6]]);
+        assert_eq!(rotate_right(&rows), [[4, 1], [5, 2], [6, 3]]);
+        assert_eq!(rotate_left(&rows), [[3, 6], [2, 5], [1, 4]]);
+        assert_eq!(transpose::<u8>(&[]), Vec::<Vec<u8>>::new());
+    }
+}
@@ -0,0 +1,135 @@
+use std::ops::{Index, IndexMut};
+
+use serde::{Deserialize, Serialize};
+
+/// A position in a [`Grid3`], as `(x, y, z)`
+pub type Pos3 = (usize, usize, usize);
+
+/// A box of voxels stored layer by layer along `z`, then row by row, in a single `Vec`
+///
+/// All the cells of a column `(x, y)` are `width * depth` apart, so scanning a column up or down with
+/// [`Grid3::column`] doesn't need any lookup.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Grid3<T> {
+    width: usize,
+    depth: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid3<T> {
+    /// A grid of `width` along `x`, `depth` along `y` and `height` along `z` with every cell set to `fill`
+    pub fn new(width: usize, depth: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            depth,
+            height,
+            cells: vec![fill; width * depth * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Is the position inside of the grid
+    pub fn contains(&self, (x, y, z): Pos3) -> bool {
+        x < self.width && y < self.depth && z < self.height
+    }
+
+    fn index_of(&self, pos: Pos3) -> Option<usize> {
+        let (x, y, z) = pos;
+        self.contains(pos)
+            .then(|| (z * self.depth + y) * self.width + x)
+    }
+
+    pub fn get(&self, pos: Pos3) -> Option<&T> {
+        self.index_of(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos3) -> Option<&mut T> {
+        self.index_of(pos).map(|i| &mut self.cells[i])
+    }
+
+    /// The cells of the column at `(x, y)`, from `z = 0` upwards, or nothing if it's outside of the grid
+    pub fn column(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        let start = if x < self.width && y < self.depth {
+            y * self.width + x
+        } else {
+            self.cells.len()
+        };
+        self.cells[start..]
+            .iter()
+            .step_by((self.width * self.depth).max(1))
+    }
+}
+
+impl<T> Grid3<Option<T>> {
+    /// Is there something in the cell, `false` outside of the grid
+    pub fn is_occupied(&self, pos: Pos3) -> bool {
+        matches!(self.get(pos), Some(Some(_)))
+    }
+
+    /// The highest occupied `z` of the column at `(x, y)` which is strictly below `below`
+    pub fn top_below(&self, x: usize, y: usize, below: usize) -> Option<usize> {
+        self.column(x, y).take(below).rposition(Option::is_some)
+    }
+}
+
+impl<T> Index<Pos3> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos3) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside of the grid"))
+    }
+}
+
+impl<T> IndexMut<Pos3> for Grid3<T> {
+    fn index_mut(&mut self, pos: Pos3) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is outside of the grid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid3() {
+        let mut grid = Grid3::new(3, 2, 4, None);
+        assert_eq!((grid.width(), grid.depth(), grid.height()), (3, 2, 4));
+        grid[(2, 1, 0)] = Some('a');
+        grid[(2, 1, 2)] = Some('b');
+        grid[(0, 1, 3)] = Some('c');
+        assert!(grid.is_occupied((2, 1, 2)));
+        assert!(!grid.is_occupied((1, 1, 2)));
+        assert!(!grid.is_occupied((3, 0, 0)));
+        assert_eq!(grid.get((0, 2, 0)), None);
+        assert_eq!(
+            grid.column(2, 1).collect::<Vec<_>>(),
+            [&Some('a'), &None, &Some('b'), &None]
+        );
+        assert_eq!(grid.column(3, 1).count(), 0);
+        assert_eq!(grid.top_below(2, 1, 4), Some(2));
+        assert_eq!(grid.top_below(2, 1, 2), Some(0));
+        assert_eq!(grid.top_below(0, 1, 3), None);
+        assert_eq!(grid.top_below(1, 0, 4), None);
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt::{self, Display};
+
+use owo_colors::{OwoColorize, Style};
+
+use super::{Grid, Vec2};
+use crate::color;
+
+/// The style of a display which isn't styled
+type Unstyled<T> = fn(Vec2<isize>, &T) -> Style;
+
+/// Draws a grid one line per row, with a character for each cell and optionally a style
+///
+/// The style is only applied when the colors are enabled (see [`color::enabled`]), so the drawing can be styled
+/// unconditionally.
+pub struct GridDisplay<'a, T, G, S = Unstyled<T>> {
+    grid: &'a Grid<T>,
+    glyph: G,
+    style: Option<S>,
+}
+
+impl<'a, T: Clone + Into<char>> GridDisplay<'a, T, fn(Vec2<isize>, &T) -> char> {
+    /// Draw each cell as the character it converts to
+    pub fn new(grid: &'a Grid<T>) -> Self {
+        Self::with_glyphs(grid, |_, cell| cell.clone().into())
+    }
+}
+
+impl<'a, T, G: Fn(Vec2<isize>, &T) -> char> GridDisplay<'a, T, G> {
+    /// Draw each cell as the character `glyph` gives for it and its position
+    pub fn with_glyphs(grid: &'a Grid<T>, glyph: G) -> Self {
+        Self {
+            grid,
+            glyph,
+            style: None,
+        }
+    }
+
+    /// Style each cell with what `style` gives for it and its position
+    pub fn styled<S: Fn(Vec2<isize>, &T) -> Style>(self, style: S) -> GridDisplay<'a, T, G, S> {
+        GridDisplay {
+            grid: self.grid,
+            glyph: self.glyph,
+            style: Some(style),
+        }
+    }
+}
+
+impl<T, G, S> Display for GridDisplay<'_, T, G, S>
+where
+    G: Fn(Vec2<isize>, &T) -> char,
+    S: Fn(Vec2<isize>, &T) -> Style,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let style = self.style.as_ref().filter(|_| color::enabled());
+        for (pos, cell) in self.grid.iter() {
+            if pos.x == 0 && pos.y > 0 {
+                writeln!(f)?;
+            }
+            let glyph = (self.glyph)(pos, cell);
+            match style {
+                Some(style) => write!(f, "{}", glyph.style(style(pos, cell)))?,
+                None => write!(f, "{glyph}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_display() {
+        let grid = Grid::from_rows(vec![vec!['#', '.'], vec!['.', '#']]).unwrap();
+        assert_eq!(GridDisplay::new(&grid).to_string(), "#.\n.#");
+        let walls = Grid::from_rows(vec![vec![true, false, false]]).unwrap();
+        // the colors are disabled in the tests, so the style is ignored
+        let display = GridDisplay::with_glyphs(&walls, |pos, &wall| match (wall, pos.x) {
+            (true, _) => '#',
+            (false, x) => char::from_digit(u32::try_from(x).unwrap(), 10).unwrap(),
+        })
+        .styled(|_, _| Style::new().red());
+        assert_eq!(display.to_string(), "#12");
+    }
+}
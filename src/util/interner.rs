@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use super::FastMap;
+
+/// Gives each distinct name a small id, numbered from 0 in the order the names were first seen
+///
+/// The ids can index a `Vec` (or be node indices) in place of a `HashMap` keyed by the names, and the names are still
+/// there to find the start of a puzzle or to display an answer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interner<'a> {
+    #[serde(borrow)]
+    names: Vec<&'a str>,
+    #[serde(borrow)]
+    ids: FastMap<&'a str, u32>,
+}
+
+impl<'a> Interner<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of `name`, which is given the next free one if it wasn't seen before
+    pub fn intern(&mut self, name: &'a str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = u32::try_from(self.names.len()).expect("too many names to intern");
+        self.names.push(name);
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// The id of `name`, `None` if it was never interned
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    /// The name with the given id
+    ///
+    /// Panics if the id wasn't given by this interner.
+    pub fn name(&self, id: u32) -> &'a str {
+        self.names[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// The ids with their names, in the order of the ids
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &'a str)> + '_ {
+        (0..).zip(self.names.iter().copied())
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Interner<'a> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut interner = Self::new();
+        for name in iter {
+            interner.intern(name);
+        }
+        interner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut names: Interner = ["in", "px", "in"].into_iter().collect();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.intern("qkq"), 2);
+        assert_eq!(names.intern("px"), 1);
+        assert_eq!((names.get("in"), names.get("rfg")), (Some(0), None));
+        assert_eq!(names.name(2), "qkq");
+        assert_eq!(
+            names.iter().collect::<Vec<_>>(),
+            [(0, "in"), (1, "px"), (2, "qkq")]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::ops::{Range, Sub};
+
+use num::Zero;
+use serde::{Deserialize, Serialize};
+
+/// A half-open interval of values `start..end`, like a [`Range`] but `Copy` and with set operations
+///
+/// The operations can return empty intervals, which always have `start == end` so that their [`Interval::len`] is
+/// zero. [`Interval::non_empty`] turns them into `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Copy + Ord> Interval<T> {
+    /// The interval `start..end`, which is empty if `end <= start`
+    pub fn new(start: T, end: T) -> Self {
+        Self {
+            start,
+            end: end.max(start),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// `None` if the interval is empty
+    pub fn non_empty(self) -> Option<Self> {
+        (!self.is_empty()).then_some(self)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// The values which are in both intervals
+    pub fn intersection(self, other: Self) -> Self {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// Split the interval into the values below `at` and the ones from `at` onwards
+    pub fn split_at(self, at: T) -> (Self, Self) {
+        let at = at.clamp(self.start, self.end);
+        (Self::new(self.start, at), Self::new(at, self.end))
+    }
+
+    /// The values which are not in `other`, as the parts below and above it
+    pub fn difference(self, other: Self) -> (Self, Self) {
+        let (below, rest) = self.split_at(other.start);
+        (below, rest.split_at(other.end).1)
+    }
+}
+
+impl<T: Copy + Ord + Sub<Output = T> + Zero> Interval<T> {
+    /// The number of values in the interval
+    pub fn len(&self) -> T {
+        if self.is_empty() {
+            T::zero()
+        } else {
+            self.end - self.start
+        }
+    }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for Interval<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl<T> From<Interval<T>> for Range<T> {
+    fn from(interval: Interval<T>) -> Self {
+        interval.start..interval.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval() {
+        let a = Interval::new(2, 10);
+        assert_eq!(a.len(), 8);
+        assert!(a.contains(2) && !a.contains(10));
+        assert_eq!(a.intersection(Interval::new(5, 20)), Interval::new(5, 10));
+        assert!(a.intersection(Interval::new(10, 20)).is_empty());
+        assert_eq!(a.intersection(Interval::new(12, 20)).len(), 0);
+        assert_eq!(a.split_at(4), (Interval::new(2, 4), Interval::new(4, 10)));
+        assert_eq!(a.split_at(0).1, a);
+        assert!(a.split_at(0).0.is_empty());
+        assert_eq!(
+            a.difference(Interval::new(4, 6)),
+            (Interval::new(2, 4), Interval::new(6, 10))
+        );
+        let (below, above) = a.difference(Interval::new(0, 20));
+        assert_eq!(below.non_empty().or(above.non_empty()), None);
+        assert_eq!(Interval::new(5u64, 3).len(), 0);
+        assert_eq!(Range::from(Interval::from(3..5)).sum::<i32>(), 7);
+    }
+}
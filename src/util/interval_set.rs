@@ -0,0 +1,175 @@
+use std::ops::{Add, Sub};
+
+use num::Zero;
+use serde::{Deserialize, Serialize};
+
+use super::Interval;
+
+/// A set of values stored as sorted, disjoint intervals
+///
+/// Intervals which overlap or touch are merged when inserted, so that each value of the set is in exactly one interval
+/// and the set has a single representation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The intervals of the set, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = Interval<T>> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    /// Add the values of an interval to the set
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        // the intervals which overlap or touch the new one are replaced by their union with it
+        let first = self.intervals.partition_point(|i| i.end < interval.start);
+        let last = self.intervals.partition_point(|i| i.start <= interval.end);
+        let mut merged = interval;
+        if first < last {
+            merged.start = merged.start.min(self.intervals[first].start);
+            merged.end = merged.end.max(self.intervals[last - 1].end);
+        }
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.intervals.partition_point(|i| i.end <= value);
+        self.intervals.get(i).is_some_and(|i| i.contains(value))
+    }
+
+    /// The smallest value of the set
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    /// The end of the part of the set which is below `value`, which is one more than the largest value of the set
+    /// smaller than `value` for integers
+    pub fn end_before(&self, value: T) -> Option<T> {
+        let i = self.intervals.partition_point(|i| i.start < value);
+        i.checked_sub(1).map(|i| self.intervals[i].end.min(value))
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for interval in other.iter() {
+            union.insert(interval);
+        }
+        union
+    }
+
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut intervals = Vec::new();
+        while let (Some(&a), Some(&b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            if let Some(both) = a.intersection(b).non_empty() {
+                intervals.push(both);
+            }
+            // the interval which ends first can't overlap with the next ones of the other set
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { intervals }
+    }
+
+    /// The values of the set which are not in `other`
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        for &interval in &self.intervals {
+            let mut rest = interval;
+            let first = other.intervals.partition_point(|o| o.end <= interval.start);
+            for &removed in other.intervals[first..]
+                .iter()
+                .take_while(|o| o.start < interval.end)
+            {
+                let (below, above) = rest.difference(removed);
+                intervals.extend(below.non_empty());
+                rest = above;
+            }
+            intervals.extend(rest.non_empty());
+        }
+        Self { intervals }
+    }
+}
+
+impl<T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Zero> IntervalSet<T> {
+    /// The number of values in the set
+    pub fn len(&self) -> T {
+        self.iter().fold(T::zero(), |acc, i| acc + i.len())
+    }
+}
+
+impl<T: Copy + Ord> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        Self::from_iter([interval])
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for interval in iter {
+            set.insert(interval);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals
+            .iter()
+            .map(|&(a, b)| Interval::new(a, b))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut s = set(&[(10, 15), (0, 3), (20, 25)]);
+        assert_eq!(s.iter().count(), 3);
+        assert_eq!(s.len(), 13);
+        s.insert(Interval::new(3, 5));
+        s.insert(Interval::new(12, 21));
+        s.insert(Interval::new(7, 7));
+        assert_eq!(s, set(&[(0, 5), (10, 25)]));
+        assert!(s.contains(4) && s.contains(10) && !s.contains(5) && !s.contains(25));
+        assert_eq!(s.min(), Some(0));
+        assert_eq!(s.end_before(8), Some(5));
+        assert_eq!(s.end_before(12), Some(12));
+        assert_eq!(s.end_before(0), None);
+    }
+
+    #[test]
+    fn test_operations() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25), (28, 40)]);
+        assert_eq!(a.union(&b), set(&[(0, 40)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25), (28, 30)]));
+        assert_eq!(a.difference(&b), set(&[(0, 5), (25, 28)]));
+        assert_eq!(b.difference(&a), set(&[(10, 20), (30, 40)]));
+        assert!(a.difference(&a).is_empty());
+        assert_eq!(a.intersection(&IntervalSet::new()), IntervalSet::new());
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    hash::Hash,
+    ops::{Add, Sub},
+};
+
+use itertools::Itertools;
+
+use super::Vec2;
+
+/// Iterator adapters for the compositions that keep coming back in the puzzles
+pub trait IterExt: Iterator + Sized {
+    /// The groups of lines separated by blank ones, several blank lines in a row counting as one separator
+    fn chunks_by_blank_line(self) -> impl Iterator<Item = Vec<Self::Item>>
+    where
+        Self::Item: AsRef<str>,
+    {
+        let blank = |line: &Self::Item| line.as_ref().trim().is_empty();
+        let mut lines = self.peekable();
+        std::iter::from_fn(move || {
+            while lines.next_if(blank).is_some() {}
+            let chunk = lines.by_ref().take_while(|line| !blank(line)).collect_vec();
+            (!chunk.is_empty()).then_some(chunk)
+        })
+    }
+
+    /// The differences between each value and the one before it, one fewer than the values
+    fn pairwise_diffs<T>(self) -> impl Iterator<Item = T>
+    where
+        Self: Iterator<Item = T>,
+        T: Copy + Sub<Output = T>,
+    {
+        self.tuple_windows().map(|(a, b)| b - a)
+    }
+
+    /// How many times each distinct value occurs, from the most common one to the least common one
+    fn counts_sorted_desc(self) -> Vec<usize>
+    where
+        Self::Item: Eq + Hash,
+    {
+        let mut counts = self.counts().into_values().collect_vec();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts
+    }
+
+    /// The Manhattan distance between each unordered pair of points
+    fn manhattan_pairs<T>(self) -> impl Iterator<Item = T>
+    where
+        Self: Iterator<Item = Vec2<T>>,
+        T: Copy + Ord + Sub<Output = T> + Add<Output = T>,
+    {
+        self.collect_vec()
+            .into_iter()
+            .tuple_combinations()
+            .map(|(a, b)| a.manhattan(b))
+    }
+}
+
+impl<I: Iterator> IterExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iter_ext() {
+        let text = "ab\ncd\n\n\nef\n\n";
+        assert_eq!(
+            text.lines().chunks_by_blank_line().collect_vec(),
+            [vec!["ab", "cd"], vec!["ef"]]
+        );
+        assert_eq!(
+            [0, 3, 6, 10, 15].into_iter().pairwise_diffs().collect_vec(),
+            [3, 3, 4, 5]
+        );
+        assert_eq!("T55J5".chars().counts_sorted_desc(), [3, 1, 1]);
+        let points = [Vec2::new(1, 6), Vec2::new(5, 11), Vec2::new(4, 0)];
+        assert_eq!(
+            points.into_iter().manhattan_pairs().collect_vec(),
+            [9, 9, 12]
+        );
+    }
+}
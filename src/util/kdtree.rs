@@ -0,0 +1,212 @@
+//! A k-d tree over points in `N` dimensions, for the range and nearest neighbor queries which would otherwise scan
+//! every point
+//!
+//! The points are given as arrays, `[x, y]` or `[x, y, z]`, so a `Vec2<i64>` or a `Vec3<i64>` can be passed as it is.
+
+use super::Aabb;
+
+/// How far apart two points are for [`KdTree::nearest`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// The sum of the distances along each axis
+    Manhattan,
+    /// The square of the Euclidean distance, which orders the points the same way without a square root
+    EuclidSq,
+}
+
+impl Metric {
+    pub fn distance<const N: usize>(self, a: [i64; N], b: [i64; N]) -> i128 {
+        a.into_iter()
+            .zip(b)
+            .map(|(a, b)| self.axis_distance(i128::from(a) - i128::from(b)))
+            .sum()
+    }
+
+    /// The part of the distance of a difference of `diff` along an axis, which bounds the distance to any point on
+    /// the other side of a splitting plane
+    fn axis_distance(self, diff: i128) -> i128 {
+        match self {
+            Metric::Manhattan => diff.abs(),
+            Metric::EuclidSq => diff * diff,
+        }
+    }
+}
+
+/// Points with a value each, stored in a balanced k-d tree which is built once
+///
+/// The tree is implicit: each slice of the points is split at its middle point along an axis, with the points below it
+/// on the left and the ones above it on the right, and both halves are split along the next axis.
+#[derive(Debug, Clone)]
+pub struct KdTree<T, const N: usize> {
+    nodes: Vec<([i64; N], T)>,
+}
+
+impl<T, const N: usize> KdTree<T, N> {
+    pub fn new<P: Into<[i64; N]>>(items: impl IntoIterator<Item = (P, T)>) -> Self {
+        let mut nodes: Vec<_> = items
+            .into_iter()
+            .map(|(point, value)| (point.into(), value))
+            .collect();
+        build(&mut nodes, 0);
+        Self { nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The points and their values, in the order of the tree
+    pub fn iter(&self) -> impl Iterator<Item = (&[i64; N], &T)> {
+        self.nodes.iter().map(|(point, value)| (point, value))
+    }
+
+    /// The points inside of `aabb` and their values, in no particular order
+    pub fn range(&self, aabb: &Aabb<i64, N>) -> Vec<(&[i64; N], &T)> {
+        let mut found = Vec::new();
+        range_in(&self.nodes, 0, aabb, &mut found);
+        found
+    }
+
+    /// The point closest to `target` with its value and its distance, `None` if the tree is empty
+    ///
+    /// Of several points at the same distance, any one can be returned.
+    pub fn nearest(
+        &self,
+        target: impl Into<[i64; N]>,
+        metric: Metric,
+    ) -> Option<(&[i64; N], &T, i128)> {
+        let mut best = None;
+        nearest_in(&self.nodes, 0, target.into(), metric, &mut best);
+        best.map(|((point, value), distance)| (point, value, distance))
+    }
+}
+
+impl<T, P: Into<[i64; N]>, const N: usize> FromIterator<(P, T)> for KdTree<T, N> {
+    fn from_iter<I: IntoIterator<Item = (P, T)>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+/// Split the nodes at their middle along `axis`, and both halves along the next axis
+fn build<T, const N: usize>(nodes: &mut [([i64; N], T)], axis: usize) {
+    if nodes.len() <= 1 {
+        return;
+    }
+    let mid = nodes.len() / 2;
+    nodes.select_nth_unstable_by_key(mid, |(point, _)| point[axis]);
+    let (left, right) = nodes.split_at_mut(mid);
+    build(left, (axis + 1) % N);
+    build(&mut right[1..], (axis + 1) % N);
+}
+
+fn range_in<'a, T, const N: usize>(
+    nodes: &'a [([i64; N], T)],
+    axis: usize,
+    aabb: &Aabb<i64, N>,
+    found: &mut Vec<(&'a [i64; N], &'a T)>,
+) {
+    if nodes.is_empty() {
+        return;
+    }
+    let mid = nodes.len() / 2;
+    let (point, value) = &nodes[mid];
+    if aabb.contains(*point) {
+        found.push((point, value));
+    }
+    // the points equal to the middle one along the axis can be on both sides
+    if aabb.min[axis] <= point[axis] {
+        range_in(&nodes[..mid], (axis + 1) % N, aabb, found);
+    }
+    if aabb.max[axis] >= point[axis] {
+        range_in(&nodes[mid + 1..], (axis + 1) % N, aabb, found);
+    }
+}
+
+type Nearest<'a, T, const N: usize> = Option<(&'a ([i64; N], T), i128)>;
+
+fn nearest_in<'a, T, const N: usize>(
+    nodes: &'a [([i64; N], T)],
+    axis: usize,
+    target: [i64; N],
+    metric: Metric,
+    best: &mut Nearest<'a, T, N>,
+) {
+    if nodes.is_empty() {
+        return;
+    }
+    let mid = nodes.len() / 2;
+    let node = &nodes[mid];
+    let distance = metric.distance(node.0, target);
+    if best.is_none_or(|(_, best)| distance < best) {
+        *best = Some((node, distance));
+    }
+    // search the side of the target first, then the other side only if it can hold a closer point
+    let diff = i128::from(target[axis]) - i128::from(node.0[axis]);
+    let (left, right) = (&nodes[..mid], &nodes[mid + 1..]);
+    let (near, far) = if diff < 0 {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    nearest_in(near, (axis + 1) % N, target, metric, best);
+    if best.is_none_or(|(_, best)| metric.axis_distance(diff) < best) {
+        nearest_in(far, (axis + 1) % N, target, metric, best);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{gen::Rng, Vec2, Vec3};
+
+    #[test]
+    fn test_kdtree_2d() {
+        let tree: KdTree<char, 2> = [(0, 0, 'a'), (5, 1, 'b'), (2, 8, 'c'), (-3, 4, 'd')]
+            .into_iter()
+            .map(|(x, y, name)| (Vec2::new(x, y), name))
+            .collect();
+        assert_eq!(tree.len(), 4);
+        let nearest = tree.nearest(Vec2::new(-2, 2), Metric::Manhattan);
+        assert_eq!(nearest, Some((&[-3, 4], &'d', 3)));
+        assert_eq!(tree.nearest([4, 4], Metric::EuclidSq).unwrap().1, &'b');
+        let mut names: Vec<_> = tree
+            .range(&Aabb::new([0, 0], [5, 8]))
+            .into_iter()
+            .map(|(_, &name)| name)
+            .collect();
+        names.sort_unstable();
+        assert_eq!(names, ['a', 'b', 'c']);
+        let empty = KdTree::<(), 2>::new(Vec::<([i64; 2], ())>::new());
+        assert_eq!(empty.nearest([0, 0], Metric::Manhattan), None);
+    }
+
+    #[test]
+    fn test_kdtree_brute_force() {
+        let mut rng = Rng::new(22);
+        let mut point = || Vec3::from([(); 3].map(|()| rng.range(-50..=50)));
+        let points: Vec<_> = (0..500).map(|_| point()).collect();
+        let tree: KdTree<usize, 3> = points.iter().copied().zip(0..).collect();
+        for _ in 0..100 {
+            let target = point();
+            for metric in [Metric::Manhattan, Metric::EuclidSq] {
+                let closest = points
+                    .iter()
+                    .map(|&p| metric.distance(p.into(), target.into()))
+                    .min();
+                let nearest = tree.nearest(target, metric).map(|(.., d)| d);
+                assert_eq!(nearest, closest);
+            }
+            let aabb = Aabb::from_points([target.into(), point().into()]).unwrap();
+            let mut found: Vec<_> = tree.range(&aabb).into_iter().map(|(_, &i)| i).collect();
+            found.sort_unstable();
+            let inside: Vec<_> = (0..points.len())
+                .filter(|&i| aabb.contains(points[i].into()))
+                .collect();
+            assert_eq!(found, inside);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::ops::{Index, IndexMut, Mul};
+
+use num::{Num, Signed};
+
+/// A dense matrix stored row by row, over `f64` or an exact number type like [`super::Ratio`]
+///
+/// The elimination picks the pivot with the largest absolute value in each column, which keeps the rounding errors low
+/// with floats and doesn't matter with exact numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone + Num + Signed + PartialOrd> Matrix<T> {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            cells: vec![T::zero(); rows * cols],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zeros(size, size);
+        for i in 0..size {
+            matrix[(i, i)] = T::one();
+        }
+        matrix
+    }
+
+    /// Build a matrix from its rows, or `None` if they don't all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let cols = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        if rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+        Some(Self {
+            rows: height,
+            cols,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for col in 0..self.cols {
+            self.cells.swap(a * self.cols + col, b * self.cols + col);
+        }
+    }
+
+    /// Put the matrix in reduced row echelon form with Gauss-Jordan elimination, returning its rank
+    ///
+    /// Each pivot is one and is the only non-zero value of its column.
+    pub fn row_reduce(&mut self) -> usize {
+        let mut rank = 0;
+        for col in 0..self.cols {
+            if rank == self.rows {
+                break;
+            }
+            let Some(pivot) = (rank..self.rows)
+                .filter(|&row| !self[(row, col)].is_zero())
+                .max_by(|&a, &b| {
+                    self[(a, col)]
+                        .abs()
+                        .partial_cmp(&self[(b, col)].abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+            else {
+                continue;
+            };
+            self.swap_rows(rank, pivot);
+            let scale = self[(rank, col)].clone();
+            for c in col..self.cols {
+                self[(rank, c)] = self[(rank, c)].clone() / scale.clone();
+            }
+            for row in (0..self.rows).filter(|&row| row != rank) {
+                let factor = self[(row, col)].clone();
+                if factor.is_zero() {
+                    continue;
+                }
+                for c in col..self.cols {
+                    self[(row, c)] =
+                        self[(row, c)].clone() - factor.clone() * self[(rank, c)].clone();
+                }
+            }
+            rank += 1;
+        }
+        rank
+    }
+
+    /// The solution `x` of `self * x = b`, `None` if there is no solution or more than one
+    ///
+    /// # Panics
+    /// If `b` doesn't have one value per row of the matrix.
+    pub fn solve(&self, b: &[T]) -> Option<Vec<T>> {
+        assert_eq!(b.len(), self.rows, "one value per row is needed");
+        // the augmented matrix `[self | b]`
+        let mut augmented = Self::zeros(self.rows, self.cols + 1);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                augmented[(row, col)] = self[(row, col)].clone();
+            }
+            augmented[(row, self.cols)] = b[row].clone();
+        }
+        let rank = augmented.row_reduce();
+        // a pivot in the last column means `0 = 1`, and fewer pivots than unknowns leave some of them free
+        if (0..rank).any(|row| augmented.row(row)[..self.cols].iter().all(T::is_zero))
+            || rank < self.cols
+        {
+            return None;
+        }
+        Some(
+            (0..self.cols)
+                .map(|row| augmented[(row, self.cols)].clone())
+                .collect(),
+        )
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    /// The value at `(row, col)`
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({row}, {col}) is outside of the matrix"
+        );
+        &self.cells[row * self.cols + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        assert!(
+            row < self.rows && col < self.cols,
+            "({row}, {col}) is outside of the matrix"
+        );
+        &mut self.cells[row * self.cols + col]
+    }
+}
+
+impl<T: Clone + Num + Signed + PartialOrd> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    /// # Panics
+    /// If the number of columns of the left matrix is not the number of rows of the right one.
+    fn mul(self, rhs: Self) -> Matrix<T> {
+        assert_eq!(self.cols, rhs.rows, "the sizes of the matrices don't match");
+        let mut product = Matrix::zeros(self.rows, rhs.cols);
+        for row in 0..self.rows {
+            for col in 0..rhs.cols {
+                product[(row, col)] = (0..self.cols).fold(T::zero(), |acc, k| {
+                    acc + self[(row, k)].clone() * rhs[(k, col)].clone()
+                });
+            }
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Ratio;
+
+    fn ratios(rows: &[&[i128]]) -> Matrix<Ratio> {
+        Matrix::from_rows(
+            rows.iter()
+                .map(|row| row.iter().map(|&v| Ratio::from_integer(v)).collect())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_solve() {
+        // 2x + y - z = 8, -3x - y + 2z = -11, -2x + y + 2z = -3
+        let a = ratios(&[&[2, 1, -1], &[-3, -1, 2], &[-2, 1, 2]]);
+        let b = [8, -11, -3].map(Ratio::from_integer);
+        assert_eq!(
+            a.solve(&b),
+            Some([2, 3, -1].map(Ratio::from_integer).to_vec())
+        );
+        // no solution and infinitely many
+        let singular = ratios(&[&[1, 2], &[2, 4]]);
+        assert_eq!(
+            singular.solve(&[Ratio::from_integer(1), Ratio::from_integer(3)]),
+            None
+        );
+        assert_eq!(
+            singular.solve(&[Ratio::from_integer(1), Ratio::from_integer(2)]),
+            None
+        );
+        // exact fractions
+        let a = ratios(&[&[3, 0], &[0, 7]]);
+        let x = a
+            .solve(&[Ratio::from_integer(1), Ratio::from_integer(2)])
+            .unwrap();
+        assert_eq!(x, [Ratio::new(1, 3), Ratio::new(2, 7)]);
+        // floats
+        let a = Matrix::from_rows(vec![vec![1e-3, 1.], vec![1., 1.]]).unwrap();
+        let x = a.solve(&[1., 2.]).unwrap();
+        assert!((x[0] - 1.001).abs() < 1e-3 && (x[1] - 0.999).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let mut a = ratios(&[&[1, 2, 3], &[2, 4, 6], &[1, 0, 1]]);
+        let i = Matrix::identity(3);
+        assert_eq!(&a * &i, a);
+        assert_eq!(
+            (&ratios(&[&[1, 2]]) * &ratios(&[&[3], &[4]]))[(0, 0)],
+            Ratio::from_integer(11)
+        );
+        assert_eq!(a.row_reduce(), 2);
+        assert_eq!(a, ratios(&[&[1, 0, 1], &[0, 1, 1], &[0, 0, 0]]));
+        assert_eq!(Matrix::<f64>::from_rows(vec![vec![1.], vec![]]), None);
+    }
+}
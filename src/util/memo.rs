@@ -0,0 +1,178 @@
+use std::hash::Hash;
+
+use super::FastMap;
+
+/// A cache of the values of a recursive function, for dynamic programming
+///
+/// [`Memo::get_or_compute`] passes the memo itself to the function computing a missing value, so that it can recurse
+/// through it. The memo counts how many values were found in the cache and how many had to be computed, and can be
+/// bounded: once it holds `capacity` values, the new ones are still computed but no longer stored.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: FastMap<K, V>,
+    capacity: Option<usize>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: FastMap::default(),
+            capacity: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// A memo which stores at most `capacity` values
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    /// The cached value for `key`, or the value computed by `f` which is then cached
+    pub fn get_or_compute(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let value = f(self);
+        if self
+            .capacity
+            .is_none_or(|capacity| self.cache.len() < capacity)
+        {
+            self.cache.insert(key, value.clone());
+        }
+        value
+    }
+
+    /// The number of values in the cache
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// How many times a value was found in the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// How many times a value had to be computed
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Empty the cache and reset the statistics
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Define a recursive function whose values are cached in a [`Memo`] keyed by its arguments
+///
+/// ```ignore
+/// memo_fn! {
+///     fn paths<'a>(grid: &'a [u8], pos: usize) -> usize {
+///         if pos + 1 >= grid.len() { 1 } else { paths(grid, pos + 1) + paths(grid, pos + 2) }
+///     }
+/// }
+/// ```
+///
+/// The body calls the function by its name as usual, and those calls go through the memo. Each call from outside of
+/// the function starts with an empty memo, which is dropped when it returns. The arguments must be `Clone + Eq + Hash`
+/// and the return value `Clone`, and the lifetimes of references in the arguments must be named in the signature.
+macro_rules! memo_fn {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident $(<$($lt:lifetime),+>)?
+        ($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block
+    ) => {
+        $(#[$attr])*
+        $vis fn $name $(<$($lt),+>)? ($($arg: $ty),*) -> $ret {
+            fn recurse $(<$($lt),+>)? (
+                memo: &mut $crate::util::Memo<($($ty,)*), $ret>,
+                $($arg: $ty),*
+            ) -> $ret {
+                memo.get_or_compute(($(::std::clone::Clone::clone(&$arg),)*), |memo| {
+                    // the calls of the body to the function go through the memo
+                    #[allow(unused_mut, unused_variables)]
+                    let mut $name = |$($arg: $ty),*| recurse(memo, $($arg),*);
+                    $body
+                })
+            }
+            recurse(&mut $crate::util::Memo::new(), $($arg),*)
+        }
+    };
+}
+pub(crate) use memo_fn;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        memo.get_or_compute(n, |memo| fibonacci(memo, n - 1) + fibonacci(memo, n - 2))
+    }
+
+    memo_fn! {
+        fn binomial(n: u64, k: u64) -> u64 {
+            if k == 0 || k == n {
+                1
+            } else {
+                binomial(n - 1, k - 1) + binomial(n - 1, k)
+            }
+        }
+    }
+
+    memo_fn! {
+        /// The number of ways to write `total` as a sum of the coins, in any order
+        fn change<'a>(coins: &'a [u64], total: u64) -> u64 {
+            match coins.split_first() {
+                _ if total == 0 => 1,
+                None => 0,
+                Some((&coin, rest)) => {
+                    let with_coin = if coin <= total { change(coins, total - coin) } else { 0 };
+                    with_coin + change(rest, total)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_memo_fn() {
+        assert_eq!(binomial(60, 30), 118_264_581_564_861_424);
+        assert_eq!(change(&[1, 2, 5, 10, 20, 50, 100, 200], 200), 73_682);
+    }
+
+    #[test]
+    fn test_memo() {
+        let mut memo = Memo::new();
+        assert_eq!(fibonacci(&mut memo, 80), 23_416_728_348_467_685);
+        assert_eq!((memo.len(), memo.misses(), memo.hits()), (79, 79, 77));
+        assert_eq!(fibonacci(&mut memo, 80), 23_416_728_348_467_685);
+        assert_eq!(memo.hits(), 78);
+        memo.clear();
+        assert!(memo.is_empty());
+        let mut bounded = Memo::bounded(10);
+        assert_eq!(fibonacci(&mut bounded, 30), 832_040);
+        assert_eq!(bounded.len(), 10);
+    }
+}
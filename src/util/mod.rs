@@ -0,0 +1,100 @@
+//! Building blocks shared by the days
+//!
+//! The days using a helper can be compiled out, so the helpers are allowed to be unused in the builds of only some of
+//! the days. The modules marked with `allow(dead_code)` are more general than what the current solutions need, for
+//! the puzzles to come.
+#![cfg_attr(
+    not(feature = "all-days"),
+    allow(dead_code, unused_imports, unused_macros)
+)]
+
+#[allow(dead_code)]
+pub mod aabb;
+pub mod arena;
+pub mod bisect;
+#[allow(dead_code)]
+pub mod bitmask;
+pub mod bitset;
+pub mod collections;
+// the compressed grid and the fills are only the cross-checks of the tests of days 10 and 18
+#[cfg(test)]
+#[allow(dead_code)]
+pub mod compress;
+#[allow(dead_code)]
+pub mod cycle;
+pub mod direction;
+#[allow(dead_code)]
+pub mod dsu;
+#[cfg(test)]
+pub mod flood;
+pub mod gen;
+#[allow(dead_code)]
+pub mod geom;
+#[cfg(feature = "petgraph")]
+#[allow(dead_code)]
+pub mod graph;
+#[allow(dead_code)]
+pub mod grid;
+#[allow(dead_code)]
+pub mod grid3;
+pub mod grid_display;
+#[allow(dead_code)]
+pub mod interner;
+pub mod interval;
+#[allow(dead_code)]
+pub mod interval_set;
+#[allow(dead_code)]
+pub mod iter_ext;
+#[allow(dead_code)]
+pub mod kdtree;
+pub mod linalg;
+#[allow(dead_code)]
+pub mod memo;
+#[allow(dead_code)]
+pub mod modint;
+pub mod neighbors;
+#[allow(dead_code)]
+pub mod numtheory;
+pub mod polygon;
+#[allow(dead_code)]
+pub mod pose;
+#[allow(dead_code)]
+pub mod prefix_sum;
+pub mod ratio;
+#[allow(dead_code)]
+pub mod search;
+#[allow(dead_code)]
+pub mod sparse_grid;
+pub mod symmetry;
+pub mod vec2;
+pub mod vec3;
+
+pub use aabb::Aabb;
+pub use bitmask::VisitedMask;
+pub use bitset::BitSet;
+pub use collections::{FastMap, FastSet};
+#[cfg(test)]
+pub use compress::CompressedAxis;
+#[allow(unused_imports)]
+pub use cycle::find_cycle;
+pub use direction::Direction;
+pub use dsu::Dsu;
+pub use grid::Grid;
+pub use grid3::Grid3;
+pub use grid_display::GridDisplay;
+pub use interner::Interner;
+pub use interval::Interval;
+pub use interval_set::IntervalSet;
+pub use iter_ext::IterExt;
+pub use kdtree::KdTree;
+pub(crate) use memo::memo_fn;
+pub use memo::Memo;
+#[allow(unused_imports)]
+pub use modint::ModInt;
+pub use polygon::Polygon;
+pub use pose::Pose;
+pub use prefix_sum::{PrefixSum, PrefixSum2D};
+pub use ratio::Ratio;
+pub use sparse_grid::SparseGrid;
+pub use vec2::Vec2;
+pub use vec3::Vec3;
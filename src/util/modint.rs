@@ -0,0 +1,159 @@
+use std::{
+    fmt::{self, Display},
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::numtheory::mod_inverse;
+
+/// An integer modulo `M`, always kept in `0..M`
+///
+/// The products are computed on 128 bits, so any modulus up to `u64::MAX` works. There is no division operator since
+/// only the numbers coprime with `M` can be divided by, see [`ModInt::inverse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    pub const fn new(value: u64) -> Self {
+        Self(value % M)
+    }
+
+    /// The value in `0..M`
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Raise to the power `exp` by squaring
+    pub fn pow(self, mut exp: u64) -> Self {
+        let (mut base, mut result) = (self, Self::new(1));
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// The number which gives 1 when multiplied by this one, if this one is coprime with `M`
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn inverse(self) -> Option<Self> {
+        mod_inverse(i128::from(self.0), i128::from(M)).map(|inv| Self(inv.unsigned_abs() as u64))
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A negative number is congruent to its remainder in `0..M`
+impl<const M: u64> From<i64> for ModInt<M> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn from(value: i64) -> Self {
+        Self(i128::from(value).rem_euclid(i128::from(M)) as u64)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn add(self, rhs: Self) -> Self {
+        Self(((u128::from(self.0) + u128::from(rhs.0)) % u128::from(M)) as u64)
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self((M - self.0) % M)
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn mul(self, rhs: Self) -> Self {
+        Self((u128::from(self.0) * u128::from(rhs.0) % u128::from(M)) as u64)
+    }
+}
+
+impl<const M: u64> AddAssign for ModInt<M> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const M: u64> SubAssign for ModInt<M> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const M: u64> MulAssign for ModInt<M> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(0), Add::add)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(1), Mul::mul)
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type M7 = ModInt<7>;
+
+    #[test]
+    fn test_modint() {
+        let (a, b) = (M7::new(5), M7::new(4));
+        assert_eq!((a + b).value(), 2);
+        assert_eq!((b - a).value(), 6);
+        assert_eq!((a * b).value(), 6);
+        assert_eq!((-a).value(), 2);
+        assert_eq!(M7::from(-1i64).value(), 6);
+        assert_eq!(a.pow(6).value(), 1); // Fermat's little theorem
+        assert_eq!(a.inverse().map(|inv| a * inv), Some(M7::new(1)));
+        assert_eq!(ModInt::<10>::new(4).inverse(), None);
+        assert_eq!((1..=6).map(M7::new).product::<M7>(), M7::from(-1i64)); // Wilson's theorem
+        assert_eq!([a, b, a].into_iter().sum::<M7>().to_string(), "0");
+        // products which overflow 64 bits
+        let big = ModInt::<{ u64::MAX - 58 }>::new(u64::MAX - 59);
+        assert_eq!(big * big, ModInt::new(1));
+        assert_eq!(
+            ModInt::<1_000_000_007>::new(2).pow(1_000_000_006).value(),
+            1
+        );
+    }
+}
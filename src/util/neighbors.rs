@@ -0,0 +1,83 @@
+use num::PrimInt;
+
+use super::Vec2;
+
+/// The offsets of the orthogonal neighbors, clockwise from the north
+const ORTHOGONAL: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The offsets of the eight neighbors including the diagonals, row by row
+const ALL: [(i8, i8); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// The coordinate one step away from `v`, if it is in `0..bound`
+fn offset<T: PrimInt>(v: T, delta: i8, bound: T) -> Option<T> {
+    match delta {
+        -1 => v.checked_sub(&T::one()),
+        1 => v.checked_add(&T::one()),
+        _ => Some(v),
+    }
+    .filter(|&n| n >= T::zero() && n < bound)
+}
+
+fn neighbors<T: PrimInt>(
+    p: Vec2<T>,
+    bounds: Vec2<T>,
+    offsets: &'static [(i8, i8)],
+) -> impl Iterator<Item = Vec2<T>> {
+    offsets.iter().filter_map(move |&(dx, dy)| {
+        Some(Vec2::new(
+            offset(p.x, dx, bounds.x)?,
+            offset(p.y, dy, bounds.y)?,
+        ))
+    })
+}
+
+/// The orthogonal neighbors of `p` which are inside of `0..bounds.x` and `0..bounds.y`, clockwise from the north
+///
+/// The coordinates are checked before they are computed, so this works with unsigned coordinates on the border too.
+pub fn orthogonal_neighbors<T: PrimInt>(
+    p: Vec2<T>,
+    bounds: Vec2<T>,
+) -> impl Iterator<Item = Vec2<T>> {
+    neighbors(p, bounds, &ORTHOGONAL)
+}
+
+/// The neighbors of `p` including the diagonals which are inside of `0..bounds.x` and `0..bounds.y`, row by row
+pub fn all_neighbors<T: PrimInt>(p: Vec2<T>, bounds: Vec2<T>) -> impl Iterator<Item = Vec2<T>> {
+    neighbors(p, bounds, &ALL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbors() {
+        let bounds = Vec2::<usize>::new(3, 2);
+        assert_eq!(
+            orthogonal_neighbors(Vec2::new(0, 0), bounds).collect::<Vec<_>>(),
+            [Vec2::new(1, 0), Vec2::new(0, 1)]
+        );
+        assert_eq!(
+            all_neighbors(Vec2::new(2, 1), bounds).collect::<Vec<_>>(),
+            [Vec2::new(1, 0), Vec2::new(2, 0), Vec2::new(1, 1)]
+        );
+        assert_eq!(all_neighbors(Vec2::new(1, 0), bounds).count(), 5);
+        // signed coordinates are rejected below zero too
+        let p = Vec2::<isize>::new(0, 5);
+        assert_eq!(orthogonal_neighbors(p, Vec2::new(10, 10)).count(), 3);
+        assert_eq!(all_neighbors(p, Vec2::new(10, 6)).count(), 3);
+        assert_eq!(
+            orthogonal_neighbors(Vec2::new(4, 4), Vec2::new(9, 9)).collect::<Vec<_>>(),
+            Vec2::new(4, 4).neighbors()
+        );
+    }
+}
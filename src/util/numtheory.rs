@@ -0,0 +1,100 @@
+//! Greatest common divisors, least common multiples and systems of congruences
+//!
+//! The functions take the values by copy and work with any integer type of the `num` crate. The ones which need
+//! negative coefficients only work with signed types, use `i128` if the intermediate products could overflow.
+use num::{Integer, Signed};
+
+/// The greatest common divisor, which is never negative
+pub fn gcd<T: Integer + Copy>(a: T, b: T) -> T {
+    a.gcd(&b)
+}
+
+/// The least common multiple, which is zero if one of the numbers is zero
+pub fn lcm<T: Integer + Copy>(a: T, b: T) -> T {
+    a.lcm(&b)
+}
+
+/// The least common multiple of all the numbers, `None` if there are none
+pub fn lcm_all<T: Integer + Copy>(numbers: impl IntoIterator<Item = T>) -> Option<T> {
+    numbers.into_iter().reduce(lcm)
+}
+
+/// The greatest common divisor `g` of `a` and `b` along with coefficients `x` and `y` such that `a * x + b * y = g`
+#[allow(clippy::many_single_char_names)]
+pub fn extended_gcd<T: Integer + Signed + Copy>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let q = old_r.div_floor(&r);
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r.is_negative() {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of `a` modulo `m`, in `0..m`, if `a` and `m` are coprime
+pub fn mod_inverse<T: Integer + Signed + Copy>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = extended_gcd(a, m);
+    g.is_one().then(|| x.mod_floor(&m))
+}
+
+/// Solve a system of congruences `x ≡ r (mod m)`, given as `(r, m)` pairs with positive moduli
+///
+/// The moduli don't need to be coprime. The solutions are all the `x ≡ r (mod m)` for the returned `(r, m)`, where `m`
+/// is the least common multiple of the moduli and `r` is in `0..m`. Returns `None` if the congruences are incompatible,
+/// and `(0, 1)` if there are none.
+pub fn crt<T: Integer + Signed + Copy>(
+    congruences: impl IntoIterator<Item = (T, T)>,
+) -> Option<(T, T)> {
+    congruences
+        .into_iter()
+        .try_fold((T::zero(), T::one()), |(r1, m1), (r2, m2)| {
+            let g = gcd(m1, m2);
+            let diff = r2 - r1;
+            if !diff.mod_floor(&g).is_zero() {
+                return None;
+            }
+            // r1 + m1 * k ≡ r2 (mod m2) <=> (m1 / g) * k ≡ diff / g (mod m2 / g)
+            let m2g = m2 / g;
+            let k = (diff / g).mod_floor(&m2g) * mod_inverse((m1 / g).mod_floor(&m2g), m2g)? % m2g;
+            let m = m1 * m2g;
+            Some(((r1 + m1 * k).mod_floor(&m), m))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(lcm(4usize, 6), 12);
+        assert_eq!(lcm_all([2, 3, 4]), Some(12));
+        assert_eq!(lcm_all(Vec::<u64>::new()), None);
+        for (a, b) in [(240, 46), (-12, 18), (7, 0), (0, -5)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(4, 10), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // moduli which are not coprime
+        assert_eq!(crt([(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        // cycles without offsets, where the solution is the least common multiple
+        assert_eq!(crt([(0i64, 6), (0, 10)]), Some((0, 30)));
+        assert_eq!(crt(Vec::<(i32, i32)>::new()), Some((0, 1)));
+    }
+}
@@ -0,0 +1,115 @@
+use num::{Integer, Signed};
+use serde::{Deserialize, Serialize};
+
+use super::Vec2;
+
+/// A simple polygon (its edges don't cross each other) with integer vertices, given in order around it
+///
+/// The last vertex is joined back to the first one. Consecutive vertices can be on the same line, so the points of a
+/// path along a grid can be used directly as the vertices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Polygon<T> {
+    vertices: Vec<Vec2<T>>,
+}
+
+impl<T: Integer + Signed + Copy> Polygon<T> {
+    pub fn new(vertices: Vec<Vec2<T>>) -> Self {
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Vec2<T>] {
+        &self.vertices
+    }
+
+    /// The edges as pairs of vertices, including the one from the last vertex to the first
+    fn edges(&self) -> impl Iterator<Item = (Vec2<T>, Vec2<T>)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
+    /// Twice the area with the shoelace formula, which is positive if the vertices go clockwise with `y` pointing down
+    pub fn signed_double_area(&self) -> T {
+        self.edges()
+            .fold(T::zero(), |acc, (a, b)| acc + a.x * b.y - a.y * b.x)
+    }
+
+    /// Twice the area, which is always an integer
+    pub fn double_area(&self) -> T {
+        self.signed_double_area().abs()
+    }
+
+    /// The number of points with integer coordinates on the edges, which is the length of the perimeter when all the
+    /// edges are horizontal or vertical
+    pub fn boundary_points(&self) -> T {
+        self.edges().fold(T::zero(), |acc, (a, b)| {
+            let d = b - a;
+            acc + d.x.abs().gcd(&d.y.abs())
+        })
+    }
+
+    /// The number of points with integer coordinates strictly inside of the polygon, from Pick's theorem:
+    /// `area = interior + boundary / 2 - 1`
+    pub fn interior_points(&self) -> T {
+        let two = T::one() + T::one();
+        (self.double_area() - self.boundary_points() + two) / two
+    }
+
+    /// Is the point strictly inside of the polygon, `false` for a point on an edge
+    pub fn contains(&self, p: Vec2<T>) -> bool {
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            let (ab, ap) = (b - a, p - a);
+            if ab.x * ap.y == ab.y * ap.x
+                && a.x.min(b.x) <= p.x
+                && p.x <= a.x.max(b.x)
+                && a.y.min(b.y) <= p.y
+                && p.y <= a.y.max(b.y)
+            {
+                return false;
+            }
+            // count the edges crossed by a ray going from the point towards positive x
+            if (a.y > p.y) != (b.y > p.y) {
+                let (lhs, rhs) = (ap.x * ab.y, ap.y * ab.x);
+                if (ab.y > T::zero() && lhs < rhs) || (ab.y < T::zero() && lhs > rhs) {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+impl<T: Integer + Signed + Copy> FromIterator<Vec2<T>> for Polygon<T> {
+    fn from_iter<I: IntoIterator<Item = Vec2<T>>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polygon() {
+        // a 4x3 rectangle, clockwise on screen
+        let rect: Polygon<i64> = [(0, 0), (4, 0), (4, 3), (0, 3)]
+            .into_iter()
+            .map(|(x, y)| Vec2::new(x, y))
+            .collect();
+        assert_eq!(rect.signed_double_area(), 24);
+        assert_eq!(rect.boundary_points(), 14);
+        assert_eq!(rect.interior_points(), 6);
+        assert!(rect.contains(Vec2::new(1, 1)));
+        assert!(!rect.contains(Vec2::new(4, 1)));
+        assert!(!rect.contains(Vec2::new(5, 1)));
+        // a triangle with a diagonal edge, counterclockwise on screen
+        let triangle = Polygon::new(vec![Vec2::new(0, 0), Vec2::new(0, 4), Vec2::new(4, 0)]);
+        assert_eq!(triangle.signed_double_area(), -16);
+        assert_eq!(triangle.double_area(), 16);
+        assert_eq!(triangle.boundary_points(), 12);
+        assert_eq!(triangle.interior_points(), 3);
+        assert!(triangle.contains(Vec2::new(1, 2)));
+        assert!(!triangle.contains(Vec2::new(2, 2)));
+        assert!(!triangle.contains(Vec2::new(3, 2)));
+    }
+}
@@ -0,0 +1,63 @@
+use num::Signed;
+use serde::{Deserialize, Serialize};
+
+use super::{Direction, Vec2};
+
+/// A position on a grid along with the direction something there is heading, like a beam or a digger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Pose<T> {
+    pub pos: Vec2<T>,
+    pub dir: Direction,
+}
+
+impl<T: Signed + Copy> Pose<T> {
+    pub const fn new(pos: Vec2<T>, dir: Direction) -> Self {
+        Self { pos, dir }
+    }
+
+    /// One step forward
+    pub fn step(self) -> Self {
+        Self::new(self.pos.step(self.dir), self.dir)
+    }
+
+    /// `steps` steps forward
+    pub fn advance(self, steps: T) -> Self {
+        Self::new(self.pos + Vec2::from(self.dir) * steps, self.dir)
+    }
+
+    /// The same position, heading in `dir`
+    pub fn facing(self, dir: Direction) -> Self {
+        Self::new(self.pos, dir)
+    }
+
+    /// A quarter turn counterclockwise, without moving
+    pub fn turn_left(self) -> Self {
+        self.facing(self.dir.turn_left())
+    }
+
+    /// A quarter turn clockwise, without moving
+    pub fn turn_right(self) -> Self {
+        self.facing(self.dir.turn_right())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pose() {
+        let pose = Pose::new(Vec2::new(2, 3), Direction::North);
+        assert_eq!(pose.step().pos, Vec2::new(2, 2));
+        assert_eq!(pose.turn_right().advance(4).pos, Vec2::new(6, 3));
+        assert_eq!(
+            pose.turn_left().step(),
+            Pose::new(Vec2::new(1, 3), Direction::West)
+        );
+        assert_eq!(pose.turn_left().turn_left(), pose.facing(Direction::South));
+        assert_eq!(
+            pose.advance(3).facing(Direction::South).advance(3),
+            pose.facing(Direction::South)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::ops::{Add, Bound, RangeBounds, Sub};
+
+use num::Zero;
+
+/// The running sums of a list of values, to get the sum of any range of them in constant time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSum<T> {
+    /// The sum of the first `i` values at index `i`, so it starts with a zero and has one more element than the values
+    sums: Vec<T>,
+}
+
+impl<T: Copy + Zero + Add<Output = T> + Sub<Output = T>> PrefixSum<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        values.into_iter().collect()
+    }
+
+    /// The number of values
+    pub fn len(&self) -> usize {
+        self.sums.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The sum of the values in `range`, the part of it past the end of the list counting as zeros
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = clip(&range, self.len());
+        self.sums[end] - self.sums[start]
+    }
+
+    /// The sum of all the values
+    pub fn total(&self) -> T {
+        self.sums[self.len()]
+    }
+}
+
+impl<T: Copy + Zero + Add<Output = T>> FromIterator<T> for PrefixSum<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sums = vec![T::zero()];
+        let mut total = T::zero();
+        for value in iter {
+            total = total + value;
+            sums.push(total);
+        }
+        Self { sums }
+    }
+}
+
+/// The running sums of a grid of values, to get the sum of any rectangle of them in constant time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSum2D<T> {
+    width: usize,
+    height: usize,
+    /// The sum of the values above and to the left of `(x, y)` at index `y * (width + 1) + x`, with a row and a column
+    /// of zeros at the top and on the left
+    sums: Vec<T>,
+}
+
+impl<T: Copy + Zero + Add<Output = T> + Sub<Output = T>> PrefixSum2D<T> {
+    /// The sums of the grid whose value at `(x, y)` is `value(x, y)`
+    pub fn from_fn(width: usize, height: usize, mut value: impl FnMut(usize, usize) -> T) -> Self {
+        let stride = width + 1;
+        let mut sums = vec![T::zero(); stride * (height + 1)];
+        for y in 0..height {
+            let mut row = T::zero();
+            for x in 0..width {
+                row = row + value(x, y);
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+            }
+        }
+        Self {
+            width,
+            height,
+            sums,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The sum of the values in the columns `xs` and the rows `ys`, the part of the rectangle outside of the grid
+    /// counting as zeros
+    pub fn sum(&self, xs: impl RangeBounds<usize>, ys: impl RangeBounds<usize>) -> T {
+        let (x0, x1) = clip(&xs, self.width);
+        let (y0, y1) = clip(&ys, self.height);
+        let at = |x: usize, y: usize| self.sums[y * (self.width + 1) + x];
+        at(x1, y1) + at(x0, y0) - at(x0, y1) - at(x1, y0)
+    }
+
+    /// The sum of all the values
+    pub fn total(&self) -> T {
+        self.sum(.., ..)
+    }
+}
+
+/// The start and end of a range as indices into the sums of `len` values, an empty range if it is reversed
+fn clip(range: &impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.saturating_add(1),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    let end = end.min(len);
+    (start.min(end), end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_sum() {
+        let sums: PrefixSum<i32> = [3, -1, 4, 1, -5, 9].into_iter().collect();
+        assert_eq!((sums.len(), sums.total()), (6, 11));
+        assert_eq!(sums.sum(1..4), 4);
+        assert_eq!(sums.sum(..=1), 2);
+        assert_eq!(sums.sum(4..100), 4);
+        assert_eq!(sums.sum(8..10), 0);
+        assert_eq!(PrefixSum::<u8>::new([]).sum(..), 0);
+    }
+
+    #[test]
+    fn test_prefix_sum_2d() {
+        // 0 1 2 3
+        // 1 2 3 4
+        // 2 3 4 5
+        let sums = PrefixSum2D::from_fn(4, 3, |x, y| x + y);
+        assert_eq!(sums.total(), 30);
+        assert_eq!(sums.sum(1..3, 1..3), 12);
+        assert_eq!(sums.sum(3.., ..), 12);
+        assert_eq!(sums.sum(2..10, 2..=5), 9);
+        assert_eq!(sums.sum(1..1, ..), 0);
+    }
+}
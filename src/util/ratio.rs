@@ -0,0 +1,54 @@
+use num::{rational, Float, Zero};
+
+/// An exact fraction of two `i128`, always reduced and with a positive denominator
+///
+/// The operations panic if the numerator or denominator overflows, which leaves room for products of a few 64-bit
+/// numbers.
+pub type Ratio = rational::Ratio<i128>;
+
+/// The exact value of a float, `None` if it's not finite or doesn't fit in a [`Ratio`]
+///
+/// Unlike `Ratio::from_f64`, which finds a close fraction with small terms, this keeps every bit of the mantissa so
+/// that a ratio can be compared exactly with a bound given as a float.
+pub fn from_f64(value: f64) -> Option<Ratio> {
+    if !value.is_finite() {
+        return None;
+    }
+    if value.is_zero() {
+        return Some(Ratio::zero());
+    }
+    let (mantissa, exponent, sign) = value.integer_decode();
+    let numer = i128::from(sign) * i128::from(mantissa);
+    let shift = u32::from(exponent.unsigned_abs());
+    if exponent >= 0 {
+        numer
+            .checked_mul(1i128.checked_shl(shift)?)
+            .filter(|n| n.signum() == i128::from(sign))
+            .map(Ratio::from_integer)
+    } else {
+        let denom = 1i128.checked_shl(shift).filter(|d| *d > 0)?;
+        Some(Ratio::new(numer, denom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_f64() {
+        assert_eq!(from_f64(7.), Some(Ratio::from_integer(7)));
+        assert_eq!(from_f64(-0.375), Some(Ratio::new(-3, 8)));
+        assert_eq!(
+            from_f64(200_000_000_000_000.),
+            Some(Ratio::from_integer(200_000_000_000_000))
+        );
+        // 0.1 is not exactly a tenth as a float
+        assert_ne!(from_f64(0.1), Some(Ratio::new(1, 10)));
+        assert!(from_f64(0.1).unwrap() > Ratio::new(1, 10));
+        assert_eq!(from_f64(0.), Some(Ratio::zero()));
+        assert_eq!(from_f64(f64::NAN), None);
+        assert_eq!(from_f64(1e300), None);
+        assert_eq!(from_f64(1e-300), None);
+    }
+}
@@ -0,0 +1,209 @@
+//! Shortest paths in a graph given by a function returning the successors of a node along with the cost to reach them
+//!
+//! The functions have the same signatures as the ones of the `pathfinding` crate. The nodes are stored once in a `Vec`
+//! with the index of their parent and their best known cost, and the queue only holds indices into it.
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, BinaryHeap},
+    hash::Hash,
+};
+
+use num::Zero;
+
+use super::FastMap;
+
+/// A node in the queue, ordered so that the one with the smallest estimated total cost is popped first
+struct Queued<C> {
+    estimate: C,
+    cost: C,
+    index: usize,
+}
+
+impl<C: Ord> Ord for Queued<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // between two nodes with the same estimate, the one furthest from the start is probably closer to the goal
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl<C: Ord> PartialOrd for Queued<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Ord> PartialEq for Queued<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: Ord> Eq for Queued<C> {}
+
+/// The nodes reached by the search, with the index of their parent and their cost
+struct Reached<N, C> {
+    nodes: Vec<(N, usize, C)>,
+    indices: FastMap<N, usize>,
+}
+
+impl<N: Eq + Hash + Clone, C> Reached<N, C> {
+    /// The path from the start to the node at `index`
+    fn path(&self, mut index: usize) -> Vec<N> {
+        let mut path = vec![self.nodes[index].0.clone()];
+        while index != 0 {
+            index = self.nodes[index].1;
+            path.push(self.nodes[index].0.clone());
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Explore the graph from `start` in order of estimated total cost until a node for which `success` is true is found,
+/// returning its index
+fn explore<N, C, FN, IN, FH, FS>(
+    start: &N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+) -> (Reached<N, C>, Option<usize>)
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Copy,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    let mut reached = Reached {
+        nodes: vec![(start.clone(), 0, C::zero())],
+        indices: [(start.clone(), 0)].into_iter().collect(),
+    };
+    let mut queue = BinaryHeap::from([Queued {
+        estimate: heuristic(start),
+        cost: C::zero(),
+        index: 0,
+    }]);
+    while let Some(Queued { cost, index, .. }) = queue.pop() {
+        let node = reached.nodes[index].0.clone();
+        if cost > reached.nodes[index].2 {
+            // a cheaper way to this node was found after it was queued
+            continue;
+        }
+        if success(&node) {
+            return (reached, Some(index));
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            let next_index = match reached.indices.entry(next) {
+                Entry::Vacant(entry) => {
+                    let next_index = reached.nodes.len();
+                    reached.nodes.push((entry.key().clone(), index, next_cost));
+                    entry.insert(next_index);
+                    next_index
+                }
+                Entry::Occupied(entry) => {
+                    let next_index = *entry.get();
+                    let (_, parent, best) = &mut reached.nodes[next_index];
+                    if next_cost >= *best {
+                        continue;
+                    }
+                    (*parent, *best) = (index, next_cost);
+                    next_index
+                }
+            };
+            queue.push(Queued {
+                estimate: next_cost + heuristic(&reached.nodes[next_index].0),
+                cost: next_cost,
+                index: next_index,
+            });
+        }
+    }
+    (reached, None)
+}
+
+/// The cheapest path from `start` to a node for which `success` is true, along with its cost, with Dijkstra's
+/// algorithm
+pub fn dijkstra<N, C, FN, IN, FS>(start: &N, successors: FN, success: FS) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Copy,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FS: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| C::zero(), success)
+}
+
+/// The cheapest path from `start` to a node for which `success` is true, along with its cost, with the A* algorithm
+///
+/// The `heuristic` must never overestimate the remaining cost to reach the goal, or the path might not be the cheapest.
+pub fn astar<N, C, FN, IN, FH, FS>(
+    start: &N,
+    successors: FN,
+    heuristic: FH,
+    success: FS,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Copy,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    let (reached, goal) = explore(start, successors, heuristic, success);
+    goal.map(|index| (reached.path(index), reached.nodes[index].2))
+}
+
+/// The cost of the cheapest path from `start` to each reachable node, including `start` itself
+pub fn dijkstra_all<N, C, FN, IN>(start: &N, successors: FN) -> FastMap<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Copy,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+{
+    let (reached, _) = explore(start, successors, |_| C::zero(), |_| false);
+    reached
+        .nodes
+        .into_iter()
+        .map(|(node, _, cost)| (node, cost))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::Vec2;
+
+    /// Moves on a 10x10 grid, where going right costs 1, going down costs 2 and the column 5 is a wall except in the
+    /// last row
+    fn moves(p: Vec2<i32>) -> Vec<(Vec2<i32>, u32)> {
+        [(Vec2::new(p.x + 1, p.y), 1), (Vec2::new(p.x, p.y + 1), 2)]
+            .into_iter()
+            .filter(|(n, _)| n.x < 10 && n.y < 10 && (n.x != 5 || n.y == 9))
+            .collect()
+    }
+
+    #[test]
+    fn test_search() {
+        let start = Vec2::new(0, 0);
+        let goal = Vec2::new(9, 9);
+        let (path, cost) = dijkstra(&start, |p| moves(*p), |p| *p == goal).unwrap();
+        assert_eq!(cost, 9 + 18);
+        assert_eq!(path.len(), 19);
+        assert_eq!((path[0], path[18]), (start, goal));
+        let heuristic = |p: &Vec2<i32>| p.manhattan(goal).unsigned_abs();
+        let (path, cost) = astar(&start, |p| moves(*p), heuristic, |p| *p == goal).unwrap();
+        assert_eq!((path.len(), cost), (19, 27));
+        assert_eq!(dijkstra(&start, |p| moves(*p), |p| p.x > 9), None);
+        let costs = dijkstra_all(&start, |p| moves(*p));
+        assert_eq!(costs.len(), 5 * 10 + 5);
+        assert_eq!(costs[&start], 0);
+        assert_eq!(costs[&Vec2::new(6, 9)], 24);
+    }
+}
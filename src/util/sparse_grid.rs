@@ -0,0 +1,141 @@
+use super::{Aabb, FastMap, Grid, Vec2};
+
+/// The cells of an unbounded grid which have a value, along with the box of all the positions which ever had one
+///
+/// The bounds only grow: removing the cells on the border doesn't shrink them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: FastMap<Vec2<isize>, T>,
+    bounds: Option<Aabb<isize, 2>>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: FastMap::default(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The box of the positions which had a value, `None` if there were none
+    pub fn bounds(&self) -> Option<Aabb<isize, 2>> {
+        self.bounds
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Set the value of a cell, returning its previous value
+    pub fn insert(&mut self, pos: Vec2<isize>, value: T) -> Option<T> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.expand(pos.into()),
+            None => self.bounds = Some(Aabb::point(pos.into())),
+        }
+        self.cells.insert(pos, value)
+    }
+
+    pub fn remove(&mut self, pos: Vec2<isize>) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    pub fn get(&self, pos: Vec2<isize>) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: Vec2<isize>) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    /// The cells which have a value, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2<isize>, &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    /// Every position inside of the bounds with its value if it has one, row by row
+    pub fn iter_bounds(&self) -> impl Iterator<Item = (Vec2<isize>, Option<&T>)> {
+        self.bounds
+            .iter()
+            .flat_map(Aabb::points)
+            .map(|p| (Vec2::from(p), self.cells.get(&Vec2::from(p))))
+    }
+
+    /// Draw the grid inside of its bounds, one line per row, with the character `cell` gives for each position
+    pub fn render(&self, mut cell: impl FnMut(Option<&T>) -> char) -> String {
+        let mut drawing = String::new();
+        for (pos, value) in self.iter_bounds() {
+            if self
+                .bounds
+                .is_some_and(|b| pos.x == b.min[0] && pos.y > b.min[1])
+            {
+                drawing.push('\n');
+            }
+            drawing.push(cell(value));
+        }
+        drawing
+    }
+
+    /// A dense grid of the cells inside of the bounds, where the cells without a value are `empty`, along with the
+    /// position of its top left corner
+    #[allow(clippy::cast_sign_loss)]
+    pub fn to_grid(&self, empty: T) -> Option<(Grid<T>, Vec2<isize>)>
+    where
+        T: Clone,
+    {
+        let Aabb { min, max } = self.bounds?;
+        let origin = Vec2::from(min);
+        let size = Vec2::from(max) - origin + Vec2::new(1, 1);
+        let mut grid = Grid::new(size.x as usize, size.y as usize, empty);
+        for (pos, value) in self.iter() {
+            grid[pos - origin] = value.clone();
+        }
+        Some((grid, origin))
+    }
+}
+
+impl<T> FromIterator<(Vec2<isize>, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Vec2<isize>, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        for (pos, value) in iter {
+            grid.insert(pos, value);
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_grid() {
+        let mut grid: SparseGrid<char> = [(Vec2::new(-2, 1), 'a'), (Vec2::new(1, -1), 'b')]
+            .into_iter()
+            .collect();
+        assert_eq!(grid.bounds(), Some(Aabb::new([-2, -1], [1, 1])));
+        assert_eq!(grid.insert(Vec2::new(0, 0), 'c'), None);
+        assert_eq!(grid.get(Vec2::new(0, 0)), Some(&'c'));
+        assert_eq!(grid.render(|c| *c.unwrap_or(&'.')), "...b\n..c.\na...");
+        // the bounds don't shrink
+        grid.remove(Vec2::new(1, -1));
+        assert_eq!((grid.len(), grid.iter_bounds().count()), (2, 12));
+        let (dense, origin) = grid.to_grid(' ').unwrap();
+        assert_eq!(
+            (dense.width(), dense.height(), origin),
+            (4, 3, Vec2::new(-2, -1))
+        );
+        assert_eq!(dense[Vec2::new(2, 1)], 'c');
+        assert_eq!(SparseGrid::<u8>::new().to_grid(0), None);
+        assert_eq!(SparseGrid::<u8>::new().render(|_| '#'), "");
+    }
+}
@@ -0,0 +1,57 @@
+//! Mirror lines of a grid
+//!
+//! The mirrors are vertical lines between two columns, identified by the number of columns on their left. The mirrors
+//! between two rows are the vertical ones of the transposed grid, e.g. `find_reflection(&grid.transposed(), 0)`.
+
+use super::Grid;
+
+/// The number of cells which differ from their mirror image across the vertical line on the left of column `x`
+///
+/// Only the columns which have a mirror image on the other side are compared, and the count stops as soon as it is
+/// over `limit`.
+pub fn mirror_diffs<T: PartialEq>(grid: &Grid<T>, x: usize, limit: usize) -> usize {
+    let mut diffs = 0;
+    for row in grid.rows() {
+        let (left, right) = row.split_at(x);
+        diffs += right
+            .iter()
+            .zip(left.iter().rev())
+            .filter(|(a, b)| a != b)
+            .count();
+        if diffs > limit {
+            break;
+        }
+    }
+    diffs
+}
+
+/// The first vertical mirror across which exactly `tolerated_diffs` cells differ from their image, as the number of
+/// columns on its left
+///
+/// With zero differences the mirror is perfect, with one it is only off by the smudge of day 13.
+pub fn find_reflection<T: PartialEq>(grid: &Grid<T>, tolerated_diffs: usize) -> Option<usize> {
+    (1..grid.width()).find(|&x| mirror_diffs(grid, x, tolerated_diffs) == tolerated_diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_reflection() {
+        let grid = Grid::from_rows(
+            ["#.##..##.", "..#.##.#.", "##......#"]
+                .iter()
+                .map(|row| row.chars().collect())
+                .collect(),
+        )
+        .unwrap();
+        assert_eq!(find_reflection(&grid, 0), Some(5));
+        assert_eq!(mirror_diffs(&grid, 5, 0), 0);
+        assert_eq!(mirror_diffs(&grid, 2, 100), 4);
+        // the count stops after the first row
+        assert_eq!(mirror_diffs(&grid, 2, 0), 1);
+        assert_eq!(find_reflection(&grid, 1), Some(1));
+        assert_eq!(find_reflection(&grid.transposed(), 0), None);
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use num::Signed;
+use serde::{Deserialize, Serialize};
+
+use super::Direction;
+
+/// A position or a displacement on a grid, with `y` pointing down like the lines of an input
+///
+/// Points are ordered like the cells of a grid are read: by row, then by column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Vec2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+/// The distance between two values, which also works for unsigned ones
+fn abs_diff<T: Copy + Ord + Sub<Output = T>>(a: T, b: T) -> T {
+    a.max(b) - a.min(b)
+}
+
+/// The distances between two points, which all work for unsigned coordinates too
+impl<T: Copy + Ord + Sub<Output = T> + Add<Output = T>> Vec2<T> {
+    /// Manhattan distance: the number of orthogonal steps from one point to the other
+    pub fn manhattan(self, other: Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    /// Chebyshev distance: the number of steps from one point to the other when the diagonal steps are allowed
+    pub fn chebyshev(self, other: Self) -> T {
+        abs_diff(self.x, other.x).max(abs_diff(self.y, other.y))
+    }
+
+    /// The square of the Euclidean distance, which is an integer for integer coordinates and compares like the distance
+    pub fn euclid_sq(self, other: Self) -> T
+    where
+        T: Mul<Output = T>,
+    {
+        let (dx, dy) = (abs_diff(self.x, other.x), abs_diff(self.y, other.y));
+        dx * dx + dy * dy
+    }
+}
+
+impl<T: Signed + Copy> Vec2<T> {
+    /// The point one step away in `dir`
+    pub fn step(self, dir: Direction) -> Self {
+        self + Self::from(dir)
+    }
+
+    /// The four orthogonal neighbors, clockwise from the north
+    pub fn neighbors(self) -> [Self; 4] {
+        Direction::ALL.map(|dir| self.step(dir))
+    }
+
+    /// The eight neighbors including the diagonals, row by row
+    pub fn neighbors8(self) -> [Self; 8] {
+        let (zero, one) = (T::zero(), T::one());
+        [
+            Self::new(-one, -one),
+            Self::new(zero, -one),
+            Self::new(one, -one),
+            Self::new(-one, zero),
+            Self::new(one, zero),
+            Self::new(-one, one),
+            Self::new(zero, one),
+            Self::new(one, one),
+        ]
+        .map(|delta| self + delta)
+    }
+}
+
+/// The unit vector pointing in a direction
+impl<T: Signed> From<Direction> for Vec2<T> {
+    fn from(dir: Direction) -> Self {
+        let (zero, one) = (T::zero, T::one);
+        match dir {
+            Direction::North => Self::new(zero(), -one()),
+            Direction::East => Self::new(one(), zero()),
+            Direction::South => Self::new(zero(), one()),
+            Direction::West => Self::new(-one(), zero()),
+        }
+    }
+}
+
+impl<T: Ord> Ord for Vec2<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.y.cmp(&other.y).then_with(|| self.x.cmp(&other.x))
+    }
+}
+
+impl<T: Ord> PartialOrd for Vec2<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vec2<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vec2<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Vec2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+/// Scaling by a factor
+impl<T: Mul<Output = T> + Copy> Mul<T> for Vec2<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Vec2<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Vec2<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T: Display> Display for Vec2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vec2() {
+        let a = Vec2::new(1, 2);
+        let b = Vec2::new(4, -2);
+        assert_eq!(a + b, Vec2::new(5, 0));
+        assert_eq!(a - b, Vec2::new(-3, 4));
+        assert_eq!(-a * 3, Vec2::new(-3, -6));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(Vec2::<usize>::new(3, 1).manhattan(Vec2::new(1, 4)), 5);
+        assert_eq!(a.chebyshev(b), 4);
+        assert_eq!(a.euclid_sq(b), 25);
+        assert_eq!(Vec2::<u8>::new(3, 1).euclid_sq(Vec2::new(1, 4)), 13);
+        assert!(a.neighbors8().iter().all(|n| n.chebyshev(a) == 1));
+        assert_eq!(a.step(Direction::North), Vec2::new(1, 1));
+        assert_eq!(a.neighbors().map(|n| n.manhattan(a)), [1; 4]);
+        assert!(a
+            .neighbors8()
+            .iter()
+            .all(|n| n.manhattan(a) <= 2 && *n != a));
+        // reading order: by row, then by column
+        let mut points = vec![Vec2::new(0, 1), Vec2::new(1, 0), Vec2::new(0, 0)];
+        points.sort();
+        assert_eq!(points, [Vec2::new(0, 0), Vec2::new(1, 0), Vec2::new(0, 1)]);
+    }
+}
@@ -0,0 +1,79 @@
+#![cfg_attr(not(feature = "all-days"), allow(dead_code))]
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// Directory where all visualizations are written
+pub const OUTPUT_DIR: &str = "outputs";
+
+/// Minimal builder for Graphviz DOT documents describing undirected graphs
+#[derive(Debug, Default)]
+pub struct DotGraph {
+    nodes: Vec<String>,
+    edges: Vec<String>,
+}
+
+impl DotGraph {
+    /// Add a node with a label and a fill color
+    pub fn node(&mut self, id: usize, label: &str, color: &str) {
+        self.nodes.push(format!(
+            "  n{id} [label=\"{label}\", style=filled, fillcolor=\"{color}\"];"
+        ));
+    }
+
+    /// Add an edge between two nodes, optionally highlighted with a thick red stroke
+    pub fn edge(&mut self, a: usize, b: usize, highlight: bool) {
+        if highlight {
+            self.edges
+                .push(format!("  n{a} -- n{b} [color=red, penwidth=4];"));
+        } else {
+            self.edges
+                .push(format!("  n{a} -- n{b} [color=\"#00000040\"];"));
+        }
+    }
+
+    /// Render the DOT document
+    pub fn render(&self) -> String {
+        let mut dot = String::from("graph {\n  node [shape=circle, fontsize=8];\n");
+        for line in self.nodes.iter().chain(&self.edges) {
+            dot.push_str(line);
+            dot.push('\n');
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Write a DOT document to `outputs/{name}.dot` and render it to `outputs/{name}.svg` if Graphviz is installed
+///
+/// Returns the paths of the files that were written.
+pub fn export_dot(name: &str, dot: &str) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(OUTPUT_DIR)?;
+    let dot_path = PathBuf::from(format!("{OUTPUT_DIR}/{name}.dot"));
+    fs::write(&dot_path, dot)?;
+    let mut written = vec![dot_path];
+
+    // the `dot` binary is optional, we simply skip the SVG if it's not available
+    let Ok(mut child) = Command::new("dot")
+        .arg("-Tsvg")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+    else {
+        return Ok(written);
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(dot.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        let svg_path = PathBuf::from(format!("{OUTPUT_DIR}/{name}.svg"));
+        fs::write(&svg_path, output.stdout)?;
+        written.push(svg_path);
+    }
+    Ok(written)
+}
@@ -0,0 +1,58 @@
+use std::{fs, path::Path, thread, time::Duration, time::SystemTime};
+
+/// How often the watched file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Modification time of a file, or `None` if it doesn't exist
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Block until the modification time of the file differs from `last`, and return the new one
+///
+/// Creating or deleting the file also counts as a modification.
+fn wait_for_change(path: &Path, last: Option<SystemTime>) -> Option<SystemTime> {
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = modified(path);
+        if current != last {
+            return current;
+        }
+    }
+}
+
+/// Call `f`, then call it again every time the file at `path` is modified
+pub fn watch(path: &str, mut f: impl FnMut()) -> ! {
+    let path = Path::new(path);
+    let mut last = modified(path);
+    loop {
+        f();
+        eprintln!("Watching {} for changes...", path.display());
+        last = wait_for_change(path, last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_wait_for_change() {
+        let path = env::temp_dir().join(format!("aoc_watch_{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                fs::write(path, "1").unwrap();
+            })
+        };
+        let changed = wait_for_change(&path, None);
+        writer.join().unwrap();
+        assert!(changed.is_some());
+        assert_eq!(changed, modified(&path));
+        fs::remove_file(&path).unwrap();
+    }
+}